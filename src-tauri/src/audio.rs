@@ -2,32 +2,104 @@
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use nnnoiseless::{DenoiseState, FRAME_SIZE as RNNOISE_FRAME_SIZE};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::env;
 use tauri::Emitter;
 
+use crate::error::CrispyError;
 use crate::recording;
 
 fn audio_debug_enabled() -> bool {
     env::var("CRISPY_AUDIO_DEBUG").is_ok()
 }
 
-#[derive(serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
 pub struct AudioDevice {
+    /// What to pass back to select the device; unique within one listing.
     pub id: String,
     pub name: String,
 }
 
+/// Ids and display names for devices in enumeration order. Devices sharing a
+/// name are told apart by their position among them: the first keeps the bare
+/// name (so ids saved before this still match), later ones become
+/// `<name>#2`, shown as `<name> (2)`.
+fn label_devices(names: Vec<String>) -> Vec<AudioDevice> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let count = seen.entry(name.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                AudioDevice { id: name.clone(), name }
+            } else {
+                AudioDevice {
+                    id: format!("{}#{}", name, count),
+                    name: format!("{} ({})", name, count),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Named devices with their labels, in enumeration order.
+fn labeled_devices(devices: impl Iterator<Item = cpal::Device>) -> Vec<(AudioDevice, cpal::Device)> {
+    let (names, devices): (Vec<String>, Vec<cpal::Device>) = devices
+        .filter_map(|device| device.name().ok().map(|name| (name, device)))
+        .unzip();
+    label_devices(names).into_iter().zip(devices).collect()
+}
+
+/// The device listed under `id` by `get_input_devices`/`get_output_devices`.
+fn find_device_by_id(devices: impl Iterator<Item = cpal::Device>, id: &str) -> Option<cpal::Device> {
+    labeled_devices(devices)
+        .into_iter()
+        .find(|(label, _)| label.id == id)
+        .map(|(_, device)| device)
+}
+
 pub struct AudioMonitorState {
     pub input_stream: Option<cpal::Stream>,
     pub output_stream: Option<cpal::Stream>,
     shared: Option<Arc<Mutex<NsState>>>,
+    /// Applied to the monitoring output; kept across restarts of the streams.
+    gate: Arc<Mutex<NoiseGate>>,
+    /// Applied to the monitoring output after the gate; kept across restarts.
+    compressor: Arc<Mutex<Compressor>>,
+    /// Applied to the mic input ahead of noise suppression.
+    agc: Arc<Mutex<Agc>>,
+    /// Emit per-channel input levels (`microphone-channels`) next to the
+    /// mono meter; read by the input callback, kept across restarts.
+    channel_metering: Arc<AtomicBool>,
+    /// Keep every Nth raw mic sample for `microphone-waveform`; 0 is off.
+    waveform_decimation: Arc<AtomicUsize>,
     pub last_input_rate: Option<f32>,
     pub last_output_rate: Option<f32>,
     pub current_input_device: Option<String>,
     pub current_output_device: Option<String>,
+    /// cpal backend the current streams were opened on.
+    pub current_host: Option<cpal::HostId>,
+    /// Set while streams are being rebuilt after the device disappeared.
+    recovering: bool,
+    /// Bumped whenever streams are (re)opened, so a default-device watcher
+    /// from an earlier open knows to exit.
+    streams_generation: u64,
+    /// Don't drop RNNoise's warm-up frame (`rnnoise_low_latency` setting).
+    low_latency_start: bool,
+    /// Noise the "noisy" model injects; kept across restarts.
+    noise_params: NoiseParams,
+    /// Noise-suppression model and output volume in use, kept up to date by
+    /// `set_monitoring_model`/`set_monitoring_volume` so a restart of the
+    /// streams reopens them as they are now rather than as first opened.
+    model_name: String,
+    volume: f32,
+    /// The mic opened at 48kHz; otherwise it runs at the device default
+    /// (`last_input_rate`) and the input callback resamples it to 48kHz.
+    pub negotiated_48k: bool,
 }
 
 impl AudioMonitorState {
@@ -36,10 +108,23 @@ impl AudioMonitorState {
             input_stream: None,
             output_stream: None,
             shared: None,
+            gate: Arc::new(Mutex::new(NoiseGate::default())),
+            compressor: Arc::new(Mutex::new(Compressor::default())),
+            agc: Arc::new(Mutex::new(Agc::default())),
+            channel_metering: Arc::new(AtomicBool::new(false)),
+            waveform_decimation: Arc::new(AtomicUsize::new(0)),
             last_input_rate: None,
             last_output_rate: None,
             current_input_device: None,
             current_output_device: None,
+            current_host: None,
+            recovering: false,
+            streams_generation: 0,
+            low_latency_start: false,
+            noise_params: NoiseParams::default(),
+            model_name: String::new(),
+            volume: 1.0,
+            negotiated_48k: false,
         }
     }
 }
@@ -59,6 +144,24 @@ impl ModelKind {
     }
 }
 
+/// White noise the "noisy" model adds: an LCG started at `seed`, uniform in
+/// `[-amplitude, amplitude]`. The same seed gives the same sequence, so A/B
+/// runs are reproducible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseParams {
+    pub seed: u32,
+    pub amplitude: f32,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            seed: 0x1234_abcd,
+            amplitude: 0.05,
+        }
+    }
+}
+
 struct SharedAudio {
     buffer: VecDeque<f32>,
     max_len: usize,
@@ -68,6 +171,7 @@ struct SharedAudio {
     model: ModelKind,
     volume: f32,
     rng_state: u32,
+    noise_amplitude: f32,
 }
 
 struct LinearResampler {
@@ -95,18 +199,24 @@ impl LinearResampler {
         (self.input_rate, self.output_rate)
     }
 
+    /// Change rates mid-stream. The last sample and the output position are
+    /// kept, so the next output continues from where the old rate left off.
     fn set_rates(&mut self, input_rate: f32, output_rate: f32) {
         self.input_rate = input_rate;
         self.output_rate = output_rate;
-        // Reset internal state so interpolation is consistent after a rate change.
-        self.last_sample = 0.0;
-        self.has_last = false;
-        self.input_pos = 0.0;
-        self.next_output_pos = 0.0;
     }
 
     fn process_sample<F: FnMut(f32)>(&mut self, sample: f32, mut emit: F) {
         if (self.input_rate - self.output_rate).abs() < 1.0 {
+            // Track position anyway, for a seamless switch to resampling
+            if self.has_last {
+                self.input_pos += 1.0;
+            } else {
+                self.has_last = true;
+                self.input_pos = 0.0;
+            }
+            self.last_sample = sample;
+            self.next_output_pos = self.input_pos + 1.0;
             emit(sample);
             return;
         }
@@ -133,8 +243,148 @@ impl LinearResampler {
     }
 }
 
+/// Taps of the windowed-sinc kernel; half of them sit on each side of the
+/// interpolated position, which is also the resampler's latency in input samples.
+const SINC_TAPS: usize = 16;
+
+struct SincResampler {
+    input_rate: f32,
+    output_rate: f32,
+    /// Most recent input, oldest first; the front is zero-padded at start.
+    history: VecDeque<f32>,
+    /// Input samples consumed since the last reset.
+    consumed: u64,
+    next_output_pos: f64,
+}
+
+impl SincResampler {
+    fn new(input_rate: f32, output_rate: f32) -> Self {
+        let mut resampler = Self {
+            input_rate,
+            output_rate,
+            history: VecDeque::new(),
+            consumed: 0,
+            next_output_pos: 0.0,
+        };
+        resampler.set_rates(input_rate, output_rate);
+        resampler
+    }
+
+    fn rates(&self) -> (f32, f32) {
+        (self.input_rate, self.output_rate)
+    }
+
+    /// Change rates mid-stream. History and output position are kept, so
+    /// there's no restart from silence at the switch.
+    fn set_rates(&mut self, input_rate: f32, output_rate: f32) {
+        self.input_rate = input_rate;
+        self.output_rate = output_rate;
+        // Room for the kernel plus the input a single downsampling step skips.
+        // Never shrinks: outputs still pending at the old step may need it.
+        let len = SINC_TAPS + (input_rate / output_rate).ceil() as usize + 1;
+        while self.history.len() < len {
+            self.history.push_front(0.0);
+        }
+    }
+
+    /// Blackman-windowed sinc at offset `t` (input samples), with the cutoff
+    /// lowered to the output Nyquist when downsampling.
+    fn kernel(t: f64, cutoff: f64) -> f64 {
+        let half = (SINC_TAPS / 2) as f64;
+        if t.abs() >= half {
+            return 0.0;
+        }
+        let x = std::f64::consts::PI * cutoff * t;
+        let sinc = if x.abs() < 1e-9 { 1.0 } else { x.sin() / x };
+        let phase = std::f64::consts::PI * t / half;
+        let window = 0.42 + 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+        sinc * window
+    }
+
+    fn process_sample<F: FnMut(f32)>(&mut self, sample: f32, mut emit: F) {
+        self.history.pop_front();
+        self.history.push_back(sample);
+        self.consumed += 1;
+        let newest = self.consumed as f64 - 1.0;
+
+        // Equal rates pass samples straight through, unless outputs are still
+        // behind from resampling before a rate change; those catch up through
+        // the kernel instead of skipping ahead.
+        if (self.input_rate - self.output_rate).abs() < 1.0 && self.next_output_pos >= newest {
+            self.next_output_pos = newest + 1.0;
+            emit(sample);
+            return;
+        }
+
+        let half = (SINC_TAPS / 2) as f64;
+        let step = (self.input_rate / self.output_rate) as f64;
+        let cutoff = (self.output_rate / self.input_rate).min(1.0) as f64;
+        // Index (in input samples) of history[0]
+        let first = newest - (self.history.len() - 1) as f64;
+
+        while self.next_output_pos + half <= newest {
+            let base = self.next_output_pos.floor();
+            let frac = self.next_output_pos - base;
+            let mut acc = 0.0f64;
+            let mut norm = 0.0f64;
+            for j in (1 - SINC_TAPS as i64 / 2)..=(SINC_TAPS as i64 / 2) {
+                let weight = Self::kernel(j as f64 - frac, cutoff);
+                let index = (base + j as f64 - first) as usize;
+                acc += self.history[index] as f64 * weight;
+                norm += weight;
+            }
+            emit((acc / norm) as f32);
+            self.next_output_pos += step;
+        }
+    }
+}
+
+/// Interpolation used to bring mic audio to the recording rate. Linear is
+/// cheap; sinc avoids the aliasing and dulled highs of linear at 44.1k↔48k.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResamplerKind {
+    #[default]
+    Linear,
+    Sinc,
+}
+
+enum StreamResampler {
+    Linear(LinearResampler),
+    Sinc(SincResampler),
+}
+
+impl StreamResampler {
+    fn new(kind: ResamplerKind, input_rate: f32, output_rate: f32) -> Self {
+        match kind {
+            ResamplerKind::Linear => Self::Linear(LinearResampler::new(input_rate, output_rate)),
+            ResamplerKind::Sinc => Self::Sinc(SincResampler::new(input_rate, output_rate)),
+        }
+    }
+
+    fn rates(&self) -> (f32, f32) {
+        match self {
+            Self::Linear(r) => r.rates(),
+            Self::Sinc(r) => r.rates(),
+        }
+    }
+
+    fn set_rates(&mut self, input_rate: f32, output_rate: f32) {
+        match self {
+            Self::Linear(r) => r.set_rates(input_rate, output_rate),
+            Self::Sinc(r) => r.set_rates(input_rate, output_rate),
+        }
+    }
+
+    fn process_sample<F: FnMut(f32)>(&mut self, sample: f32, emit: F) {
+        match self {
+            Self::Linear(r) => r.process_sample(sample, emit),
+            Self::Sinc(r) => r.process_sample(sample, emit),
+        }
+    }
+}
+
 impl SharedAudio {
-    fn new(input_rate: f32, output_rate: f32, model: ModelKind, volume: f32) -> Self {
+    fn new(input_rate: f32, output_rate: f32, model: ModelKind, volume: f32, noise: NoiseParams) -> Self {
         let max_len = input_rate as usize;
         Self {
             buffer: VecDeque::with_capacity(max_len),
@@ -144,10 +394,27 @@ impl SharedAudio {
             output_rate,
             model,
             volume,
-            rng_state: 0x1234_abcd,
+            rng_state: noise.seed,
+            noise_amplitude: noise.amplitude,
         }
     }
 
+    /// Restart the noise sequence from `noise.seed`.
+    fn set_noise_params(&mut self, noise: NoiseParams) {
+        self.rng_state = noise.seed;
+        self.noise_amplitude = noise.amplitude;
+    }
+
+    /// Next injected noise sample for the "noisy" model.
+    fn next_noise(&mut self) -> f32 {
+        self.rng_state = self
+            .rng_state
+            .wrapping_mul(1_664_525)
+            .wrapping_add(1_013_904_223);
+        let noise = (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0;
+        noise * self.noise_amplitude
+    }
+
     fn push_sample(&mut self, sample: f32) -> Option<Vec<f32>> {
         if self.buffer.len() >= self.max_len {
             self.buffer.pop_front();
@@ -156,12 +423,7 @@ impl SharedAudio {
 
         let mut processed = sample * self.volume;
         if let ModelKind::Noisy = self.model {
-            self.rng_state = self
-                .rng_state
-                .wrapping_mul(1_664_525)
-                .wrapping_add(1_013_904_223);
-            let noise = (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0;
-            processed += noise * 0.05;
+            processed += self.next_noise();
         }
         Some(vec![processed])
     }
@@ -186,12 +448,7 @@ impl SharedAudio {
         let mut sample = s0 + (s1 - s0) * frac;
 
         if let ModelKind::Noisy = self.model {
-            self.rng_state = self
-                .rng_state
-                .wrapping_mul(1_664_525)
-                .wrapping_add(1_013_904_223);
-            let noise = (self.rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0;
-            sample += noise * 0.05;
+            sample += self.next_noise();
         }
 
         self.resample_pos += step;
@@ -207,91 +464,78 @@ struct RnnNoiseProcessor {
     input_rate: f32,
     output_rate: f32,
     volume: f32,
-    first_frame: bool,
+    /// The first denoised frame is still to be dropped as model warm-up.
+    warmup_pending: bool,
     max_output_len: usize,
-    input_resampler: Option<LinearResampler>,
 }
 
 impl RnnNoiseProcessor {
+    /// `input_rate` is the processing rate, the 48kHz RNNoise works at:
+    /// `push_mono_to_buffers` resamples the mic before it gets here.
     fn new(input_rate: f32, output_rate: f32, volume: f32) -> Self {
-        let (effective_input_rate, input_resampler) = if (input_rate - 48000.0).abs() >= 1.0 {
-            (
-                48000.0,
-                Some(LinearResampler::new(input_rate, 48000.0)),
-            )
-        } else {
-            (input_rate, None)
-        };
-
-        let max_output_len = effective_input_rate as usize;
+        let max_output_len = input_rate as usize;
 
         Self {
             denoise: DenoiseState::new(),
             input_buf: VecDeque::with_capacity(RNNOISE_FRAME_SIZE * 2),
             output_buf: VecDeque::with_capacity(max_output_len),
             resample_pos: 0.0,
-            input_rate: effective_input_rate,
+            input_rate,
             output_rate,
             volume: volume.clamp(0.0, 1.0),
-            first_frame: true,
+            warmup_pending: true,
             max_output_len,
-            input_resampler,
         }
     }
 
     fn push_sample(&mut self, sample: f32) -> Option<Vec<f32>> {
-        let mut samples_to_process = Vec::new();
-
-        if let Some(mut resampler) = self.input_resampler.take() {
-            resampler.process_sample(sample, |s| samples_to_process.push(s));
-            self.input_resampler = Some(resampler);
-        } else {
-            samples_to_process.push(sample);
+        if self.input_buf.len() >= self.max_output_len {
+            self.input_buf.pop_front();
+        }
+        self.input_buf.push_back(sample);
+        if self.input_buf.len() < RNNOISE_FRAME_SIZE {
+            return None;
         }
 
-        let mut output_accumulator = Vec::new();
-
-        for s in samples_to_process {
-            if self.input_buf.len() >= self.max_output_len {
-                self.input_buf.pop_front();
-            }
-            self.input_buf.push_back(s);
-
-            if self.input_buf.len() >= RNNOISE_FRAME_SIZE {
-                let mut input_frame = [0.0f32; 480];
-                for (i, val) in self.input_buf.drain(..RNNOISE_FRAME_SIZE).enumerate() {
-                    if i < RNNOISE_FRAME_SIZE {
-                        input_frame[i] = val * 32768.0;
-                    }
-                }
-                let mut output_frame = [0.0f32; 480];
-                self.denoise.process_frame(&mut output_frame[..], &input_frame[..]);
+        let mut input_frame = [0.0f32; 480];
+        for (i, val) in self.input_buf.drain(..RNNOISE_FRAME_SIZE).enumerate() {
+            input_frame[i] = val * 32768.0;
+        }
+        let mut output_frame = [0.0f32; 480];
+        self.denoise.process_frame(&mut output_frame[..], &input_frame[..]);
 
-                let out_samples: Vec<f32> = output_frame
-                    .iter()
-                    .map(|&val| (val / 32768.0).clamp(-1.0, 1.0) * self.volume)
-                    .collect();
+        let out_samples: Vec<f32> = output_frame
+            .iter()
+            .map(|&val| (val / 32768.0).clamp(-1.0, 1.0) * self.volume)
+            .collect();
 
-                if self.first_frame {
-                    self.first_frame = false;
-                    continue;
-                }
+        if self.warmup_pending {
+            self.warmup_pending = false;
+            return None;
+        }
 
-                for &out in &out_samples {
-                    if self.output_buf.len() >= self.max_output_len {
-                        self.output_buf.pop_front();
-                    }
-                    self.output_buf.push_back(out);
-                }
-                output_accumulator.extend(out_samples);
+        for &out in &out_samples {
+            if self.output_buf.len() >= self.max_output_len {
+                self.output_buf.pop_front();
             }
+            self.output_buf.push_back(out);
         }
+        Some(out_samples)
+    }
 
-        if output_accumulator.is_empty() {
-            None
-        } else {
-            Some(output_accumulator)
-        }
+    /// Samples (at the processing rate) a sample waits before it is denoised:
+    /// input is collected into whole frames, so the first sample of a frame
+    /// waits one full frame. Dropping the warm-up frame only delays the start
+    /// of output, not this steady-state latency; the model's own analysis
+    /// window is not counted.
+    fn latency_samples(&self) -> usize {
+        RNNOISE_FRAME_SIZE
+    }
+
+    /// Keep the first frame instead of dropping it as warm-up, so output
+    /// starts one frame sooner. Only has an effect before the first frame.
+    fn set_low_latency_start(&mut self, enabled: bool) {
+        self.warmup_pending = !enabled;
     }
 
     fn next_sample(&mut self) -> f32 {
@@ -349,6 +593,27 @@ impl NsState {
         }
     }
 
+    /// Delay the processor adds before a sample reaches the output, in samples
+    /// at `produced_rate_hz`.
+    fn latency_samples(&self) -> usize {
+        match self {
+            NsState::Legacy(_) => 0,
+            NsState::RnnNoise(s) => s.latency_samples(),
+        }
+    }
+
+    fn set_low_latency_start(&mut self, enabled: bool) {
+        if let NsState::RnnNoise(s) = self {
+            s.set_low_latency_start(enabled);
+        }
+    }
+
+    fn set_noise_params(&mut self, noise: NoiseParams) {
+        if let NsState::Legacy(s) = self {
+            s.set_noise_params(noise);
+        }
+    }
+
     fn produced_rate_hz(&self) -> f32 {
         match self {
             NsState::Legacy(s) => s.input_rate,
@@ -357,99 +622,979 @@ impl NsState {
     }
 }
 
+impl NsState {
+    /// Build the processor for an NS model id; `None` for "dummy"/unknown-empty.
+    /// `noise` only matters to the "noisy" model.
+    fn for_model(
+        model_name: &str,
+        input_rate: f32,
+        output_rate: f32,
+        volume: f32,
+        noise: NoiseParams,
+    ) -> Option<Self> {
+        if model_name == "dummy" || model_name.is_empty() {
+            None
+        } else if model_name == "rnnnoise" {
+            Some(NsState::RnnNoise(RnnNoiseProcessor::new(input_rate, output_rate, volume)))
+        } else {
+            Some(NsState::Legacy(SharedAudio::new(
+                input_rate,
+                output_rate,
+                ModelKind::from_name(model_name),
+                volume,
+                noise,
+            )))
+        }
+    }
+}
+
+/// Thresholds at or below this turn the monitoring gate off.
+const GATE_OFF_DB: f32 = -96.0;
+/// Default for how long the gate stays open after the level drops below the
+/// threshold.
+pub const GATE_HOLD_MS: f32 = 50.0;
+/// Decay of the level detector; short enough to follow syllables.
+const GATE_DETECTOR_MS: f32 = 10.0;
+
+/// One-pole smoothing coefficient for a time constant in ms.
+fn smoothing_coeff(ms: f32, sample_rate: f32) -> f32 {
+    1.0 - (-1000.0 / (ms.max(0.01) * sample_rate)).exp()
+}
+
+/// Noise gate on the monitoring output: a peak envelope opens the gate above
+/// the threshold, a hold time keeps it open across short dips, and the gain
+/// moves with separate attack/release smoothing so it doesn't chatter.
+struct NoiseGate {
+    enabled: bool,
+    threshold: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    hold_ms: f32,
+    sample_rate: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    detector_decay: f32,
+    hold_samples: u32,
+    envelope: f32,
+    hold_left: u32,
+    gain: f32,
+}
+
+impl Default for NoiseGate {
+    fn default() -> Self {
+        let mut gate = Self {
+            enabled: false,
+            threshold: 0.0,
+            attack_ms: 1.0,
+            release_ms: 100.0,
+            hold_ms: GATE_HOLD_MS,
+            sample_rate: 48000.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            detector_decay: 0.0,
+            hold_samples: 0,
+            envelope: 0.0,
+            hold_left: 0,
+            gain: 1.0,
+        };
+        gate.update_coeffs();
+        gate
+    }
+}
+
+impl NoiseGate {
+    fn configure(&mut self, threshold_db: f32, attack_ms: f32, release_ms: f32, hold_ms: f32) {
+        self.enabled = threshold_db > GATE_OFF_DB;
+        self.threshold = 10f32.powf(threshold_db / 20.0);
+        self.attack_ms = attack_ms;
+        self.release_ms = release_ms;
+        self.hold_ms = hold_ms;
+        self.update_coeffs();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_coeffs();
+    }
+
+    fn update_coeffs(&mut self) {
+        self.attack_coeff = smoothing_coeff(self.attack_ms, self.sample_rate);
+        self.release_coeff = smoothing_coeff(self.release_ms, self.sample_rate);
+        self.detector_decay = 1.0 - smoothing_coeff(GATE_DETECTOR_MS, self.sample_rate);
+        self.hold_samples = (self.hold_ms * self.sample_rate / 1000.0) as u32;
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        if !self.enabled {
+            return sample;
+        }
+        self.envelope = sample.abs().max(self.envelope * self.detector_decay);
+        if self.envelope >= self.threshold {
+            self.hold_left = self.hold_samples;
+        } else if self.hold_left > 0 {
+            self.hold_left -= 1;
+        }
+        let open = self.envelope >= self.threshold || self.hold_left > 0;
+        let (target, coeff) = if open {
+            (1.0, self.attack_coeff)
+        } else {
+            (0.0, self.release_coeff)
+        };
+        self.gain += (target - self.gain) * coeff;
+        sample * self.gain
+    }
+}
+
+/// Averaging time of the AGC level detector.
+const AGC_DETECTOR_MS: f32 = 400.0;
+/// Gain rises slowly (no breathing between words) and falls faster.
+const AGC_GAIN_UP_MS: f32 = 1500.0;
+const AGC_GAIN_DOWN_MS: f32 = 150.0;
+/// Release of the peak follower that caps the gain on transients.
+const AGC_PEAK_RELEASE_MS: f32 = 50.0;
+/// Output peaks are kept below this.
+const AGC_PEAK_CEILING: f32 = 0.9;
+/// Below this input RMS (-80 dBFS) the gain is held rather than raised.
+const AGC_NOISE_FLOOR: f32 = 1e-4;
+
+/// Automatic gain control on the mono mic input: a slow RMS detector steers
+/// the gain toward the target, capped at `max_gain`. The applied gain is further
+/// limited by a fast peak follower, so a transient is held under the ceiling
+/// for that moment without dragging the steady-state gain down (no pumping).
+struct Agc {
+    enabled: bool,
+    target_rms: f32,
+    max_gain: f32,
+    detector_coeff: f32,
+    up_coeff: f32,
+    down_coeff: f32,
+    peak_decay: f32,
+    power: f32,
+    peak: f32,
+    gain: f32,
+}
+
+impl Default for Agc {
+    fn default() -> Self {
+        let mut agc = Self {
+            enabled: false,
+            target_rms: 10f32.powf(-18.0 / 20.0),
+            max_gain: 10.0, // 20 dB
+            detector_coeff: 0.0,
+            up_coeff: 0.0,
+            down_coeff: 0.0,
+            peak_decay: 0.0,
+            power: 0.0,
+            peak: 0.0,
+            gain: 1.0,
+        };
+        agc.set_sample_rate(48000.0);
+        agc
+    }
+}
+
+/// AGC configuration as set through `set_agc`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AgcSettings {
+    pub enabled: bool,
+    pub target_dbfs: f32,
+    pub max_gain_db: f32,
+}
+
+impl Agc {
+    fn settings(&self) -> AgcSettings {
+        // Back to the 0.1 dB steps it was configured in
+        let db = |linear: f32| (200.0 * linear.log10()).round() / 10.0;
+        AgcSettings {
+            enabled: self.enabled,
+            target_dbfs: db(self.target_rms),
+            max_gain_db: db(self.max_gain),
+        }
+    }
+
+    fn configure(&mut self, enabled: bool, target_dbfs: f32, max_gain_db: f32) {
+        self.enabled = enabled;
+        self.target_rms = 10f32.powf(target_dbfs / 20.0);
+        self.max_gain = 10f32.powf(max_gain_db / 20.0);
+        self.gain = self.gain.min(self.max_gain);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.detector_coeff = smoothing_coeff(AGC_DETECTOR_MS, sample_rate);
+        self.up_coeff = smoothing_coeff(AGC_GAIN_UP_MS, sample_rate);
+        self.down_coeff = smoothing_coeff(AGC_GAIN_DOWN_MS, sample_rate);
+        self.peak_decay = 1.0 - smoothing_coeff(AGC_PEAK_RELEASE_MS, sample_rate);
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        if !self.enabled {
+            return sample;
+        }
+        self.power += (sample * sample - self.power) * self.detector_coeff;
+        let rms = self.power.sqrt();
+        if rms > AGC_NOISE_FLOOR {
+            let desired = (self.target_rms / rms).min(self.max_gain);
+            let coeff = if desired < self.gain {
+                self.down_coeff
+            } else {
+                self.up_coeff
+            };
+            self.gain += (desired - self.gain) * coeff;
+        }
+        self.peak = sample.abs().max(self.peak * self.peak_decay);
+        let gain = if self.peak * self.gain > AGC_PEAK_CEILING {
+            AGC_PEAK_CEILING / self.peak
+        } else {
+            self.gain
+        };
+        sample * gain
+    }
+}
+
+/// Averaging time of the compressor's RMS level detector; a few cycles of
+/// voice pitch, so the gain follows syllables without tracking the waveform.
+const COMPRESSOR_DETECTOR_MS: f32 = 5.0;
+/// Levels below this (-100 dBFS) are treated as silence by the detector.
+const COMPRESSOR_LEVEL_FLOOR: f32 = 1e-10;
+
+/// Soft-knee compressor on the monitoring output. An RMS detector feeds a
+/// static curve (`threshold_db`, `ratio`, a `knee_db` wide quadratic knee), and
+/// the resulting gain reduction moves with attack/release smoothing in dB.
+struct Compressor {
+    enabled: bool,
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    sample_rate: f32,
+    detector_coeff: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    power: f32,
+    reduction_db: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        let mut compressor = Self {
+            enabled: false,
+            threshold_db: -24.0,
+            ratio: 3.0,
+            knee_db: 6.0,
+            attack_ms: 10.0,
+            release_ms: 150.0,
+            sample_rate: 48000.0,
+            detector_coeff: 0.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            power: 0.0,
+            reduction_db: 0.0,
+        };
+        compressor.update_coeffs();
+        compressor
+    }
+}
+
+impl Compressor {
+    fn configure(
+        &mut self,
+        enabled: bool,
+        threshold_db: f32,
+        ratio: f32,
+        knee_db: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) {
+        self.enabled = enabled;
+        self.threshold_db = threshold_db;
+        self.ratio = ratio;
+        self.knee_db = knee_db;
+        self.attack_ms = attack_ms;
+        self.release_ms = release_ms;
+        self.update_coeffs();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_coeffs();
+    }
+
+    fn update_coeffs(&mut self) {
+        self.detector_coeff = smoothing_coeff(COMPRESSOR_DETECTOR_MS, self.sample_rate);
+        self.attack_coeff = smoothing_coeff(self.attack_ms, self.sample_rate);
+        self.release_coeff = smoothing_coeff(self.release_ms, self.sample_rate);
+    }
+
+    /// Static curve: gain reduction in dB (>= 0) for a detector level in dBFS.
+    fn reduction_for(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        let slope = 1.0 - 1.0 / self.ratio;
+        if 2.0 * over <= -self.knee_db {
+            0.0
+        } else if 2.0 * over < self.knee_db {
+            let into_knee = over + self.knee_db / 2.0;
+            slope * into_knee * into_knee / (2.0 * self.knee_db)
+        } else {
+            slope * over
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        if !self.enabled {
+            return sample;
+        }
+        self.power += (sample * sample - self.power) * self.detector_coeff;
+        let level_db = 10.0 * self.power.max(COMPRESSOR_LEVEL_FLOOR).log10();
+        let target = self.reduction_for(level_db);
+        let coeff = if target > self.reduction_db {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.reduction_db += (target - self.reduction_db) * coeff;
+        sample * 10f32.powf(-self.reduction_db / 20.0)
+    }
+}
+
+/// Run a whole buffer through an NS model offline, at unity volume. Output is
+/// what the model produces for recording (RNNoise drops its first frame, so it
+/// can be shorter than the input); "dummy" returns the input unchanged.
+pub fn denoise_buffer(
+    model_name: &str,
+    samples: &[f32],
+    sample_rate: f32,
+    noise: NoiseParams,
+) -> Vec<f32> {
+    let Some(mut ns) = NsState::for_model(model_name, sample_rate, sample_rate, 1.0, noise) else {
+        return samples.to_vec();
+    };
+    let mut out = Vec::with_capacity(samples.len());
+    for &sample in samples {
+        if let Some(processed) = ns.push_sample(sample) {
+            out.extend(processed);
+        }
+    }
+    out
+}
+
+// --- Audio hosts ---
+
+#[derive(serde::Serialize)]
+pub struct AudioHost {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// cpal host for a host id as listed by `get_audio_hosts` (case-insensitive).
+fn host_id_from_str(id: &str) -> Option<cpal::HostId> {
+    cpal::ALL_HOSTS
+        .iter()
+        .copied()
+        .find(|h| h.name().eq_ignore_ascii_case(id.trim()))
+}
+
+/// The requested audio backend; the platform default for None, "" or "default".
+fn audio_host(host: Option<&str>) -> Result<cpal::Host, CrispyError> {
+    let id = match host.map(str::trim) {
+        None | Some("") => return Ok(cpal::default_host()),
+        Some(id) if id.eq_ignore_ascii_case("default") => return Ok(cpal::default_host()),
+        Some(id) => host_id_from_str(id)
+            .ok_or_else(|| CrispyError::InvalidArgument(format!("Unknown audio host: {}", id)))?,
+    };
+    cpal::host_from_id(id).map_err(|e| {
+        CrispyError::Unsupported(format!("Audio host {} is unavailable: {}", id.name(), e))
+    })
+}
+
+#[tauri::command]
+pub fn get_audio_hosts() -> Vec<AudioHost> {
+    let default_id = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| AudioHost {
+            id: id.name().to_string(),
+            name: id.name().to_string(),
+            is_default: id == default_id,
+        })
+        .collect()
+}
+
 // --- Device list commands (no state) ---
 
+/// Error kind prefix for "this machine has no microphone"; the UI matches on it.
+pub const NO_INPUT_DEVICES: &str = "no_input_devices";
+
+/// Monitoring can't start because there is no microphone at all.
+fn no_input_devices_error() -> CrispyError {
+    CrispyError::NoInputDevices(format!(
+        "{}: Monitoring can't start because no microphone is connected.",
+        NO_INPUT_DEVICES
+    ))
+}
+
+fn emit_no_input_devices(app_handle: &tauri::AppHandle) {
+    let _ = app_handle.emit("no-input-devices", ());
+}
+
 #[tauri::command]
-pub fn get_input_devices() -> Result<Vec<AudioDevice>, String> {
-    let host = cpal::default_host();
+pub fn get_input_devices(
+    app_handle: tauri::AppHandle,
+    host: Option<String>,
+) -> Result<Vec<AudioDevice>, CrispyError> {
+    let host = audio_host(host.as_deref())?;
     match host.input_devices() {
         Ok(devices) => {
-            let mut result = Vec::new();
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    result.push(AudioDevice {
-                        id: name.clone(),
-                        name,
-                    });
-                }
-            }
+            let mut result: Vec<AudioDevice> =
+                labeled_devices(devices).into_iter().map(|(label, _)| label).collect();
             result.sort_by(|a, b| a.name.cmp(&b.name));
-            result.dedup_by(|a, b| a.name == b.name);
+            // No microphone is a valid (empty) list; the event prompts the UI
+            if result.is_empty() {
+                emit_no_input_devices(&app_handle);
+            }
             Ok(result)
         }
-        Err(e) => Err(format!("Failed to get input devices: {}", e)),
+        Err(e) => Err(format!("Failed to get input devices: {}", e).into()),
     }
 }
 
 #[tauri::command]
-pub fn get_output_devices() -> Result<Vec<AudioDevice>, String> {
-    let host = cpal::default_host();
+pub fn get_output_devices(host: Option<String>) -> Result<Vec<AudioDevice>, CrispyError> {
+    let host = audio_host(host.as_deref())?;
     match host.output_devices() {
         Ok(devices) => {
-            let mut result = Vec::new();
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    result.push(AudioDevice {
-                        id: name.clone(),
-                        name,
-                    });
-                }
-            }
+            let mut result: Vec<AudioDevice> =
+                labeled_devices(devices).into_iter().map(|(label, _)| label).collect();
             result.sort_by(|a, b| a.name.cmp(&b.name));
-            result.dedup_by(|a, b| a.name == b.name);
             Ok(result)
         }
-        Err(e) => Err(format!("Failed to get output devices: {}", e)),
+        Err(e) => Err(format!("Failed to get output devices: {}", e).into()),
     }
 }
 
 #[derive(serde::Serialize)]
 pub struct DefaultDevices {
     pub default_input: Option<String>,
+    /// Preferred BlackHole output (the first of `blackhole_outputs`).
     pub blackhole_output: Option<String>,
+    /// Every BlackHole output found, most suitable first.
+    pub blackhole_outputs: Vec<String>,
+}
+
+fn is_blackhole(name: &str) -> bool {
+    let name_lower = name.to_lowercase();
+    name_lower.contains("blackhole") || name_lower.contains("black hole")
+}
+
+/// Channel count from a BlackHole device name ("BlackHole 16ch" -> 16).
+fn blackhole_channels(name: &str) -> Option<u32> {
+    let name_lower = name.to_lowercase();
+    let before = name_lower[..name_lower.find("ch")?].trim_end();
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit())
+        .last()?
+        .0;
+    before[start..].parse().ok()
+}
+
+/// BlackHole devices in the order to pick them: 2ch first, since routing a
+/// mono mic needs no more, then the rest by channel count; names without one
+/// go last. Ties keep the system's order.
+pub fn rank_blackhole_devices(names: Vec<String>) -> Vec<String> {
+    let mut devices: Vec<String> = names.into_iter().filter(|name| is_blackhole(name)).collect();
+    devices.sort_by_key(|name| {
+        let channels = blackhole_channels(name);
+        (channels != Some(2), channels.unwrap_or(u32::MAX))
+    });
+    devices
 }
 
 #[tauri::command]
-pub fn get_default_devices() -> Result<DefaultDevices, String> {
-    let host = cpal::default_host();
+pub fn get_default_devices(host: Option<String>) -> Result<DefaultDevices, CrispyError> {
+    let host = audio_host(host.as_deref())?;
 
     let default_input = host
         .default_input_device()
         .and_then(|device| device.name().ok());
 
-    let mut blackhole_output: Option<String> = None;
-    if let Ok(devices) = host.output_devices() {
-        for device in devices {
-            if let Ok(name) = device.name() {
-                let name_lower = name.to_lowercase();
-                if name_lower.contains("blackhole") || name_lower.contains("black hole") {
-                    blackhole_output = Some(name);
-                    break;
-                }
-            }
-        }
-    }
+    let output_names = host
+        .output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default();
+    let blackhole_outputs = rank_blackhole_devices(output_names);
 
     Ok(DefaultDevices {
         default_input,
-        blackhole_output,
+        blackhole_output: blackhole_outputs.first().cloned(),
+        blackhole_outputs,
     })
 }
 
-// --- Monitoring: pub fns called from main with state ---
+/// A contiguous span of supported sample rates, in Hz.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct SampleRateRange {
+    pub min: u32,
+    pub max: u32,
+}
 
-pub fn start_monitoring(
-    audio: Arc<Mutex<AudioMonitorState>>,
-    recording_mic_buffer: Arc<Mutex<VecDeque<f32>>>,
-    app_handle: tauri::AppHandle,
-    device_name: String,
-    output_device_name: String,
-    model_name: String,
-    volume: f32,
-) -> Result<(), String> {
-    if device_name.trim().is_empty() {
-        return Err("No input device selected".to_string());
+/// What one direction (input or output) of a device supports.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct StreamCapabilities {
+    /// Merged and sorted; gaps are rates the device can't run at.
+    pub sample_rates: Vec<SampleRateRange>,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// Whether monitoring can run at 48 kHz rather than falling back.
+    pub supports_48k: bool,
+    pub channels: Vec<u16>,
+    pub sample_formats: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct DeviceCapabilities {
+    /// None when the device has no such direction.
+    pub input: Option<StreamCapabilities>,
+    pub output: Option<StreamCapabilities>,
+}
+
+/// One supported config range as reported by cpal.
+struct ConfigRange {
+    channels: u16,
+    min_rate: u32,
+    max_rate: u32,
+    format: String,
+}
+
+impl From<cpal::SupportedStreamConfigRange> for ConfigRange {
+    fn from(range: cpal::SupportedStreamConfigRange) -> Self {
+        Self {
+            channels: range.channels(),
+            min_rate: range.min_sample_rate(),
+            max_rate: range.max_sample_rate(),
+            format: range.sample_format().to_string(),
+        }
     }
+}
+
+/// Rate monitoring asks the mic for; RNNoise works natively at it.
+const MONITORING_RATE: u32 = 48000;
+
+/// How the mic's rate was picked for monitoring.
+#[derive(Debug, PartialEq)]
+struct InputRateChoice {
+    /// Supported range to open at 48kHz; None means the device default.
+    range: Option<usize>,
+    rate: u32,
+    negotiated_48k: bool,
+}
+
+/// The first range that covers 48kHz, else the device default rate.
+fn choose_input_rate(ranges: &[ConfigRange], default_rate: u32) -> InputRateChoice {
+    match ranges
+        .iter()
+        .position(|r| r.min_rate <= MONITORING_RATE && r.max_rate >= MONITORING_RATE)
+    {
+        Some(i) => InputRateChoice {
+            range: Some(i),
+            rate: MONITORING_RATE,
+            negotiated_48k: true,
+        },
+        None => InputRateChoice {
+            range: None,
+            rate: default_rate,
+            negotiated_48k: default_rate == MONITORING_RATE,
+        },
+    }
+}
+
+/// Sent when the mic couldn't be opened at 48kHz and monitoring runs at the
+/// device default instead.
+#[derive(Clone, serde::Serialize)]
+struct MonitoringRateFallbackEvent {
+    device: String,
+    sample_rate: u32,
+}
+
+/// Rate the monitoring mic actually runs at.
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+pub struct MonitoringInputRate {
+    pub sample_rate: u32,
+    pub negotiated_48k: bool,
+}
+
+/// The mic rate of the running monitoring streams; None when monitoring is off.
+pub fn monitoring_input_rate(audio: &Mutex<AudioMonitorState>) -> Option<MonitoringInputRate> {
+    let mon = audio.lock().unwrap();
+    mon.input_stream.as_ref()?;
+    Some(MonitoringInputRate {
+        sample_rate: mon.last_input_rate? as u32,
+        negotiated_48k: mon.negotiated_48k,
+    })
+}
+
+fn summarize_config_ranges(ranges: &[ConfigRange]) -> Option<StreamCapabilities> {
+    let mut spans: Vec<(u32, u32)> = ranges.iter().map(|r| (r.min_rate, r.max_rate)).collect();
+    spans.sort_unstable();
+    let mut sample_rates: Vec<SampleRateRange> = Vec::new();
+    for (min, max) in spans {
+        match sample_rates.last_mut() {
+            Some(last) if min <= last.max => last.max = last.max.max(max),
+            _ => sample_rates.push(SampleRateRange { min, max }),
+        }
+    }
+    let mut channels: Vec<u16> = ranges.iter().map(|r| r.channels).collect();
+    channels.sort_unstable();
+    channels.dedup();
+    let mut sample_formats: Vec<String> = Vec::new();
+    for range in ranges {
+        if !sample_formats.contains(&range.format) {
+            sample_formats.push(range.format.clone());
+        }
+    }
+    Some(StreamCapabilities {
+        min_sample_rate: sample_rates.first()?.min,
+        max_sample_rate: sample_rates.iter().map(|r| r.max).max()?,
+        supports_48k: sample_rates.iter().any(|r| r.min <= 48000 && r.max >= 48000),
+        sample_rates,
+        channels,
+        sample_formats,
+    })
+}
+
+/// Supported sample rates, channel counts and formats of a device, for both
+/// directions it has. "Default" means the default input device.
+#[tauri::command]
+pub fn get_device_capabilities(
+    device_name: String,
+    host: Option<String>,
+) -> Result<DeviceCapabilities, CrispyError> {
+    let host = audio_host(host.as_deref())?;
+    let input_device = if device_name == "Default" {
+        host.default_input_device()
+    } else {
+        host.input_devices()
+            .ok()
+            .and_then(|devices| find_device_by_id(devices, &device_name))
+    };
+    let output_device = host
+        .output_devices()
+        .ok()
+        .and_then(|devices| find_device_by_id(devices, &device_name));
+    if input_device.is_none() && output_device.is_none() {
+        return Err(CrispyError::DeviceNotFound(format!("Device not found: {}", device_name)));
+    }
+
+    let input = input_device
+        .and_then(|d| d.supported_input_configs().ok())
+        .and_then(|configs| summarize_config_ranges(&configs.map(ConfigRange::from).collect::<Vec<_>>()));
+    let output = output_device
+        .and_then(|d| d.supported_output_configs().ok())
+        .and_then(|configs| summarize_config_ranges(&configs.map(ConfigRange::from).collect::<Vec<_>>()));
+    Ok(DeviceCapabilities { input, output })
+}
+
+// --- Monitoring: pub fns called from main with state ---
+
+/// Restart attempts after the monitored device disappears, before giving up.
+const DEVICE_LOST_MAX_RESTARTS: u32 = 5;
+/// Wait before the first restart attempt; doubles with every further attempt.
+const DEVICE_LOST_BACKOFF_MS: u64 = 500;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct MonitoringDeviceLostEvent {
+    pub input_device: String,
+    pub output_device: String,
+    pub error: String,
+    /// False once restart attempts are exhausted and monitoring has stopped.
+    pub restarting: bool,
+}
+
+/// A lost device's streams, torn down off the audio callback thread.
+struct LostDevice {
+    input_device: String,
+    output_device: String,
+    streams: (Option<cpal::Stream>, Option<cpal::Stream>),
+}
+
+fn restart_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(DEVICE_LOST_BACKOFF_MS << attempt.saturating_sub(1).min(6))
+}
+
+/// Stream error callback body. A lost device is reported through `emit` once
+/// (input and output streams both fail on unplug) and its streams are handed
+/// back for teardown; other errors are only logged.
+fn handle_stream_error(
+    audio: &Mutex<AudioMonitorState>,
+    err: &cpal::StreamError,
+    emit: impl FnOnce(MonitoringDeviceLostEvent),
+) -> Option<LostDevice> {
+    eprintln!("Audio stream error: {}", err);
+    if !matches!(err, cpal::StreamError::DeviceNotAvailable) {
+        return None;
+    }
+    let mut mon = audio.lock().unwrap();
+    if mon.recovering {
+        return None;
+    }
+    let input_device = mon.current_input_device.clone()?;
+    let output_device = mon.current_output_device.clone().unwrap_or_default();
+    mon.recovering = true;
+    mon.shared = None;
+    let streams = (mon.input_stream.take(), mon.output_stream.take());
+    drop(mon);
+    emit(MonitoringDeviceLostEvent {
+        input_device: input_device.clone(),
+        output_device: output_device.clone(),
+        error: err.to_string(),
+        restarting: true,
+    });
+    Some(LostDevice {
+        input_device,
+        output_device,
+        streams,
+    })
+}
+
+/// How often the system default input is checked while monitoring "Default".
+const DEFAULT_DEVICE_POLL_MS: u64 = 2000;
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct DefaultDeviceChangedEvent {
+    pub previous: Option<String>,
+    pub current: String,
+}
+
+/// The new default input when it differs from the one monitoring was opened
+/// on. No default at all (e.g. mid-switch) is not a change.
+fn default_input_change(previous: Option<&str>, current: Option<&str>) -> Option<String> {
+    match current {
+        Some(current) if previous != Some(current) => Some(current.to_string()),
+        _ => None,
+    }
+}
+
+/// Everything needed to rebuild monitoring after its device was lost.
+#[derive(Clone)]
+struct MonitoringRestart {
+    audio: Arc<Mutex<AudioMonitorState>>,
+    recording_mic_buffer: Arc<Mutex<VecDeque<f32>>>,
+    app_handle: tauri::AppHandle,
+    host: Option<String>,
+}
+
+impl MonitoringRestart {
+    fn on_stream_error(&self, err: cpal::StreamError) {
+        // Only a lost device needs recovering; anything else is just logged
+        if !matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            eprintln!("Audio stream error: {}", err);
+            return;
+        }
+        // Handled off the audio thread: stop_monitoring drops the streams while
+        // holding the state lock, so a callback waiting on it would deadlock.
+        let restart = self.clone();
+        std::thread::spawn(move || {
+            let app_handle = restart.app_handle.clone();
+            let lost = handle_stream_error(&restart.audio, &err, |event| {
+                let _ = app_handle.emit("monitoring-device-lost", event);
+            });
+            if let Some(lost) = lost {
+                restart.recover(lost);
+            }
+        });
+    }
+
+    /// Reopen the same devices with growing backoff, with the model and
+    /// volume in use at that point. Gives up early if monitoring was stopped
+    /// or started on other devices in the meantime.
+    fn recover(self, lost: LostDevice) {
+        drop(lost.streams);
+        for attempt in 1..=DEVICE_LOST_MAX_RESTARTS {
+            std::thread::sleep(restart_backoff(attempt));
+            // Checked under the streams lock, so a stop_monitoring that runs
+            // first is seen here and one that runs later drops these streams
+            let _opening = MONITORING_STREAMS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let (model_name, volume) = {
+                let mon = self.audio.lock().unwrap();
+                if !mon.recovering {
+                    return;
+                }
+                (mon.model_name.clone(), mon.volume)
+            };
+            match open_monitoring_streams_locked(
+                self.audio.clone(),
+                self.recording_mic_buffer.clone(),
+                self.app_handle.clone(),
+                lost.input_device.clone(),
+                lost.output_device.clone(),
+                model_name,
+                volume,
+                self.host.clone(),
+            ) {
+                Ok(()) => {
+                    self.audio.lock().unwrap().recovering = false;
+                    return;
+                }
+                Err(e) => eprintln!("[audio] restart attempt {} failed: {}", attempt, e),
+            }
+        }
+        let _ = stop_monitoring(self.audio.clone());
+        let _ = self.app_handle.emit(
+            "monitoring-device-lost",
+            MonitoringDeviceLostEvent {
+                input_device: lost.input_device,
+                output_device: lost.output_device,
+                error: format!("Device did not come back after {} attempts", DEVICE_LOST_MAX_RESTARTS),
+                restarting: false,
+            },
+        );
+    }
+
+    /// Poll the system default input while monitoring "Default" and move the
+    /// streams, with the current model and volume, to the new default when it
+    /// changes. Exits once monitoring is
+    /// stopped or its streams are reopened (`generation` no longer current).
+    fn watch_default_input(self, generation: u64, output_device: String, opened_on: Option<String>) {
+        std::thread::spawn(move || {
+            let mut previous = opened_on;
+            loop {
+                std::thread::sleep(Duration::from_millis(DEFAULT_DEVICE_POLL_MS));
+                {
+                    let mon = self.audio.lock().unwrap();
+                    if mon.streams_generation != generation || mon.input_stream.is_none() {
+                        return;
+                    }
+                    // Device-loss recovery is already reopening "Default"
+                    if mon.recovering {
+                        continue;
+                    }
+                }
+                let current = audio_host(self.host.as_deref())
+                    .ok()
+                    .and_then(|host| host.default_input_device())
+                    .and_then(|device| device.name().ok());
+                let Some(current) = default_input_change(previous.as_deref(), current.as_deref())
+                else {
+                    continue;
+                };
+                eprintln!(
+                    "[audio] default input changed from {:?} to {}, reopening monitoring",
+                    previous, current
+                );
+                let _ = self.app_handle.emit(
+                    "default-device-changed",
+                    DefaultDeviceChangedEvent {
+                        previous: previous.clone(),
+                        current: current.clone(),
+                    },
+                );
+                let (model_name, volume) = {
+                    let mut mon = self.audio.lock().unwrap();
+                    if mon.streams_generation != generation {
+                        return;
+                    }
+                    // The selection is still "Default", so force the rebuild
+                    mon.current_input_device = None;
+                    (mon.model_name.clone(), mon.volume)
+                };
+                // A successful open starts its own watcher; on failure keep polling
+                match open_monitoring_streams(
+                    self.audio.clone(),
+                    self.recording_mic_buffer.clone(),
+                    self.app_handle.clone(),
+                    "Default".to_string(),
+                    output_device.clone(),
+                    model_name,
+                    volume,
+                    self.host.clone(),
+                ) {
+                    Ok(()) => return,
+                    Err(e) => {
+                        eprintln!("[audio] reopening on the new default input failed: {}", e);
+                        previous = Some(current);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Start monitoring on the given cpal host (the platform default for None).
+/// If the device is unplugged later, the streams are rebuilt on the same
+/// devices automatically (see `MonitoringRestart`).
+#[allow(clippy::too_many_arguments)]
+pub fn start_monitoring(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    recording_mic_buffer: Arc<Mutex<VecDeque<f32>>>,
+    app_handle: tauri::AppHandle,
+    device_name: String,
+    output_device_name: String,
+    model_name: String,
+    volume: f32,
+    host: Option<String>,
+) -> Result<(), CrispyError> {
+    // An explicit start supersedes any pending recovery
+    audio.lock().unwrap().recovering = false;
+    open_monitoring_streams(
+        audio,
+        recording_mic_buffer,
+        app_handle,
+        device_name,
+        output_device_name,
+        model_name,
+        volume,
+        host,
+    )
+}
+
+/// Held while monitoring streams are opened or dropped. The settings UI, the
+/// launch-time resume and device recovery can all start monitoring at once;
+/// serialized, the later start finds the streams the first one opened.
+static MONITORING_STREAMS_LOCK: Mutex<()> = Mutex::new(());
+
+#[allow(clippy::too_many_arguments)]
+fn open_monitoring_streams(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    recording_mic_buffer: Arc<Mutex<VecDeque<f32>>>,
+    app_handle: tauri::AppHandle,
+    device_name: String,
+    output_device_name: String,
+    model_name: String,
+    volume: f32,
+    host_name: Option<String>,
+) -> Result<(), CrispyError> {
+    let _opening = MONITORING_STREAMS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    open_monitoring_streams_locked(
+        audio,
+        recording_mic_buffer,
+        app_handle,
+        device_name,
+        output_device_name,
+        model_name,
+        volume,
+        host_name,
+    )
+}
+
+/// `open_monitoring_streams` for a caller already holding
+/// `MONITORING_STREAMS_LOCK`.
+#[allow(clippy::too_many_arguments)]
+fn open_monitoring_streams_locked(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    recording_mic_buffer: Arc<Mutex<VecDeque<f32>>>,
+    app_handle: tauri::AppHandle,
+    device_name: String,
+    output_device_name: String,
+    model_name: String,
+    volume: f32,
+    host_name: Option<String>,
+) -> Result<(), CrispyError> {
+    if device_name.trim().is_empty() {
+        return Err(CrispyError::InvalidArgument("No input device selected".to_string()));
+    }
+    let host = audio_host(host_name.as_deref())?;
 
     {
         let mut mon = audio.lock().unwrap();
@@ -457,7 +1602,8 @@ pub fn start_monitoring(
         // Model/volume changes are handled by set_monitoring_model/set_monitoring_volume.
         let same_input = mon.current_input_device.as_deref() == Some(device_name.as_str());
         let same_output = mon.current_output_device.as_deref() == Some(output_device_name.as_str());
-        if mon.input_stream.is_some() && same_input && same_output {
+        let same_host = mon.current_host == Some(host.id());
+        if mon.input_stream.is_some() && same_input && same_output && same_host {
             return Ok(());
         }
 
@@ -466,28 +1612,41 @@ pub fn start_monitoring(
         mon.shared = None;
         mon.current_input_device = None;
         mon.current_output_device = None;
+        mon.current_host = None;
     }
 
-    let host = cpal::default_host();
+    let has_inputs = host
+        .input_devices()
+        .map(|mut devices| devices.next().is_some())
+        .unwrap_or(false);
+    if !has_inputs && host.default_input_device().is_none() {
+        emit_no_input_devices(&app_handle);
+        return Err(no_input_devices_error());
+    }
 
     let device = if device_name == "Default" {
         host.default_input_device()
     } else {
-        host.input_devices()
-            .map_err(|e| e.to_string())?
-            .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
+        find_device_by_id(host.input_devices().map_err(|e| e.to_string())?, &device_name)
     }
-    .ok_or("Failed to find input device")?;
+    .ok_or_else(|| CrispyError::DeviceNotFound("Failed to find input device".to_string()))?;
 
     // Try to force 48kHz to avoid pitch issues
     let default_config = device.default_input_config().map_err(|e| e.to_string())?;
-    
-    // Check if we can use 48kHz
-    let config = if let Ok(mut configs) = device.supported_input_configs() {
-        if let Some(range) = configs.find(|c| c.min_sample_rate() <= 48000 && c.max_sample_rate() >= 48000) {
-            // Device supports 48kHz - use it
-            range.with_sample_rate(48000)
-        } else {
+    let ranges: Vec<cpal::SupportedStreamConfigRange> = match device.supported_input_configs() {
+        Ok(configs) => configs.collect(),
+        Err(_) => {
+            if audio_debug_enabled() {
+                eprintln!("Warning: Could not query supported configs, using default");
+            }
+            Vec::new()
+        }
+    };
+    let summary: Vec<ConfigRange> = ranges.iter().cloned().map(ConfigRange::from).collect();
+    let choice = choose_input_rate(&summary, default_config.sample_rate());
+    let config = match choice.range {
+        Some(i) => ranges[i].clone().with_sample_rate(MONITORING_RATE),
+        None => {
             if audio_debug_enabled() {
                 eprintln!(
                     "Warning: Device doesn't support 48kHz, using default ({}Hz)",
@@ -496,26 +1655,29 @@ pub fn start_monitoring(
             }
             default_config
         }
-    } else {
-        if audio_debug_enabled() {
-            eprintln!("Warning: Could not query supported configs, using default");
-        }
-        default_config
     };
 
     let input_channels = config.channels() as usize;
     let input_sample_format = config.sample_format();
     let input_config: cpal::StreamConfig = config.clone().into();
-    let err_fn = |err| eprintln!("Audio stream error: {}", err);
+    let restart = MonitoringRestart {
+        audio: audio.clone(),
+        recording_mic_buffer: recording_mic_buffer.clone(),
+        app_handle: app_handle.clone(),
+        host: host_name,
+    };
+    let watcher = (device_name == "Default").then(|| restart.clone());
+    let err_fn = move |err| restart.on_stream_error(err);
 
     let output_device = if output_device_name.trim().is_empty() {
         None
     } else if output_device_name == "Default" {
         host.default_output_device()
     } else {
-        host.output_devices()
-            .map_err(|e| e.to_string())?
-            .find(|d| d.name().map(|n| n == output_device_name).unwrap_or(false))
+        find_device_by_id(
+            host.output_devices().map_err(|e| e.to_string())?,
+            &output_device_name,
+        )
     };
 
     let (output_config, output_channels, output_sample_format, output_stream_config) =
@@ -537,62 +1699,99 @@ pub fn start_monitoring(
         };
 
     // Create noise suppression processor regardless of output device
-    // (recording needs it even without monitoring)
-    let input_rate = config.sample_rate() as f32;
-    let output_rate = output_config.as_ref().map(|c| c.sample_rate() as f32).unwrap_or(input_rate);
+    // (recording needs it even without monitoring). It runs at the recording
+    // rate: the input callback resamples the mic to it once.
+    let processing_rate = recording::SAMPLE_RATE as f32;
+    let output_rate = output_config
+        .as_ref()
+        .map(|c| c.sample_rate() as f32)
+        .unwrap_or(processing_rate);
     let vol = volume.clamp(0.0, 1.0);
-    
-    let shared: Option<Arc<Mutex<NsState>>> = if model_name == "dummy" || model_name.is_empty() {
-        None
-    } else {
-        let ns = if model_name == "rnnnoise" {
-            NsState::RnnNoise(RnnNoiseProcessor::new(input_rate, output_rate, vol))
-        } else {
-            NsState::Legacy(SharedAudio::new(
-                input_rate,
-                output_rate,
-                ModelKind::from_name(&model_name),
-                vol,
-            ))
-        };
-        Some(Arc::new(Mutex::new(ns)))
+    let settings = crate::settings::load_app_settings(&app_handle).unwrap_or_default();
+    let low_latency_start = settings.rnnoise_low_latency == "true";
+    let noise_params = {
+        let mut mon = audio.lock().unwrap();
+        mon.low_latency_start = low_latency_start;
+        mon.model_name = model_name.clone();
+        mon.volume = vol;
+        mon.noise_params
     };
+    
+    let shared: Option<Arc<Mutex<NsState>>> =
+        NsState::for_model(&model_name, processing_rate, output_rate, vol, noise_params)
+            .map(|mut ns| {
+                ns.set_low_latency_start(low_latency_start);
+                Arc::new(Mutex::new(ns))
+            });
 
     let last_emit = Arc::new(Mutex::new(Instant::now()));
+    let agc = audio.lock().unwrap().agc.clone();
+    agc.lock().unwrap().set_sample_rate(processing_rate);
+    let (channel_metering, waveform_decimation) = {
+        let mon = audio.lock().unwrap();
+        (mon.channel_metering.clone(), mon.waveform_decimation.clone())
+    };
+    let resampler_kind = settings.resampler_quality();
 
     let input_stream = match input_sample_format {
-        cpal::SampleFormat::F32 => build_input_stream_f32(
+        cpal::SampleFormat::F32 => build_input_stream::<f32, _>(
             &device,
             &input_config,
             input_channels,
             shared.clone(),
+            agc.clone(),
             recording_mic_buffer.clone(),
             last_emit.clone(),
+            channel_metering.clone(),
+            waveform_decimation.clone(),
             app_handle.clone(),
-            err_fn,
+            resampler_kind,
+            err_fn.clone(),
         )?,
-        cpal::SampleFormat::I16 => build_input_stream_i16(
+        cpal::SampleFormat::I16 => build_input_stream::<i16, _>(
             &device,
             &input_config,
             input_channels,
             shared.clone(),
+            agc.clone(),
             recording_mic_buffer.clone(),
             last_emit.clone(),
+            channel_metering.clone(),
+            waveform_decimation.clone(),
             app_handle.clone(),
-            err_fn,
+            resampler_kind,
+            err_fn.clone(),
         )?,
-        cpal::SampleFormat::U16 => build_input_stream_u16(
+        cpal::SampleFormat::U16 => build_input_stream::<u16, _>(
             &device,
             &input_config,
             input_channels,
             shared.clone(),
+            agc.clone(),
             recording_mic_buffer.clone(),
             last_emit.clone(),
+            channel_metering.clone(),
+            waveform_decimation.clone(),
             app_handle.clone(),
-            err_fn,
+            resampler_kind,
+            err_fn.clone(),
         )?,
-        _ => return Err(format!("Unsupported sample format: {}", input_sample_format)),
+        _ => {
+            return Err(CrispyError::Unsupported(format!(
+                "Unsupported sample format: {}",
+                input_sample_format
+            )))
+        }
+    };
+
+    let (gate, compressor) = {
+        let mon = audio.lock().unwrap();
+        (mon.gate.clone(), mon.compressor.clone())
     };
+    if let Some(rate) = output_config.as_ref().map(|c| c.sample_rate() as f32) {
+        gate.lock().unwrap().set_sample_rate(rate);
+        compressor.lock().unwrap().set_sample_rate(rate);
+    }
 
     let output_stream = if let (Some(output_device), Some(output_stream_config), Some(output_channels), Some(output_sample_format), Some(shared_out)) =
         (
@@ -604,58 +1803,42 @@ pub fn start_monitoring(
         )
     {
         let s = match output_sample_format {
-            cpal::SampleFormat::F32 => output_device
-                .build_output_stream(
-                    &output_stream_config,
-                    move |data: &mut [f32], _: &_| {
-                        let mut shared = shared_out.lock().unwrap();
-                        for frame in data.chunks_mut(output_channels) {
-                            let sample = shared.next_sample();
-                            for out in frame.iter_mut() {
-                                *out = sample;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| e.to_string())?,
-            cpal::SampleFormat::I16 => output_device
-                .build_output_stream(
-                    &output_stream_config,
-                    move |data: &mut [i16], _: &_| {
-                        let mut shared = shared_out.lock().unwrap();
-                        for frame in data.chunks_mut(output_channels) {
-                            let sample = shared.next_sample();
-                            let clamped = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
-                            for out in frame.iter_mut() {
-                                *out = clamped;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| e.to_string())?,
-            cpal::SampleFormat::U16 => output_device
-                .build_output_stream(
-                    &output_stream_config,
-                    move |data: &mut [u16], _: &_| {
-                        let mut shared = shared_out.lock().unwrap();
-                        for frame in data.chunks_mut(output_channels) {
-                            let sample = shared.next_sample();
-                            let clamped = (sample.clamp(-1.0, 1.0) * 0.5 + 0.5) * 65535.0;
-                            let out_sample = clamped as u16;
-                            for out in frame.iter_mut() {
-                                *out = out_sample;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .map_err(|e| e.to_string())?,
-            _ => return Err(format!("Unsupported output sample format: {}", output_sample_format)),
+            cpal::SampleFormat::F32 => build_output_stream::<f32, _>(
+                &output_device,
+                &output_stream_config,
+                output_channels,
+                shared_out,
+                gate.clone(),
+                compressor.clone(),
+                app_handle.clone(),
+                err_fn,
+            )?,
+            cpal::SampleFormat::I16 => build_output_stream::<i16, _>(
+                &output_device,
+                &output_stream_config,
+                output_channels,
+                shared_out,
+                gate.clone(),
+                compressor.clone(),
+                app_handle.clone(),
+                err_fn,
+            )?,
+            cpal::SampleFormat::U16 => build_output_stream::<u16, _>(
+                &output_device,
+                &output_stream_config,
+                output_channels,
+                shared_out,
+                gate.clone(),
+                compressor.clone(),
+                app_handle.clone(),
+                err_fn,
+            )?,
+            _ => {
+                return Err(CrispyError::Unsupported(format!(
+                    "Unsupported output sample format: {}",
+                    output_sample_format
+                )))
+            }
         };
         Some(s)
     } else {
@@ -673,178 +1856,360 @@ pub fn start_monitoring(
     mon.shared = shared.clone();
     mon.last_input_rate = Some(config.sample_rate() as f32);
     mon.last_output_rate = output_config.as_ref().map(|c| c.sample_rate() as f32);
-    mon.current_input_device = Some(device_name);
-    mon.current_output_device = Some(output_device_name);
+    mon.negotiated_48k = choice.negotiated_48k;
+    mon.current_input_device = Some(device_name.clone());
+    mon.current_output_device = Some(output_device_name.clone());
+    mon.current_host = Some(host.id());
+    mon.streams_generation += 1;
+    let generation = mon.streams_generation;
+    drop(mon);
+
+    if !choice.negotiated_48k {
+        let _ = app_handle.emit(
+            "monitoring-rate-fallback",
+            MonitoringRateFallbackEvent {
+                device: device_name,
+                sample_rate: choice.rate,
+            },
+        );
+    }
+
+    if let Some(watcher) = watcher {
+        watcher.watch_default_input(generation, output_device_name, device.name().ok());
+    }
 
     Ok(())
 }
 
+/// Bring one raw mic sample to the processing rate (the recording rate),
+/// then run it through AGC and noise suppression into the recording buffer.
+/// This is the recording path's only resampling stage: processors run at
+/// `recording::SAMPLE_RATE`, so their output is recorded as is, and the
+/// monitoring output converts from that rate once.
+#[allow(clippy::too_many_arguments)]
 fn push_mono_to_buffers(
     shared: Option<&Arc<Mutex<NsState>>>,
-    rec_resampler: &mut LinearResampler,
+    agc: &mut Agc,
+    input_resampler: &mut StreamResampler,
     rec_buffer: &Mutex<VecDeque<f32>>,
     mono: f32,
     raw_input_rate_hz: f32,
     sum: &mut f32,
     frames: &mut f32,
 ) {
-    // Collect (produced_rate, samples) without holding locks while pushing into rec_buffer.
-    let (produced_rate_hz, samples_opt): (f32, Option<Vec<f32>>) = if let Some(shared) = shared {
-        let mut guard = shared.lock().unwrap();
-        let rate = guard.produced_rate_hz();
-        let samples = guard.push_sample(mono);
-        (rate, samples)
-    } else {
-        (raw_input_rate_hz, Some(vec![mono]))
-    };
+    let processing_rate_hz = recording::SAMPLE_RATE as f32;
+
+    // Reconfigure only when a rate actually changed; set_rates keeps the
+    // resampler's state, so the recording has no gap at the switch.
+    let (cur_in, cur_out) = input_resampler.rates();
+    if (cur_in - raw_input_rate_hz).abs() >= 1.0 || (cur_out - processing_rate_hz).abs() >= 1.0 {
+        input_resampler.set_rates(raw_input_rate_hz, processing_rate_hz);
+    }
+    let mut resampled = Vec::with_capacity(2);
+    input_resampler.process_sample(mono, |s| resampled.push(s));
+
+    // Collect the processed samples without holding the recording buffer
+    // lock while the processor runs.
+    let mut out = Vec::with_capacity(resampled.len());
+    for sample in resampled {
+        let sample = agc.process(sample);
+        *sum += sample * sample;
+        *frames += 1.0;
+        match shared {
+            Some(shared) => {
+                if let Some(processed) = shared.lock().unwrap().push_sample(sample) {
+                    out.extend(processed);
+                }
+            }
+            None => out.push(sample),
+        }
+    }
+
+    if !out.is_empty() {
+        let mut rec_buf = rec_buffer.lock().unwrap();
+        let max_len = recording::SAMPLE_RATE * recording::ROLLING_BUFFER_SECONDS;
+        for sample in out {
+            if rec_buf.len() >= max_len {
+                rec_buf.pop_front();
+            }
+            rec_buf.push_back(sample);
+        }
+    }
+}
 
-    if let Some(samples) = samples_opt {
-        let target_rate_hz = recording::SAMPLE_RATE as f32;
+/// Default for `set_microphone_waveform`: keep 1 raw sample in 16.
+pub const DEFAULT_WAVEFORM_DECIMATION: usize = 16;
+/// How often a `microphone-waveform` batch is sent.
+const WAVEFORM_EMIT_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Raw mic samples since the previous `microphone-waveform`.
+#[derive(Clone, serde::Serialize)]
+struct WaveformEvent {
+    /// Rate of `samples` after decimation.
+    sample_rate: f32,
+    samples: Vec<f32>,
+}
 
-        // Reconfigure resampler only if rates changed (don't reset it every call).
-        let (cur_in, cur_out) = rec_resampler.rates();
-        if (cur_in - produced_rate_hz).abs() >= 1.0 || (cur_out - target_rate_hz).abs() >= 1.0 {
-            rec_resampler.set_rates(produced_rate_hz, target_rate_hz);
+/// Keep every `factor`th sample of `samples`, continuing from `phase` (position
+/// within the current run of `factor`) so the spacing holds across callbacks.
+pub fn decimate_into(samples: &[f32], factor: usize, phase: &mut usize, out: &mut Vec<f32>) {
+    let factor = factor.max(1);
+    for &sample in samples {
+        if *phase == 0 {
+            out.push(sample);
         }
+        *phase = (*phase + 1) % factor;
+    }
+}
+
+/// Decimated mono batch for `microphone-waveform`; lives in the input
+/// callback.
+struct WaveformTap {
+    phase: usize,
+    batch: Vec<f32>,
+    last_emit: Instant,
+}
 
-        // Resample into a temp vec to avoid locking the recording buffer per emitted sample.
-        let mut out = Vec::with_capacity(samples.len().saturating_mul(2));
-        for s in samples {
-            rec_resampler.process_sample(s, |o| out.push(o));
+impl WaveformTap {
+    fn new() -> Self {
+        Self {
+            phase: 0,
+            batch: Vec::new(),
+            last_emit: Instant::now(),
         }
+    }
 
-        if !out.is_empty() {
-            let mut rec_buf = rec_buffer.lock().unwrap();
-            let max_len = recording::SAMPLE_RATE * 10;
-            for sample in out {
-                if rec_buf.len() >= max_len {
-                    rec_buf.pop_front();
-                }
-                rec_buf.push_back(sample);
-            }
+    fn push(&mut self, sample: f32, factor: usize) {
+        decimate_into(&[sample], factor, &mut self.phase, &mut self.batch);
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0;
+        self.batch.clear();
+    }
+
+    /// The batch, once per emit interval.
+    fn take_due(&mut self) -> Option<Vec<f32>> {
+        if self.batch.is_empty() || self.last_emit.elapsed() < WAVEFORM_EMIT_INTERVAL {
+            return None;
         }
+        self.last_emit = Instant::now();
+        Some(std::mem::take(&mut self.batch))
     }
 
-    *sum += mono * mono;
-    *frames += 1.0;
+    /// End of an input callback: send the batch as `microphone-waveform` when
+    /// due, or drop it while the waveform is off (`factor` 0).
+    fn emit_due(&mut self, app_handle: &tauri::AppHandle, input_rate: f32, factor: usize) {
+        if factor == 0 {
+            self.reset();
+        } else if let Some(samples) = self.take_due() {
+            let _ = app_handle.emit(
+                "microphone-waveform",
+                WaveformEvent {
+                    sample_rate: input_rate / factor as f32,
+                    samples,
+                },
+            );
+        }
+    }
 }
 
-fn build_input_stream_f32<F>(
-    device: &cpal::Device,
-    config: &cpal::StreamConfig,
-    input_channels: usize,
-    shared: Option<Arc<Mutex<NsState>>>,
-    rec_buffer: Arc<Mutex<VecDeque<f32>>>,
-    last_emit: Arc<Mutex<Instant>>,
-    app_handle: tauri::AppHandle,
-    err_fn: F,
-) -> Result<cpal::Stream, String>
-where
-    F: FnMut(cpal::StreamError) + Send + 'static,
-{
-    let input_rate = config.sample_rate as f32;
-    let mut resampler = LinearResampler::new(input_rate, recording::SAMPLE_RATE as f32);
+/// A delivery gap this many buffer lengths long counts as an xrun.
+const XRUN_GAP_FACTOR: f64 = 1.5;
+/// Timestamp jitter tolerated on top of the expected interval.
+const XRUN_SLACK_SECONDS: f64 = 0.002;
+
+/// Sent when a monitoring stream misses audio; `count` is the total for that
+/// stream since monitoring started.
+#[derive(Clone, serde::Serialize)]
+struct XrunEvent {
+    stream: &'static str,
+    count: u64,
+}
 
-    device
-        .build_input_stream(
-            config,
-            move |data: &[f32], _: &_| {
-                let mut sum = 0.0;
-                let mut frames = 0.0;
-                for frame in data.chunks(input_channels) {
-                    let mono = frame.iter().sum::<f32>() / input_channels as f32;
-                    
-                    if let Some(shared) = shared.as_ref() {
-                        push_mono_to_buffers(
-                            Some(shared),
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
-                    } else {
-                        push_mono_to_buffers(
-                            None,
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
-                    }
-                }
-                if frames > 0.0 {
-                    let rms = (sum / frames).sqrt();
-                    let mut last = last_emit.lock().unwrap();
-                    if last.elapsed() >= Duration::from_millis(16) {
-                        *last = Instant::now();
-                        let _ = app_handle.emit("microphone-level", rms);
-                    }
-                }
-            },
-            err_fn,
-            None,
-        )
-        .map_err(|e| e.to_string())
+/// Spots dropouts from callback timestamps. Each buffer should follow the
+/// previous one by that buffer's duration; a noticeably longer gap means the
+/// device dropped or inserted audio.
+pub struct XrunDetector {
+    stream: &'static str,
+    sample_rate: f64,
+    origin: Option<cpal::StreamInstant>,
+    /// Timestamp (seconds) and frame count of the previous buffer.
+    last: Option<(f64, usize)>,
+    count: u64,
+}
+
+impl XrunDetector {
+    pub fn new(stream: &'static str, sample_rate: f64) -> Self {
+        Self {
+            stream,
+            sample_rate,
+            origin: None,
+            last: None,
+            count: 0,
+        }
+    }
+
+    /// Record a buffer of `frames` stamped `timestamp` seconds; true if the
+    /// gap since the previous buffer is an xrun.
+    pub fn observe(&mut self, timestamp: f64, frames: usize) -> bool {
+        let xrun = self.last.is_some_and(|(previous, previous_frames)| {
+            let expected = previous_frames as f64 / self.sample_rate;
+            timestamp - previous > expected * XRUN_GAP_FACTOR + XRUN_SLACK_SECONDS
+        });
+        self.last = Some((timestamp, frames));
+        if xrun {
+            self.count += 1;
+        }
+        xrun
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// `observe` for a cpal callback; emits `audio-xrun` on a gap.
+    fn on_callback(&mut self, app_handle: &tauri::AppHandle, instant: cpal::StreamInstant, frames: usize) {
+        let origin = *self.origin.get_or_insert(instant);
+        let timestamp = instant
+            .duration_since(&origin)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        if self.observe(timestamp, frames) {
+            let _ = app_handle.emit(
+                "audio-xrun",
+                XrunEvent {
+                    stream: self.stream,
+                    count: self.count,
+                },
+            );
+        }
+    }
+}
+
+/// RMS and peak of one input channel over a callback, before the downmix.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct ChannelLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Per-channel accumulator for `microphone-channels`; lives in the input
+/// callback and is reset every call, so it doesn't allocate there.
+struct ChannelMeter {
+    sum_squares: Vec<f32>,
+    peak: Vec<f32>,
+    frames: usize,
+}
+
+impl ChannelMeter {
+    fn new(channels: usize) -> Self {
+        Self {
+            sum_squares: vec![0.0; channels],
+            peak: vec![0.0; channels],
+            frames: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sum_squares.fill(0.0);
+        self.peak.fill(0.0);
+        self.frames = 0;
+    }
+
+    /// One interleaved frame, already converted to f32.
+    fn add_frame(&mut self, frame: impl Iterator<Item = f32>) {
+        for ((sum, peak), sample) in self.sum_squares.iter_mut().zip(self.peak.iter_mut()).zip(frame) {
+            *sum += sample * sample;
+            *peak = peak.max(sample.abs());
+        }
+        self.frames += 1;
+    }
+
+    fn levels(&self) -> Vec<ChannelLevel> {
+        let frames = self.frames.max(1) as f32;
+        self.sum_squares
+            .iter()
+            .zip(&self.peak)
+            .map(|(&sum, &peak)| ChannelLevel {
+                rms: (sum / frames).sqrt(),
+                peak,
+            })
+            .collect()
+    }
 }
 
-fn build_input_stream_i16<F>(
+/// Monitoring input stream for any sample format; samples are converted to
+/// f32 before the downmix.
+#[allow(clippy::too_many_arguments)]
+fn build_input_stream<T, F>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
     input_channels: usize,
     shared: Option<Arc<Mutex<NsState>>>,
+    agc: Arc<Mutex<Agc>>,
     rec_buffer: Arc<Mutex<VecDeque<f32>>>,
     last_emit: Arc<Mutex<Instant>>,
+    channel_metering: Arc<AtomicBool>,
+    waveform_decimation: Arc<AtomicUsize>,
     app_handle: tauri::AppHandle,
+    resampler_kind: ResamplerKind,
     err_fn: F,
 ) -> Result<cpal::Stream, String>
 where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
     F: FnMut(cpal::StreamError) + Send + 'static,
 {
     let input_rate = config.sample_rate as f32;
-    let mut resampler = LinearResampler::new(input_rate, recording::SAMPLE_RATE as f32);
+    let mut resampler =
+        StreamResampler::new(resampler_kind, input_rate, recording::SAMPLE_RATE as f32);
+    let mut channel_meter = ChannelMeter::new(input_channels);
+    let mut xruns = XrunDetector::new("input", input_rate as f64);
+    let mut waveform = WaveformTap::new();
 
     device
         .build_input_stream(
             config,
-            move |data: &[i16], _: &_| {
+            move |data: &[T], info: &cpal::InputCallbackInfo| {
+                xruns.on_callback(&app_handle, info.timestamp().capture, data.len() / input_channels);
                 let mut sum = 0.0;
                 let mut frames = 0.0;
+                let mut agc = agc.lock().unwrap();
+                let metering = channel_metering.load(Ordering::Relaxed);
+                let decimation = waveform_decimation.load(Ordering::Relaxed);
+                channel_meter.reset();
                 for frame in data.chunks(input_channels) {
-                    let mono = frame.iter().map(|&s| s as f32 / 32768.0).sum::<f32>()
-                        / input_channels as f32;
-                    
-                    if let Some(shared) = shared.as_ref() {
-                        push_mono_to_buffers(
-                            Some(shared),
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
-                    } else {
-                        push_mono_to_buffers(
-                            None,
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
+                    let samples = frame.iter().map(|&s| cpal::Sample::to_sample::<f32>(s));
+                    if metering {
+                        channel_meter.add_frame(samples.clone());
+                    }
+                    let mono = samples.sum::<f32>() / input_channels as f32;
+                    if decimation > 0 {
+                        waveform.push(mono, decimation);
                     }
+
+                    push_mono_to_buffers(
+                        shared.as_ref(),
+                        &mut agc,
+                        &mut resampler,
+                        &rec_buffer,
+                        mono,
+                        input_rate,
+                        &mut sum,
+                        &mut frames,
+                    );
                 }
+                waveform.emit_due(&app_handle, input_rate, decimation);
                 if frames > 0.0 {
                     let rms = (sum / frames).sqrt();
                     let mut last = last_emit.lock().unwrap();
                     if last.elapsed() >= Duration::from_millis(16) {
                         *last = Instant::now();
                         let _ = app_handle.emit("microphone-level", rms);
+                        if metering {
+                            let _ = app_handle.emit("microphone-channels", channel_meter.levels());
+                        }
                     }
                 }
             },
@@ -854,64 +2219,36 @@ where
         .map_err(|e| e.to_string())
 }
 
-fn build_input_stream_u16<F>(
+/// Monitoring output stream for any sample format: the processed mic through
+/// the gate and compressor, the same sample on every channel.
+#[allow(clippy::too_many_arguments)]
+fn build_output_stream<T, F>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
-    input_channels: usize,
-    shared: Option<Arc<Mutex<NsState>>>,
-    rec_buffer: Arc<Mutex<VecDeque<f32>>>,
-    last_emit: Arc<Mutex<Instant>>,
+    output_channels: usize,
+    shared: Arc<Mutex<NsState>>,
+    gate: Arc<Mutex<NoiseGate>>,
+    compressor: Arc<Mutex<Compressor>>,
     app_handle: tauri::AppHandle,
     err_fn: F,
 ) -> Result<cpal::Stream, String>
 where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
     F: FnMut(cpal::StreamError) + Send + 'static,
 {
-    let input_rate = config.sample_rate as f32;
-    let mut resampler = LinearResampler::new(input_rate, recording::SAMPLE_RATE as f32);
+    let mut xruns = XrunDetector::new("output", config.sample_rate as f64);
 
     device
-        .build_input_stream(
+        .build_output_stream(
             config,
-            move |data: &[u16], _: &_| {
-                let mut sum = 0.0;
-                let mut frames = 0.0;
-                for frame in data.chunks(input_channels) {
-                    let mono = frame
-                        .iter()
-                        .map(|&s| (s as f32 - 32768.0) / 32768.0)
-                        .sum::<f32>()
-                        / input_channels as f32;
-                    
-                    if let Some(shared) = shared.as_ref() {
-                        push_mono_to_buffers(
-                            Some(shared),
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
-                    } else {
-                        push_mono_to_buffers(
-                            None,
-                            &mut resampler,
-                            &rec_buffer,
-                            mono,
-                            input_rate,
-                            &mut sum,
-                            &mut frames,
-                        );
-                    }
-                }
-                if frames > 0.0 {
-                    let rms = (sum / frames).sqrt();
-                    let mut last = last_emit.lock().unwrap();
-                    if last.elapsed() >= Duration::from_millis(16) {
-                        *last = Instant::now();
-                        let _ = app_handle.emit("microphone-level", rms);
-                    }
+            move |data: &mut [T], info: &cpal::OutputCallbackInfo| {
+                xruns.on_callback(&app_handle, info.timestamp().playback, data.len() / output_channels);
+                let mut shared = shared.lock().unwrap();
+                let mut gate = gate.lock().unwrap();
+                let mut compressor = compressor.lock().unwrap();
+                for frame in data.chunks_mut(output_channels) {
+                    let sample = compressor.process(gate.process(shared.next_sample()));
+                    frame.fill(cpal::Sample::from_sample(sample.clamp(-1.0, 1.0)));
                 }
             },
             err_fn,
@@ -920,18 +2257,29 @@ where
         .map_err(|e| e.to_string())
 }
 
-pub fn stop_monitoring(audio: Arc<Mutex<AudioMonitorState>>) -> Result<(), String> {
+pub fn stop_monitoring(audio: Arc<Mutex<AudioMonitorState>>) -> Result<(), CrispyError> {
+    // A start in progress would otherwise install its streams after the stop
+    let _opening = MONITORING_STREAMS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut mon = audio.lock().unwrap();
     mon.input_stream = None;
     mon.output_stream = None;
     mon.shared = None;
     mon.current_input_device = None;
     mon.current_output_device = None;
+    mon.current_host = None;
+    mon.recovering = false;
     Ok(())
 }
 
-pub fn set_monitoring_volume(audio: Arc<Mutex<AudioMonitorState>>, volume: f32) -> Result<(), String> {
-    let mon = audio.lock().unwrap();
+/// Whether monitoring streams are running. False while recovering from a
+/// lost device, so the UI can show that instead of a frozen meter.
+pub fn is_monitoring_active(audio: Arc<Mutex<AudioMonitorState>>) -> bool {
+    audio.lock().unwrap().input_stream.is_some()
+}
+
+pub fn set_monitoring_volume(audio: Arc<Mutex<AudioMonitorState>>, volume: f32) -> Result<(), CrispyError> {
+    let mut mon = audio.lock().unwrap();
+    mon.volume = volume.clamp(0.0, 1.0);
     if let Some(shared) = mon.shared.as_ref() {
         let mut shared = shared.lock().unwrap();
         shared.set_volume(volume);
@@ -939,17 +2287,167 @@ pub fn set_monitoring_volume(audio: Arc<Mutex<AudioMonitorState>>, volume: f32)
     Ok(())
 }
 
+/// Configure the monitoring noise gate. A threshold at or below -96 dBFS
+/// turns it off; `hold_ms` defaults to `GATE_HOLD_MS`. Takes effect
+/// immediately, and on later monitoring sessions.
+pub fn set_monitoring_gate(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    threshold_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    hold_ms: Option<f32>,
+) -> Result<(), CrispyError> {
+    if !threshold_db.is_finite() || threshold_db > 0.0 {
+        return Err(CrispyError::InvalidArgument(
+            "Gate threshold must be at most 0 dBFS".to_string(),
+        ));
+    }
+    for (name, ms) in [("attack", attack_ms), ("release", release_ms)] {
+        if !ms.is_finite() || !(0.1..=5000.0).contains(&ms) {
+            return Err(CrispyError::InvalidArgument(format!(
+                "Gate {} must be between 0.1 and 5000 ms",
+                name
+            )));
+        }
+    }
+    let hold_ms = hold_ms.unwrap_or(GATE_HOLD_MS);
+    if !hold_ms.is_finite() || !(0.0..=5000.0).contains(&hold_ms) {
+        return Err(CrispyError::InvalidArgument(
+            "Gate hold must be between 0 and 5000 ms".to_string(),
+        ));
+    }
+    let gate = audio.lock().unwrap().gate.clone();
+    gate.lock()
+        .unwrap()
+        .configure(threshold_db, attack_ms, release_ms, hold_ms);
+    Ok(())
+}
+
+/// Configure automatic gain control on the mic input (monitoring and the
+/// recording tee). Takes effect immediately, and on later monitoring sessions.
+pub fn set_agc(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    enabled: bool,
+    target_dbfs: f32,
+    max_gain_db: f32,
+) -> Result<(), CrispyError> {
+    if !target_dbfs.is_finite() || !(-40.0..=-3.0).contains(&target_dbfs) {
+        return Err(CrispyError::InvalidArgument(
+            "AGC target must be between -40 and -3 dBFS".to_string(),
+        ));
+    }
+    if !max_gain_db.is_finite() || !(0.0..=40.0).contains(&max_gain_db) {
+        return Err(CrispyError::InvalidArgument(
+            "AGC max gain must be between 0 and 40 dB".to_string(),
+        ));
+    }
+    let agc = audio.lock().unwrap().agc.clone();
+    agc.lock().unwrap().configure(enabled, target_dbfs, max_gain_db);
+    Ok(())
+}
+
+/// Turn per-channel input metering on or off. Takes effect immediately, and
+/// on later monitoring sessions.
+pub fn set_channel_metering(audio: &Mutex<AudioMonitorState>, enabled: bool) {
+    audio
+        .lock()
+        .unwrap()
+        .channel_metering
+        .store(enabled, Ordering::Relaxed);
+}
+
+/// Start or stop `microphone-waveform`, keeping every `decimation`th raw mic
+/// sample (default 16). Takes effect immediately, and on later monitoring
+/// sessions.
+pub fn set_microphone_waveform(audio: &Mutex<AudioMonitorState>, enabled: bool, decimation: Option<usize>) {
+    let factor = if enabled {
+        decimation.unwrap_or(DEFAULT_WAVEFORM_DECIMATION).max(1)
+    } else {
+        0
+    };
+    audio
+        .lock()
+        .unwrap()
+        .waveform_decimation
+        .store(factor, Ordering::Relaxed);
+}
+
+/// The "noisy" model's current seed and amplitude, for offline denoising.
+pub fn noise_params(audio: &Mutex<AudioMonitorState>) -> NoiseParams {
+    audio.lock().unwrap().noise_params
+}
+
+/// Set the seed and amplitude of the "noisy" model's injected noise. A running
+/// noisy model restarts its sequence from the new seed.
+pub fn set_noise_params(audio: &Mutex<AudioMonitorState>, seed: u32, amplitude: f32) -> Result<(), CrispyError> {
+    if !amplitude.is_finite() || !(0.0..=1.0).contains(&amplitude) {
+        return Err(CrispyError::InvalidArgument(
+            "Noise amplitude must be between 0 and 1".to_string(),
+        ));
+    }
+    let noise = NoiseParams { seed, amplitude };
+    let mut mon = audio.lock().unwrap();
+    mon.noise_params = noise;
+    if let Some(shared) = mon.shared.as_ref() {
+        shared.lock().unwrap().set_noise_params(noise);
+    }
+    Ok(())
+}
+
+/// Configure the soft-knee compressor on the monitoring output. Takes effect
+/// immediately, and on later monitoring sessions.
+pub fn set_compressor(
+    audio: Arc<Mutex<AudioMonitorState>>,
+    enabled: bool,
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+) -> Result<(), CrispyError> {
+    let invalid = |message: String| Err(CrispyError::InvalidArgument(message));
+    if !threshold_db.is_finite() || !(-60.0..=0.0).contains(&threshold_db) {
+        return invalid("Compressor threshold must be between -60 and 0 dBFS".to_string());
+    }
+    if !ratio.is_finite() || !(1.0..=20.0).contains(&ratio) {
+        return invalid("Compressor ratio must be between 1 and 20".to_string());
+    }
+    if !knee_db.is_finite() || !(0.0..=24.0).contains(&knee_db) {
+        return invalid("Compressor knee must be between 0 and 24 dB".to_string());
+    }
+    for (name, ms) in [("attack", attack_ms), ("release", release_ms)] {
+        if !ms.is_finite() || !(0.1..=5000.0).contains(&ms) {
+            return invalid(format!("Compressor {} must be between 0.1 and 5000 ms", name));
+        }
+    }
+    let compressor = audio.lock().unwrap().compressor.clone();
+    compressor
+        .lock()
+        .unwrap()
+        .configure(enabled, threshold_db, ratio, knee_db, attack_ms, release_ms);
+    Ok(())
+}
+
+pub fn agc_settings(audio: &Mutex<AudioMonitorState>) -> AgcSettings {
+    let agc = audio.lock().unwrap().agc.clone();
+    let guard = agc.lock().unwrap();
+    guard.settings()
+}
+
 pub fn set_monitoring_model(
     audio: Arc<Mutex<AudioMonitorState>>,
     model_name: String,
-) -> Result<(), String> {
-    let mon = audio.lock().unwrap();
-    let shared = mon.shared.as_ref().ok_or("Monitoring not started")?;
+) -> Result<(), CrispyError> {
+    let mut mon = audio.lock().unwrap();
+    let shared = mon
+        .shared
+        .clone()
+        .ok_or_else(|| CrispyError::NotRunning("Monitoring not started".to_string()))?;
     let (vol, input_rate, output_rate) = {
         let guard = shared.lock().unwrap();
         let v = guard.volume();
-        let ir = mon.last_input_rate.unwrap_or(48000.0);
-        let or = mon.last_output_rate.unwrap_or(48000.0);
+        let ir = recording::SAMPLE_RATE as f32;
+        let or = mon.last_output_rate.unwrap_or(ir);
         (v, ir, or)
     };
     let mut guard = shared.lock().unwrap();
@@ -961,35 +2459,166 @@ pub fn set_monitoring_model(
             output_rate,
             ModelKind::from_name(&model_name),
             vol,
+            mon.noise_params,
         ))
     };
+    guard.set_low_latency_start(mon.low_latency_start);
+    drop(guard);
+    mon.model_name = model_name;
     Ok(())
 }
 
+/// Latency the noise-suppression model adds to monitoring, in ms; None when
+/// monitoring is off.
+pub fn monitoring_latency_ms(audio: &Mutex<AudioMonitorState>) -> Option<f32> {
+    let shared = audio.lock().unwrap().shared.clone()?;
+    let guard = shared.lock().unwrap();
+    Some(guard.latency_samples() as f32 / guard.produced_rate_hz() * 1000.0)
+}
+
+/// A mic test counts as hearing something when the peak exceeds this.
+const MIC_TEST_NOISE_FLOOR_DBFS: f32 = -60.0;
+/// Samples at or beyond this are taken as clipped.
+const MIC_TEST_CLIP_LEVEL: f32 = 0.999;
+const MIC_TEST_MAX_SECONDS: f32 = 30.0;
+
+/// Outcome of `run_mic_test`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct MicTestResult {
+    /// Some signal rose above the noise floor.
+    pub detected: bool,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    pub clipped: bool,
+}
+
+/// Peak/RMS accumulator for the mic test, fed every channel of every frame.
+#[derive(Default)]
+struct MicTestMeter {
+    peak: f32,
+    sum_squares: f64,
+    samples: u64,
+}
+
+impl MicTestMeter {
+    fn push(&mut self, sample: f32) {
+        self.peak = self.peak.max(sample.abs());
+        self.sum_squares += (sample as f64) * (sample as f64);
+        self.samples += 1;
+    }
+
+    fn result(&self) -> MicTestResult {
+        let dbfs = |level: f32| 20.0 * level.max(1e-6).log10();
+        let rms = if self.samples > 0 {
+            (self.sum_squares / self.samples as f64).sqrt() as f32
+        } else {
+            0.0
+        };
+        let peak_dbfs = dbfs(self.peak);
+        MicTestResult {
+            detected: peak_dbfs > MIC_TEST_NOISE_FLOOR_DBFS,
+            peak_dbfs,
+            rms_dbfs: dbfs(rms),
+            clipped: self.peak >= MIC_TEST_CLIP_LEVEL,
+        }
+    }
+}
+
+fn build_mic_test_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    meter: Arc<Mutex<MicTestMeter>>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &_| {
+                let mut meter = meter.lock().unwrap();
+                for &sample in data {
+                    meter.push(cpal::Sample::to_sample::<f32>(sample));
+                }
+            },
+            |err| eprintln!("[audio] mic test stream error: {}", err),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Listen to an input device for `seconds` on a stream of its own (separate
+/// from monitoring) and report whether it picked up any signal.
+pub fn run_mic_test(
+    device_name: &str,
+    seconds: f32,
+    host_name: Option<&str>,
+) -> Result<MicTestResult, CrispyError> {
+    if !seconds.is_finite() || seconds <= 0.0 || seconds > MIC_TEST_MAX_SECONDS {
+        return Err(CrispyError::InvalidArgument(format!(
+            "Mic test length must be between 0 and {} seconds",
+            MIC_TEST_MAX_SECONDS
+        )));
+    }
+    let host = audio_host(host_name)?;
+    let device = if device_name == "Default" {
+        host.default_input_device()
+    } else {
+        find_device_by_id(host.input_devices().map_err(|e| e.to_string())?, device_name)
+    }
+    .ok_or_else(|| CrispyError::DeviceNotFound("Failed to find input device".to_string()))?;
+    let config = device.default_input_config().map_err(|e| e.to_string())?;
+    let stream_config: cpal::StreamConfig = config.clone().into();
+
+    let meter = Arc::new(Mutex::new(MicTestMeter::default()));
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_mic_test_stream::<f32>(&device, &stream_config, meter.clone())?,
+        cpal::SampleFormat::I16 => build_mic_test_stream::<i16>(&device, &stream_config, meter.clone())?,
+        cpal::SampleFormat::U16 => build_mic_test_stream::<u16>(&device, &stream_config, meter.clone())?,
+        format => {
+            return Err(CrispyError::Unsupported(format!(
+                "Unsupported sample format: {}",
+                format
+            )))
+        }
+    };
+    stream.play().map_err(|e| e.to_string())?;
+    std::thread::sleep(Duration::from_secs_f32(seconds));
+    drop(stream);
+
+    let result = meter.lock().unwrap().result();
+    Ok(result)
+}
+
 // --- System volume (macOS) ---
 
 #[tauri::command]
-pub fn get_system_input_volume() -> Result<u8, String> {
+pub fn get_system_input_volume() -> Result<u8, CrispyError> {
     #[cfg(target_os = "macos")]
     {
         let v = crate::system_input_volume::get_system_input_volume()?;
         Ok((v * 100.0).round() as u8)
     }
     #[cfg(not(target_os = "macos"))]
-    Err("System input volume is only supported on macOS.".to_string())
+    Err(CrispyError::Unsupported(
+        "System input volume is only supported on macOS.".to_string(),
+    ))
 }
 
 #[tauri::command]
-pub fn set_system_input_volume(volume: u8) -> Result<(), String> {
+pub fn set_system_input_volume(volume: u8) -> Result<(), CrispyError> {
     #[cfg(target_os = "macos")]
     {
         let v = (volume.min(100) as f32) / 100.0;
-        crate::system_input_volume::set_system_input_volume(v)
+        crate::system_input_volume::set_system_input_volume(v).map_err(CrispyError::from)
     }
     #[cfg(not(target_os = "macos"))]
     let _ = volume;
     #[cfg(not(target_os = "macos"))]
-    Err("System input volume is only supported on macOS.".to_string())
+    Err(CrispyError::Unsupported(
+        "System input volume is only supported on macOS.".to_string(),
+    ))
 }
 
 // --- BlackHole status ---
@@ -1001,7 +2630,7 @@ pub struct BlackHoleStatus {
 }
 
 #[tauri::command]
-pub fn get_blackhole_status() -> Result<BlackHoleStatus, String> {
+pub fn get_blackhole_status() -> Result<BlackHoleStatus, CrispyError> {
     #[cfg(target_os = "macos")]
     {
         let candidates = [
@@ -1037,6 +2666,593 @@ pub fn get_blackhole_status() -> Result<BlackHoleStatus, String> {
 mod tests {
     use super::*;
 
+    fn monitoring(input: &str) -> Mutex<AudioMonitorState> {
+        let mut mon = AudioMonitorState::new();
+        mon.current_input_device = Some(input.to_string());
+        mon.current_output_device = Some("Speakers".to_string());
+        Mutex::new(mon)
+    }
+
+    #[test]
+    fn host_id_parses_cpal_names_case_insensitively() {
+        for &id in cpal::ALL_HOSTS {
+            assert_eq!(host_id_from_str(id.name()), Some(id));
+            assert_eq!(host_id_from_str(&id.name().to_lowercase()), Some(id));
+        }
+        assert_eq!(host_id_from_str("no such host"), None);
+        assert!(audio_host(Some("no such host")).is_err());
+    }
+
+    #[test]
+    fn channel_meter_levels_each_interleaved_channel() {
+        // L: steady 0.5, R: full-scale square wave, 3rd: silent
+        let data: Vec<f32> = (0..8)
+            .flat_map(|i| [0.5, if i % 2 == 0 { 1.0 } else { -1.0 }, 0.0])
+            .collect();
+        let mut meter = ChannelMeter::new(3);
+        for frame in data.chunks(3) {
+            meter.add_frame(frame.iter().copied());
+        }
+        let levels = meter.levels();
+        assert_eq!(levels.len(), 3);
+        assert!((levels[0].rms - 0.5).abs() < 1e-6 && levels[0].peak == 0.5);
+        assert!((levels[1].rms - 1.0).abs() < 1e-6 && levels[1].peak == 1.0);
+        assert_eq!(levels[2], ChannelLevel { rms: 0.0, peak: 0.0 });
+
+        meter.reset();
+        assert!(meter.levels().iter().all(|l| l.rms == 0.0 && l.peak == 0.0));
+    }
+
+    #[test]
+    fn blackhole_2ch_is_preferred_then_fewest_channels() {
+        let names = [
+            "MacBook Pro Speakers",
+            "BlackHole 64ch",
+            "BlackHole",
+            "BlackHole 16ch",
+            "BlackHole 2ch",
+            "External Headphones",
+        ]
+        .map(String::from);
+        assert_eq!(
+            rank_blackhole_devices(names.to_vec()),
+            ["BlackHole 2ch", "BlackHole 16ch", "BlackHole 64ch", "BlackHole"]
+        );
+        assert_eq!(blackhole_channels("BlackHole16ch"), Some(16));
+        assert!(rank_blackhole_devices(vec!["Speakers".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn noisy_model_noise_follows_seed_and_amplitude() {
+        let noise = |seed, amplitude| {
+            let params = NoiseParams { seed, amplitude };
+            let mut model = SharedAudio::new(48_000.0, 48_000.0, ModelKind::Noisy, 1.0, params);
+            (0..256)
+                .map(|_| model.push_sample(0.0).unwrap()[0])
+                .collect::<Vec<f32>>()
+        };
+        let a = noise(7, 0.05);
+        assert_eq!(a, noise(7, 0.05));
+        assert_ne!(a, noise(8, 0.05));
+        assert!(a.iter().all(|s| s.abs() <= 0.05));
+
+        let louder = noise(7, 0.2);
+        for (quiet, loud) in a.iter().zip(&louder) {
+            assert!((loud - quiet * 4.0).abs() < 1e-6);
+        }
+
+        // Re-seeding a running model replays the sequence
+        let mut model =
+            SharedAudio::new(48_000.0, 48_000.0, ModelKind::Noisy, 1.0, NoiseParams::default());
+        model.push_sample(0.0);
+        model.set_noise_params(NoiseParams { seed: 7, amplitude: 0.05 });
+        assert_eq!(model.push_sample(0.0).unwrap()[0], a[0]);
+
+        // Offline denoising adds the same noise as monitoring with those params
+        let params = NoiseParams { seed: 7, amplitude: 0.05 };
+        let silence = vec![0.0f32; 256];
+        assert_eq!(denoise_buffer("noisy", &silence, 48_000.0, params), a);
+    }
+
+    #[test]
+    fn decimation_keeps_every_nth_sample_across_calls() {
+        let samples: Vec<f32> = (0..40).map(|i| i as f32).collect();
+        let mut out = Vec::new();
+        let mut phase = 0;
+        // Uneven callback sizes must not shift the spacing
+        for chunk in [&samples[..7], &samples[7..23], &samples[23..]] {
+            decimate_into(chunk, 16, &mut phase, &mut out);
+        }
+        assert_eq!(out, [0.0, 16.0, 32.0]);
+
+        let mut out = Vec::new();
+        let mut phase = 0;
+        decimate_into(&samples[..5], 1, &mut phase, &mut out);
+        assert_eq!(out, samples[..5]);
+    }
+
+    #[test]
+    fn xrun_detector_flags_late_deliveries() {
+        // 480-frame buffers at 48 kHz arrive every 10 ms
+        let mut xruns = XrunDetector::new("input", 48_000.0);
+        let mut t = 0.0;
+        for _ in 0..5 {
+            assert!(!xruns.observe(t, 480));
+            t += 0.010;
+        }
+        // Jitter within the slack is not a dropout
+        t += 0.001;
+        assert!(!xruns.observe(t, 480));
+        // One buffer skipped: a 20 ms gap
+        t += 0.020;
+        assert!(xruns.observe(t, 480));
+        // A short buffer sets a shorter expectation for the next gap
+        t += 0.010;
+        assert!(!xruns.observe(t, 240));
+        t += 0.010;
+        assert!(xruns.observe(t, 480));
+        assert_eq!(xruns.count(), 2);
+    }
+
+    #[test]
+    fn same_named_devices_survive_with_distinct_ids() {
+        let names = ["USB Mic", "MacBook Mic", "USB Mic", "USB Mic"].map(String::from);
+        let devices = label_devices(names.to_vec());
+        assert_eq!(devices.len(), 4);
+        let ids: Vec<&str> = devices.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["USB Mic", "MacBook Mic", "USB Mic#2", "USB Mic#3"]);
+        assert_eq!(devices[2].name, "USB Mic (2)");
+        // A lone device keeps its name as id, so saved selections still match
+        assert_eq!(devices[1], AudioDevice { id: "MacBook Mic".into(), name: "MacBook Mic".into() });
+    }
+
+    #[test]
+    fn lost_device_emits_event_once_and_starts_recovery() {
+        let audio = monitoring("USB Mic");
+        let mut events = Vec::new();
+        let lost = handle_stream_error(&audio, &cpal::StreamError::DeviceNotAvailable, |e| {
+            events.push(e)
+        })
+        .expect("device loss should be handled");
+        assert_eq!(lost.input_device, "USB Mic");
+        assert_eq!(lost.output_device, "Speakers");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].input_device, "USB Mic");
+        assert!(events[0].restarting);
+        assert!(audio.lock().unwrap().recovering);
+
+        // The output stream failing for the same unplug is not a second loss
+        let again = handle_stream_error(&audio, &cpal::StreamError::DeviceNotAvailable, |e| {
+            events.push(e)
+        });
+        assert!(again.is_none());
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn volume_changes_are_kept_for_a_restart() {
+        // Device loss drops the NsState, so the volume must live on the state
+        let audio = Arc::new(monitoring("USB Mic"));
+        set_monitoring_volume(audio.clone(), 0.3).unwrap();
+        assert_eq!(audio.lock().unwrap().volume, 0.3);
+        handle_stream_error(&audio, &cpal::StreamError::DeviceNotAvailable, |_| {});
+        assert_eq!(audio.lock().unwrap().volume, 0.3);
+        set_monitoring_volume(audio.clone(), 1.5).unwrap();
+        assert_eq!(audio.lock().unwrap().volume, 1.0);
+    }
+
+    #[test]
+    fn stream_error_without_device_loss_is_only_logged() {
+        let audio = monitoring("USB Mic");
+        let err = cpal::StreamError::BackendSpecific {
+            err: cpal::BackendSpecificError {
+                description: "glitch".to_string(),
+            },
+        };
+        assert!(handle_stream_error(&audio, &err, |_| panic!("no event expected")).is_none());
+        assert!(!audio.lock().unwrap().recovering);
+
+        // Nothing to recover when monitoring isn't running
+        let idle = Mutex::new(AudioMonitorState::new());
+        let lost = handle_stream_error(&idle, &cpal::StreamError::DeviceNotAvailable, |_| {
+            panic!("no event expected")
+        });
+        assert!(lost.is_none());
+    }
+
+    #[test]
+    fn default_input_change_compares_with_previous_default() {
+        assert_eq!(default_input_change(Some("Headset"), Some("Headset")), None);
+        assert_eq!(
+            default_input_change(Some("Headset"), Some("MacBook Pro Microphone")),
+            Some("MacBook Pro Microphone".to_string())
+        );
+        // No default for a moment while the system switches
+        assert_eq!(default_input_change(Some("Headset"), None), None);
+        assert_eq!(default_input_change(None, Some("Headset")), Some("Headset".to_string()));
+    }
+
+    #[test]
+    fn restart_backoff_doubles_per_attempt() {
+        assert_eq!(restart_backoff(1), Duration::from_millis(500));
+        assert_eq!(restart_backoff(2), Duration::from_millis(1000));
+        assert_eq!(restart_backoff(DEVICE_LOST_MAX_RESTARTS), Duration::from_millis(8000));
+    }
+
+    #[test]
+    fn missing_microphone_has_no_input_devices_error_kind() {
+        let err = no_input_devices_error();
+        assert_eq!(err.code(), NO_INPUT_DEVICES);
+        assert!(err.message().starts_with(NO_INPUT_DEVICES));
+    }
+
+    /// Energy left after removing the best-fit sinusoid at `freq`, relative
+    /// to the total: harmonics, aliasing and interpolation noise.
+    fn residual_ratio(signal: &[f32], freq: f64, rate: f64) -> f64 {
+        let w = 2.0 * std::f64::consts::PI * freq / rate;
+        let (mut ss, mut sc, mut cc, mut xs, mut xc) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (i, &x) in signal.iter().enumerate() {
+            let (s, c) = (w * i as f64).sin_cos();
+            ss += s * s;
+            sc += s * c;
+            cc += c * c;
+            xs += x as f64 * s;
+            xc += x as f64 * c;
+        }
+        let det = ss * cc - sc * sc;
+        let a = (xs * cc - xc * sc) / det;
+        let b = (xc * ss - xs * sc) / det;
+        let (mut residual, mut total) = (0.0, 0.0);
+        for (i, &x) in signal.iter().enumerate() {
+            let (s, c) = (w * i as f64).sin_cos();
+            let e = x as f64 - a * s - b * c;
+            residual += e * e;
+            total += x as f64 * x as f64;
+        }
+        residual / total
+    }
+
+    #[test]
+    fn sinc_resampler_has_less_distortion_than_linear() {
+        let tone: Vec<f32> = (0..44100)
+            .map(|i| (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / 44100.0).sin() as f32 * 0.5)
+            .collect();
+        let mut linear = LinearResampler::new(44100.0, 48000.0);
+        let mut sinc = SincResampler::new(44100.0, 48000.0);
+        let (mut lin_out, mut sinc_out) = (Vec::new(), Vec::new());
+        for &s in &tone {
+            linear.process_sample(s, |o| lin_out.push(o));
+            sinc.process_sample(s, |o| sinc_out.push(o));
+        }
+        assert!(lin_out.len().abs_diff(48000) < 20, "{}", lin_out.len());
+        assert!(sinc_out.len().abs_diff(48000) < 20, "{}", sinc_out.len());
+        // Skip the start-up transient of both
+        let linear_thd = residual_ratio(&lin_out[100..], 1000.0, 48000.0);
+        let sinc_thd = residual_ratio(&sinc_out[100..], 1000.0, 48000.0);
+        // At least 10 dB less energy outside the tone
+        assert!(
+            sinc_thd < linear_thd / 10.0,
+            "sinc {} vs linear {}",
+            sinc_thd,
+            linear_thd
+        );
+    }
+
+    #[test]
+    fn sinc_resampler_downsample_keeps_dc_level() {
+        let mut resampler = StreamResampler::new(ResamplerKind::Sinc, 48000.0, 16000.0);
+        let mut output = Vec::new();
+        for _ in 0..300 {
+            resampler.process_sample(0.5, |s| output.push(s));
+        }
+        // 3:1, less the kernel's half-width of latency
+        assert!(output.len() > 90 && output.len() <= 100, "got {}", output.len());
+        assert!(output[10..].iter().all(|s| (s - 0.5).abs() < 1e-4));
+    }
+
+    #[test]
+    fn resampler_rate_change_keeps_output_continuous() {
+        // 200 Hz at 0.5 moves at most ~0.013 per sample at 48 kHz; a restart
+        // from silence or a skipped stretch of input would jump much further.
+        let max_step = 0.04;
+        for kind in [ResamplerKind::Linear, ResamplerKind::Sinc] {
+            let mut resampler = StreamResampler::new(kind, 48000.0, 48000.0);
+            let mut phase = 0.0f64;
+            let mut output = Vec::new();
+            for input_rate in [48000.0f32, 44100.0, 48000.0, 32000.0] {
+                resampler.set_rates(input_rate, 48000.0);
+                for _ in 0..(input_rate as usize / 10) {
+                    let sample = (phase.sin() * 0.5) as f32;
+                    phase += 2.0 * std::f64::consts::PI * 200.0 / input_rate as f64;
+                    resampler.process_sample(sample, |o| output.push(o));
+                }
+            }
+            let worst = output.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+            assert!(worst < max_step, "{:?}: jump of {}", kind, worst);
+            assert!(output.len().abs_diff(4 * 4800) < 40, "{:?}: {} samples", kind, output.len());
+        }
+    }
+
+    #[test]
+    fn recording_path_resamples_once_when_rates_differ() {
+        let input_rate = 44100.0;
+        let processing_rate = recording::SAMPLE_RATE as f32;
+        let input: Vec<f32> = (0..4410)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 441.0 * i as f32 / input_rate).sin())
+            .collect();
+        for kind in [ResamplerKind::Linear, ResamplerKind::Sinc] {
+            // Output at 44.1k too: only the monitoring output converts back
+            let ns = NsState::for_model("passthrough", processing_rate, input_rate, 1.0, NoiseParams::default())
+                    .unwrap();
+            let ns = Arc::new(Mutex::new(ns));
+            let mut agc = Agc::default();
+            let mut resampler = StreamResampler::new(kind, input_rate, processing_rate);
+            let rec_buffer = Mutex::new(VecDeque::new());
+            let (mut sum, mut frames) = (0.0, 0.0);
+            for &sample in &input {
+                push_mono_to_buffers(
+                    Some(&ns),
+                    &mut agc,
+                    &mut resampler,
+                    &rec_buffer,
+                    sample,
+                    input_rate,
+                    &mut sum,
+                    &mut frames,
+                );
+            }
+
+            // Exactly one pass of the selected resampler, nothing stacked on it
+            let mut single = StreamResampler::new(kind, input_rate, processing_rate);
+            let mut expected = Vec::new();
+            for &sample in &input {
+                single.process_sample(sample, |o| expected.push(o));
+            }
+            let recorded: Vec<f32> = rec_buffer.into_inner().unwrap().into();
+            assert_eq!(recorded, expected, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn noise_gate_attenuates_quiet_signal_after_hold() {
+        let rate = 48000.0;
+        let mut gate = NoiseGate::default();
+        gate.set_sample_rate(rate);
+        gate.configure(-40.0, 1.0, 20.0, GATE_HOLD_MS);
+
+        let tone = |amp: f32, i: usize| {
+            amp * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate).sin()
+        };
+        // 200 ms at -6 dBFS, then 500 ms at -60 dBFS
+        let loud: Vec<f32> = (0..9600).map(|i| gate.process(tone(0.5, i))).collect();
+        let quiet: Vec<f32> = (9600..33600).map(|i| gate.process(tone(0.001, i))).collect();
+
+        // Open: the loud part passes once the attack has settled
+        assert!(loud[480..].iter().zip(480..).all(|(o, i)| (o - tone(0.5, i)).abs() < 1e-3));
+        // Detector decay plus hold: the first ~50 ms of the quiet part still pass
+        assert!(quiet[..2400].iter().zip(9600..).all(|(o, i)| (o - tone(0.001, i)).abs() < 1e-4));
+        // Well past hold + release the quiet part is attenuated by over 40 dB
+        assert!(quiet[12000..].iter().all(|o| o.abs() < 0.001 * 0.01));
+
+        // A 200 ms hold keeps it open four times as long
+        let mut gate = NoiseGate::default();
+        gate.set_sample_rate(rate);
+        gate.configure(-40.0, 1.0, 20.0, 200.0);
+        for i in 0..9600 {
+            gate.process(tone(0.5, i));
+        }
+        let quiet: Vec<f32> = (9600..33600).map(|i| gate.process(tone(0.001, i))).collect();
+        assert!(quiet[..9600].iter().zip(9600..).all(|(o, i)| (o - tone(0.001, i)).abs() < 1e-4));
+        assert!(quiet[20000..].iter().all(|o| o.abs() < 0.001 * 0.01));
+    }
+
+    #[test]
+    fn noise_gate_off_by_default_and_below_floor() {
+        let mut gate = NoiseGate::default();
+        assert_eq!(gate.process(1e-6), 1e-6);
+        gate.configure(-20.0, 1.0, 1.0, GATE_HOLD_MS);
+        gate.configure(GATE_OFF_DB, 1.0, 1.0, GATE_HOLD_MS);
+        assert_eq!(gate.process(1e-6), 1e-6);
+    }
+
+    #[test]
+    fn input_rate_choice_flags_whether_48k_was_negotiated() {
+        let range = |min_rate, max_rate| ConfigRange {
+            channels: 1,
+            min_rate,
+            max_rate,
+            format: "f32".to_string(),
+        };
+        let choice = choose_input_rate(&[range(44100, 44100), range(8000, 96000)], 44100);
+        assert_eq!(
+            choice,
+            InputRateChoice {
+                range: Some(1),
+                rate: 48000,
+                negotiated_48k: true
+            }
+        );
+
+        let choice = choose_input_rate(&[range(44100, 44100), range(16000, 16000)], 44100);
+        assert_eq!(
+            choice,
+            InputRateChoice {
+                range: None,
+                rate: 44100,
+                negotiated_48k: false
+            }
+        );
+
+        // Unknown ranges: only a 48kHz default counts
+        assert!(choose_input_rate(&[], 48000).negotiated_48k);
+        assert!(!choose_input_rate(&[], 44100).negotiated_48k);
+    }
+
+    #[test]
+    fn config_ranges_summarize_to_rates_channels_and_formats() {
+        let range = |channels, min_rate, max_rate, format: &str| ConfigRange {
+            channels,
+            min_rate,
+            max_rate,
+            format: format.to_string(),
+        };
+        let caps = summarize_config_ranges(&[
+            range(2, 44100, 48000, "f32"),
+            range(1, 44100, 44100, "i16"),
+            range(2, 8000, 16000, "i16"),
+            range(1, 96000, 96000, "f32"),
+        ])
+        .unwrap();
+        assert_eq!(
+            caps.sample_rates,
+            vec![
+                SampleRateRange { min: 8000, max: 16000 },
+                SampleRateRange { min: 44100, max: 48000 },
+                SampleRateRange { min: 96000, max: 96000 },
+            ]
+        );
+        assert_eq!((caps.min_sample_rate, caps.max_sample_rate), (8000, 96000));
+        assert!(caps.supports_48k);
+        assert_eq!(caps.channels, vec![1, 2]);
+        assert_eq!(caps.sample_formats, vec!["f32", "i16"]);
+
+        let caps = summarize_config_ranges(&[range(1, 16000, 44100, "i16")]).unwrap();
+        assert!(!caps.supports_48k);
+        assert!(summarize_config_ranges(&[]).is_none());
+    }
+
+    #[test]
+    fn mic_test_detects_signal_above_noise_floor() {
+        let measure = |samples: &[f32]| {
+            let mut meter = MicTestMeter::default();
+            samples.iter().for_each(|&s| meter.push(s));
+            meter.result()
+        };
+        let tone = |amplitude: f32| -> Vec<f32> {
+            (0..4800)
+                .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 48000.0).sin())
+                .collect()
+        };
+
+        // A dead mic: hiss around -80 dBFS
+        let hiss = measure(&tone(1e-4));
+        assert!(!hiss.detected, "{:?}", hiss);
+        assert!(!measure(&[]).detected);
+
+        let speech = measure(&tone(0.1));
+        assert!(speech.detected);
+        assert!((speech.peak_dbfs + 20.0).abs() < 0.1, "{:?}", speech);
+        assert!((speech.rms_dbfs + 23.0).abs() < 0.1, "{:?}", speech);
+        assert!(!speech.clipped);
+
+        assert!(measure(&tone(1.0)).clipped);
+    }
+
+    #[test]
+    fn rnnoise_latency_is_one_buffered_frame() {
+        let samples_until_output = |low_latency_start: bool| {
+            let mut ns = RnnNoiseProcessor::new(48000.0, 48000.0, 1.0);
+            ns.set_low_latency_start(low_latency_start);
+            let pushed = (1..=4 * RNNOISE_FRAME_SIZE)
+                .find(|_| ns.push_sample(0.0).is_some())
+                .unwrap();
+            (pushed, ns.latency_samples())
+        };
+        // First output once a whole frame is buffered
+        let (pushed, latency) = samples_until_output(true);
+        assert_eq!(latency, RNNOISE_FRAME_SIZE);
+        assert_eq!(pushed, latency);
+        // The warm-up frame is dropped by default: start is one frame later
+        let (pushed, latency) = samples_until_output(false);
+        assert_eq!(pushed, latency + RNNOISE_FRAME_SIZE);
+    }
+
+    fn rms_dbfs(samples: &[f32]) -> f32 {
+        let power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+        10.0 * power.log10()
+    }
+
+    #[test]
+    fn agc_brings_quiet_tone_to_target_within_max_gain() {
+        let rate = 48000.0;
+        // 8 s of a 1 kHz tone peaking at -40 dBFS
+        let tone: Vec<f32> = (0..8 * 48000)
+            .map(|i| 0.01 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate).sin())
+            .collect();
+        let last_second = tone.len() - 48000..;
+
+        let mut agc = Agc::default();
+        agc.set_sample_rate(rate);
+        agc.configure(true, -18.0, 30.0);
+        let out: Vec<f32> = tone.iter().map(|&s| agc.process(s)).collect();
+        let level = rms_dbfs(&out[last_second.clone()]);
+        assert!((level + 18.0).abs() < 1.0, "settled at {} dBFS", level);
+
+        // A 12 dB cap stops short of the target
+        let mut capped = Agc::default();
+        capped.set_sample_rate(rate);
+        capped.configure(true, -18.0, 12.0);
+        let out: Vec<f32> = tone.iter().map(|&s| capped.process(s)).collect();
+        assert!(capped.gain <= 10f32.powf(12.0 / 20.0) + 1e-4);
+        let gain_db = rms_dbfs(&out[last_second.clone()]) - rms_dbfs(&tone[last_second]);
+        assert!(gain_db <= 12.0 + 0.01 && gain_db > 11.0, "gain {} dB", gain_db);
+    }
+
+    #[test]
+    fn compressor_attenuates_above_threshold_by_ratio() {
+        let rate = 48000.0;
+        let tone = |amplitude: f32| -> Vec<f32> {
+            (0..2 * 48000)
+                .map(|i| amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate).sin())
+                .collect()
+        };
+        let last_half = 48000..;
+        let mut compressor = Compressor::default();
+        compressor.set_sample_rate(rate);
+
+        // Off by default
+        let loud = tone(0.5);
+        assert_eq!(compressor.process(loud[1]), loud[1]);
+
+        // A tone 20 dB over a -30 dBFS threshold comes out 20 / 4 dB over it
+        compressor.configure(true, -30.0, 4.0, 6.0, 5.0, 50.0);
+        let input = tone(10f32.powf(-10.0 / 20.0) * std::f32::consts::SQRT_2);
+        let out: Vec<f32> = input.iter().map(|&s| compressor.process(s)).collect();
+        let level = rms_dbfs(&out[last_half.clone()]);
+        assert!((level + 25.0).abs() < 0.5, "settled at {} dBFS", level);
+
+        // Well below the knee the tone passes unchanged
+        let quiet = tone(10f32.powf(-50.0 / 20.0) * std::f32::consts::SQRT_2);
+        let out: Vec<f32> = quiet.iter().map(|&s| compressor.process(s)).collect();
+        let gain_db = rms_dbfs(&out[last_half.clone()]) - rms_dbfs(&quiet[last_half]);
+        assert!(gain_db.abs() < 0.1, "gain {} dB", gain_db);
+    }
+
+    #[test]
+    fn agc_settings_report_configured_values() {
+        let mut agc = Agc::default();
+        agc.configure(true, -21.5, 12.0);
+        assert_eq!(
+            agc.settings(),
+            AgcSettings { enabled: true, target_dbfs: -21.5, max_gain_db: 12.0 }
+        );
+    }
+
+    #[test]
+    fn agc_transient_does_not_pump() {
+        let rate = 48000.0;
+        let mut agc = Agc::default();
+        agc.set_sample_rate(rate);
+        agc.configure(true, -18.0, 30.0);
+        for i in 0..5 * 48000 {
+            agc.process(0.01 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate).sin());
+        }
+        let settled = agc.gain;
+        let clap = agc.process(0.8);
+        assert!(clap.abs() <= AGC_PEAK_CEILING + 1e-6);
+        assert!((agc.gain - settled).abs() / settled < 0.01);
+    }
+
     #[test]
     fn linear_resampler_same_rate_passthrough() {
         let mut resampler = LinearResampler::new(48000.0, 48000.0);
@@ -1,5 +1,9 @@
+use tauri::{Emitter, Manager};
+
 use crate::app_state::AppState;
 use crate::audio;
+use crate::error::CrispyError;
+use crate::settings;
 
 #[tauri::command]
 pub fn get_platform() -> Result<String, String> {
@@ -21,33 +25,330 @@ pub fn start_monitoring(
     output_device_name: String,
     model_name: String,
     volume: f32,
-) -> Result<(), String> {
+    host: Option<String>,
+) -> Result<(), CrispyError> {
     let recording_mic_buffer = state.recording.lock().unwrap().mic_buffer.clone();
     audio::start_monitoring(
         state.audio.clone(),
         recording_mic_buffer,
-        app_handle,
+        app_handle.clone(),
         device_name,
         output_device_name,
         model_name,
         volume,
-    )
+        host.clone(),
+    )?;
+    remember_monitoring(&app_handle, true);
+    remember_monitoring_host(&app_handle, host.as_deref());
+    Ok(())
 }
 
 #[tauri::command]
-pub fn stop_monitoring(state: tauri::State<AppState>) -> Result<(), String> {
-    audio::stop_monitoring(state.audio.clone())
+pub fn stop_monitoring(
+    state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), CrispyError> {
+    audio::stop_monitoring(state.audio.clone())?;
+    remember_monitoring(&app_handle, false);
+    Ok(())
+}
+
+/// Record whether monitoring is on, so the next launch can resume it.
+pub fn remember_monitoring(app_handle: &tauri::AppHandle, enabled: bool) {
+    if let Err(e) = settings::update_app_setting(app_handle, "monitoring_enabled", enabled.to_string()) {
+        eprintln!("[audio] failed to save monitoring state: {}", e);
+    }
+}
+
+/// Record the cpal host monitoring was started on, so the launch-time resume
+/// opens the same backend.
+pub fn remember_monitoring_host(app_handle: &tauri::AppHandle, host: Option<&str>) {
+    let host = host.unwrap_or_default().trim().to_string();
+    if let Err(e) = settings::update_app_setting(app_handle, "monitoring_host", host) {
+        eprintln!("[audio] failed to save monitoring host: {}", e);
+    }
+}
+
+/// What to do at launch about monitoring that was on when the app quit.
+#[derive(Debug, PartialEq)]
+enum MonitoringResume {
+    Skip,
+    Start {
+        input_device: String,
+        output_device: String,
+        model_name: String,
+        volume: f32,
+        host: Option<String>,
+    },
+    /// A saved device is no longer connected.
+    MissingDevice(String),
+}
+
+fn monitoring_resume_plan(
+    settings: &settings::AppSettings,
+    input_devices: &[String],
+    output_devices: &[String],
+) -> MonitoringResume {
+    let input = settings.selected_microphone.trim();
+    if settings.monitoring_enabled != "true" || input.is_empty() {
+        return MonitoringResume::Skip;
+    }
+    let output = settings.selected_output_device.trim();
+    let present = |name: &str, devices: &[String]| name == "Default" || devices.iter().any(|d| d == name);
+    if !present(input, input_devices) {
+        return MonitoringResume::MissingDevice(input.to_string());
+    }
+    if !output.is_empty() && !present(output, output_devices) {
+        return MonitoringResume::MissingDevice(output.to_string());
+    }
+    let volume = settings
+        .microphone_volume
+        .trim()
+        .parse::<f32>()
+        .map(|percent| (percent / 100.0).clamp(0.0, 1.0))
+        .unwrap_or(1.0);
+    MonitoringResume::Start {
+        input_device: input.to_string(),
+        output_device: output.to_string(),
+        model_name: settings.selected_model.clone(),
+        volume,
+        host: settings.monitoring_host(),
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct MonitoringResumeFailedEvent {
+    device: Option<String>,
+    error: String,
+}
+
+/// Resume monitoring with the saved devices and host if it was on when the
+/// app quit. Missing devices or a failed start are reported with a
+/// `monitoring-resume-failed` event; the saved state is left as is, so
+/// monitoring comes back once the device is reconnected and the app restarted.
+/// The settings UI starts monitoring on the same devices when it mounts;
+/// stream opening is serialized, so whichever start comes second keeps the
+/// streams the first one opened.
+pub fn resume_monitoring(app_handle: &tauri::AppHandle) {
+    let Ok(saved) = settings::load_app_settings(app_handle) else {
+        return;
+    };
+    if saved.monitoring_enabled != "true" {
+        return;
+    }
+    let ids = |devices: Result<Vec<audio::AudioDevice>, CrispyError>| -> Vec<String> {
+        devices.unwrap_or_default().into_iter().map(|d| d.id).collect()
+    };
+    let host = saved.monitoring_host();
+    let inputs = ids(audio::get_input_devices(app_handle.clone(), host.clone()));
+    let outputs = ids(audio::get_output_devices(host));
+
+    let (device, error) = match monitoring_resume_plan(&saved, &inputs, &outputs) {
+        MonitoringResume::Skip => return,
+        MonitoringResume::MissingDevice(device) => {
+            let error = format!("{} is not connected", device);
+            (Some(device), error)
+        }
+        MonitoringResume::Start {
+            input_device,
+            output_device,
+            model_name,
+            volume,
+            host,
+        } => {
+            let state = app_handle.state::<AppState>();
+            let recording_mic_buffer = state.recording.lock().unwrap().mic_buffer.clone();
+            match audio::start_monitoring(
+                state.audio.clone(),
+                recording_mic_buffer,
+                app_handle.clone(),
+                input_device,
+                output_device,
+                model_name,
+                volume,
+                host,
+            ) {
+                Ok(()) => return,
+                Err(e) => (None, e.to_string()),
+            }
+        }
+    };
+    eprintln!("[audio] could not resume monitoring: {}", error);
+    let _ = app_handle.emit("monitoring-resume-failed", MonitoringResumeFailedEvent { device, error });
 }
 
 #[tauri::command]
-pub fn set_monitoring_volume(state: tauri::State<AppState>, volume: f32) -> Result<(), String> {
+pub fn is_monitoring_active(state: tauri::State<AppState>) -> bool {
+    audio::is_monitoring_active(state.audio.clone())
+}
+
+/// Not written to settings: the effective volume differs from the saved
+/// `microphone_volume` (it is 1.0 while the system input volume is in use),
+/// and the settings UI already saves the slider value itself.
+#[tauri::command]
+pub fn set_monitoring_volume(state: tauri::State<AppState>, volume: f32) -> Result<(), CrispyError> {
     audio::set_monitoring_volume(state.audio.clone(), volume)
 }
 
+#[tauri::command]
+pub fn set_monitoring_gate(
+    state: tauri::State<AppState>,
+    threshold_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    hold_ms: Option<f32>,
+) -> Result<(), CrispyError> {
+    audio::set_monitoring_gate(state.audio.clone(), threshold_db, attack_ms, release_ms, hold_ms)
+}
+
+#[tauri::command]
+pub fn set_agc(
+    state: tauri::State<AppState>,
+    enabled: bool,
+    target_dbfs: f32,
+    max_gain_db: f32,
+) -> Result<(), CrispyError> {
+    audio::set_agc(state.audio.clone(), enabled, target_dbfs, max_gain_db)
+}
+
+/// Emit `microphone-channels` with each input channel's RMS and peak while
+/// monitoring, next to the mono `microphone-level`.
+#[tauri::command]
+pub fn set_channel_metering(state: tauri::State<AppState>, enabled: bool) {
+    audio::set_channel_metering(&state.audio, enabled);
+}
+
+/// Stream decimated raw mic samples as `microphone-waveform` for a scrolling
+/// waveform; off until enabled.
+#[tauri::command]
+pub fn set_microphone_waveform(state: tauri::State<AppState>, enabled: bool, decimation: Option<usize>) {
+    audio::set_microphone_waveform(&state.audio, enabled, decimation);
+}
+
+/// Seed and amplitude of the noise the "noisy" model injects, for
+/// reproducible A/B runs. Defaults: seed 0x1234abcd, amplitude 0.05.
+#[tauri::command]
+pub fn set_noise_params(state: tauri::State<AppState>, seed: u32, amplitude: f32) -> Result<(), CrispyError> {
+    audio::set_noise_params(&state.audio, seed, amplitude)
+}
+
+#[tauri::command]
+pub fn set_compressor(
+    state: tauri::State<AppState>,
+    enabled: bool,
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+) -> Result<(), CrispyError> {
+    audio::set_compressor(
+        state.audio.clone(),
+        enabled,
+        threshold_db,
+        ratio,
+        knee_db,
+        attack_ms,
+        release_ms,
+    )
+}
+
+/// Delay noise suppression adds to monitoring, in ms (None when off).
+#[tauri::command]
+pub fn get_monitoring_latency(state: tauri::State<AppState>) -> Option<f32> {
+    audio::monitoring_latency_ms(&state.audio)
+}
+
+/// Rate the monitoring mic was opened at and whether it is the 48kHz asked
+/// for (None when off).
+#[tauri::command]
+pub fn get_monitoring_input_rate(state: tauri::State<AppState>) -> Option<audio::MonitoringInputRate> {
+    audio::monitoring_input_rate(&state.audio)
+}
+
+/// Short, standalone listen on `device` (monitoring is left alone) to check
+/// that the mic picks anything up.
+#[tauri::command]
+pub async fn run_mic_test(
+    device: String,
+    seconds: f32,
+    host: Option<String>,
+) -> Result<audio::MicTestResult, CrispyError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        audio::run_mic_test(&device, seconds, host.as_deref())
+    })
+    .await
+    .map_err(|e| CrispyError::Internal(format!("Task failed to join: {}", e)))?
+}
+
+/// Model changes are also written through to settings (debounced), so the
+/// last-used model survives a restart.
 #[tauri::command]
 pub fn set_monitoring_model(
     state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
     model_name: String,
-) -> Result<(), String> {
-    audio::set_monitoring_model(state.audio.clone(), model_name)
+) -> Result<(), CrispyError> {
+    audio::set_monitoring_model(state.audio.clone(), model_name.clone())?;
+    settings::update_app_setting_debounced(&app_handle, "selected_model", model_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(enabled: bool, input: &str, output: &str) -> settings::AppSettings {
+        settings::AppSettings {
+            monitoring_enabled: enabled.to_string(),
+            selected_microphone: input.to_string(),
+            selected_output_device: output.to_string(),
+            microphone_volume: "80".to_string(),
+            selected_model: "rnnnoise".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn monitoring_resumes_only_when_enabled_with_connected_devices() {
+        let inputs = vec!["USB Mic".to_string()];
+        let outputs = vec!["BlackHole 2ch".to_string()];
+        assert_eq!(
+            monitoring_resume_plan(&saved(true, "USB Mic", "BlackHole 2ch"), &inputs, &outputs),
+            MonitoringResume::Start {
+                input_device: "USB Mic".to_string(),
+                output_device: "BlackHole 2ch".to_string(),
+                model_name: "rnnnoise".to_string(),
+                volume: 0.8,
+                host: None,
+            }
+        );
+        let mut on_jack = saved(true, "USB Mic", "");
+        on_jack.monitoring_host = "JACK".to_string();
+        assert!(matches!(
+            monitoring_resume_plan(&on_jack, &inputs, &outputs),
+            MonitoringResume::Start { host: Some(host), .. } if host == "JACK"
+        ));
+        assert_eq!(
+            monitoring_resume_plan(&saved(false, "USB Mic", "BlackHole 2ch"), &inputs, &outputs),
+            MonitoringResume::Skip
+        );
+        assert_eq!(
+            monitoring_resume_plan(&saved(true, "", ""), &inputs, &outputs),
+            MonitoringResume::Skip
+        );
+        assert_eq!(
+            monitoring_resume_plan(&saved(true, "Headset", "BlackHole 2ch"), &inputs, &outputs),
+            MonitoringResume::MissingDevice("Headset".to_string())
+        );
+        assert_eq!(
+            monitoring_resume_plan(&saved(true, "USB Mic", "Speakers"), &inputs, &outputs),
+            MonitoringResume::MissingDevice("Speakers".to_string())
+        );
+        // "Default" and no output need no matching device
+        assert!(matches!(
+            monitoring_resume_plan(&saved(true, "Default", ""), &[], &[]),
+            MonitoringResume::Start { .. }
+        ));
+    }
 }
@@ -87,6 +87,41 @@ fn resolve_ffmpeg_path() -> Option<PathBuf> {
     None
 }
 
+/// ffprobe ships alongside ffmpeg; resolve it from the same location.
+fn resolve_ffprobe_path() -> Option<PathBuf> {
+    let ffmpeg = resolve_ffmpeg_path()?;
+    let name = if cfg!(target_os = "windows") { "ffprobe.exe" } else { "ffprobe" };
+    Some(match ffmpeg.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join(name),
+        _ => PathBuf::from(name),
+    })
+}
+
+/// Duration in seconds of any file FFmpeg can decode. None if ffprobe is
+/// unavailable or cannot read the file.
+pub fn probe_duration(path: &Path) -> Option<f64> {
+    let mut command = Command::new(resolve_ffprobe_path()?);
+    command
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path);
+    #[cfg(target_os = "windows")]
+    {
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
 #[tauri::command]
 pub async fn convert_to_wav(app: AppHandle, input_path: String) -> Result<String, String> {
     let recordings_dir = crate::paths::recordings_dir(&app)?;
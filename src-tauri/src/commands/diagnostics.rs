@@ -0,0 +1,174 @@
+// One-shot health check of the audio subsystem, for support requests.
+
+use crate::audio::{self, BlackHoleStatus, DefaultDevices, MicTestResult};
+use crate::commands::permissions::{self, PermissionStatus};
+use crate::error::CrispyError;
+use crate::managers::model::ModelManager;
+use crate::settings::{self, AppSettings};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+/// Length of the input stream check; long enough for the first callbacks.
+const INPUT_CHECK_SECONDS: f32 = 0.3;
+
+/// Outcome of one sub-check. A failure keeps its error instead of failing the
+/// whole report.
+#[derive(Serialize)]
+pub struct DiagnosticCheck<T> {
+    pub ok: bool,
+    pub value: Option<T>,
+    pub error: Option<CrispyError>,
+}
+
+impl<T, E: Into<CrispyError>> From<Result<T, E>> for DiagnosticCheck<T> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self {
+                ok: true,
+                value: Some(value),
+                error: None,
+            },
+            Err(e) => Self {
+                ok: false,
+                value: None,
+                error: Some(e.into()),
+            },
+        }
+    }
+}
+
+/// Download state of a model the settings point at.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ModelDiagnostic {
+    /// "transcription", "noise_suppression" or "diarization".
+    pub role: String,
+    pub id: String,
+    /// Built-in models count as downloaded.
+    pub downloaded: bool,
+}
+
+#[derive(Serialize)]
+pub struct DiagnosticsReport {
+    pub permissions: DiagnosticCheck<PermissionStatus>,
+    pub blackhole: DiagnosticCheck<BlackHoleStatus>,
+    pub default_devices: DiagnosticCheck<DefaultDevices>,
+    /// A short listen on the selected microphone.
+    pub input_stream: DiagnosticCheck<MicTestResult>,
+    pub virtual_mic: DiagnosticCheck<()>,
+    pub models: DiagnosticCheck<Vec<ModelDiagnostic>>,
+}
+
+/// The app routes processed audio through BlackHole; there is no
+/// shared-memory virtual microphone to probe.
+fn virtual_mic_check() -> Result<(), CrispyError> {
+    Err(CrispyError::Unsupported(
+        "This build has no shared-memory virtual microphone; processed audio goes through BlackHole"
+            .to_string(),
+    ))
+}
+
+/// Models named in the settings and whether their files are there.
+fn selected_models(app: &AppHandle, settings: &AppSettings) -> Result<Vec<ModelDiagnostic>, CrispyError> {
+    let model_manager: &Arc<ModelManager> = &*app.state();
+    model_manager
+        .update_download_status()
+        .map_err(|e| CrispyError::Internal(e.to_string()))?;
+    let downloaded = |id: &str| {
+        model_manager
+            .get_model_info(id)
+            .is_some_and(|info| info.is_downloaded)
+    };
+
+    let mut models = Vec::new();
+    if settings.selected_transcription_model != "none" {
+        let id = &settings.selected_transcription_model;
+        models.push(ModelDiagnostic {
+            role: "transcription".to_string(),
+            id: id.clone(),
+            downloaded: downloaded(id),
+        });
+    }
+    let built_in = crate::commands::ns_models::get_available_ns_models()
+        .iter()
+        .any(|m| m.id == settings.selected_model);
+    models.push(ModelDiagnostic {
+        role: "noise_suppression".to_string(),
+        id: settings.selected_model.clone(),
+        downloaded: built_in,
+    });
+    if settings.diarization_enabled == "true" {
+        for id in ["diarize-segmentation", "diarize-embedding"] {
+            models.push(ModelDiagnostic {
+                role: "diarization".to_string(),
+                id: id.to_string(),
+                downloaded: downloaded(id),
+            });
+        }
+    }
+    Ok(models)
+}
+
+/// Permissions, BlackHole, default devices, an input stream test, the virtual
+/// mic and the selected models in one report. Every section is present; a
+/// failed check carries its error.
+#[tauri::command]
+pub async fn run_diagnostics(app: AppHandle) -> Result<DiagnosticsReport, CrispyError> {
+    let settings = settings::load_app_settings(&app).unwrap_or_default();
+    let permissions = permissions::check_permissions().await.into();
+
+    let input_device = if settings.selected_microphone.is_empty() {
+        "Default".to_string()
+    } else {
+        settings.selected_microphone.clone()
+    };
+    let input_stream = tauri::async_runtime::spawn_blocking(move || {
+        audio::run_mic_test(&input_device, INPUT_CHECK_SECONDS, None)
+    })
+    .await
+    .map_err(|e| CrispyError::Internal(format!("Task failed to join: {}", e)))
+    .and_then(|result| result)
+    .into();
+
+    Ok(DiagnosticsReport {
+        permissions,
+        blackhole: audio::get_blackhole_status().into(),
+        default_devices: audio::get_default_devices(None).into(),
+        input_stream,
+        virtual_mic: virtual_mic_check().into(),
+        models: selected_models(&app, &settings).into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_keeps_every_section_when_checks_fail() {
+        let report = DiagnosticsReport {
+            permissions: Ok::<_, CrispyError>(PermissionStatus {
+                microphone: "denied".to_string(),
+                screen_recording: false,
+            })
+            .into(),
+            blackhole: Err::<BlackHoleStatus, _>("no driver".to_string()).into(),
+            default_devices: Err::<DefaultDevices, _>(CrispyError::NoInputDevices("none".to_string())).into(),
+            input_stream: Err::<MicTestResult, _>(CrispyError::DeviceNotFound("gone".to_string())).into(),
+            virtual_mic: virtual_mic_check().into(),
+            models: Ok::<_, CrispyError>(Vec::new()).into(),
+        };
+
+        let json = serde_json::to_value(&report).unwrap();
+        for section in ["permissions", "blackhole", "default_devices", "input_stream", "virtual_mic", "models"] {
+            assert!(json.get(section).is_some(), "missing {}", section);
+        }
+        assert_eq!(json["permissions"]["ok"], true);
+        assert_eq!(json["permissions"]["value"]["microphone"], "denied");
+        assert_eq!(json["blackhole"]["error"]["code"], "internal");
+        assert_eq!(json["default_devices"]["error"]["code"], "no_input_devices");
+        assert_eq!(json["input_stream"]["ok"], false);
+        assert!(json["input_stream"]["value"].is_null());
+        assert_eq!(json["virtual_mic"]["error"]["code"], "unsupported");
+    }
+}
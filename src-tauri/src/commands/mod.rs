@@ -1,8 +1,10 @@
 pub mod audio;
 pub mod convert;
+pub mod diagnostics;
 pub mod models;
 pub mod ns_models;
 pub mod permissions;
 pub mod recording;
+pub mod session;
 pub mod settings;
 pub mod transcription;
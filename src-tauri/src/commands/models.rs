@@ -1,6 +1,8 @@
 // Transcription model commands. Adapted from Handy (open license).
 
-use crate::managers::model::{ModelInfo, ModelManager};
+use crate::managers::model::{
+    recommend_models, DeviceProfile, ModelInfo, ModelManager, ModelRecommendation,
+};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 
@@ -15,6 +17,37 @@ struct ModelStateEvent {
 #[derive(Clone, Default)]
 pub struct SelectedModelState(pub Arc<std::sync::Mutex<String>>);
 
+/// Sent to every window when the selected transcription model changes.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct TranscriptionModelChangedEvent {
+    /// "none" when the selection was cleared.
+    pub model_id: String,
+}
+
+/// The selected model as reported to the frontend: "none" when empty.
+fn current_model_id(selected: &SelectedModelState) -> String {
+    let current = selected.0.lock().unwrap().clone();
+    if current.is_empty() {
+        "none".to_string()
+    } else {
+        current
+    }
+}
+
+/// Store a new selection ("none" or empty clears it) and build the event
+/// announcing it.
+fn select_model(selected: &SelectedModelState, model_id: &str) -> TranscriptionModelChangedEvent {
+    let stored = if model_id == "none" { "" } else { model_id };
+    *selected.0.lock().unwrap() = stored.to_string();
+    TranscriptionModelChangedEvent {
+        model_id: current_model_id(selected),
+    }
+}
+
+fn emit_model_changed(app_handle: &AppHandle, event: TranscriptionModelChangedEvent) {
+    let _ = app_handle.emit("transcription-model-changed", event);
+}
+
 #[tauri::command]
 pub async fn get_available_models(
     model_manager: State<'_, Arc<ModelManager>>,
@@ -30,6 +63,15 @@ pub async fn get_model_info(
     Ok(model_manager.get_model_info(&model_id))
 }
 
+/// Transcription models ranked for this machine's memory and CPU, best first.
+#[tauri::command]
+pub async fn get_recommended_models(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<Vec<ModelRecommendation>, String> {
+    let device = DeviceProfile::detect();
+    Ok(recommend_models(&model_manager.get_available_models(), &device))
+}
+
 #[tauri::command]
 pub async fn download_model(
     model_manager: State<'_, Arc<ModelManager>>,
@@ -49,9 +91,8 @@ pub async fn delete_model(
     model_id: String,
 ) -> Result<(), String> {
     model_manager.delete_model(&model_id).map_err(|e| e.to_string())?;
-    let mut sel = selected_state.0.lock().unwrap();
-    if *sel == model_id {
-        *sel = String::new();
+    if current_model_id(&selected_state) == model_id {
+        emit_model_changed(&app_handle, select_model(&selected_state, "none"));
         let _ = app_handle.emit(
             "model-state-changed",
             ModelStateEvent {
@@ -73,8 +114,7 @@ pub async fn set_active_model(
     model_id: String,
 ) -> Result<(), String> {
     if model_id == "none" {
-        let mut sel = selected_state.0.lock().unwrap();
-        *sel = String::new();
+        emit_model_changed(&app_handle, select_model(&selected_state, "none"));
         let _ = app_handle.emit(
             "model-state-changed",
             ModelStateEvent {
@@ -103,8 +143,7 @@ pub async fn set_active_model(
         },
     );
 
-    let mut sel = selected_state.0.lock().unwrap();
-    *sel = info.id.clone();
+    let changed = select_model(&selected_state, &info.id);
 
     let _ = app_handle.emit(
         "model-state-changed",
@@ -115,6 +154,7 @@ pub async fn set_active_model(
             error: None,
         },
     );
+    emit_model_changed(&app_handle, changed);
     Ok(())
 }
 
@@ -122,12 +162,7 @@ pub async fn set_active_model(
 pub async fn get_current_model(
     selected_state: State<'_, SelectedModelState>,
 ) -> Result<String, String> {
-    let current = selected_state.0.lock().unwrap().clone();
-    Ok(if current.is_empty() {
-        "none".to_string()
-    } else {
-        current
-    })
+    Ok(current_model_id(&selected_state))
 }
 
 #[tauri::command]
@@ -140,7 +175,42 @@ pub async fn cancel_download(
         .map_err(|e| e.to_string())
 }
 
+/// Best-ranked model for this machine (see `recommend_models`), falling
+/// back to Parakeet when the catalog has nothing to rank.
 #[tauri::command]
-pub async fn get_recommended_first_model() -> Result<String, String> {
-    Ok("parakeet-tdt-0.6b-v3".to_string())
+pub async fn get_recommended_first_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<String, String> {
+    let available = model_manager.get_available_models();
+    Ok(recommend_models(&available, &DeviceProfile::detect())
+        .into_iter()
+        .next()
+        .map(|m| m.model_id)
+        .unwrap_or_else(|| "parakeet-tdt-0.6b-v3".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selecting_model_updates_shared_state_and_builds_event() {
+        let selected = SelectedModelState::default();
+        assert_eq!(current_model_id(&selected), "none");
+
+        // A clone shares the state, like the handle other windows go through
+        let other_window = selected.clone();
+        let event = select_model(&selected, "parakeet-tdt-0.6b-v3");
+        assert_eq!(event.model_id, "parakeet-tdt-0.6b-v3");
+        assert_eq!(current_model_id(&other_window), "parakeet-tdt-0.6b-v3");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "model_id": "parakeet-tdt-0.6b-v3" })
+        );
+
+        let cleared = select_model(&selected, "none");
+        assert_eq!(cleared.model_id, "none");
+        assert!(other_window.0.lock().unwrap().is_empty());
+        assert_eq!(current_model_id(&other_window), "none");
+    }
 }
@@ -1,6 +1,9 @@
-// Noise suppression (NS) models: list only. No download; models are built-in or from rnnnoise.
+// Noise suppression (NS) models: list and benchmark. No download; models are built-in or from rnnnoise.
 
+use crate::audio::{denoise_buffer, NoiseParams};
+use crate::recording::SAMPLE_RATE;
 use serde::Serialize;
+use std::time::Instant;
 
 #[derive(Clone, Serialize)]
 pub struct NsModelInfo {
@@ -31,3 +34,147 @@ pub fn get_available_ns_models() -> Vec<NsModelInfo> {
         },
     ]
 }
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NsBenchmarkEntry {
+    pub id: String,
+    pub name: String,
+    /// Processing time / audio duration; below 1.0 keeps up with real time.
+    pub realtime_factor: f64,
+    /// Output SNR minus input SNR on the synthetic buffer, in dB.
+    pub snr_delta_db: f64,
+}
+
+/// Length of the synthetic benchmark buffer.
+const BENCHMARK_SECONDS: f64 = 10.0;
+/// Speech and noise-only sections alternate at this period.
+const BENCHMARK_SECTION_SECONDS: f64 = 0.5;
+/// Ignored at section edges so model latency doesn't leak between sections.
+const BENCHMARK_EDGE_SECONDS: f64 = 0.1;
+
+/// Alternating "voiced" sections (harmonics with a syllable-rate envelope)
+/// and noise-only sections, both over the same white noise floor.
+fn benchmark_signal(seconds: f64, sample_rate: usize) -> Vec<f32> {
+    let mut rng: u32 = 0x2545_f491;
+    let section = (BENCHMARK_SECTION_SECONDS * sample_rate as f64) as usize;
+    (0..(seconds * sample_rate as f64) as usize)
+        .map(|i| {
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            let noise = ((rng >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * 0.05;
+            if (i / section) % 2 == 1 {
+                return noise;
+            }
+            let t = i as f32 / sample_rate as f32;
+            let envelope = 0.5 + 0.5 * (2.0 * std::f32::consts::PI * 4.0 * t).sin();
+            let voice: f32 = (1..=5)
+                .map(|h| (2.0 * std::f32::consts::PI * 150.0 * h as f32 * t).sin() / h as f32)
+                .sum();
+            0.2 * envelope * voice + noise
+        })
+        .collect()
+}
+
+/// SNR of `signal` as laid out by `benchmark_signal`: power of the voiced
+/// sections over power of the noise-only sections, edges trimmed.
+fn section_snr_db(signal: &[f32], sample_rate: usize) -> f64 {
+    let section = (BENCHMARK_SECTION_SECONDS * sample_rate as f64) as usize;
+    let edge = (BENCHMARK_EDGE_SECONDS * sample_rate as f64) as usize;
+    let (mut voiced, mut voiced_n, mut noise, mut noise_n) = (0.0f64, 0usize, 0.0f64, 0usize);
+    for (i, s) in signal.iter().enumerate() {
+        let pos = i % section;
+        if pos < edge || pos >= section - edge {
+            continue;
+        }
+        let power = (*s as f64) * (*s as f64);
+        if (i / section).is_multiple_of(2) {
+            voiced += power;
+            voiced_n += 1;
+        } else {
+            noise += power;
+            noise_n += 1;
+        }
+    }
+    let voiced = voiced / voiced_n.max(1) as f64;
+    let noise = (noise / noise_n.max(1) as f64).max(1e-12);
+    10.0 * (voiced.max(1e-12) / noise).log10()
+}
+
+fn benchmark_models(seconds: f64) -> Vec<NsBenchmarkEntry> {
+    let input = benchmark_signal(seconds, SAMPLE_RATE);
+    let input_snr = section_snr_db(&input, SAMPLE_RATE);
+    let mut report: Vec<NsBenchmarkEntry> = get_available_ns_models()
+        .into_iter()
+        .filter(|m| m.id != "dummy")
+        .map(|model| {
+            let start = Instant::now();
+            // Fixed noise params keep the "noisy" baseline comparable between runs
+            let output = denoise_buffer(&model.id, &input, SAMPLE_RATE as f32, NoiseParams::default());
+            let elapsed = start.elapsed().as_secs_f64();
+            // Models that drop leading frames are re-aligned to the section grid
+            let dropped = input.len().saturating_sub(output.len());
+            let mut aligned = vec![0.0f32; dropped];
+            aligned.extend_from_slice(&output);
+            NsBenchmarkEntry {
+                id: model.id,
+                name: model.name,
+                realtime_factor: elapsed / seconds,
+                snr_delta_db: section_snr_db(&aligned, SAMPLE_RATE) - input_snr,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| {
+        b.snr_delta_db
+            .total_cmp(&a.snr_delta_db)
+            .then(a.realtime_factor.total_cmp(&b.realtime_factor))
+    });
+    report
+}
+
+/// Run every NS model over a fixed synthetic buffer and report speed and
+/// noise reduction, best reduction first.
+#[tauri::command]
+pub async fn benchmark_ns_models() -> Result<Vec<NsBenchmarkEntry>, String> {
+    tauri::async_runtime::spawn_blocking(|| benchmark_models(BENCHMARK_SECONDS))
+        .await
+        .map_err(|e| format!("Task failed to join: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn benchmark_reports_every_model_with_plausible_metrics() {
+        let report = benchmark_models(2.0);
+        let expected: Vec<String> = get_available_ns_models()
+            .into_iter()
+            .map(|m| m.id)
+            .filter(|id| id != "dummy")
+            .collect();
+        assert_eq!(report.len(), expected.len());
+        for id in &expected {
+            assert!(report.iter().any(|e| &e.id == id), "missing {}", id);
+        }
+        for entry in &report {
+            assert!(entry.realtime_factor.is_finite() && entry.realtime_factor > 0.0);
+            assert!(entry.snr_delta_db.is_finite());
+        }
+        // "noisy" adds noise, so it can only make the SNR worse
+        let noisy = report.iter().find(|e| e.id == "noisy").unwrap();
+        assert!(noisy.snr_delta_db < 0.0);
+        assert!(report
+            .windows(2)
+            .all(|w| w[0].snr_delta_db >= w[1].snr_delta_db));
+    }
+
+    #[test]
+    fn unprocessed_signal_has_zero_snr_delta() {
+        let input = benchmark_signal(2.0, SAMPLE_RATE);
+        let passthrough = denoise_buffer("dummy", &input, SAMPLE_RATE as f32, NoiseParams::default());
+        let delta = section_snr_db(&passthrough, SAMPLE_RATE) - section_snr_db(&input, SAMPLE_RATE);
+        assert!(delta.abs() < 1e-9);
+        assert!(section_snr_db(&input, SAMPLE_RATE) > 10.0);
+    }
+}
@@ -1,15 +1,16 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter, Manager};
 
 use crate::app_state::AppState;
+use crate::error::CrispyError;
 use crate::recording;
 
 static RECORDING_ACTIVE: AtomicBool = AtomicBool::new(false);
@@ -26,46 +27,91 @@ fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
 /// Reject any path supplied over IPC that does not resolve to an existing file
 /// inside the recordings directory. Defends destructive/read commands against a
 /// compromised or buggy frontend asking to touch arbitrary files on disk.
-fn ensure_in_recordings_dir(app: &AppHandle, path: &Path) -> Result<(), String> {
+pub fn ensure_in_recordings_dir(app: &AppHandle, path: &Path) -> Result<(), CrispyError> {
     let dir = recordings_dir(app)?;
     let canon_dir = dir
         .canonicalize()
         .map_err(|e| format!("Failed to resolve recordings directory: {}", e))?;
     let canon = path
         .canonicalize()
-        .map_err(|_| "Recording not found".to_string())?;
+        .map_err(|_| CrispyError::NotFound("Recording not found".to_string()))?;
     if !canon.starts_with(&canon_dir) {
-        return Err("Path is outside the recordings directory".to_string());
+        return Err(CrispyError::PermissionDenied(
+            "Path is outside the recordings directory".to_string(),
+        ));
     }
     Ok(())
 }
 
+/// What the app picked for recording resolves to when a recording starts.
+#[derive(Clone, Debug, PartialEq)]
+enum AppResolution {
+    /// No app selected: mic-only by choice.
+    NoneSelected,
+    /// Id of the running instance to capture.
+    Running(String),
+    /// An app was selected but no instance of it is running.
+    NotRunning,
+}
+
+/// Settings store a bundle_id (e.g. "com.spotify.client") rather than a
+/// PID-based id (e.g. "com.spotify.client_12345"); find a running instance of
+/// it. A PID-based id from older settings is kept if that process still runs.
+fn resolve_recording_app(app_id: &str, running: &[recording::RecordableApp]) -> AppResolution {
+    if app_id.is_empty() || app_id == "none" {
+        return AppResolution::NoneSelected;
+    }
+    running
+        .iter()
+        .find(|a| a.bundle_id == app_id)
+        .or_else(|| running.iter().find(|a| a.id == app_id))
+        .map_or(AppResolution::NotRunning, |a| AppResolution::Running(a.id.clone()))
+}
+
+/// Emitted when the app selected for recording isn't running, so the
+/// recording goes ahead mic-only.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RecordingAppUnavailableEvent {
+    pub app_id: String,
+}
+
 pub fn do_start_recording(
     app: &AppHandle,
     state: &AppState,
     app_id: &str,
-) -> Result<(), String> {
-    // Resolve bundle_id to actual PID-based app id if needed.
-    // Settings now store bundle_id (e.g. "com.spotify.client") instead of
-    // PID-based ids (e.g. "com.spotify.client_12345"). We resolve the bundle_id
-    // to a currently running instance here.
-    let resolved_app_id = if !app_id.is_empty() && app_id != "none" {
-        let apps = recording::get_recordable_apps().unwrap_or_default();
-        if let Some(running) = apps.iter().find(|a| a.bundle_id == app_id) {
-            running.id.clone()
-        } else {
-            // Fallback: use as-is (might be an old PID-based id)
-            app_id.to_string()
+) -> Result<(), CrispyError> {
+    // Always enumerate fresh: a cached list may still hold a PID that has
+    // since exited. If enumeration fails, the id is used as-is.
+    let resolution = match recording::get_recordable_apps_cached(true) {
+        Ok(apps) => resolve_recording_app(app_id, &apps),
+        Err(_) => match resolve_recording_app(app_id, &[]) {
+            AppResolution::NotRunning => AppResolution::Running(app_id.to_string()),
+            other => other,
+        },
+    };
+    let resolved_app_id = match resolution {
+        AppResolution::Running(id) => id,
+        AppResolution::NoneSelected => String::new(),
+        AppResolution::NotRunning => {
+            eprintln!("Warning: {} is not running, recording mic only", app_id);
+            let _ = app.emit(
+                "recording-app-unavailable",
+                RecordingAppUnavailableEvent {
+                    app_id: app_id.to_string(),
+                },
+            );
+            String::new()
         }
-    } else {
-        app_id.to_string()
     };
     let app_id = &resolved_app_id;
 
+    let recording_info = current_recording_info(app, state);
+    // Without monitoring nothing feeds the mic buffer, so what it holds is stale
+    let monitoring = crate::audio::is_monitoring_active(state.audio.clone());
     let mut recording = state.recording.lock().unwrap();
 
     if recording.writer.lock().unwrap().is_some() {
-        return Err("Recording already in progress".to_string());
+        return Err(CrispyError::Busy("Recording already in progress".to_string()));
     }
 
     let output_dir = recordings_dir(app)?;
@@ -74,21 +120,41 @@ pub fn do_start_recording(
     let filename = format!("recording_{}.wav", now.format("%Y%m%d_%H%M%S"));
     let output_path = output_dir.join(filename);
 
-    let writer = recording::WavWriter::new(output_path)
+    let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+    let wav_config = recording::WavConfig::from_setting(&settings.recording_format);
+    let mut writer = recording::WavWriter::with_config(output_path, wav_config)
         .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    writer.set_ceiling_db(settings.recording_ceiling_db());
+    writer.set_dither(settings.recording_dither == "true");
+    if let Err(e) = save_recording_info(writer.output_path(), &recording_info) {
+        eprintln!("Warning: {}", e);
+    }
 
+    let pre_roll = if monitoring {
+        (settings.recording_pre_roll_seconds() * recording::SAMPLE_RATE as f64) as usize
+    } else {
+        0
+    };
     *recording.writer.lock().unwrap() = Some(writer);
-    recording.mic_buffer.lock().unwrap().clear();
-    recording.app_buffer.lock().unwrap().clear();
+    recording::start_buffers_with_pre_roll(
+        &mut recording.mic_buffer.lock().unwrap(),
+        &mut recording.app_buffer.lock().unwrap(),
+        pre_roll,
+    );
 
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     if !app_id.is_empty() && app_id != "none" {
-        match recording::start_app_audio_capture(app_id, recording.app_buffer.clone()) {
+        match recording::start_app_audio_capture(
+            app_id,
+            recording.app_buffer.clone(),
+            settings.app_downmix(),
+        ) {
             Ok(stream) => {
                 *recording.app_audio_stream.lock().unwrap() = Some(stream);
             }
             Err(e) => {
                 eprintln!("Warning: Failed to start app audio capture: {}", e);
+                recording::invalidate_recordable_apps_cache();
             }
         }
     }
@@ -104,19 +170,26 @@ pub fn do_start_recording(
             app_id,
             recording.app_buffer.clone(),
             recording.app_audio_stop.clone(),
+            settings.app_downmix(),
         ) {
             Ok(handle) => {
                 *recording.app_audio_worker.lock().unwrap() = Some(handle);
             }
             Err(e) => {
                 eprintln!("Warning: Failed to start app audio capture: {}", e);
+                recording::invalidate_recordable_apps_cache();
                 eprintln!("Note: Process loopback requires Windows 10 2004+ (build 19041)");
                 // Continue with mic-only recording
             }
         }
     }
 
+    let limiter = (settings.recording_auto_attenuation == "true").then(|| {
+        recording::MixLimiter::new(recording::SAMPLE_RATE, MIX_LIMITER_RELEASE)
+    });
     let handle = start_recording_worker(
+        app.clone(),
+        limiter,
         recording.mic_buffer.clone(),
         recording.app_buffer.clone(),
         recording.writer.clone(),
@@ -125,7 +198,7 @@ pub fn do_start_recording(
     Ok(())
 }
 
-pub fn do_stop_recording(state: &AppState) -> Result<String, String> {
+pub fn do_stop_recording(state: &AppState) -> Result<String, CrispyError> {
     RECORDING_ACTIVE.store(false, Ordering::SeqCst);
 
     #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
@@ -182,10 +255,120 @@ pub fn do_stop_recording(state: &AppState) -> Result<String, String> {
         return Ok(output_path.to_string_lossy().to_string());
     }
 
-    Err("No recording in progress".to_string())
+    Err(CrispyError::NotRunning("No recording in progress".to_string()))
+}
+
+/// Level of the summed mic+app signal, i.e. what actually lands in the file.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct MixLevel {
+    pub peak: f32,
+    pub rms: f32,
+    /// Some sample reached full scale and will be clipped by the writer.
+    pub clipped: bool,
+}
+
+/// Throttle for `recording-mix-level` (a frame is ~24 ms at 48 kHz).
+const MIX_LEVEL_EMIT_INTERVAL: Duration = Duration::from_millis(50);
+/// Throttle for `recording-sync`; buffer health changes slowly.
+const SYNC_EMIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Capture buffer fill and alignment trims, for diagnosing lip-sync drift.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct RecordingSyncEvent {
+    /// Samples waiting in each buffer after the last mixed frame.
+    pub mic_buffered: usize,
+    pub app_buffered: usize,
+    /// Samples dropped to realign the sources since the previous event.
+    pub mic_trimmed: usize,
+    pub app_trimmed: usize,
+    pub trimmed: bool,
+}
+/// Recovery time of the optional mix limiter; slow enough not to pump.
+const MIX_LIMITER_RELEASE: Duration = Duration::from_millis(800);
+
+fn mix_level(frame: &[f32]) -> MixLevel {
+    if frame.is_empty() {
+        return MixLevel {
+            peak: 0.0,
+            rms: 0.0,
+            clipped: false,
+        };
+    }
+    let peak = frame.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+    MixLevel {
+        peak,
+        rms,
+        clipped: peak >= 1.0,
+    }
+}
+
+/// Samples dropped from the head of each capture buffer to keep them aligned.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct DesyncTrim {
+    mic: usize,
+    app: usize,
+}
+
+/// How much to drop so neither source is more than `max_desync` samples
+/// ahead of the other; only the one ahead is trimmed.
+fn desync_trim(mic_len: usize, app_len: usize, max_desync: usize) -> DesyncTrim {
+    DesyncTrim {
+        mic: mic_len.saturating_sub(app_len + max_desync),
+        app: app_len.saturating_sub(mic_len + max_desync),
+    }
+}
+
+/// Take one frame from the capture buffers and mix it, or `None` until the mic
+/// has a full frame. A source more than `max_desync` samples ahead of the other
+/// drops its oldest samples first (added to `trimmed`); an app buffer short of
+/// a frame contributes silence. Returns the (left, right) frame, both channels
+/// carrying the mix.
+fn mix_one_frame(
+    mic_buffer: &Mutex<VecDeque<f32>>,
+    app_buffer: &Mutex<VecDeque<f32>>,
+    frame_size: usize,
+    max_desync: usize,
+    trimmed: &mut DesyncTrim,
+) -> Option<(Vec<f32>, Vec<f32>)> {
+    if mic_buffer.lock().unwrap().len() < frame_size {
+        return None;
+    }
+
+    // Align buffer heads if one source gets significantly ahead.
+    {
+        let mut mic_buf = mic_buffer.lock().unwrap();
+        let mut app_buf = app_buffer.lock().unwrap();
+        let trim = desync_trim(mic_buf.len(), app_buf.len(), max_desync);
+        mic_buf.drain(..trim.mic);
+        app_buf.drain(..trim.app);
+        trimmed.mic += trim.mic;
+        trimmed.app += trim.app;
+    }
+
+    let mut left: Vec<f32> = {
+        let mut mic_buf = mic_buffer.lock().unwrap();
+        (0..frame_size)
+            .map(|_| mic_buf.pop_front().unwrap_or(0.0))
+            .collect()
+    };
+
+    {
+        let mut app_buf = app_buffer.lock().unwrap();
+        if app_buf.len() >= frame_size {
+            for (sample, app) in left.iter_mut().zip(app_buf.drain(..frame_size)) {
+                *sample += app;
+            }
+        }
+    }
+
+    let right = left.clone();
+    Some((left, right))
 }
 
 fn start_recording_worker(
+    app: AppHandle,
+    mut limiter: Option<recording::MixLimiter>,
     mic_buffer: Arc<Mutex<VecDeque<f32>>>,
     app_buffer: Arc<Mutex<VecDeque<f32>>>,
     writer: Arc<Mutex<Option<recording::WavWriter>>>,
@@ -196,9 +379,12 @@ fn start_recording_worker(
         let frame_size = 1152;
         // Keep streams roughly aligned within ~50ms to reduce lip-sync drift.
         let max_desync_samples = (recording::SAMPLE_RATE / 20).max(frame_size); // 50 ms @ 48kHz
-        let mut left_frame = vec![0.0f32; frame_size];
-        let mut right_frame = vec![0.0f32; frame_size];
         let mut frames_encoded = 0;
+        let mut last_level_emit = Instant::now();
+        // Loudest level since the last emit, so short clips aren't missed between events
+        let mut pending_level: Option<MixLevel> = None;
+        let mut last_sync_emit = Instant::now();
+        let mut trimmed = DesyncTrim::default();
 
         if std::env::var("CRISPY_AUDIO_DEBUG").is_ok() {
             println!("Recording worker started");
@@ -212,55 +398,45 @@ fn start_recording_worker(
                 }
             }
 
-            let mic_available = mic_buffer.lock().unwrap().len();
-            if mic_available < frame_size {
+            let Some((mut left_frame, mut right_frame)) =
+                mix_one_frame(&mic_buffer, &app_buffer, frame_size, max_desync_samples, &mut trimmed)
+            else {
                 thread::sleep(Duration::from_millis(10));
                 continue;
+            };
+            if let Some(limiter) = limiter.as_mut() {
+                limiter.process(&mut left_frame);
+                right_frame.copy_from_slice(&left_frame);
             }
 
-            // Align buffer heads if one source gets significantly ahead.
-            {
-                let mut mic_buf = mic_buffer.lock().unwrap();
-                let mut app_buf = app_buffer.lock().unwrap();
-                let mic_len = mic_buf.len();
-                let app_len = app_buf.len();
-
-                if mic_len > app_len + max_desync_samples {
-                    let trim = mic_len - app_len - max_desync_samples;
-                    for _ in 0..trim {
-                        let _ = mic_buf.pop_front();
-                    }
-                } else if app_len > mic_len + max_desync_samples {
-                    let trim = app_len - mic_len - max_desync_samples;
-                    for _ in 0..trim {
-                        let _ = app_buf.pop_front();
-                    }
-                }
-            }
-
-            {
-                let mut mic_buf = mic_buffer.lock().unwrap();
-                for i in 0..frame_size {
-                    left_frame[i] = mic_buf.pop_front().unwrap_or(0.0);
-                }
-            }
-
-            let app_available = app_buffer.lock().unwrap().len();
-            if app_available >= frame_size {
-                let mut app_buf = app_buffer.lock().unwrap();
-                for i in 0..frame_size {
-                    right_frame[i] = app_buf.pop_front().unwrap_or(0.0);
-                }
-            } else {
-                for i in 0..frame_size {
-                    right_frame[i] = 0.0;
+            let level = mix_level(&left_frame);
+            pending_level = Some(match pending_level {
+                Some(prev) => MixLevel {
+                    peak: prev.peak.max(level.peak),
+                    rms: prev.rms.max(level.rms),
+                    clipped: prev.clipped || level.clipped,
+                },
+                None => level,
+            });
+            if last_level_emit.elapsed() >= MIX_LEVEL_EMIT_INTERVAL {
+                last_level_emit = Instant::now();
+                if let Some(level) = pending_level.take() {
+                    let _ = app.emit("recording-mix-level", level);
                 }
             }
-
-            for i in 0..frame_size {
-                let mixed = left_frame[i] + right_frame[i];
-                left_frame[i] = mixed;
-                right_frame[i] = mixed;
+            if last_sync_emit.elapsed() >= SYNC_EMIT_INTERVAL {
+                last_sync_emit = Instant::now();
+                let _ = app.emit(
+                    "recording-sync",
+                    RecordingSyncEvent {
+                        mic_buffered: mic_buffer.lock().unwrap().len(),
+                        app_buffered: app_buffer.lock().unwrap().len(),
+                        mic_trimmed: trimmed.mic,
+                        app_trimmed: trimmed.app,
+                        trimmed: trimmed != DesyncTrim::default(),
+                    },
+                );
+                trimmed = DesyncTrim::default();
             }
 
             {
@@ -292,39 +468,57 @@ fn start_recording_worker(
 
 // --- Tauri commands ---
 
+/// Enumerating apps and resolving their icons shells out, so it runs off the
+/// async runtime.
 #[tauri::command]
-pub fn get_recordable_apps() -> Result<Vec<recording::RecordableApp>, String> {
-    recording::get_recordable_apps()
+pub async fn get_recordable_apps(
+    force: Option<bool>,
+    include_icons: Option<bool>,
+) -> Result<Vec<recording::RecordableApp>, CrispyError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut apps = recording::get_recordable_apps_cached(force.unwrap_or(false))?;
+        if include_icons.unwrap_or(false) {
+            recording::attach_app_icons(&mut apps);
+        }
+        Ok::<_, String>(apps)
+    })
+    .await
+    .map_err(|e| CrispyError::Internal(format!("Task failed to join: {}", e)))?
+    .map_err(CrispyError::from)
 }
 
+/// Start recording the mic, plus `app_id`'s audio when one is picked. With a
+/// pre-roll set and monitoring running, the file begins with that much mic
+/// audio from before the start; there is no pre-roll without monitoring or
+/// for app audio.
 #[tauri::command]
 pub fn start_recording(
     app: AppHandle,
     state: tauri::State<AppState>,
     app_id: String,
-) -> Result<(), String> {
+) -> Result<(), CrispyError> {
     do_start_recording(&app, state.inner(), &app_id)
 }
 
 #[tauri::command]
-pub fn stop_recording(state: tauri::State<AppState>) -> Result<String, String> {
+pub fn stop_recording(state: tauri::State<AppState>) -> Result<String, CrispyError> {
     do_stop_recording(state.inner())
 }
 
 #[tauri::command]
-pub fn is_recording(state: tauri::State<AppState>) -> Result<bool, String> {
+pub fn is_recording(state: tauri::State<AppState>) -> Result<bool, CrispyError> {
     let recording = state.recording.lock().unwrap();
     let is_active = recording.writer.lock().unwrap().is_some();
     Ok(is_active)
 }
 
 #[tauri::command]
-pub fn get_recordings_dir_path(app: AppHandle) -> Result<String, String> {
+pub fn get_recordings_dir_path(app: AppHandle) -> Result<String, CrispyError> {
     Ok(recordings_dir(&app)?.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-pub fn open_recordings_dir(app: AppHandle) -> Result<(), String> {
+pub fn open_recordings_dir(app: AppHandle) -> Result<(), CrispyError> {
     let recordings_dir = recordings_dir(&app)?;
 
     #[cfg(target_os = "macos")]
@@ -379,10 +573,47 @@ pub fn open_url(url: String) -> Result<(), String> {
     Ok(())
 }
 
-/// Parse WAV file header to extract duration.
-/// Returns None if parsing fails (not a valid WAV).
-/// Handles WAV files with extra chunks (LIST, INFO, etc.) by searching for "data" chunk.
-fn get_wav_duration(path: &Path) -> Option<f64> {
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Format and data-chunk location from a WAV header.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct WavHeader {
+    format_tag: u16,
+    sample_rate: u32,
+    channels: u16,
+    byte_rate: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+    fact_samples: Option<u32>,
+    /// File offset of the first byte of sample data.
+    data_offset: u64,
+    data_size: u32,
+    /// Chunks seen before the data, for the parse log.
+    chunks: Vec<String>,
+}
+
+impl WavHeader {
+    fn is_uncompressed(&self) -> bool {
+        self.format_tag == WAVE_FORMAT_PCM || self.format_tag == WAVE_FORMAT_IEEE_FLOAT
+    }
+
+    /// Bytes per frame (one sample on every channel).
+    fn frame_bytes(&self) -> u32 {
+        if self.block_align > 0 {
+            self.block_align as u32
+        } else {
+            (self.bits_per_sample as u32).div_ceil(8) * self.channels as u32
+        }
+    }
+}
+
+/// Read a WAV header up to the start of the data chunk. Returns None when the
+/// file isn't RIFF/WAVE.
+/// Walks the chunks (LIST, INFO, etc. are skipped, honouring the pad byte after
+/// odd-sized chunks) to find "fmt ", an optional "fact" and "data".
+fn read_wav_header(path: &Path) -> Option<WavHeader> {
     use std::io::{Read, Seek, SeekFrom};
     
     let mut file = std::fs::File::open(path).ok()?;
@@ -396,13 +627,9 @@ fn get_wav_duration(path: &Path) -> Option<f64> {
         return None;
     }
     
-    let mut sample_rate = 0u32;
-    let mut num_channels = 0u16;
-    let mut bits_per_sample = 0u16;
-    let mut data_size = 0u32;
+    let mut wav = WavHeader::default();
     
-    // Search for "fmt " and "data" chunks
-    let mut chunks_found = vec![];
+    // Search for "fmt ", "fact" and "data" chunks
     loop {
         let mut chunk_header = [0u8; 8];
         if file.read_exact(&mut chunk_header).is_err() {
@@ -414,8 +641,7 @@ fn get_wav_duration(path: &Path) -> Option<f64> {
         let chunk_size = u32::from_le_bytes([
             chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]
         ]);
-        
-        chunks_found.push(format!("{} ({})", chunk_id_str, chunk_size));
+        wav.chunks.push(format!("{} ({})", chunk_id_str, chunk_size));
         
         if chunk_id == b"fmt " {
             // Read fmt chunk (should be at least 16 bytes for PCM)
@@ -423,56 +649,264 @@ fn get_wav_duration(path: &Path) -> Option<f64> {
             file.read_exact(&mut fmt_data).ok()?;
             
             if fmt_data.len() >= 16 {
-                num_channels = u16::from_le_bytes([fmt_data[2], fmt_data[3]]);
-                sample_rate = u32::from_le_bytes([fmt_data[4], fmt_data[5], fmt_data[6], fmt_data[7]]);
-                bits_per_sample = u16::from_le_bytes([fmt_data[14], fmt_data[15]]);
+                wav.format_tag = u16::from_le_bytes([fmt_data[0], fmt_data[1]]);
+                wav.channels = u16::from_le_bytes([fmt_data[2], fmt_data[3]]);
+                wav.sample_rate = u32::from_le_bytes([fmt_data[4], fmt_data[5], fmt_data[6], fmt_data[7]]);
+                wav.byte_rate = u32::from_le_bytes([fmt_data[8], fmt_data[9], fmt_data[10], fmt_data[11]]);
+                wav.block_align = u16::from_le_bytes([fmt_data[12], fmt_data[13]]);
+                wav.bits_per_sample = u16::from_le_bytes([fmt_data[14], fmt_data[15]]);
             }
+            // WAVE_FORMAT_EXTENSIBLE: the real tag leads the sub-format GUID
+            if wav.format_tag == WAVE_FORMAT_EXTENSIBLE && fmt_data.len() >= 26 {
+                wav.format_tag = u16::from_le_bytes([fmt_data[24], fmt_data[25]]);
+            }
+        } else if chunk_id == b"fact" && chunk_size >= 4 {
+            let mut fact_data = [0u8; 4];
+            file.read_exact(&mut fact_data).ok()?;
+            wav.fact_samples = Some(u32::from_le_bytes(fact_data));
+            file.seek(SeekFrom::Current(chunk_size as i64 - 4)).ok()?;
         } else if chunk_id == b"data" {
-            data_size = chunk_size;
+            wav.data_size = chunk_size;
+            wav.data_offset = file.stream_position().ok()?;
             // Found data chunk, we have everything we need
             break;
         } else {
             // Skip unknown chunk
             file.seek(SeekFrom::Current(chunk_size as i64)).ok()?;
         }
+        // Chunks are word-aligned, so an odd-sized one is followed by a zero
+        // pad byte. Some writers leave it out; a chunk id never starts with 0.
+        if chunk_size % 2 == 1 {
+            let mut pad = [0u8; 1];
+            if file.read_exact(&mut pad).is_ok() && pad[0] != 0 {
+                file.seek(SeekFrom::Current(-1)).ok()?;
+            }
+        }
     }
-    
-    if sample_rate == 0 || bits_per_sample == 0 || num_channels == 0 || data_size == 0 {
+    Some(wav)
+}
+
+/// Parse WAV file header to extract duration.
+/// Returns None if parsing fails (not a valid WAV).
+/// PCM and float use the data size; other formats prefer the fact sample
+/// count and fall back to the byte rate.
+fn get_wav_duration(path: &Path) -> Option<f64> {
+    let wav = read_wav_header(path)?;
+    let uncompressed = wav.is_uncompressed();
+    let frame_bytes = wav.frame_bytes();
+    let num_samples = match wav.fact_samples {
+        Some(samples) if !uncompressed => Some(samples as f64),
+        _ if uncompressed && frame_bytes > 0 => Some((wav.data_size / frame_bytes) as f64),
+        _ if wav.byte_rate > 0 => Some(wav.data_size as f64 / wav.byte_rate as f64 * wav.sample_rate as f64),
+        _ => None,
+    };
+    let Some(num_samples) = num_samples.filter(|&n| n > 0.0 && wav.sample_rate > 0 && wav.channels > 0)
+    else {
         eprintln!(
-            "[WAV] Failed to parse {}: sr={}, bits={}, ch={}, data_size={}, chunks={:?}",
-            path.display(), sample_rate, bits_per_sample, num_channels, data_size, chunks_found
+            "[WAV] Failed to parse {}: format={:#06x}, sr={}, bits={}, ch={}, data_size={}, chunks={:?}",
+            path.display(), wav.format_tag, wav.sample_rate, wav.bits_per_sample, wav.channels, wav.data_size, wav.chunks
         );
         return None;
-    }
+    };
     
     // Calculate duration
-    let bytes_per_sample = (bits_per_sample / 8) as u32;
-    let num_samples = data_size / (bytes_per_sample * num_channels as u32);
-    let duration_seconds = num_samples as f64 / sample_rate as f64;
+    let duration_seconds = num_samples / wav.sample_rate as f64;
     
     eprintln!(
-        "[WAV] Parsed {}: {:.1}s (sr={}, ch={}, bits={}, chunks={:?})",
+        "[WAV] Parsed {}: {:.1}s (format={:#06x}, sr={}, ch={}, bits={}, chunks={:?})",
         path.file_name().and_then(|n| n.to_str()).unwrap_or("?"),
-        duration_seconds, sample_rate, num_channels, bits_per_sample, chunks_found
+        duration_seconds, wav.format_tag, wav.sample_rate, wav.channels, wav.bits_per_sample, wav.chunks
     );
     
     Some(duration_seconds)
 }
 
+/// A point in a recording and where its audio starts in the file.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct SeekPoint {
+    pub seconds: f64,
+    /// Offset from the start of the file, inside the data chunk.
+    pub byte_offset: u64,
+}
+
+/// Byte offset of the frame at `seconds` in an uncompressed WAV, clamped to
+/// the end of the data chunk. None for compressed formats, whose frames
+/// aren't a fixed size.
+fn pcm_byte_offset(wav: &WavHeader, seconds: f64) -> Option<u64> {
+    let frame_bytes = wav.frame_bytes() as u64;
+    if !wav.is_uncompressed() || frame_bytes == 0 || wav.sample_rate == 0 {
+        return None;
+    }
+    let frame = (seconds.max(0.0) * wav.sample_rate as f64).floor() as u64;
+    let data_frames = wav.data_size as u64 / frame_bytes;
+    Some(wav.data_offset + frame.min(data_frames) * frame_bytes)
+}
+
+/// Where playback or streaming should start reading to land at `seconds`,
+/// computed from the format. Only PCM and float WAVs have fixed-size frames
+/// to compute it from; there is no index for compressed recordings (imported
+/// MP3/M4A/FLAC, compressed WAV), so those are `Unsupported`.
+fn find_seek_point(recording_path: &Path, seconds: f64) -> Result<SeekPoint, CrispyError> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(CrispyError::InvalidArgument(format!(
+            "Invalid seek position: {}",
+            seconds
+        )));
+    }
+    let unsupported = || {
+        CrispyError::Unsupported(
+            "Seeking by byte offset needs an uncompressed WAV recording; convert it to WAV first"
+                .to_string(),
+        )
+    };
+    if recording_extension(recording_path).as_deref() != Some("wav") {
+        return Err(unsupported());
+    }
+    let wav = read_wav_header(recording_path).ok_or_else(unsupported)?;
+    let byte_offset = pcm_byte_offset(&wav, seconds).ok_or_else(unsupported)?;
+    let seconds =
+        (byte_offset - wav.data_offset) as f64 / wav.frame_bytes() as f64 / wav.sample_rate as f64;
+    Ok(SeekPoint { seconds, byte_offset })
+}
+
+/// Byte offset to read from to land at `seconds` in a recording. Uncompressed
+/// WAV only: compressed formats have no seek index and fail with
+/// `unsupported`; `convert_to_wav` makes them seekable.
+#[tauri::command]
+pub fn seek_offset(
+    app: AppHandle,
+    recording_path: String,
+    seconds: f64,
+) -> Result<SeekPoint, CrispyError> {
+    let path = Path::new(&recording_path);
+    ensure_in_recordings_dir(&app, path)?;
+    find_seek_point(path, seconds)
+}
+
+/// Parse the FLAC STREAMINFO block for duration (total samples / sample rate).
+/// Skips a leading ID3v2 tag. Returns None when the stream length is unknown.
+fn get_flac_duration(path: &Path) -> Option<f64> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut magic = [0u8; 4];
+    file.read_exact(&mut magic).ok()?;
+
+    if &magic[0..3] == b"ID3" {
+        // ID3v2 header: "ID3" ver(2) flags(1) size(4, synchsafe)
+        let mut rest = [0u8; 6];
+        file.read_exact(&mut rest).ok()?;
+        let size = rest[2..6]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 7) | (b & 0x7F) as u64);
+        file.seek(SeekFrom::Start(10 + size)).ok()?;
+        file.read_exact(&mut magic).ok()?;
+    }
+    if &magic != b"fLaC" {
+        return None;
+    }
+
+    // First metadata block is always STREAMINFO (type 0, 34 bytes)
+    let mut block_header = [0u8; 4];
+    file.read_exact(&mut block_header).ok()?;
+    if block_header[0] & 0x7F != 0 {
+        return None;
+    }
+    let mut info = [0u8; 34];
+    file.read_exact(&mut info).ok()?;
+
+    // 20 bits sample rate | 3 bits channels | 5 bits bps | 36 bits total samples
+    let sample_rate =
+        ((info[10] as u32) << 12) | ((info[11] as u32) << 4) | ((info[12] as u32) >> 4);
+    let total_samples = (((info[13] & 0x0F) as u64) << 32)
+        | u32::from_be_bytes([info[14], info[15], info[16], info[17]]) as u64;
+    if sample_rate == 0 || total_samples == 0 {
+        return None;
+    }
+    Some(total_samples as f64 / sample_rate as f64)
+}
+
+/// Audio formats listed as recordings. Only WAV feeds the pipeline directly;
+/// the others go through `convert_to_wav` first.
+const RECORDING_EXTENSIONS: [&str; 4] = ["wav", "mp3", "m4a", "flac"];
+
+fn recording_extension(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    RECORDING_EXTENSIONS.contains(&ext.as_str()).then_some(ext)
+}
+
+/// Duration of any supported recording: header parsing for WAV/FLAC, ffprobe
+/// for MP3/M4A (None when FFmpeg is not installed).
+fn get_audio_duration(path: &Path) -> Option<f64> {
+    match recording_extension(path)?.as_str() {
+        "wav" => get_wav_duration(path),
+        "flac" => get_flac_duration(path),
+        _ => crate::commands::convert::probe_duration(path),
+    }
+}
+
+/// Capture processing in effect when a recording started, kept next to it as
+/// `<name>.recinfo.json`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordingInfo {
+    /// Noise-suppression model applied to the mic; None when monitoring was off.
+    pub noise_model: Option<String>,
+    /// System input volume in percent, where the platform exposes it.
+    pub input_gain: Option<u8>,
+    pub agc: crate::audio::AgcSettings,
+    /// Stable id transcriptions are keyed by; assigned lazily to recordings
+    /// made before ids existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+fn recinfo_path(recording_path: &Path) -> PathBuf {
+    crate::paths::recording_info_path(recording_path)
+}
+
+fn save_recording_info(recording_path: &Path, info: &RecordingInfo) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(info).map_err(|e| e.to_string())?;
+    std::fs::write(recinfo_path(recording_path), json)
+        .map_err(|e| format!("Failed to write recording info: {}", e))
+}
+
+/// None for recordings made before recinfo existed, and for imported files.
+fn load_recording_info(recording_path: &Path) -> Option<RecordingInfo> {
+    let json = std::fs::read_to_string(recinfo_path(recording_path)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn current_recording_info(app: &AppHandle, state: &AppState) -> RecordingInfo {
+    let noise_model = if crate::audio::is_monitoring_active(state.audio.clone()) {
+        let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+        Some(settings.selected_model).filter(|m| !m.is_empty() && m != "dummy")
+    } else {
+        None
+    };
+    RecordingInfo {
+        noise_model,
+        input_gain: crate::audio::get_system_input_volume().ok(),
+        agc: crate::audio::agc_settings(&state.audio),
+        id: Some(crate::managers::transcription::new_recording_id()),
+    }
+}
+
 #[derive(serde::Serialize)]
 pub struct RecordingFile {
     pub name: String,
     pub path: String,
     pub size: u64,
     pub created: u64,
-    pub duration_seconds: Option<f64>,  // Duration from WAV header
+    pub duration_seconds: Option<f64>,  // Duration from the file header
+    pub format: String,                 // Lowercase extension: wav, mp3, m4a, flac
+    pub info: Option<RecordingInfo>,    // Capture processing, from .recinfo.json
 }
 
 #[tauri::command]
 pub fn get_recordings(
     app: AppHandle,
     state: tauri::State<AppState>,
-) -> Result<Vec<RecordingFile>, String> {
+) -> Result<Vec<RecordingFile>, CrispyError> {
     let recordings_dir = recordings_dir(&app)?;
 
     if !recordings_dir.exists() {
@@ -488,17 +922,24 @@ pub fn get_recordings(
             .map(|w| w.output_path().to_string_lossy().to_string())
     };
 
+    Ok(list_recordings(&recordings_dir, active_recording_path.as_deref())?)
+}
+
+fn list_recordings(
+    recordings_dir: &Path,
+    active_recording_path: Option<&str>,
+) -> Result<Vec<RecordingFile>, String> {
     let mut recordings = Vec::new();
-    let entries = std::fs::read_dir(&recordings_dir)
+    let entries = std::fs::read_dir(recordings_dir)
         .map_err(|e| format!("Failed to read recordings directory: {}", e))?;
 
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let path = entry.path();
 
-        if path.extension().and_then(|s| s.to_str()) == Some("wav") {
+        if let Some(format) = recording_extension(&path) {
             let path_str = path.to_string_lossy().to_string();
-            if active_recording_path.as_ref().is_some_and(|active| active == &path_str) {
+            if active_recording_path == Some(path_str.as_str()) {
                 continue;
             }
 
@@ -511,8 +952,8 @@ pub fn get_recordings(
                 .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
                 .unwrap_or(0);
 
-            // Parse WAV header to get duration (fast, only reads 44 bytes)
-            let duration_seconds = get_wav_duration(&path);
+            // Header parse for WAV/FLAC (fast); compressed formats are probed
+            let duration_seconds = get_audio_duration(&path);
             
             recordings.push(RecordingFile {
                 name: path
@@ -524,6 +965,8 @@ pub fn get_recordings(
                 size: metadata.len(),
                 created,
                 duration_seconds,
+                format,
+                info: load_recording_info(&path),
             });
         }
     }
@@ -532,80 +975,406 @@ pub fn get_recordings(
     Ok(recordings)
 }
 
+/// Give every recording that lacks one a stable id, re-linking its
+/// transcription files from the old path-hash names.
+pub fn assign_missing_recording_ids(app: &AppHandle) {
+    let Ok(entries) = recordings_dir(app).and_then(|dir| {
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read recordings: {}", e))
+    }) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if recording_extension(&path).is_none()
+            || crate::managers::transcription::recording_id(&path).is_some()
+        {
+            continue;
+        }
+        if let Err(e) =
+            crate::managers::transcription::ensure_recording_id(app, &path.to_string_lossy())
+        {
+            eprintln!("Warning: {}", e);
+        }
+    }
+}
+
 #[tauri::command]
-pub fn rename_recording(app: tauri::AppHandle, path: String, new_name: String) -> Result<(), String> {
+pub fn rename_recording(app: tauri::AppHandle, path: String, new_name: String) -> Result<(), CrispyError> {
     let old_path_str = path.clone();
     let path = Path::new(&path);
     if !path.exists() {
-        return Err("Recording not found".to_string());
+        return Err(CrispyError::NotFound("Recording not found".to_string()));
     }
     ensure_in_recordings_dir(&app, path)?;
     let parent = path.parent().ok_or("Invalid path")?;
-    let new_name = new_name.trim();
-    if new_name.is_empty() {
-        return Err("Name cannot be empty".to_string());
-    }
-    if new_name.contains(std::path::MAIN_SEPARATOR)
-        || new_name.contains('/')
-        || new_name.contains('\\')
-    {
-        return Err("Name cannot contain path separators".to_string());
-    }
-    let base = Path::new(new_name)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(new_name);
-    let new_path = parent.join(format!("{}.wav", base));
+    let base = file_stem_for_name(&new_name)?;
+    let ext = recording_extension(path).unwrap_or_else(|| "wav".to_string());
+    let new_path = parent.join(format!("{}.{}", base, ext));
     if new_path == path {
         return Ok(());
     }
     if new_path.exists() {
-        return Err("A file with this name already exists".to_string());
+        return Err(CrispyError::AlreadyExists(
+            "A file with this name already exists".to_string(),
+        ));
+    }
+    // Transcriptions follow the id in the recinfo, so only that moves along
+    if let Err(e) = crate::managers::transcription::ensure_recording_id(&app, &old_path_str) {
+        eprintln!("Warning: {}", e);
     }
     std::fs::rename(&path, &new_path).map_err(|e| format!("Failed to rename: {}", e))?;
+    transcription_manager(&app).invalidate_result(&old_path_str);
+    if recinfo_path(path).exists() {
+        let _ = std::fs::rename(recinfo_path(path), recinfo_path(&new_path));
+    }
+    Ok(())
+}
 
-    let new_path_str = new_path.to_string_lossy();
-    if let (Ok(old_txt), Ok(new_txt)) = (
-        crate::managers::transcription::transcription_result_path(&app, &old_path_str),
-        crate::managers::transcription::transcription_result_path(&app, &new_path_str),
-    ) {
-        if old_txt.exists() && old_txt != new_txt {
-            let _ = std::fs::rename(&old_txt, &new_txt);
-        }
+/// Stem of a user-entered recording name (any extension is dropped). Rejects
+/// empty names and names that would leave the recordings directory.
+fn file_stem_for_name(name: &str) -> Result<&str, CrispyError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CrispyError::InvalidArgument("Name cannot be empty".to_string()));
     }
-    if let (Ok(old_meta), Ok(new_meta)) = (
-        crate::managers::transcription::transcription_metadata_path(&app, &old_path_str),
-        crate::managers::transcription::transcription_metadata_path(&app, &new_path_str),
-    ) {
-        if old_meta.exists() && old_meta != new_meta {
-            let _ = std::fs::rename(&old_meta, &new_meta);
-        }
+    if name.contains(std::path::MAIN_SEPARATOR) || name.contains('/') || name.contains('\\') {
+        return Err(CrispyError::InvalidArgument(
+            "Name cannot contain path separators".to_string(),
+        ));
     }
-    if let (Ok(old_chat), Ok(new_chat)) = (
-        crate::managers::transcription::transcription_chat_history_path(&app, &old_path_str),
-        crate::managers::transcription::transcription_chat_history_path(&app, &new_path_str),
-    ) {
-        if old_chat.exists() && old_chat != new_chat {
-            let _ = std::fs::rename(&old_chat, &new_chat);
+    Ok(Path::new(name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(name))
+}
+
+/// Run `input` through an NS model offline and write the result as a 48kHz
+/// mono WAV. Leading frames the model drops are filled with silence so the
+/// output lines up with the original. Returns the written samples.
+fn denoise_file(
+    input: &Path,
+    output: &Path,
+    model: &str,
+    noise: crate::audio::NoiseParams,
+) -> Result<Vec<f32>, String> {
+    use crate::managers::transcription::{wav_to_mono_f32, TranscriptionSource};
+    let samples = wav_to_mono_f32(input, TranscriptionSource::Mix, recording::SAMPLE_RATE)
+        .map_err(|e| format!("Failed to read recording: {}", e))?;
+    let processed =
+        crate::audio::denoise_buffer(model, &samples, recording::SAMPLE_RATE as f32, noise);
+    let mut aligned = vec![0.0f32; samples.len().saturating_sub(processed.len())];
+    aligned.extend_from_slice(&processed);
+
+    let config = recording::WavConfig {
+        sample_rate: recording::SAMPLE_RATE as u32,
+        channels: 1,
+    };
+    let mut writer = recording::WavWriter::with_config(output.to_path_buf(), config)?;
+    writer.write_samples(&aligned, &aligned)?;
+    writer.finalize()?;
+    Ok(aligned)
+}
+
+/// Check that `input` is a WAV in the recordings directory and `model` a real
+/// (non-passthrough) noise suppression model.
+pub fn validate_denoise_request(app: &AppHandle, input: &Path, model: &str) -> Result<(), CrispyError> {
+    ensure_in_recordings_dir(app, input)?;
+    if recording_extension(input).as_deref() != Some("wav") {
+        return Err(CrispyError::Unsupported(
+            "Only WAV recordings can be denoised. Convert the file to WAV first.".to_string(),
+        ));
+    }
+    let known = crate::commands::ns_models::get_available_ns_models()
+        .iter()
+        .any(|m| m.id == model && m.id != "dummy");
+    if !known {
+        return Err(CrispyError::InvalidArgument(format!(
+            "Unknown noise suppression model: {}",
+            model
+        )));
+    }
+    Ok(())
+}
+
+/// Denoise `input` into `output` with the monitoring noise params and give
+/// the copy a recinfo of its own. Returns the denoised 48kHz mono samples; a
+/// partial output is removed on failure.
+pub fn write_denoised_copy(
+    app: &AppHandle,
+    input: &Path,
+    output: &Path,
+    model: &str,
+) -> Result<Vec<f32>, String> {
+    let noise = crate::audio::noise_params(&app.state::<AppState>().audio);
+    let samples = match denoise_file(input, output, model, noise) {
+        Ok(samples) => samples,
+        Err(e) => {
+            let _ = std::fs::remove_file(output);
+            return Err(e);
         }
+    };
+    let info = load_recording_info(input).map(|info| RecordingInfo {
+        noise_model: Some(model.to_string()),
+        id: Some(crate::managers::transcription::new_recording_id()),
+        ..info
+    });
+    if let Some(info) = info {
+        let _ = save_recording_info(output, &info);
     }
+    Ok(samples)
+}
 
-    Ok(())
+/// First free `<stem>-<suffix>.wav` next to `input`, numbered from 2 when
+/// taken.
+pub fn unused_sibling_path(input: &Path, suffix: &str) -> Result<PathBuf, String> {
+    let parent = input.parent().ok_or("Invalid path")?;
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid path")?;
+    let mut candidate = parent.join(format!("{}-{}.wav", stem, suffix));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = parent.join(format!("{}-{}-{}.wav", stem, suffix, n));
+        n += 1;
+    }
+    Ok(candidate)
 }
 
+/// Write a noise-suppressed copy of a recording as `<output_name>.wav` next to
+/// it, using the same models as live monitoring. Returns the new path.
 #[tauri::command]
-pub fn delete_recording(app: AppHandle, path: String) -> Result<(), String> {
+pub async fn denoise_recording(
+    app: AppHandle,
+    recording_path: String,
+    model: String,
+    output_name: String,
+) -> Result<String, CrispyError> {
+    let input = PathBuf::from(&recording_path);
+    validate_denoise_request(&app, &input, &model)?;
+    let parent = input.parent().ok_or("Invalid path")?;
+    let output = parent.join(format!("{}.wav", file_stem_for_name(&output_name)?));
+    if output.exists() {
+        return Err(CrispyError::AlreadyExists(
+            "A file with this name already exists".to_string(),
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        write_denoised_copy(&app, &input, &output, &model)?;
+        Ok::<_, String>(output.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| CrispyError::Internal(format!("Task failed to join: {}", e)))?
+    .map_err(CrispyError::from)
+}
+
+#[tauri::command]
+pub fn delete_recording(app: AppHandle, path: String) -> Result<(), CrispyError> {
     ensure_in_recordings_dir(&app, Path::new(&path))?;
     std::fs::remove_file(&path)
         .map_err(|e| format!("Failed to delete recording: {}", e))?;
+    let _ = std::fs::remove_file(recinfo_path(Path::new(&path)));
+    transcription_manager(&app).invalidate_result(&path);
     Ok(())
 }
 
+/// Files kept next to a recording as `<recording stem><suffix>`.
+const RECORDING_SIDECAR_SUFFIXES: [&str; 1] = [".recinfo.json"];
+
+/// Delete transcription files and recording sidecars whose recording no longer
+/// exists (e.g. it was deleted in Finder); returns how many were removed. Does
+/// nothing without a recordings dir, so a moved or unmounted folder doesn't
+/// look like every recording is gone.
+fn remove_orphaned_sidecars(recordings_dir: &Path, transcriptions_dir: &Path) -> Result<usize, String> {
+    if !recordings_dir.is_dir() {
+        return Ok(0);
+    }
+    let entries: Vec<PathBuf> = std::fs::read_dir(recordings_dir)
+        .map_err(|e| format!("Failed to read recordings directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    let recordings: Vec<&PathBuf> = entries
+        .iter()
+        .filter(|path| recording_extension(path).is_some())
+        .collect();
+    let transcription_stems: HashSet<String> = recordings
+        .iter()
+        .flat_map(|path| crate::managers::transcription::transcription_file_stems(path))
+        .collect();
+    let recording_stems: HashSet<&str> = recordings
+        .iter()
+        .filter_map(|path| path.file_stem()?.to_str())
+        .collect();
+    let file_name = |path: &Path| path.file_name().and_then(|n| n.to_str()).map(str::to_string);
+
+    let mut orphans = Vec::new();
+    for path in &entries {
+        let Some(name) = file_name(path) else { continue };
+        let stem = RECORDING_SIDECAR_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix));
+        if stem.is_some_and(|stem| !recording_stems.contains(stem)) {
+            orphans.push(path.clone());
+        }
+    }
+    if let Ok(transcriptions) = std::fs::read_dir(transcriptions_dir) {
+        for path in transcriptions.flatten().map(|entry| entry.path()) {
+            let Some(name) = file_name(&path) else { continue };
+            let stem = crate::managers::transcription::transcription_file_stem_of(&name);
+            if stem.is_some_and(|stem| !transcription_stems.contains(stem)) {
+                orphans.push(path);
+            }
+        }
+    }
+
+    let mut removed = 0;
+    for path in orphans {
+        match std::fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => eprintln!("Warning: failed to delete {}: {}", path.display(), e),
+        }
+    }
+    Ok(removed)
+}
+
+/// Delete transcriptions and sidecars left behind by recordings removed
+/// outside the app. Returns the number of files deleted.
+#[tauri::command]
+pub fn cleanup_orphaned_sidecars(app: AppHandle) -> Result<usize, CrispyError> {
+    let transcriptions_dir = crate::paths::transcriptions_dir(&app)?;
+    Ok(remove_orphaned_sidecars(&recordings_dir(&app)?, &transcriptions_dir)?)
+}
+
+fn transcription_manager(
+    app: &AppHandle,
+) -> tauri::State<'_, Arc<crate::managers::transcription::TranscriptionManager>> {
+    app.state()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
 
+    fn buffer(samples: impl IntoIterator<Item = f32>) -> Mutex<VecDeque<f32>> {
+        Mutex::new(samples.into_iter().collect())
+    }
+
+    fn running_app(id: &str, bundle_id: &str) -> recording::RecordableApp {
+        recording::RecordableApp {
+            id: id.to_string(),
+            name: bundle_id.to_string(),
+            bundle_id: bundle_id.to_string(),
+            icon_base64: None,
+        }
+    }
+
+    #[test]
+    fn recording_app_resolution_reports_missing_apps() {
+        let running = [
+            running_app("com.spotify.client_123", "com.spotify.client"),
+            running_app("us.zoom.xos_456", "us.zoom.xos"),
+        ];
+        assert_eq!(resolve_recording_app("", &running), AppResolution::NoneSelected);
+        assert_eq!(resolve_recording_app("none", &running), AppResolution::NoneSelected);
+        assert_eq!(
+            resolve_recording_app("us.zoom.xos", &running),
+            AppResolution::Running("us.zoom.xos_456".to_string())
+        );
+        // A PID-based id from older settings, still running
+        assert_eq!(
+            resolve_recording_app("com.spotify.client_123", &running),
+            AppResolution::Running("com.spotify.client_123".to_string())
+        );
+        assert_eq!(
+            resolve_recording_app("com.apple.Music", &running),
+            AppResolution::NotRunning
+        );
+        assert_eq!(
+            resolve_recording_app("com.spotify.client_999", &running),
+            AppResolution::NotRunning
+        );
+    }
+
+    #[test]
+    fn mix_one_frame_waits_for_a_full_mic_frame() {
+        let mic = buffer([0.1; 3]);
+        let app = buffer([0.2; 8]);
+        assert!(mix_one_frame(&mic, &app, 4, 16, &mut DesyncTrim::default()).is_none());
+        assert_eq!(mic.lock().unwrap().len(), 3);
+        assert_eq!(app.lock().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn mix_one_frame_sums_sources_and_fills_missing_app_with_silence() {
+        let mic = buffer([0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]);
+        let app = buffer([0.5, 0.5, 0.5, 0.5, 0.9]);
+        let (left, right) = mix_one_frame(&mic, &app, 4, 16, &mut DesyncTrim::default()).unwrap();
+        assert_eq!(left, vec![0.6, 0.7, 0.8, 0.9]);
+        assert_eq!(left, right);
+
+        // One app sample left: not a full frame, so the mic plays over silence
+        // and the app sample waits for the next frame
+        let (left, _) = mix_one_frame(&mic, &app, 4, 16, &mut DesyncTrim::default()).unwrap();
+        assert_eq!(left, vec![0.5, 0.6, 0.7, 0.8]);
+        assert_eq!(app.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn mix_one_frame_drops_oldest_samples_of_the_source_ahead() {
+        // Mic 6 samples ahead of the app with a tolerance of 2: drop 4 mic samples
+        let mic = buffer((0..10).map(|i| i as f32));
+        let app = buffer([0.0; 4]);
+        let mut trimmed = DesyncTrim::default();
+        let (left, _) = mix_one_frame(&mic, &app, 4, 2, &mut trimmed).unwrap();
+        assert_eq!(left, vec![4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(trimmed, DesyncTrim { mic: 4, app: 0 });
+
+        // App ahead: its oldest samples go instead
+        let mic = buffer([0.0; 4]);
+        let app = buffer((0..10).map(|i| i as f32 * 0.1));
+        let (left, _) = mix_one_frame(&mic, &app, 4, 2, &mut DesyncTrim::default()).unwrap();
+        let expected: Vec<f32> = (4..8).map(|i| i as f32 * 0.1).collect();
+        assert_eq!(left, expected);
+        assert_eq!(app.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn desync_trim_drops_only_the_excess_of_the_source_ahead() {
+        assert_eq!(desync_trim(100, 100, 20), DesyncTrim::default());
+        // Within tolerance either way
+        assert_eq!(desync_trim(120, 100, 20), DesyncTrim::default());
+        assert_eq!(desync_trim(100, 120, 20), DesyncTrim::default());
+        assert_eq!(desync_trim(150, 100, 20), DesyncTrim { mic: 30, app: 0 });
+        assert_eq!(desync_trim(0, 2400, 2400), DesyncTrim::default());
+        assert_eq!(desync_trim(0, 5000, 2400), DesyncTrim { mic: 0, app: 2600 });
+    }
+
+    #[test]
+    fn mix_level_flags_sum_over_full_scale() {
+        let mic = [0.7f32, -0.6, 0.5, 0.0];
+        let app = [0.6f32, -0.5, 0.2, 0.0];
+        assert!(mix_level(&mic).peak < 1.0 && !mix_level(&mic).clipped);
+        assert!(mix_level(&app).peak < 1.0 && !mix_level(&app).clipped);
+
+        let mixed: Vec<f32> = mic.iter().zip(&app).map(|(m, a)| m + a).collect();
+        let level = mix_level(&mixed);
+        assert!((level.peak - 1.3).abs() < 1e-6);
+        assert!(level.clipped);
+        let expected_rms = ((1.3f32 * 1.3 + 1.1 * 1.1 + 0.7 * 0.7) / 4.0).sqrt();
+        assert!((level.rms - expected_rms).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mix_level_of_silence_is_zero() {
+        let level = mix_level(&[0.0; 8]);
+        assert_eq!(level.peak, 0.0);
+        assert_eq!(level.rms, 0.0);
+        assert!(!level.clipped);
+        assert!(!mix_level(&[]).clipped);
+    }
+
     /// Build a minimal valid WAV file in memory and write it to a temp file.
     fn write_test_wav(
         path: &Path,
@@ -646,6 +1415,136 @@ mod tests {
         file.write_all(&buf).unwrap();
     }
 
+    #[test]
+    fn recinfo_round_trips_and_appears_in_listing() {
+        let dir = std::env::temp_dir().join("crispy_test_recinfo");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let with_info = dir.join("recording_1.wav");
+        let without_info = dir.join("imported.wav");
+        write_test_wav(&with_info, 48000, 1, 16, 48000);
+        write_test_wav(&without_info, 48000, 1, 16, 48000);
+
+        let info = RecordingInfo {
+            noise_model: Some("rnnnoise".to_string()),
+            input_gain: Some(70),
+            agc: crate::audio::AgcSettings { enabled: true, target_dbfs: -18.0, max_gain_db: 20.0 },
+            id: Some("rec-1".to_string()),
+        };
+        save_recording_info(&with_info, &info).unwrap();
+        assert_eq!(load_recording_info(&with_info), Some(info.clone()));
+
+        let listed = list_recordings(&dir, None).unwrap();
+        assert_eq!(listed.len(), 2, "the sidecar itself is not a recording");
+        let info_for = |name: &str| listed.iter().find(|r| r.name == name).unwrap().info.clone();
+        assert_eq!(info_for("recording_1.wav"), Some(info));
+        assert_eq!(info_for("imported.wav"), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn only_orphaned_sidecars_are_removed() {
+        let root = std::env::temp_dir().join("crispy_test_orphaned_sidecars");
+        let _ = std::fs::remove_dir_all(&root);
+        let recordings = root.join("Recordings");
+        let transcriptions = root.join("Transcriptions");
+        std::fs::create_dir_all(&recordings).unwrap();
+        std::fs::create_dir_all(&transcriptions).unwrap();
+
+        // "with_id" is keyed by its recinfo id, "imported" by its path hash
+        let with_id = recordings.join("with_id.wav");
+        let imported = recordings.join("imported.mp3");
+        std::fs::write(&with_id, b"RIFF").unwrap();
+        std::fs::write(&imported, b"ID3").unwrap();
+        std::fs::write(recinfo_path(&with_id), r#"{"id": "rec-1"}"#).unwrap();
+        let legacy = crate::managers::transcription::transcription_file_stems(&imported)[0].clone();
+
+        let kept = [
+            transcriptions.join("rec-1.txt"),
+            transcriptions.join("rec-1.meta"),
+            transcriptions.join("rec-1.diarization.json"),
+            transcriptions.join(format!("{}.json", legacy)),
+            transcriptions.join("notes.md"),
+            recinfo_path(&with_id),
+        ];
+        let orphaned = [
+            transcriptions.join("rec-gone.txt"),
+            transcriptions.join("rec-gone.chat.json"),
+            transcriptions.join("0123456789abcdef.words.json"),
+            recordings.join("deleted.recinfo.json"),
+        ];
+        for path in kept.iter().chain(&orphaned) {
+            if !path.exists() {
+                std::fs::write(path, "x").unwrap();
+            }
+        }
+
+        assert_eq!(remove_orphaned_sidecars(&recordings, &transcriptions), Ok(orphaned.len()));
+        for path in &kept {
+            assert!(path.exists(), "{} was deleted", path.display());
+        }
+        for path in &orphaned {
+            assert!(!path.exists(), "{} was kept", path.display());
+        }
+        assert!(with_id.exists() && imported.exists());
+
+        // Without a recordings dir nothing counts as orphaned
+        assert_eq!(remove_orphaned_sidecars(&root.join("missing"), &transcriptions), Ok(0));
+        assert!(transcriptions.join("rec-1.txt").exists());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn denoised_copy_of_noisy_recording_is_quieter() {
+        let dir = std::env::temp_dir().join("crispy_test_denoise_recording");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("noisy.wav");
+        let output = dir.join("noisy_denoised.wav");
+
+        // Two seconds of white noise, stereo at 44.1kHz to exercise the resampler
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&input, spec).unwrap();
+        let mut rng: u32 = 0x1234_5678;
+        for _ in 0..2 * 44100 * 2 {
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            let noise = ((rng >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * 0.1;
+            writer.write_sample((noise * 32767.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        denoise_file(&input, &output, "rnnnoise", crate::audio::NoiseParams::default()).unwrap();
+
+        let rms = |path: &Path| {
+            let mut reader = hound::WavReader::open(path).unwrap();
+            let samples: Vec<f32> = reader
+                .samples::<i16>()
+                .map(|s| s.unwrap() as f32 / 32767.0)
+                .collect();
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+        let out_spec = hound::WavReader::open(&output).unwrap().spec();
+        assert_eq!(out_spec.sample_rate, 48000);
+        assert_eq!(out_spec.channels, 1);
+        assert!(
+            rms(&output) < rms(&input) * 0.5,
+            "{} vs {}",
+            rms(&output),
+            rms(&input)
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn wav_duration_48khz_stereo_16bit() {
         let dir = std::env::temp_dir().join("crispy_test_wav_48k");
@@ -758,6 +1657,122 @@ mod tests {
         std::fs::remove_dir_all(&dir).ok();
     }
 
+    /// RIFF/WAVE bytes from (id, body) chunks, adding the pad byte after
+    /// odd-sized bodies.
+    fn riff_wave(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut body = b"WAVE".to_vec();
+        for (id, data) in chunks {
+            body.extend_from_slice(*id);
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut buf = b"RIFF".to_vec();
+        buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        buf.extend(body);
+        buf
+    }
+
+    fn fmt_body(format_tag: u16, channels: u16, sample_rate: u32, bits: u16, byte_rate: u32, block_align: u16) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&format_tag.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&byte_rate.to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+        fmt.extend_from_slice(&0u16.to_le_bytes()); // cbSize
+        fmt
+    }
+
+    #[test]
+    fn wav_duration_of_float_and_compressed_files_with_fact() {
+        let dir = std::env::temp_dir().join("crispy_test_wav_fact");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // 32-bit float stereo, 0.5 s at 48 kHz, fact chunk before data
+        let path = dir.join("float.wav");
+        let frames: u32 = 24000;
+        let buf = riff_wave(&[
+            (b"fmt ", fmt_body(3, 2, 48000, 32, 48000 * 8, 8)),
+            (b"fact", frames.to_le_bytes().to_vec()),
+            (b"data", vec![0u8; frames as usize * 8]),
+        ]);
+        std::fs::write(&path, &buf).unwrap();
+        let duration = get_wav_duration(&path).unwrap();
+        assert!((duration - 0.5).abs() < 0.001, "Expected ~0.5s, got {}", duration);
+
+        // IMA ADPCM: 4-bit blocks that don't map to bytes; the fact count wins
+        let path = dir.join("adpcm.wav");
+        let buf = riff_wave(&[
+            (b"fmt ", fmt_body(0x11, 1, 16000, 4, 8110, 512)),
+            (b"fact", 32000u32.to_le_bytes().to_vec()),
+            (b"data", vec![0u8; 8192]),
+        ]);
+        std::fs::write(&path, &buf).unwrap();
+        let duration = get_wav_duration(&path).unwrap();
+        assert!((duration - 2.0).abs() < 0.001, "Expected ~2.0s, got {}", duration);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn wav_duration_skips_pad_byte_of_odd_sized_chunk() {
+        let dir = std::env::temp_dir().join("crispy_test_wav_pad");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("padded.wav");
+
+        // 1 s of 16-bit mono behind a 5-byte LIST chunk and its pad byte
+        let buf = riff_wave(&[
+            (b"fmt ", fmt_body(1, 1, 16000, 16, 32000, 2)),
+            (b"LIST", b"INFOx".to_vec()),
+            (b"data", vec![0u8; 32000]),
+        ]);
+        std::fs::write(&path, &buf).unwrap();
+        let duration = get_wav_duration(&path).unwrap();
+        assert!((duration - 1.0).abs() < 0.001, "Expected ~1.0s, got {}", duration);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn seek_offset_matches_the_pcm_layout() {
+        let dir = std::env::temp_dir().join("crispy_test_seek_offset");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stereo.wav");
+
+        // 25 s of 16-bit stereo at 8 kHz behind a padded LIST chunk:
+        // RIFF header 12 + fmt 8+18 + LIST 8+5+1 + data header 8
+        let frames = 25 * 8000;
+        let buf = riff_wave(&[
+            (b"fmt ", fmt_body(1, 2, 8000, 16, 32000, 4)),
+            (b"LIST", b"INFOx".to_vec()),
+            (b"data", vec![0u8; frames * 4]),
+        ]);
+        std::fs::write(&path, &buf).unwrap();
+        let wav = read_wav_header(&path).unwrap();
+        assert_eq!(wav.data_offset, 60);
+
+        let point = find_seek_point(&path, 12.5).unwrap();
+        assert_eq!(point.byte_offset, 60 + 12 * 8000 * 4 + 4000 * 4);
+        assert_eq!(point.seconds, 12.5);
+        // Past the end clamps to the end of the data
+        assert_eq!(pcm_byte_offset(&wav, 99.0), Some(60 + frames as u64 * 4));
+
+        // A compressed WAV has no fixed frame size to compute an offset from
+        let adpcm = dir.join("adpcm.wav");
+        std::fs::write(
+            &adpcm,
+            riff_wave(&[(b"fmt ", fmt_body(2, 1, 8000, 4, 4096, 256)), (b"data", vec![0u8; 4096])]),
+        )
+        .unwrap();
+        assert_eq!(find_seek_point(&adpcm, 1.0).unwrap_err().code(), "unsupported");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn wav_duration_returns_none_for_truncated_header() {
         let dir = std::env::temp_dir().join("crispy_test_wav_trunc");
@@ -774,4 +1789,89 @@ mod tests {
 
         std::fs::remove_dir_all(&dir).ok();
     }
+
+    /// Minimal FLAC header: magic + STREAMINFO. Enough for duration parsing.
+    fn write_test_flac(path: &Path, sample_rate: u32, total_samples: u64, id3_prefix: bool) {
+        let mut buf = Vec::new();
+        if id3_prefix {
+            buf.extend_from_slice(b"ID3\x04\x00\x00");
+            buf.extend_from_slice(&[0, 0, 0, 5]); // synchsafe size = 5
+            buf.extend_from_slice(&[0u8; 5]);
+        }
+        buf.extend_from_slice(b"fLaC");
+        buf.extend_from_slice(&[0x80, 0, 0, 34]); // last block, STREAMINFO, 34 bytes
+        let mut info = [0u8; 34];
+        info[0..2].copy_from_slice(&4096u16.to_be_bytes());
+        info[2..4].copy_from_slice(&4096u16.to_be_bytes());
+        info[10] = (sample_rate >> 12) as u8;
+        info[11] = (sample_rate >> 4) as u8;
+        // low 4 bits of rate | channels-1 (1 => stereo) | high bit of bps-1 (15)
+        info[12] = ((sample_rate & 0x0F) << 4) as u8 | (1 << 1);
+        info[13] = (15 << 4) as u8 | ((total_samples >> 32) & 0x0F) as u8;
+        info[14..18].copy_from_slice(&(total_samples as u32).to_be_bytes());
+        buf.extend_from_slice(&info);
+        std::fs::write(path, buf).unwrap();
+    }
+
+    #[test]
+    fn flac_duration_from_streaminfo() {
+        let dir = std::env::temp_dir().join("crispy_test_flac");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.flac");
+
+        write_test_flac(&path, 44100, 44100 * 3, false);
+        let duration = get_flac_duration(&path).unwrap();
+        assert!((duration - 3.0).abs() < 0.001, "Expected ~3.0s, got {}", duration);
+
+        write_test_flac(&path, 48000, 24000, true);
+        let duration = get_flac_duration(&path).unwrap();
+        assert!((duration - 0.5).abs() < 0.001, "Expected ~0.5s with ID3 tag, got {}", duration);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn flac_duration_none_for_unknown_length_or_non_flac() {
+        let dir = std::env::temp_dir().join("crispy_test_flac_bad");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("unknown.flac");
+        write_test_flac(&path, 44100, 0, false);
+        assert!(get_flac_duration(&path).is_none());
+
+        let wav_path = dir.join("actually_wav.flac");
+        write_test_wav(&wav_path, 48000, 2, 16, 48000);
+        assert!(get_flac_duration(&wav_path).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn audio_duration_dispatches_by_extension() {
+        let dir = std::env::temp_dir().join("crispy_test_audio_duration");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let wav = dir.join("a.WAV");
+        write_test_wav(&wav, 48000, 2, 16, 96000);
+        assert!((get_audio_duration(&wav).unwrap() - 2.0).abs() < 0.001);
+
+        let flac = dir.join("b.flac");
+        write_test_flac(&flac, 16000, 16000 * 7, false);
+        assert!((get_audio_duration(&flac).unwrap() - 7.0).abs() < 0.001);
+
+        let txt = dir.join("c.txt");
+        std::fs::write(&txt, b"hello").unwrap();
+        assert!(get_audio_duration(&txt).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn recording_extension_recognizes_imported_formats() {
+        assert_eq!(recording_extension(Path::new("x.wav")).as_deref(), Some("wav"));
+        assert_eq!(recording_extension(Path::new("x.MP3")).as_deref(), Some("mp3"));
+        assert_eq!(recording_extension(Path::new("x.m4a")).as_deref(), Some("m4a"));
+        assert_eq!(recording_extension(Path::new("x.flac")).as_deref(), Some("flac"));
+        assert_eq!(recording_extension(Path::new("x.ogg")), None);
+        assert_eq!(recording_extension(Path::new("x")), None);
+    }
 }
@@ -0,0 +1,259 @@
+// Session: monitoring + (optional) recording started and stopped together from
+// one config. Composes the existing monitoring and recording functions.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::app_state::AppState;
+use crate::audio;
+use crate::commands::audio::{remember_monitoring, remember_monitoring_host};
+use crate::commands::recording::{do_start_recording, do_stop_recording};
+use crate::error::CrispyError;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionConfig {
+    pub input_device: String,
+    #[serde(default)]
+    pub output_device: String,
+    #[serde(default = "default_model_name")]
+    pub model_name: String,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    /// Record alongside monitoring; otherwise the session only monitors.
+    #[serde(default)]
+    pub record: bool,
+    /// App whose audio is mixed into the recording ("none" for mic only).
+    #[serde(default = "default_app_id")]
+    pub app_id: String,
+    /// cpal host to open the devices on; the platform default when absent.
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+fn default_model_name() -> String {
+    "dummy".to_string()
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+fn default_app_id() -> String {
+    "none".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SessionStopResult {
+    /// Finalized recording, if the session was recording.
+    pub recording_path: Option<String>,
+}
+
+/// The monitoring/recording operations a session is built from.
+trait SessionOps {
+    fn start_monitoring(&mut self, config: &SessionConfig) -> Result<(), CrispyError>;
+    fn stop_monitoring(&mut self) -> Result<(), CrispyError>;
+    fn start_recording(&mut self, app_id: &str) -> Result<(), CrispyError>;
+    fn stop_recording(&mut self) -> Result<String, CrispyError>;
+    fn is_recording(&self) -> bool;
+}
+
+struct AppSessionOps<'a> {
+    app: &'a AppHandle,
+    state: &'a AppState,
+}
+
+impl SessionOps for AppSessionOps<'_> {
+    fn start_monitoring(&mut self, config: &SessionConfig) -> Result<(), CrispyError> {
+        let recording_mic_buffer = self.state.recording.lock().unwrap().mic_buffer.clone();
+        audio::start_monitoring(
+            self.state.audio.clone(),
+            recording_mic_buffer,
+            self.app.clone(),
+            config.input_device.clone(),
+            config.output_device.clone(),
+            config.model_name.clone(),
+            config.volume,
+            config.host.clone(),
+        )?;
+        remember_monitoring(self.app, true);
+        remember_monitoring_host(self.app, config.host.as_deref());
+        Ok(())
+    }
+
+    fn stop_monitoring(&mut self) -> Result<(), CrispyError> {
+        audio::stop_monitoring(self.state.audio.clone())?;
+        remember_monitoring(self.app, false);
+        Ok(())
+    }
+
+    fn start_recording(&mut self, app_id: &str) -> Result<(), CrispyError> {
+        do_start_recording(self.app, self.state, app_id)
+    }
+
+    fn stop_recording(&mut self) -> Result<String, CrispyError> {
+        do_stop_recording(self.state)
+    }
+
+    fn is_recording(&self) -> bool {
+        let recording = self.state.recording.lock().unwrap();
+        let active = recording.writer.lock().unwrap().is_some();
+        active
+    }
+}
+
+/// Monitoring first: the recording's mic buffer is fed by the monitoring
+/// input stream. If recording fails to start, monitoring is torn down again
+/// so a failed session leaves nothing running.
+fn start_session_with(ops: &mut impl SessionOps, config: &SessionConfig) -> Result<(), CrispyError> {
+    if ops.is_recording() {
+        return Err(CrispyError::Busy("Recording already in progress".to_string()));
+    }
+    ops.start_monitoring(config)?;
+    if config.record {
+        if let Err(e) = ops.start_recording(&config.app_id) {
+            let _ = ops.stop_monitoring();
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Recording first so it is finalized while its input is still flowing;
+/// monitoring is stopped even if finalizing the recording fails.
+fn stop_session_with(ops: &mut impl SessionOps) -> Result<SessionStopResult, CrispyError> {
+    let recording = if ops.is_recording() {
+        Some(ops.stop_recording())
+    } else {
+        None
+    };
+    ops.stop_monitoring()?;
+    Ok(SessionStopResult {
+        recording_path: recording.transpose()?,
+    })
+}
+
+#[tauri::command]
+pub fn start_session(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+    config: SessionConfig,
+) -> Result<(), CrispyError> {
+    start_session_with(
+        &mut AppSessionOps {
+            app: &app,
+            state: state.inner(),
+        },
+        &config,
+    )
+}
+
+#[tauri::command]
+pub fn stop_session(
+    app: AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<SessionStopResult, CrispyError> {
+    stop_session_with(&mut AppSessionOps {
+        app: &app,
+        state: state.inner(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeOps {
+        monitoring: bool,
+        recording: bool,
+        fail_recording_start: bool,
+        finalized: Vec<String>,
+        calls: Vec<&'static str>,
+    }
+
+    impl SessionOps for FakeOps {
+        fn start_monitoring(&mut self, _config: &SessionConfig) -> Result<(), CrispyError> {
+            self.calls.push("start_monitoring");
+            self.monitoring = true;
+            Ok(())
+        }
+
+        fn stop_monitoring(&mut self) -> Result<(), CrispyError> {
+            self.calls.push("stop_monitoring");
+            self.monitoring = false;
+            Ok(())
+        }
+
+        fn start_recording(&mut self, _app_id: &str) -> Result<(), CrispyError> {
+            self.calls.push("start_recording");
+            if self.fail_recording_start {
+                return Err(CrispyError::Internal("writer failed".to_string()));
+            }
+            self.recording = true;
+            Ok(())
+        }
+
+        fn stop_recording(&mut self) -> Result<String, CrispyError> {
+            self.calls.push("stop_recording");
+            self.recording = false;
+            self.finalized.push("recording.wav".to_string());
+            Ok("recording.wav".to_string())
+        }
+
+        fn is_recording(&self) -> bool {
+            self.recording
+        }
+    }
+
+    fn config(record: bool) -> SessionConfig {
+        serde_json::from_value(serde_json::json!({
+            "input_device": "Default",
+            "record": record,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn session_config_defaults() {
+        let config = config(false);
+        assert_eq!(config.model_name, "dummy");
+        assert_eq!(config.volume, 1.0);
+        assert_eq!(config.app_id, "none");
+        assert!(config.output_device.is_empty());
+    }
+
+    #[test]
+    fn recording_session_lifecycle_finalizes_recording() {
+        let mut ops = FakeOps::default();
+        start_session_with(&mut ops, &config(true)).unwrap();
+        assert!(ops.monitoring && ops.recording);
+
+        let result = stop_session_with(&mut ops).unwrap();
+        assert_eq!(result.recording_path.as_deref(), Some("recording.wav"));
+        assert_eq!(ops.finalized, vec!["recording.wav"]);
+        assert!(!ops.monitoring && !ops.recording);
+        assert_eq!(
+            ops.calls,
+            vec!["start_monitoring", "start_recording", "stop_recording", "stop_monitoring"]
+        );
+    }
+
+    #[test]
+    fn monitoring_only_session_has_no_recording() {
+        let mut ops = FakeOps::default();
+        start_session_with(&mut ops, &config(false)).unwrap();
+        assert!(ops.monitoring && !ops.recording);
+        assert_eq!(stop_session_with(&mut ops).unwrap(), SessionStopResult::default());
+        assert!(!ops.monitoring);
+    }
+
+    #[test]
+    fn failed_recording_start_tears_down_monitoring() {
+        let mut ops = FakeOps {
+            fail_recording_start: true,
+            ..FakeOps::default()
+        };
+        assert!(start_session_with(&mut ops, &config(true)).is_err());
+        assert!(!ops.monitoring && !ops.recording);
+    }
+}
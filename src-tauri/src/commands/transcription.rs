@@ -1,12 +1,17 @@
 // Commands for transcription: start, get result, open result window.
 
 use crate::commands::models::SelectedModelState;
+use crate::managers::subtitles::{self, SubtitleFormat};
+use crate::managers::transcript_diff::{self, DiffSpan};
 use crate::managers::transcription::{
-    load_transcription_chat_history, load_transcription_metadata, load_transcription_result,
-    save_transcription_chat_history, save_transcription_metadata, save_transcription_result,
-    ChatHistoryMessage, TranscriptionManager, TranscriptionState,
+    has_speech, load_transcription_chat_history, load_transcription_full,
+    load_transcription_metadata, load_transcription_result, load_transcription_runs, save_transcription_chat_history,
+    load_transcription_stats, load_transcription_words, plain_transcript_text, save_transcription_full, save_transcription_words,
+    transcribe_windows, transcription_diarization_path, wav_to_16k_mono_f32,
+    ChatHistoryMessage, ChunkSettings, ModelWarmedEvent, StoredTranscription, TranscriptionOptions, TranscriptionSegment, TranscriptionStats, TranscriptionWord, TranscriptionManager, TranscriptionState,
     TranscriptionStatusEvent, TranscriptionPhaseEvent, TranscriptionProgressEvent,
-    TranscriptionOpenEvent,
+    TranscriptionOpenEvent, TranscriptionSaveFailedEvent, wav_has_speech,
+    WavMonoDecoder, WindowedTranscript, TARGET_SAMPLE_RATE,
 };
 use async_openai::{
     config::OpenAIConfig,
@@ -18,10 +23,9 @@ use async_openai::{
     Client,
 };
 use futures_util::StreamExt;
-use hound::WavReader;
-use rubato::{FftFixedIn, Resampler};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
@@ -36,8 +40,33 @@ pub async fn start_transcription(
     transcription_manager: State<'_, Arc<TranscriptionManager>>,
     selected_model_state: State<'_, SelectedModelState>,
 ) -> Result<(), String> {
-    transcription_manager.inner().set_state(
-        &recording_path,
+    report_transcription_started(&app, transcription_manager.inner(), &recording_path);
+
+    let app_clone = app.clone();
+    let path_clone = recording_path.clone();
+    let tm = Arc::clone(transcription_manager.inner());
+    let sel = selected_model_state.0.clone();
+    let cancel_flag = tm.create_cancel_flag(&recording_path);
+
+    std::thread::spawn(move || {
+        let result = run_transcription(
+            &app_clone,
+            &path_clone,
+            &tm,
+            &sel,
+            &cancel_flag,
+            TranscriptionInput::default(),
+        );
+        tm.remove_cancel_flag(&path_clone);
+        let _ = report_transcription_outcome(&app_clone, &tm, &path_clone, &cancel_flag, result);
+    });
+
+    Ok(())
+}
+
+fn report_transcription_started(app: &AppHandle, tm: &TranscriptionManager, recording_path: &str) {
+    tm.set_state(
+        recording_path,
         TranscriptionState {
             status: "started".to_string(),
             progress: 0.0,
@@ -48,51 +77,76 @@ pub async fn start_transcription(
     let _ = app.emit(
         "transcription-status",
         TranscriptionStatusEvent {
-            recording_path: recording_path.clone(),
+            recording_path: recording_path.to_string(),
             status: "started".to_string(),
             error: None,
+            stats: None,
         },
     );
+}
 
-    let app_clone = app.clone();
-    let path_clone = recording_path.clone();
-    let tm = Arc::clone(transcription_manager.inner());
-    let sel = selected_model_state.0.clone();
-    let cancel_flag = tm.create_cancel_flag(&recording_path);
-
-    std::thread::spawn(move || {
-        let result = run_transcription(&app_clone, &path_clone, &tm, &sel, &cancel_flag);
-        tm.remove_cancel_flag(&path_clone);
-        let (status, err) = match result {
-            Ok(()) => {
-                if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                    ("cancelled".to_string(), None)
-                } else {
-                    ("completed".to_string(), None)
-                }
+/// Store the final state of a transcription run and announce it with a
+/// `transcription-status` event. Errs with the failure, or when cancelled.
+fn report_transcription_outcome(
+    app: &AppHandle,
+    tm: &TranscriptionManager,
+    recording_path: &str,
+    cancel_flag: &AtomicBool,
+    result: Result<Option<TranscriptionStats>, Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<(), String> {
+    let (status, err, stats) = match result {
+        Ok(stats) => {
+            if cancel_flag.load(Ordering::Relaxed) {
+                ("cancelled".to_string(), None, None)
+            } else {
+                ("completed".to_string(), None, stats)
             }
-            Err(e) => ("error".to_string(), Some(e.to_string())),
-        };
-        tm.set_state(
-            &path_clone,
-            TranscriptionState {
-                status: status.clone(),
-                progress: if status == "completed" { 1.0 } else { 0.0 },
-                eta_seconds: if status == "completed" { Some(0) } else { None },
-                phase: None,
-            },
-        );
-        let _ = app_clone.emit(
-            "transcription-status",
-            TranscriptionStatusEvent {
-                recording_path: path_clone,
-                status,
-                error: err,
-            },
-        );
-    });
+        }
+        Err(e) => ("error".to_string(), Some(e.to_string()), None),
+    };
+    tm.set_state(
+        recording_path,
+        TranscriptionState {
+            status: status.clone(),
+            progress: if status == "completed" { 1.0 } else { 0.0 },
+            eta_seconds: if status == "completed" { Some(0) } else { None },
+            phase: None,
+        },
+    );
+    let _ = app.emit(
+        "transcription-status",
+        TranscriptionStatusEvent {
+            recording_path: recording_path.to_string(),
+            status: status.clone(),
+            error: err.clone(),
+            stats,
+        },
+    );
+    match (status.as_str(), err) {
+        ("completed", _) => Ok(()),
+        (_, Some(e)) => Err(e),
+        _ => Err("Transcription was cancelled".to_string()),
+    }
+}
 
-    Ok(())
+/// (max_speakers, threshold, merge_gap) from settings, with the built-in fallbacks.
+#[cfg(feature = "diarization")]
+fn diarization_params(settings: &crate::settings::AppSettings) -> (usize, f64, f64) {
+    (
+        settings.diarization_max_speakers.parse().unwrap_or(3),
+        settings.diarization_threshold.parse().unwrap_or(0.50),
+        settings.diarization_merge_gap.parse().unwrap_or(2.5),
+    )
+}
+
+/// What a transcription run works on when started by another pipeline rather
+/// than by `start_transcription`.
+#[derive(Default)]
+struct TranscriptionInput<'a> {
+    /// Already decoded 16kHz mono audio; the recording is decoded when None.
+    audio: Option<&'a [f32]>,
+    /// The caller diarizes afterwards, so don't do it here even if enabled.
+    skip_diarization: bool,
 }
 
 fn run_transcription(
@@ -101,7 +155,8 @@ fn run_transcription(
     tm: &TranscriptionManager,
     selected_model: &Arc<std::sync::Mutex<String>>,
     cancel_flag: &AtomicBool,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    input: TranscriptionInput<'_>,
+) -> Result<Option<TranscriptionStats>, Box<dyn std::error::Error + Send + Sync>> {
     eprintln!("[transcription] run_transcription called for: {}", recording_path);
     let model_id = {
         let sel = selected_model.lock().map_err(|e| e.to_string())?;
@@ -110,22 +165,22 @@ fn run_transcription(
     if model_id.is_empty() || model_id == "none" {
         return Err("No transcription model selected. Choose a model in the bottom left corner.".into());
     }
+    if !recording_path.to_ascii_lowercase().ends_with(".wav") {
+        return Err("Only WAV recordings can be transcribed. Convert the file to WAV first.".into());
+    }
 
     // Load diarization settings. Diarization is feature-gated (pyannote-rs is
     // currently incompatible with ort rc.12); when the feature is off it is forced
     // off so the rest of the pipeline produces a plain transcript.
-    #[cfg(feature = "diarization")]
     let app_settings = crate::settings::load_app_settings(app).unwrap_or_default();
     #[cfg(feature = "diarization")]
     let diarization_enabled = app_settings.diarization_enabled == "true";
     #[cfg(not(feature = "diarization"))]
     let diarization_enabled = false;
+    let diarization_enabled = diarization_enabled && !input.skip_diarization;
     #[cfg(feature = "diarization")]
-    let diarization_max_speakers: usize = app_settings.diarization_max_speakers.parse().unwrap_or(3);
-    #[cfg(feature = "diarization")]
-    let diarization_threshold: f64 = app_settings.diarization_threshold.parse().unwrap_or(0.50);
-    #[cfg(feature = "diarization")]
-    let diarization_merge_gap: f64 = app_settings.diarization_merge_gap.parse().unwrap_or(2.5);
+    let (diarization_max_speakers, diarization_threshold, diarization_merge_gap) =
+        diarization_params(&app_settings);
     #[cfg(feature = "diarization")]
     eprintln!(
         "[transcription] diarization: enabled={}, max_speakers={}, threshold={}, merge_gap={}",
@@ -167,245 +222,113 @@ fn run_transcription(
                 phase: Some("loading-model".to_string()),
             },
         );
+        // Status can be stale (a download started, finished or was cancelled
+        // since it was last read), so re-read it before loading
+        let model_manager: &Arc<crate::managers::model::ModelManager> = &*app.state();
+        model_manager.update_download_status()?;
         tm.load_model(&model_id)?;
     }
 
-    const TARGET_SAMPLE_RATE: usize = 16000;
-    const RESAMPLER_CHUNK: usize = 1024;
-    const TRANSCRIBE_CHUNK_SECONDS: usize = 30;
-    let transcribe_chunk_samples = TRANSCRIBE_CHUNK_SECONDS * TARGET_SAMPLE_RATE;
-
-    let mut reader = WavReader::open(Path::new(recording_path))?;
-    let spec = reader.spec();
-    let sample_rate_in = spec.sample_rate as usize;
-    let channels = spec.channels as usize;
-
-    let total_input_samples = reader.len() as usize;
-    let total_frames_in = if channels > 0 {
-        total_input_samples / channels
-    } else {
-        0
-    };
-    if total_frames_in == 0 {
-        save_transcription_result(app, recording_path, "")?;
-        save_transcription_metadata(app, recording_path, &model_id)?;
-        return Ok(());
-    }
-
-    let total_seconds = total_frames_in as f32 / sample_rate_in as f32;
-    let total_out_samples = (total_seconds * TARGET_SAMPLE_RATE as f32).round() as usize;
-
-    let mut resampler = if sample_rate_in == TARGET_SAMPLE_RATE {
-        None
-    } else {
-        Some(FftFixedIn::<f32>::new(
-            sample_rate_in,
-            TARGET_SAMPLE_RATE,
-            RESAMPLER_CHUNK,
-            1,
-            1,
-        )?)
-    };
-
-    let mut input_mono: Vec<f32> = Vec::with_capacity(RESAMPLER_CHUNK);
-    let mut pending_16k: Vec<f32> = Vec::with_capacity(transcribe_chunk_samples);
-    let mut processed_out_samples = 0usize;
-    let start = Instant::now();
-    // Store (start_time_seconds, end_time_seconds, text) for each segment -- needed for diarization alignment
-    let mut parts: Vec<(f64, f64, String)> = Vec::new();
-    let mut transcription_started = false;
-
-    // Collect all resampled audio for diarization (only if enabled)
-    let mut all_audio_16k: Vec<f32> = if diarization_enabled {
-        Vec::with_capacity(total_out_samples)
-    } else {
-        Vec::new()
-    };
-
-    let emit_progress = |app: &AppHandle,
-                         tm: &TranscriptionManager,
-                         recording_path: &str,
-                         progress: f32,
-                         eta_seconds: Option<u64>| {
-        tm.set_state(
-            recording_path,
-            TranscriptionState {
-                status: "transcribing".to_string(),
-                progress,
-                eta_seconds,
-                phase: Some("transcribing".to_string()),
-            },
-        );
-        let _ = app.emit(
-            "transcription-progress",
-            TranscriptionProgressEvent {
-                recording_path: recording_path.to_string(),
-                progress,
-                eta_seconds,
-            },
+    let options = app_settings.transcription_options();
+    if options.has_language_options() && !tm.engine_takes_options() {
+        eprintln!(
+            "[transcription] {} ignores language/translate options; only Whisper uses them",
+            model_id
         );
-    };
+    }
 
-    let mut process_pending = |pending_16k: &mut Vec<f32>| -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        while pending_16k.len() >= transcribe_chunk_samples {
-            if cancel_flag.load(Ordering::Relaxed) {
-                return Ok(());
-            }
-            let chunk: Vec<f32> = pending_16k.drain(..transcribe_chunk_samples).collect();
-            if diarization_enabled {
-                all_audio_16k.extend_from_slice(&chunk);
-            }
-            let chunk_start_seconds = processed_out_samples as f64 / TARGET_SAMPLE_RATE as f64;
-            if !transcription_started {
-                transcription_started = true;
-                let _ = app.emit(
-                    "transcription-phase",
-                    TranscriptionPhaseEvent {
-                        recording_path: recording_path.to_string(),
-                        phase: "transcribing".to_string(),
-                    },
-                );
-            }
-            if diarization_enabled {
-                // Word-level timestamps for precise speaker alignment
-                let word_segments = tm.transcribe_with_timestamps(chunk, chunk_start_seconds)?;
-                for (start, end, text) in word_segments {
-                    if !text.trim().is_empty() {
-                        parts.push((start, end, text));
-                    }
-                }
-            } else {
-                let chunk_text = tm.transcribe(chunk)?;
-                if !chunk_text.trim().is_empty() {
-                    let chunk_end_seconds = (processed_out_samples + transcribe_chunk_samples) as f64 / TARGET_SAMPLE_RATE as f64;
-                    parts.push((chunk_start_seconds, chunk_end_seconds, chunk_text));
-                }
-            }
-            processed_out_samples = processed_out_samples.saturating_add(transcribe_chunk_samples);
-            let progress = if total_out_samples > 0 {
-                (processed_out_samples as f32 / total_out_samples as f32).min(1.0)
-            } else {
-                0.0
-            };
-            let processed_seconds = processed_out_samples as f32 / TARGET_SAMPLE_RATE as f32;
-            let eta_seconds = if processed_seconds > 0.5 {
-                let elapsed = start.elapsed().as_secs_f32();
-                let rate = elapsed / processed_seconds;
-                let remaining_seconds = (total_seconds - processed_seconds).max(0.0) * rate;
-                Some(remaining_seconds.round() as u64)
-            } else {
-                None
-            };
-            emit_progress(app, tm, recording_path, progress, eta_seconds);
-        }
-        Ok(())
-    };
+    let (chunk_seconds, overlap_seconds) = app_settings.transcription_chunking();
+    let transcribe_chunk_samples = (chunk_seconds * TARGET_SAMPLE_RATE as f64) as usize;
+    let overlap_samples = (overlap_seconds * TARGET_SAMPLE_RATE as f64) as usize;
 
-    let mut channel_index = 0usize;
-    match spec.sample_format {
-        hound::SampleFormat::Int => {
-            let max_val = 32768.0f32;
-            for s in reader.samples::<i16>() {
-                let s = s?;
-                if channel_index == 0 {
-                    input_mono.push(s as f32 / max_val);
-                }
-                channel_index = (channel_index + 1) % channels.max(1);
-                if input_mono.len() >= RESAMPLER_CHUNK {
-                    if let Some(resampler) = resampler.as_mut() {
-                        let out_chunk = resampler.process(&[&input_mono[..RESAMPLER_CHUNK]], None)?;
-                        pending_16k.extend_from_slice(&out_chunk[0]);
-                    } else {
-                        pending_16k.extend_from_slice(&input_mono[..RESAMPLER_CHUNK]);
-                    }
-                    input_mono.clear();
-                    process_pending(&mut pending_16k)?;
-                }
+    // Decoded window by window, so a long recording is never held in memory whole
+    let source = app_settings.transcription_source();
+    let (total_out_samples, blocks): (usize, Box<dyn Iterator<Item = anyhow::Result<Vec<f32>>> + '_>) =
+        match input.audio {
+            Some(audio) => (
+                audio.len(),
+                Box::new(audio.chunks(TARGET_SAMPLE_RATE).map(|block| Ok(block.to_vec()))),
+            ),
+            None => {
+                let decoder =
+                    WavMonoDecoder::open(Path::new(recording_path), source, TARGET_SAMPLE_RATE)?;
+                (decoder.output_len(), Box::new(decoder))
             }
-        }
-        hound::SampleFormat::Float => {
-            for s in reader.samples::<f32>() {
-                let s = s?;
-                if channel_index == 0 {
-                    input_mono.push(s);
-                }
-                channel_index = (channel_index + 1) % channels.max(1);
-                if input_mono.len() >= RESAMPLER_CHUNK {
-                    if let Some(resampler) = resampler.as_mut() {
-                        let out_chunk = resampler.process(&[&input_mono[..RESAMPLER_CHUNK]], None)?;
-                        pending_16k.extend_from_slice(&out_chunk[0]);
-                    } else {
-                        pending_16k.extend_from_slice(&input_mono[..RESAMPLER_CHUNK]);
-                    }
-                    input_mono.clear();
-                    process_pending(&mut pending_16k)?;
-                }
-            }
-        }
+        };
+    if total_out_samples == 0 {
+        let empty = StoredTranscription::new(String::new(), Vec::new(), &model_id);
+        save_result(app, tm, recording_path, &empty);
+        return Ok(None);
     }
 
-    if !input_mono.is_empty() {
-        if let Some(resampler) = resampler.as_mut() {
-            let mut pad = input_mono;
-            pad.resize(RESAMPLER_CHUNK, 0.0);
-            let out_chunk = resampler.process(&[&pad], None)?;
-            pending_16k.extend_from_slice(&out_chunk[0]);
-        } else {
-            pending_16k.extend_from_slice(&input_mono);
+    // Music-only or silent recordings make engines hallucinate lyrics; store an
+    // empty result flagged as no-speech instead of running inference.
+    if app_settings.skip_non_speech == "true" {
+        let speech = match input.audio {
+            Some(audio) => has_speech(audio, TARGET_SAMPLE_RATE),
+            None => wav_has_speech(Path::new(recording_path), source)?,
+        };
+        if !speech {
+            eprintln!("[transcription] no speech detected in {}, skipping inference", recording_path);
+            let mut empty = StoredTranscription::new(String::new(), Vec::new(), &model_id);
+            empty.no_speech = true;
+            save_result(app, tm, recording_path, &empty);
+            return Ok(None);
         }
-        process_pending(&mut pending_16k)?;
     }
 
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Ok(());
-    }
+    let _ = app.emit(
+        "transcription-phase",
+        TranscriptionPhaseEvent {
+            recording_path: recording_path.to_string(),
+            phase: "transcribing".to_string(),
+        },
+    );
 
-    if !pending_16k.is_empty() {
-        if !transcription_started {
+    let chunking = ChunkSettings {
+        chunk_samples: transcribe_chunk_samples,
+        overlap_samples,
+        word_timestamps: diarization_enabled,
+    };
+    let WindowedTranscript {
+        parts,
+        words,
+        stats,
+    } = transcribe_windows(
+        tm,
+        blocks,
+        total_out_samples,
+        chunking,
+        &options,
+        cancel_flag,
+        |progress, eta_seconds| {
+            tm.set_state(
+                recording_path,
+                TranscriptionState {
+                    status: "transcribing".to_string(),
+                    progress,
+                    eta_seconds,
+                    phase: Some("transcribing".to_string()),
+                },
+            );
             let _ = app.emit(
-                "transcription-phase",
-                TranscriptionPhaseEvent {
+                "transcription-progress",
+                TranscriptionProgressEvent {
                     recording_path: recording_path.to_string(),
-                    phase: "transcribing".to_string(),
+                    progress,
+                    eta_seconds,
                 },
             );
-        }
-        let chunk: Vec<f32> = pending_16k.drain(..).collect();
-        if diarization_enabled {
-            all_audio_16k.extend_from_slice(&chunk);
-        }
-        let chunk_start_seconds = processed_out_samples as f64 / TARGET_SAMPLE_RATE as f64;
-        let chunk_len = chunk.len();
-        if diarization_enabled {
-            let word_segments = tm.transcribe_with_timestamps(chunk, chunk_start_seconds)?;
-            for (start, end, text) in word_segments {
-                if !text.trim().is_empty() {
-                    parts.push((start, end, text));
-                }
-            }
-        } else {
-            let chunk_text = tm.transcribe(chunk)?;
-            if !chunk_text.trim().is_empty() {
-                let chunk_end_seconds = (processed_out_samples + chunk_len) as f64 / TARGET_SAMPLE_RATE as f64;
-                parts.push((chunk_start_seconds, chunk_end_seconds, chunk_text));
-            }
-        }
-        processed_out_samples = processed_out_samples.saturating_add(chunk_len);
-        let progress = if total_out_samples > 0 {
-            (processed_out_samples as f32 / total_out_samples as f32).min(1.0)
-        } else {
-            1.0
-        };
-        emit_progress(app, tm, recording_path, progress, Some(0));
-    }
+        },
+    )?;
 
     if cancel_flag.load(Ordering::Relaxed) {
-        return Ok(());
+        return Ok(None);
     }
 
     // Run diarization if enabled (feature-gated; see diarization_enabled above).
     #[cfg(feature = "diarization")]
-    let text = if diarization_enabled && !all_audio_16k.is_empty() {
+    let text = if diarization_enabled {
         let _ = app.emit(
             "transcription-phase",
             TranscriptionPhaseEvent {
@@ -431,9 +354,13 @@ fn run_transcription(
         eprintln!("[transcription] diarization model paths: seg={:?}, emb={:?}", seg_path, emb_path);
         match (seg_path, emb_path) {
             (Ok(seg), Ok(emb)) => {
-                // Try reading audio directly via pyannote's own reader for comparison
+                // Transcription streamed the audio; diarization needs all of it at once.
                 // Convert resampled f32 audio to i16 for diarization
                 // IMPORTANT: Use all_audio_16k (16kHz mono) NOT raw WAV samples
+                let all_audio_16k: std::borrow::Cow<[f32]> = match input.audio {
+                    Some(audio) => std::borrow::Cow::Borrowed(audio),
+                    None => std::borrow::Cow::Owned(wav_to_16k_mono_f32(Path::new(recording_path), source)?),
+                };
                 eprintln!("[transcription] converting {} samples @ 16kHz to i16", all_audio_16k.len());
                 let samples_i16 = crate::managers::diarization::f32_to_i16(&all_audio_16k);
                 let sr = TARGET_SAMPLE_RATE as u32; // 16000 Hz
@@ -446,46 +373,904 @@ fn run_transcription(
                     diarization_max_speakers,
                     diarization_threshold,
                     diarization_merge_gap,
+                    load_known_speakers(app),
                 ) {
                     Ok(speaker_segments) => {
                         eprintln!("[transcription] diarization OK: {} speaker segments found", speaker_segments.len());
+                        let speaker_segments = store_speaker_segments(app, recording_path, speaker_segments);
                         let formatted = crate::managers::diarization::format_diarized_text(&parts, &speaker_segments);
                         eprintln!("[transcription] diarized text length: {} chars", formatted.len());
                         formatted
                     }
                     Err(e) => {
                         eprintln!("[transcription] diarization FAILED: {}", e);
-                        parts.iter().map(|(_, _, t)| t.as_str()).collect::<Vec<_>>().join(" ")
+                        plain_transcript_text(&parts)
                     }
                 }
             }
             _ => {
                 eprintln!("[transcription] diarization models not downloaded, falling back to plain text");
-                parts.iter().map(|(_, _, t)| t.as_str()).collect::<Vec<_>>().join(" ")
+                plain_transcript_text(&parts)
             }
         }
     } else {
-        parts.iter().map(|(_, _, t)| t.as_str()).collect::<Vec<_>>().join(" ")
+        plain_transcript_text(&parts)
     };
 
     // Diarization feature disabled: always emit a plain transcript.
     #[cfg(not(feature = "diarization"))]
     let text = {
-        let _ = &all_audio_16k;
-        parts.iter().map(|(_, _, t)| t.as_str()).collect::<Vec<_>>().join(" ")
+        plain_transcript_text(&parts)
     };
 
-    save_transcription_result(app, recording_path, &text)?;
-    save_transcription_metadata(app, recording_path, &model_id)?;
-    Ok(())
+    let segments = parts
+        .into_iter()
+        .map(|(start, end, text)| TranscriptionSegment { start, end, text })
+        .collect();
+    let mut stored = StoredTranscription::new(text, segments, &model_id);
+    stored.options = tm.effective_options(&options);
+    stored.stats = Some(stats);
+    eprintln!(
+        "[transcription] {} transcribed {:.1}s of audio in {:.1}s (RTF {:.2})",
+        model_id,
+        stats.audio_seconds,
+        stats.processing_seconds,
+        stats.real_time_factor.unwrap_or(0.0)
+    );
+    save_result(app, tm, recording_path, &stored);
+    if let Err(e) = save_transcription_words(app, recording_path, &words) {
+        eprintln!("[transcription] failed to save words for {}: {}", recording_path, e);
+    }
+    Ok(Some(stats))
+}
+
+/// Persist a finished transcription. Failing to write it (read-only or full
+/// volume) doesn't fail the run: the result stays in memory and the failure
+/// goes out as a separate `transcription-save-failed` warning.
+fn save_result(
+    app: &AppHandle,
+    tm: &TranscriptionManager,
+    recording_path: &str,
+    stored: &StoredTranscription,
+) {
+    if let Err(e) = tm.save_result(app, recording_path, stored) {
+        eprintln!("[transcription] failed to save result for {}: {}", recording_path, e);
+        let _ = app.emit(
+            "transcription-save-failed",
+            TranscriptionSaveFailedEvent {
+                recording_path: recording_path.to_string(),
+                error: e.to_string(),
+                text: stored.text.clone(),
+            },
+        );
+    }
 }
 
 #[tauri::command]
 pub async fn get_transcription_result(
     app: AppHandle,
     recording_path: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
 ) -> Result<Option<String>, String> {
-    load_transcription_result(&app, &recording_path).map_err(|e| e.to_string())
+    transcription_manager
+        .load_result(&app, &recording_path)
+        .map(|stored| stored.map(|s| s.text))
+        .map_err(|e| e.to_string())
+}
+
+/// Structured transcription (segments, model). Legacy .txt-only results are
+/// migrated on first access.
+#[tauri::command]
+pub async fn get_transcription_full(
+    app: AppHandle,
+    recording_path: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Option<StoredTranscription>, String> {
+    transcription_manager
+        .load_result(&app, &recording_path)
+        .map_err(|e| e.to_string())
+}
+
+/// Per-word timings for an interactive transcript; empty when the engine
+/// only gave phrase-level segments.
+#[tauri::command]
+pub async fn get_transcription_words(
+    app: AppHandle,
+    recording_path: String,
+) -> Result<Vec<TranscriptionWord>, String> {
+    load_transcription_words(&app, &recording_path).map_err(|e| e.to_string())
+}
+
+/// Timing of the last transcription (audio length, processing time and
+/// real-time factor), for comparing models. None for results saved before
+/// timing was recorded.
+#[tauri::command]
+pub async fn get_transcription_stats(
+    app: AppHandle,
+    recording_path: String,
+) -> Result<Option<TranscriptionStats>, String> {
+    load_transcription_stats(&app, &recording_path).map_err(|e| e.to_string())
+}
+
+/// Transcript labeled by speaker, built from the saved word timings and
+/// speaker timeline. Falls back to the plain transcript when either is missing.
+#[tauri::command]
+pub async fn get_diarized_transcript(
+    app: AppHandle,
+    recording_path: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Option<String>, String> {
+    transcript_text(&app, &recording_path, &transcription_manager, true)
+}
+
+/// Transcript from persisted data, as a clean reading copy (`diarized` false)
+/// or under `[Speaker|time]` markers. Both modes render the same words.
+#[tauri::command]
+pub async fn get_transcript(
+    app: AppHandle,
+    recording_path: String,
+    diarized: bool,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Option<String>, String> {
+    transcript_text(&app, &recording_path, &transcription_manager, diarized)
+}
+
+/// Word timings when saved, otherwise the stored segments; the stored text
+/// is only used for results that have neither.
+fn transcript_text(
+    app: &AppHandle,
+    recording_path: &str,
+    tm: &TranscriptionManager,
+    diarized: bool,
+) -> Result<Option<String>, String> {
+    let Some(stored) = tm.load_result(app, recording_path).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let words = load_transcription_words(app, recording_path).map_err(|e| e.to_string())?;
+    let parts: Vec<(f64, f64, String)> = if words.is_empty() {
+        stored
+            .segments
+            .into_iter()
+            .map(|s| (s.start, s.end, s.text))
+            .collect()
+    } else {
+        words.into_iter().map(|w| (w.start, w.end, w.text)).collect()
+    };
+    if parts.is_empty() {
+        return Ok(Some(stored.text));
+    }
+    if diarized {
+        if let Some(text) = diarize_parts(app, recording_path, &parts)? {
+            return Ok(Some(text));
+        }
+    }
+    Ok(Some(plain_transcript_text(&parts)))
+}
+
+/// `parts` under speaker markers, or None without a saved speaker timeline.
+#[cfg(feature = "diarization")]
+fn diarize_parts(
+    app: &AppHandle,
+    recording_path: &str,
+    parts: &[(f64, f64, String)],
+) -> Result<Option<String>, String> {
+    let path = transcription_diarization_path(app, recording_path).map_err(|e| e.to_string())?;
+    let speakers = crate::managers::diarization::load_stored_speakers(&path)
+        .map_err(|e| e.to_string())?
+        .resolved();
+    if speakers.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(crate::managers::diarization::format_diarized_text(parts, &speakers)))
+}
+
+#[cfg(not(feature = "diarization"))]
+fn diarize_parts(
+    _app: &AppHandle,
+    _recording_path: &str,
+    _parts: &[(f64, f64, String)],
+) -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+/// Render the stored segments as SRT or WebVTT. No audio or inference involved.
+#[tauri::command]
+pub async fn get_transcription_subtitles(
+    app: AppHandle,
+    recording_path: String,
+    format: String,
+) -> Result<String, String> {
+    let format = SubtitleFormat::parse(&format)?;
+    let stored = load_transcription_full(&app, &recording_path)
+        .map_err(|e| e.to_string())?
+        .ok_or("No transcription for this recording")?;
+    Ok(subtitles::render(&stored.segments, format))
+}
+
+/// Write the transcript as an SRT or WebVTT file next to the recording and
+/// return its path. Needs segment timestamps; results from before they were
+/// stored have none.
+#[tauri::command]
+pub async fn export_transcription(
+    app: AppHandle,
+    recording_path: String,
+    format: String,
+) -> Result<String, String> {
+    let format = SubtitleFormat::parse(&format)?;
+    let stored = load_transcription_full(&app, &recording_path)
+        .map_err(|e| e.to_string())?
+        .ok_or("No transcription for this recording")?;
+    if stored.segments.is_empty() {
+        return Err("This transcription has no timestamps. Transcribe it again to export subtitles.".to_string());
+    }
+    let dir = crate::paths::recordings_dir(&app)?;
+    crate::paths::ensure_dir(&dir)?;
+    let path = subtitles::export_path(&dir, Path::new(&recording_path), format);
+    std::fs::write(&path, subtitles::render(&stored.segments, format))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Re-run speaker assignment for an existing transcription. Text and timings
+/// come from the stored segments; the audio is only read for the speaker
+/// embeddings, so no transcription model is needed.
+#[tauri::command]
+pub async fn rediarize_transcription(app: AppHandle, recording_path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || rediarize(&app, &recording_path))
+        .await
+        .map_err(|e| format!("Task failed to join: {}", e))?
+}
+
+#[cfg(feature = "diarization")]
+fn rediarize(app: &AppHandle, recording_path: &str) -> Result<String, String> {
+    let mut stored = load_transcription_full(app, recording_path)
+        .map_err(|e| e.to_string())?
+        .ok_or("No transcription for this recording")?;
+    if stored.segments.is_empty() {
+        return Err("This transcription has no stored segments. Transcribe it again first.".to_string());
+    }
+
+    let speaker_segments = run_and_save_diarization(app, recording_path)?;
+    rerender_diarized_text(app, recording_path, &mut stored, &speaker_segments)?;
+    Ok(stored.text)
+}
+
+#[cfg(not(feature = "diarization"))]
+fn rediarize(_app: &AppHandle, _recording_path: &str) -> Result<String, String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+/// Diarize a recording with the current settings and save the speaker
+/// timeline to its .diarization.json. Manual overrides from earlier runs are
+/// kept; the returned timeline has them applied.
+#[cfg(feature = "diarization")]
+fn run_and_save_diarization(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<Vec<crate::managers::diarization::SpeakerSegment>, String> {
+    let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+    let audio = wav_to_16k_mono_f32(Path::new(recording_path), settings.transcription_source())
+        .map_err(|e| e.to_string())?;
+    diarize_and_save(app, recording_path, &audio)
+}
+
+/// `run_and_save_diarization` on 16kHz mono audio that is already decoded.
+#[cfg(feature = "diarization")]
+fn diarize_and_save(
+    app: &AppHandle,
+    recording_path: &str,
+    audio: &[f32],
+) -> Result<Vec<crate::managers::diarization::SpeakerSegment>, String> {
+    use crate::managers::diarization::{load_stored_speakers, save_stored_speakers};
+    let model_manager: &Arc<crate::managers::model::ModelManager> = &*app.state();
+    let seg_path = model_manager
+        .get_model_path("diarize-segmentation")
+        .map_err(|e| e.to_string())?;
+    let emb_path = model_manager
+        .get_model_path("diarize-embedding")
+        .map_err(|e| e.to_string())?;
+    let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+    let (max_speakers, threshold, merge_gap) = diarization_params(&settings);
+
+    let samples_i16 = crate::managers::diarization::f32_to_i16(audio);
+    let speaker_segments = crate::managers::diarization::run_diarization(
+        &samples_i16,
+        TARGET_SAMPLE_RATE as u32,
+        &seg_path,
+        &emb_path,
+        max_speakers,
+        threshold,
+        merge_gap,
+        load_known_speakers(app),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let path = transcription_diarization_path(app, recording_path).map_err(|e| e.to_string())?;
+    let mut speakers = load_stored_speakers(&path).map_err(|e| e.to_string())?;
+    speakers.segments = speaker_segments;
+    save_stored_speakers(&path, &speakers).map_err(|e| e.to_string())?;
+    Ok(speakers.resolved())
+}
+
+#[derive(Clone, Serialize)]
+struct DiarizationStatusEvent {
+    recording_path: String,
+    status: String,
+    error: Option<String>,
+}
+
+/// Diarize a recording in the background. Progress is reported through
+/// `diarization-status` events; an existing transcription is re-rendered with
+/// the new speakers when done.
+#[tauri::command]
+pub async fn start_diarization(app: AppHandle, recording_path: String) -> Result<(), String> {
+    let _ = app.emit(
+        "diarization-status",
+        DiarizationStatusEvent {
+            recording_path: recording_path.clone(),
+            status: "started".to_string(),
+            error: None,
+        },
+    );
+
+    std::thread::spawn(move || {
+        let (status, error) = match diarize_recording(&app, &recording_path) {
+            Ok(()) => ("completed".to_string(), None),
+            Err(e) => {
+                eprintln!("[diarization] failed for {}: {}", recording_path, e);
+                ("error".to_string(), Some(e))
+            }
+        };
+        let _ = app.emit(
+            "diarization-status",
+            DiarizationStatusEvent {
+                recording_path,
+                status,
+                error,
+            },
+        );
+    });
+
+    Ok(())
+}
+
+#[cfg(feature = "diarization")]
+fn diarize_recording(app: &AppHandle, recording_path: &str) -> Result<(), String> {
+    let speaker_segments = run_and_save_diarization(app, recording_path)?;
+    rerender_existing_transcript(app, recording_path, &speaker_segments)
+}
+
+#[cfg(not(feature = "diarization"))]
+fn diarize_recording(_app: &AppHandle, _recording_path: &str) -> Result<(), String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+/// `diarize_recording` on 16kHz mono audio that is already decoded.
+#[cfg(feature = "diarization")]
+fn diarize_audio(app: &AppHandle, recording_path: &str, audio: &[f32]) -> Result<(), String> {
+    let speaker_segments = diarize_and_save(app, recording_path, audio)?;
+    rerender_existing_transcript(app, recording_path, &speaker_segments)
+}
+
+#[cfg(not(feature = "diarization"))]
+fn diarize_audio(_app: &AppHandle, _recording_path: &str, _audio: &[f32]) -> Result<(), String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+#[cfg(feature = "diarization")]
+fn rerender_existing_transcript(
+    app: &AppHandle,
+    recording_path: &str,
+    speaker_segments: &[crate::managers::diarization::SpeakerSegment],
+) -> Result<(), String> {
+    if let Ok(Some(mut stored)) = load_transcription_full(app, recording_path) {
+        if !stored.segments.is_empty() {
+            rerender_diarized_text(app, recording_path, &mut stored, speaker_segments)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stages of `process_recording`, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PipelineStage {
+    Denoise,
+    Transcribe,
+    Diarize,
+}
+
+/// Which stages `process_recording` runs. `noise_model` defaults to the
+/// selected monitoring model, or RNNoise while monitoring is a passthrough.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ProcessRecordingOptions {
+    #[serde(default)]
+    pub denoise: bool,
+    #[serde(default)]
+    pub transcribe: bool,
+    #[serde(default)]
+    pub diarize: bool,
+    #[serde(default)]
+    pub noise_model: Option<String>,
+}
+
+impl ProcessRecordingOptions {
+    /// The enabled stages, in pipeline order.
+    fn stages(&self) -> Vec<PipelineStage> {
+        [
+            (PipelineStage::Denoise, self.denoise),
+            (PipelineStage::Transcribe, self.transcribe),
+            (PipelineStage::Diarize, self.diarize),
+        ]
+        .into_iter()
+        .filter_map(|(stage, enabled)| enabled.then_some(stage))
+        .collect()
+    }
+
+    /// The model to denoise with, given the selected monitoring model.
+    fn noise_model(&self, selected: &str) -> String {
+        match &self.noise_model {
+            Some(model) => model.clone(),
+            None if selected.is_empty() || selected == "dummy" => "rnnnoise".to_string(),
+            None => selected.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct ProcessRecordingEvent {
+    recording_path: String,
+    stage: PipelineStage,
+    status: String, // "started" | "completed" | "error"
+    error: Option<String>,
+}
+
+/// Denoise, transcribe and diarize a recording in one call, running only the
+/// stages asked for. The denoised audio is kept as `<name>-denoised.wav` and
+/// transcribed straight from memory; transcript and speakers are stored
+/// against that copy, or against the original when not denoising. Each stage
+/// reports through `process-recording-progress`. Returns the path the
+/// results belong to.
+#[tauri::command]
+pub async fn process_recording(
+    app: AppHandle,
+    recording_path: String,
+    options: ProcessRecordingOptions,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    selected_model_state: State<'_, SelectedModelState>,
+) -> Result<String, String> {
+    let stages = options.stages();
+    if stages.is_empty() {
+        return Err("Choose at least one stage to run".to_string());
+    }
+    if !recording_path.to_ascii_lowercase().ends_with(".wav") {
+        return Err("Only WAV recordings can be processed. Convert the file to WAV first.".to_string());
+    }
+    #[cfg(not(feature = "diarization"))]
+    if options.diarize {
+        return Err("Diarization is not available in this build".to_string());
+    }
+    let input = PathBuf::from(&recording_path);
+    crate::commands::recording::ensure_in_recordings_dir(&app, &input).map_err(|e| e.to_string())?;
+    if transcription_manager.is_transcribing(&recording_path) {
+        return Err("This recording is already being transcribed".to_string());
+    }
+    let noise_model = options.noise_model(
+        &crate::settings::load_app_settings(&app)
+            .unwrap_or_default()
+            .selected_model,
+    );
+    if options.denoise {
+        crate::commands::recording::validate_denoise_request(&app, &input, &noise_model)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let tm = Arc::clone(transcription_manager.inner());
+    let sel = selected_model_state.0.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        run_pipeline(&app, &input, &stages, &noise_model, &tm, &sel)
+    })
+    .await
+    .map_err(|e| format!("Task failed to join: {}", e))?
+}
+
+fn run_pipeline(
+    app: &AppHandle,
+    input: &Path,
+    stages: &[PipelineStage],
+    noise_model: &str,
+    tm: &TranscriptionManager,
+    selected_model: &Arc<std::sync::Mutex<String>>,
+) -> Result<String, String> {
+    let recording_path = input.to_string_lossy().to_string();
+    let emit = |stage: PipelineStage, status: &str, error: Option<String>| {
+        let _ = app.emit(
+            "process-recording-progress",
+            ProcessRecordingEvent {
+                recording_path: recording_path.clone(),
+                stage,
+                status: status.to_string(),
+                error,
+            },
+        );
+    };
+    // Where the results are stored, and its 16kHz audio once decoded
+    let mut target = input.to_path_buf();
+    let mut audio_16k: Option<Vec<f32>> = None;
+
+    for &stage in stages {
+        emit(stage, "started", None);
+        let target_str = target.to_string_lossy().to_string();
+        let result = match stage {
+            PipelineStage::Denoise => {
+                crate::commands::recording::unused_sibling_path(input, "denoised").and_then(|output| {
+                    let denoised =
+                        crate::commands::recording::write_denoised_copy(app, input, &output, noise_model)?;
+                    let resampled = crate::managers::transcription::resample_mono(
+                        &denoised,
+                        crate::recording::SAMPLE_RATE,
+                        TARGET_SAMPLE_RATE,
+                    )
+                    .map_err(|e| e.to_string())?;
+                    target = output;
+                    audio_16k = Some(resampled);
+                    Ok(())
+                })
+            }
+            PipelineStage::Transcribe => {
+                pipeline_audio(app, &target, &mut audio_16k).and_then(|audio| {
+                    let cancel_flag = tm
+                        .claim_cancel_flag(&target_str)
+                        .ok_or_else(|| "This recording is already being transcribed".to_string())?;
+                    report_transcription_started(app, tm, &target_str);
+                    let input = TranscriptionInput {
+                        audio: Some(audio),
+                        skip_diarization: true,
+                    };
+                    let result =
+                        run_transcription(app, &target_str, tm, selected_model, &cancel_flag, input);
+                    tm.remove_cancel_flag(&target_str);
+                    report_transcription_outcome(app, tm, &target_str, &cancel_flag, result)
+                })
+            }
+            PipelineStage::Diarize => pipeline_audio(app, &target, &mut audio_16k)
+                .and_then(|audio| diarize_audio(app, &target_str, audio)),
+        };
+        if let Err(e) = result {
+            emit(stage, "error", Some(e.clone()));
+            return Err(e);
+        }
+        emit(stage, "completed", None);
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// The pipeline's 16kHz audio, decoded from `path` the first time a stage
+/// needs it.
+fn pipeline_audio<'a>(
+    app: &AppHandle,
+    path: &Path,
+    audio: &'a mut Option<Vec<f32>>,
+) -> Result<&'a [f32], String> {
+    if audio.is_none() {
+        let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+        let decoded = wav_to_16k_mono_f32(path, settings.transcription_source())
+            .map_err(|e| e.to_string())?;
+        *audio = Some(decoded);
+    }
+    Ok(audio.as_deref().unwrap_or_default())
+}
+
+/// The saved speaker timeline for a recording, with manual overrides applied.
+/// Empty if the recording hasn't been diarized.
+#[cfg(feature = "diarization")]
+#[tauri::command]
+pub async fn get_diarization(
+    app: AppHandle,
+    recording_path: String,
+) -> Result<Vec<crate::managers::diarization::SpeakerSegment>, String> {
+    let path = transcription_diarization_path(&app, &recording_path).map_err(|e| e.to_string())?;
+    crate::managers::diarization::load_stored_speakers(&path)
+        .map(|speakers| speakers.resolved())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "diarization"))]
+#[tauri::command]
+pub async fn get_diarization(
+    _app: AppHandle,
+    _recording_path: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    Ok(Vec::new())
+}
+
+/// Persist fresh diarization output, keeping manual overrides from earlier
+/// runs, and return the speaker timeline with those overrides applied.
+#[cfg(feature = "diarization")]
+fn store_speaker_segments(
+    app: &AppHandle,
+    recording_path: &str,
+    segments: Vec<crate::managers::diarization::SpeakerSegment>,
+) -> Vec<crate::managers::diarization::SpeakerSegment> {
+    use crate::managers::diarization::{load_stored_speakers, save_stored_speakers};
+    let path = match transcription_diarization_path(app, recording_path) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[transcription] no diarization path for {}: {}", recording_path, e);
+            return segments;
+        }
+    };
+    let mut speakers = load_stored_speakers(&path).unwrap_or_default();
+    speakers.segments = segments;
+    if let Err(e) = save_stored_speakers(&path, &speakers) {
+        eprintln!("[transcription] failed to save speakers for {}: {}", recording_path, e);
+    }
+    speakers.resolved()
+}
+
+/// Rebuild the diarized text from the stored segments and save it.
+#[cfg(feature = "diarization")]
+fn rerender_diarized_text(
+    app: &AppHandle,
+    recording_path: &str,
+    stored: &mut StoredTranscription,
+    speaker_segments: &[crate::managers::diarization::SpeakerSegment],
+) -> Result<(), String> {
+    let parts: Vec<(f64, f64, String)> = stored
+        .segments
+        .iter()
+        .map(|s| (s.start, s.end, s.text.clone()))
+        .collect();
+    stored.text = crate::managers::diarization::format_diarized_text(&parts, speaker_segments);
+    let tm: &Arc<TranscriptionManager> = &*app.state();
+    save_transcription_full(app, recording_path, stored).map_err(|e| e.to_string())?;
+    tm.cache_result(recording_path, stored);
+    Ok(())
+}
+
+/// Manually label a time range with a speaker. The override is stored with the
+/// diarization and re-applied after every re-diarization; returns the
+/// re-rendered transcript.
+#[tauri::command]
+pub async fn set_speaker_for_range(
+    app: AppHandle,
+    recording_path: String,
+    start: f64,
+    end: f64,
+    speaker: String,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        set_speaker_override(&app, &recording_path, start, end, speaker)
+    })
+    .await
+    .map_err(|e| format!("Task failed to join: {}", e))?
+}
+
+#[cfg(feature = "diarization")]
+fn set_speaker_override(
+    app: &AppHandle,
+    recording_path: &str,
+    start: f64,
+    end: f64,
+    speaker: String,
+) -> Result<String, String> {
+    use crate::managers::diarization::{load_stored_speakers, save_stored_speakers, SpeakerOverride};
+    let speaker = speaker.trim().to_string();
+    if speaker.is_empty() {
+        return Err("Speaker name can't be empty".to_string());
+    }
+    if !(start.is_finite() && end.is_finite() && start >= 0.0 && start < end) {
+        return Err("Invalid time range".to_string());
+    }
+    let mut stored = load_transcription_full(app, recording_path)
+        .map_err(|e| e.to_string())?
+        .ok_or("No transcription for this recording")?;
+    if stored.segments.is_empty() {
+        return Err("This transcription has no stored segments. Transcribe it again first.".to_string());
+    }
+
+    let path = transcription_diarization_path(app, recording_path).map_err(|e| e.to_string())?;
+    let mut speakers = load_stored_speakers(&path).map_err(|e| e.to_string())?;
+    speakers.overrides.push(SpeakerOverride { start, end, speaker });
+    save_stored_speakers(&path, &speakers).map_err(|e| e.to_string())?;
+
+    rerender_diarized_text(app, recording_path, &mut stored, &speakers.resolved())?;
+    Ok(stored.text)
+}
+
+#[cfg(not(feature = "diarization"))]
+fn set_speaker_override(
+    _app: &AppHandle,
+    _recording_path: &str,
+    _start: f64,
+    _end: f64,
+    _speaker: String,
+) -> Result<String, String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+/// Rename a speaker in a recording's diarization. The name is kept per
+/// diarization label, so it's re-applied when the recording is re-diarized.
+#[tauri::command]
+pub async fn rename_speaker(
+    app: AppHandle,
+    recording_path: String,
+    old_label: String,
+    new_label: String,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        rename_stored_speaker(&app, &recording_path, &old_label, &new_label)
+    })
+    .await
+    .map_err(|e| format!("Task failed to join: {}", e))?
+}
+
+#[cfg(feature = "diarization")]
+fn rename_stored_speaker(
+    app: &AppHandle,
+    recording_path: &str,
+    old_label: &str,
+    new_label: &str,
+) -> Result<(), String> {
+    use crate::managers::diarization::{load_stored_speakers, save_stored_speakers};
+    let path = transcription_diarization_path(app, recording_path).map_err(|e| e.to_string())?;
+    let mut speakers = load_stored_speakers(&path).map_err(|e| e.to_string())?;
+    speakers.rename(old_label, new_label).map_err(|e| e.to_string())?;
+    save_stored_speakers(&path, &speakers).map_err(|e| e.to_string())?;
+
+    if let Ok(Some(mut stored)) = load_transcription_full(app, recording_path) {
+        if !stored.segments.is_empty() {
+            rerender_diarized_text(app, recording_path, &mut stored, &speakers.resolved())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "diarization"))]
+fn rename_stored_speaker(
+    _app: &AppHandle,
+    _recording_path: &str,
+    _old_label: &str,
+    _new_label: &str,
+) -> Result<(), String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+/// Enrolled speakers live next to settings.json, shared by all recordings.
+#[cfg(feature = "diarization")]
+fn known_speakers_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = crate::paths::crispy_documents_root(app)?;
+    crate::paths::ensure_dir(&dir)?;
+    Ok(dir.join("known_speakers.json"))
+}
+
+/// Enrolled speakers for labeling clusters; none if the store can't be read.
+#[cfg(feature = "diarization")]
+fn load_known_speakers(app: &AppHandle) -> crate::managers::diarization::KnownSpeakers {
+    known_speakers_path(app)
+        .and_then(|path| {
+            crate::managers::diarization::load_known_speakers(&path).map_err(|e| e.to_string())
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("[diarization] known speakers unavailable: {}", e);
+            Default::default()
+        })
+}
+
+/// Enroll a speaker by name from one segment of a diarized recording, so
+/// later diarization labels matching voices with that name.
+#[tauri::command]
+pub async fn enroll_speaker(
+    app: AppHandle,
+    name: String,
+    recording_path: String,
+    segment_index: usize,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        enroll_speaker_from_segment(&app, &name, &recording_path, segment_index)
+    })
+    .await
+    .map_err(|e| format!("Task failed to join: {}", e))?
+}
+
+#[cfg(feature = "diarization")]
+fn enroll_speaker_from_segment(
+    app: &AppHandle,
+    name: &str,
+    recording_path: &str,
+    segment_index: usize,
+) -> Result<(), String> {
+    use crate::managers::diarization::{load_stored_speakers, save_known_speakers, speaker_embedding};
+    if name.trim().is_empty() {
+        return Err("Speaker name can't be empty".to_string());
+    }
+    let path = transcription_diarization_path(app, recording_path).map_err(|e| e.to_string())?;
+    let segments = load_stored_speakers(&path).map_err(|e| e.to_string())?.resolved();
+    let segment = segments
+        .get(segment_index)
+        .ok_or("No such speaker segment. Diarize the recording first.")?;
+
+    let model_manager: &Arc<crate::managers::model::ModelManager> = &*app.state();
+    let emb_path = model_manager
+        .get_model_path("diarize-embedding")
+        .map_err(|e| e.to_string())?;
+    let settings = crate::settings::load_app_settings(app).unwrap_or_default();
+    let audio = wav_to_16k_mono_f32(Path::new(recording_path), settings.transcription_source())
+        .map_err(|e| e.to_string())?;
+    let start = ((segment.start * TARGET_SAMPLE_RATE as f64) as usize).min(audio.len());
+    let end = ((segment.end * TARGET_SAMPLE_RATE as f64) as usize).clamp(start, audio.len());
+    let samples_i16 = crate::managers::diarization::f32_to_i16(&audio[start..end]);
+    let embedding = speaker_embedding(&emb_path, &samples_i16).map_err(|e| e.to_string())?;
+
+    let store_path = known_speakers_path(app)?;
+    let mut known = crate::managers::diarization::load_known_speakers(&store_path)
+        .map_err(|e| e.to_string())?;
+    known.enroll(name, &embedding).map_err(|e| e.to_string())?;
+    save_known_speakers(&store_path, &known).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "diarization"))]
+fn enroll_speaker_from_segment(
+    _app: &AppHandle,
+    _name: &str,
+    _recording_path: &str,
+    _segment_index: usize,
+) -> Result<(), String> {
+    Err("Diarization is not available in this build".to_string())
+}
+
+/// Load the selected (or given) model and run a silent buffer through it, so
+/// the first real transcription doesn't pay for lazy engine initialization.
+#[tauri::command]
+pub async fn warmup_transcription_model(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    selected_model_state: State<'_, SelectedModelState>,
+    model_id: Option<String>,
+) -> Result<(), String> {
+    let model_id = model_id.unwrap_or_else(|| selected_model_state.0.lock().unwrap().clone());
+    if model_id.is_empty() || model_id == "none" {
+        return Err("No transcription model selected".to_string());
+    }
+    let tm = Arc::clone(transcription_manager.inner());
+    let start = Instant::now();
+    let warmed_id = model_id.clone();
+    tauri::async_runtime::spawn_blocking(move || tm.warmup(&warmed_id))
+        .await
+        .map_err(|e| format!("Task failed to join: {}", e))?
+        .map_err(|e| e.to_string())?;
+    let _ = app.emit(
+        "model-warmed",
+        ModelWarmedEvent {
+            model_id,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_transcription_model_loaded(
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<bool, String> {
+    Ok(transcription_manager.inner().is_model_loaded())
+}
+
+/// Language ("auto" or blank to detect) and translate-to-English for the
+/// next transcriptions, saved in settings. Only Whisper models use them.
+#[tauri::command]
+pub async fn set_transcription_options(
+    app: AppHandle,
+    language: Option<String>,
+    translate: bool,
+) -> Result<(), String> {
+    let options = TranscriptionOptions::new(language.as_deref(), translate)?;
+    let language = options.language.unwrap_or_else(|| "auto".to_string());
+    crate::settings::update_app_setting(&app, "transcription_language", language)
+        .and_then(|()| {
+            crate::settings::update_app_setting(&app, "transcription_translate", translate.to_string())
+        })
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -528,11 +1313,77 @@ pub async fn open_transcription_window(app: AppHandle, recording_path: String) -
     Ok(())
 }
 
+/// Latest transcription from every model that has transcribed the recording.
+/// The current result counts for its model, so recordings transcribed before
+/// runs were kept still have one.
+fn transcription_runs(
+    app: &AppHandle,
+    recording_path: &str,
+    tm: &TranscriptionManager,
+) -> Result<BTreeMap<String, StoredTranscription>, String> {
+    let mut runs = load_transcription_runs(app, recording_path).map_err(|e| e.to_string())?;
+    if let Some(current) = tm.load_result(app, recording_path).map_err(|e| e.to_string())? {
+        // The current result is the newest run of its model
+        if let Some(model_id) = current.model_id.clone() {
+            runs.insert(model_id, current);
+        }
+    }
+    Ok(runs)
+}
+
+/// Transcript words without speaker markers, for comparing runs.
+fn run_text(stored: &StoredTranscription) -> String {
+    if stored.segments.is_empty() {
+        return stored.text.clone();
+    }
+    let parts: Vec<(f64, f64, String)> = stored
+        .segments
+        .iter()
+        .map(|s| (s.start, s.end, s.text.clone()))
+        .collect();
+    plain_transcript_text(&parts)
+}
+
+/// Model ids the recording has a transcription from, for picking a diff.
+#[tauri::command]
+pub async fn get_transcription_runs(
+    app: AppHandle,
+    recording_path: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Vec<String>, String> {
+    Ok(transcription_runs(&app, &recording_path, &transcription_manager)?
+        .into_keys()
+        .collect())
+}
+
+/// Word-level diff from `model_a`'s transcript of the recording to
+/// `model_b`'s: equal spans, inserts (only in b) and deletes (only in a).
+#[tauri::command]
+pub async fn diff_transcripts(
+    app: AppHandle,
+    recording_path: String,
+    model_a: String,
+    model_b: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<Vec<DiffSpan>, String> {
+    let runs = transcription_runs(&app, &recording_path, &transcription_manager)?;
+    let text = |model_id: &str| {
+        runs.get(model_id)
+            .map(run_text)
+            .ok_or_else(|| format!("No transcription from {} for this recording", model_id))
+    };
+    Ok(transcript_diff::diff_words(&text(&model_a)?, &text(&model_b)?))
+}
+
 #[tauri::command]
 pub async fn has_transcription_result(
     app: AppHandle,
     recording_path: String,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
 ) -> Result<bool, String> {
+    if transcription_manager.unsaved_result(&recording_path).is_some() {
+        return Ok(true);
+    }
     let path = crate::managers::transcription::transcription_result_path(&app, &recording_path)
         .map_err(|e| e.to_string())?;
     Ok(path.exists())
@@ -561,6 +1412,7 @@ pub async fn cancel_transcription(
                 recording_path,
                 status: "cancelled".to_string(),
                 error: None,
+                stats: None,
             },
         );
     }
@@ -732,3 +1584,47 @@ async fn do_stream_chat(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(denoise: bool, transcribe: bool, diarize: bool) -> ProcessRecordingOptions {
+        ProcessRecordingOptions {
+            denoise,
+            transcribe,
+            diarize,
+            noise_model: None,
+        }
+    }
+
+    #[test]
+    fn process_stages_run_in_pipeline_order_and_skip_disabled_ones() {
+        use PipelineStage::{Denoise, Diarize, Transcribe};
+        assert_eq!(options(true, true, true).stages(), vec![Denoise, Transcribe, Diarize]);
+        assert_eq!(options(true, false, true).stages(), vec![Denoise, Diarize]);
+        assert_eq!(options(false, true, true).stages(), vec![Transcribe, Diarize]);
+        assert_eq!(options(false, true, false).stages(), vec![Transcribe]);
+        assert!(options(false, false, false).stages().is_empty());
+
+        // Missing fields in the command payload mean "off"
+        let parsed: ProcessRecordingOptions =
+            serde_json::from_str(r#"{"diarize": true, "denoise": true}"#).unwrap();
+        assert_eq!(parsed.stages(), vec![Denoise, Diarize]);
+    }
+
+    #[test]
+    fn process_denoises_with_rnnoise_while_monitoring_is_a_passthrough() {
+        let default = options(true, false, false);
+        assert_eq!(default.noise_model("dummy"), "rnnnoise");
+        assert_eq!(default.noise_model(""), "rnnnoise");
+        assert_eq!(default.noise_model("noisy"), "noisy");
+
+        // An explicit choice is kept, and validated as is
+        let explicit = ProcessRecordingOptions {
+            noise_model: Some("dummy".to_string()),
+            ..default
+        };
+        assert_eq!(explicit.noise_model("rnnnoise"), "dummy");
+    }
+}
@@ -0,0 +1,146 @@
+// Error type for the audio, monitoring and recording commands. It serializes
+// as `{ code, message }`: the frontend branches on the code and shows the
+// message.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CrispyError {
+    /// The machine has no microphone at all.
+    NoInputDevices(String),
+    /// A named device or audio host isn't there.
+    DeviceNotFound(String),
+    /// The OS or the app refused access (mic, capture, files outside the
+    /// recordings directory).
+    PermissionDenied(String),
+    /// Already doing it, e.g. a recording is in progress.
+    Busy(String),
+    /// Asked to stop or change something that isn't running.
+    NotRunning(String),
+    /// The recording or file named in the request doesn't exist.
+    NotFound(String),
+    /// Something with that name already exists.
+    AlreadyExists(String),
+    /// A parameter is out of range or malformed.
+    InvalidArgument(String),
+    /// A format, platform or feature that isn't supported.
+    Unsupported(String),
+    /// Anything else; the message says what.
+    Internal(String),
+}
+
+impl CrispyError {
+    /// Machine-readable kind, stable across message rewording.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoInputDevices(_) => crate::audio::NO_INPUT_DEVICES,
+            Self::DeviceNotFound(_) => "device_not_found",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::Busy(_) => "busy",
+            Self::NotRunning(_) => "not_running",
+            Self::NotFound(_) => "not_found",
+            Self::AlreadyExists(_) => "already_exists",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::Unsupported(_) => "unsupported",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NoInputDevices(m)
+            | Self::DeviceNotFound(m)
+            | Self::PermissionDenied(m)
+            | Self::Busy(m)
+            | Self::NotRunning(m)
+            | Self::NotFound(m)
+            | Self::AlreadyExists(m)
+            | Self::InvalidArgument(m)
+            | Self::Unsupported(m)
+            | Self::Internal(m) => m,
+        }
+    }
+}
+
+impl std::fmt::Display for CrispyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CrispyError {}
+
+impl Serialize for CrispyError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CrispyError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+/// Plain-string errors from helpers that don't classify their failures. The
+/// `no_input_devices:` prefix some of them use is kept as its own kind.
+impl From<String> for CrispyError {
+    fn from(message: String) -> Self {
+        if message.starts_with(crate::audio::NO_INPUT_DEVICES) {
+            Self::NoInputDevices(message)
+        } else {
+            Self::Internal(message)
+        }
+    }
+}
+
+impl From<&str> for CrispyError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::io::Error> for CrispyError {
+    fn from(e: std::io::Error) -> Self {
+        let message = e.to_string();
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Self::NotFound(message),
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied(message),
+            std::io::ErrorKind::AlreadyExists => Self::AlreadyExists(message),
+            _ => Self::Internal(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_variant_serializes_with_its_code() {
+        let cases = [
+            (CrispyError::NoInputDevices("m".into()), "no_input_devices"),
+            (CrispyError::DeviceNotFound("m".into()), "device_not_found"),
+            (CrispyError::PermissionDenied("m".into()), "permission_denied"),
+            (CrispyError::Busy("m".into()), "busy"),
+            (CrispyError::NotRunning("m".into()), "not_running"),
+            (CrispyError::NotFound("m".into()), "not_found"),
+            (CrispyError::AlreadyExists("m".into()), "already_exists"),
+            (CrispyError::InvalidArgument("m".into()), "invalid_argument"),
+            (CrispyError::Unsupported("m".into()), "unsupported"),
+            (CrispyError::Internal("m".into()), "internal"),
+        ];
+        for (error, code) in cases {
+            assert_eq!(
+                serde_json::to_value(&error).unwrap(),
+                serde_json::json!({ "code": code, "message": "m" })
+            );
+        }
+    }
+
+    #[test]
+    fn string_errors_keep_the_no_input_devices_kind() {
+        let error = CrispyError::from(format!("{}: No microphone found.", crate::audio::NO_INPUT_DEVICES));
+        assert_eq!(error.code(), "no_input_devices");
+        assert_eq!(CrispyError::from("Task failed").code(), "internal");
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        assert_eq!(CrispyError::from(io).code(), "permission_denied");
+    }
+}
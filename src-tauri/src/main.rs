@@ -4,6 +4,7 @@
 mod app_state;
 mod audio;
 mod commands;
+mod error;
 mod settings;
 mod managers;
 mod paths;
@@ -264,6 +265,16 @@ fn main() {
                 }
             }
 
+            // Resume monitoring if it was on at quit; device enumeration can be
+            // slow, so keep it off the setup path.
+            let resume_handle = app.handle().clone();
+            thread::spawn(move || commands::audio::resume_monitoring(&resume_handle));
+
+            // Re-link transcriptions still keyed by recording path to stable
+            // ids, before the recordings can be moved.
+            let ids_handle = app.handle().clone();
+            thread::spawn(move || commands::recording::assign_missing_recording_ids(&ids_handle));
+
             // On macOS we want a template icon so it adapts to light/dark menu bar.
             // On other platforms we use a solid black icon so it's always visible.
             #[cfg(target_os = "macos")]
@@ -371,31 +382,52 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::audio::get_platform,
+            audio::get_audio_hosts,
             audio::get_input_devices,
             audio::get_output_devices,
             audio::get_default_devices,
+            audio::get_device_capabilities,
             commands::audio::start_monitoring,
             commands::audio::stop_monitoring,
+            commands::audio::is_monitoring_active,
             commands::audio::set_monitoring_volume,
             commands::audio::set_monitoring_model,
+            commands::audio::set_monitoring_gate,
+            commands::audio::set_agc,
+            commands::audio::set_compressor,
+            commands::audio::set_channel_metering,
+            commands::audio::set_microphone_waveform,
+            commands::audio::set_noise_params,
+            commands::audio::run_mic_test,
+            commands::audio::get_monitoring_latency,
+            commands::audio::get_monitoring_input_rate,
             audio::get_system_input_volume,
             audio::set_system_input_volume,
             audio::get_blackhole_status,
+            commands::diagnostics::run_diagnostics,
             commands::recording::get_recordable_apps,
             commands::recording::start_recording,
             commands::recording::stop_recording,
             commands::recording::is_recording,
+            commands::session::start_session,
+            commands::session::stop_session,
             commands::recording::get_recordings_dir_path,
             commands::recording::open_recordings_dir,
             commands::recording::open_url,
             window::show_main_window_cmd,
             window::quit_app,
+            window::set_tray_popup_size,
             commands::recording::get_recordings,
             commands::recording::rename_recording,
+            commands::recording::denoise_recording,
             commands::recording::delete_recording,
+            commands::recording::cleanup_orphaned_sidecars,
+            commands::recording::seek_offset,
             commands::models::get_available_models,
             commands::ns_models::get_available_ns_models,
+            commands::ns_models::benchmark_ns_models,
             commands::models::get_model_info,
+            commands::models::get_recommended_models,
             commands::models::download_model,
             commands::models::delete_model,
             commands::models::set_active_model,
@@ -404,10 +436,29 @@ fn main() {
             commands::models::get_recommended_first_model,
             commands::transcription::start_transcription,
             commands::transcription::get_transcription_result,
+            commands::transcription::get_transcription_full,
+            commands::transcription::get_transcription_words,
+            commands::transcription::get_transcription_stats,
+            commands::transcription::get_transcription_subtitles,
+            commands::transcription::export_transcription,
+            commands::transcription::rediarize_transcription,
+            commands::transcription::start_diarization,
+            commands::transcription::process_recording,
+            commands::transcription::get_diarization,
+            commands::transcription::get_diarized_transcript,
+            commands::transcription::get_transcript,
+            commands::transcription::rename_speaker,
+            commands::transcription::enroll_speaker,
+            commands::transcription::set_speaker_for_range,
+            commands::transcription::warmup_transcription_model,
+            commands::transcription::is_transcription_model_loaded,
             commands::transcription::get_transcription_model,
+            commands::transcription::set_transcription_options,
             commands::transcription::get_transcription_state,
             commands::transcription::open_transcription_window,
             commands::transcription::has_transcription_result,
+            commands::transcription::get_transcription_runs,
+            commands::transcription::diff_transcripts,
             commands::settings::get_llm_settings,
             commands::settings::set_llm_settings,
             commands::transcription::stream_transcription_chat,
@@ -16,14 +16,235 @@ use ort::{
     session::Session,
     value::{Tensor, TensorRef},
 };
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SpeakerSegment {
     pub start: f64,
     pub end: f64,
     pub speaker: String,
+    /// How clearly this segment belongs to its speaker, in [0, 1]: 0 is a coin
+    /// flip between the two nearest speakers. None with a single speaker.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+/// A manual speaker label for a time range, applied over diarization output.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SpeakerOverride {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: String,
+}
+
+/// Persisted speaker timeline for a recording: the last diarization output
+/// plus manual overrides and speaker names, which are kept across re-diarization.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct StoredSpeakers {
+    #[serde(default)]
+    pub segments: Vec<SpeakerSegment>,
+    #[serde(default)]
+    pub overrides: Vec<SpeakerOverride>,
+    /// Custom names for diarization labels, e.g. "Speaker 1" -> "Alice".
+    #[serde(default)]
+    pub names: BTreeMap<String, String>,
+}
+
+impl StoredSpeakers {
+    /// Diarization output with speaker names and then the overrides applied,
+    /// in the order they were made.
+    pub fn resolved(&self) -> Vec<SpeakerSegment> {
+        let named: Vec<SpeakerSegment> = self
+            .segments
+            .iter()
+            .map(|seg| match self.names.get(&seg.speaker) {
+                Some(name) => SpeakerSegment { speaker: name.clone(), ..seg.clone() },
+                None => seg.clone(),
+            })
+            .collect();
+        apply_speaker_overrides(&named, &self.overrides)
+    }
+
+    /// Rename a speaker as it appears in `resolved()`. Diarization labels are
+    /// renamed through `names`, so a re-run picks the name up again; overrides
+    /// carrying the old label are rewritten in place.
+    pub fn rename(&mut self, old_label: &str, new_label: &str) -> Result<()> {
+        let new_label = new_label.trim();
+        if new_label.is_empty() {
+            bail!("Speaker name can't be empty");
+        }
+        let labels: Vec<String> = self.resolved().into_iter().map(|s| s.speaker).collect();
+        if !labels.iter().any(|l| l == old_label) {
+            bail!("No speaker named \"{}\"", old_label);
+        }
+        if new_label == old_label {
+            return Ok(());
+        }
+        if labels.iter().any(|l| l == new_label) {
+            bail!("A speaker named \"{}\" already exists", new_label);
+        }
+
+        for seg in &self.segments {
+            let shown = self.names.get(&seg.speaker).unwrap_or(&seg.speaker);
+            if shown == old_label {
+                let label = seg.speaker.clone();
+                self.names.insert(label, new_label.to_string());
+                break;
+            }
+        }
+        for o in &mut self.overrides {
+            if o.speaker == old_label {
+                o.speaker = new_label.to_string();
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn load_stored_speakers(path: &Path) -> Result<StoredSpeakers> {
+    if !path.exists() {
+        return Ok(StoredSpeakers::default());
+    }
+    let json = std::fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("speakers: {}", e))
+}
+
+pub fn save_stored_speakers(path: &Path, speakers: &StoredSpeakers) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(speakers)?)?;
+    Ok(())
+}
+
+/// Cut each override's range out of the timeline (splitting segments that
+/// straddle its edges) and put the override's speaker there. Later overrides win.
+pub fn apply_speaker_overrides(
+    segments: &[SpeakerSegment],
+    overrides: &[SpeakerOverride],
+) -> Vec<SpeakerSegment> {
+    let mut out = segments.to_vec();
+    for o in overrides {
+        let mut next = Vec::with_capacity(out.len() + 2);
+        for seg in out {
+            if seg.end <= o.start || seg.start >= o.end {
+                next.push(seg);
+                continue;
+            }
+            if seg.start < o.start {
+                next.push(SpeakerSegment {
+                    end: o.start,
+                    ..seg.clone()
+                });
+            }
+            if seg.end > o.end {
+                next.push(SpeakerSegment { start: o.end, ..seg });
+            }
+        }
+        next.push(SpeakerSegment {
+            start: o.start,
+            end: o.end,
+            speaker: o.speaker.clone(),
+            confidence: Some(1.0),
+        });
+        next.sort_by(|a, b| a.start.total_cmp(&b.start));
+        out = next;
+    }
+    out
+}
+
+/// Clusters whose centroid is within this cosine distance of an enrolled
+/// speaker take that speaker's name.
+pub const KNOWN_SPEAKER_MAX_DISTANCE: f32 = 0.4;
+
+/// An enrolled speaker's voice: the mean of its normalized CAM++ embeddings.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KnownSpeaker {
+    pub embedding: Vec<f32>,
+    /// How many embeddings went into the mean.
+    pub samples: usize,
+}
+
+/// Speakers enrolled by name, matched against clusters in every recording.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct KnownSpeakers {
+    #[serde(default)]
+    pub speakers: BTreeMap<String, KnownSpeaker>,
+}
+
+impl KnownSpeakers {
+    /// Add an embedding to a speaker's running mean, enrolling them if new.
+    pub fn enroll(&mut self, name: &str, embedding: &[f32]) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("Speaker name can't be empty");
+        }
+        let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if embedding.is_empty() || norm == 0.0 {
+            bail!("Speaker embedding is empty");
+        }
+        let unit: Vec<f32> = embedding.iter().map(|x| x / norm).collect();
+        match self.speakers.get_mut(name) {
+            Some(known) => {
+                if known.embedding.len() != unit.len() {
+                    bail!("Speaker \"{}\" was enrolled with a different embedding model", name);
+                }
+                let n = known.samples as f32;
+                for (mean, x) in known.embedding.iter_mut().zip(&unit) {
+                    *mean = (*mean * n + x) / (n + 1.0);
+                }
+                known.samples += 1;
+            }
+            None => {
+                self.speakers.insert(
+                    name.to_string(),
+                    KnownSpeaker { embedding: unit, samples: 1 },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The enrolled name for each cluster centroid, if one is within
+    /// `max_distance`. Closest pairs are matched first and each name is used
+    /// at most once.
+    pub fn match_clusters(&self, centroids: &[Vec<f32>], max_distance: f32) -> Vec<Option<String>> {
+        let mut pairs: Vec<(f32, usize, &str)> = Vec::new();
+        for (cluster, centroid) in centroids.iter().enumerate() {
+            for (name, known) in &self.speakers {
+                if known.embedding.len() != centroid.len() {
+                    continue;
+                }
+                let distance = cosine_distance(centroid, &known.embedding);
+                if distance <= max_distance {
+                    pairs.push((distance, cluster, name));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut names: Vec<Option<String>> = vec![None; centroids.len()];
+        let mut used: Vec<&str> = Vec::new();
+        for (_, cluster, name) in pairs {
+            if names[cluster].is_none() && !used.contains(&name) {
+                names[cluster] = Some(name.to_string());
+                used.push(name);
+            }
+        }
+        names
+    }
+}
+
+pub fn load_known_speakers(path: &Path) -> Result<KnownSpeakers> {
+    if !path.exists() {
+        return Ok(KnownSpeakers::default());
+    }
+    let json = std::fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("known speakers: {}", e))
+}
+
+pub fn save_known_speakers(path: &Path, speakers: &KnownSpeakers) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(speakers)?)?;
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
@@ -74,14 +295,158 @@ impl EmbeddingExtractor {
     }
 }
 
+/// Tunables for speech detection and speaker clustering.
+#[derive(Debug, Clone)]
+pub struct DiarizationConfig {
+    /// Upper bound for the automatic speaker-count estimate (not a hard target).
+    pub max_speakers: usize,
+    /// Speech separated by less than this many seconds is joined into one segment.
+    pub merge_gap: f64,
+    /// Median filter length over frame labels, in ms; speech/silence runs
+    /// shorter than about half of it are smoothed away. 0 disables it.
+    pub smoothing_ms: f64,
+    /// A frame is silence when the model's silence probability exceeds this.
+    pub silence_prob_threshold: f32,
+    /// Enrolled speakers; matching clusters are labeled with their names.
+    pub known_speakers: KnownSpeakers,
+    /// Most speech chunks clustered directly. NME-SC is cubic in the chunk
+    /// count, so above this, window averages are clustered instead and every
+    /// chunk takes the nearest resulting speaker.
+    pub max_cluster_chunks: usize,
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        Self {
+            max_speakers: 6,
+            merge_gap: 2.5,
+            smoothing_ms: 185.0,
+            silence_prob_threshold: 0.5,
+            known_speakers: KnownSpeakers::default(),
+            max_cluster_chunks: 1000,
+        }
+    }
+}
+
+impl DiarizationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.merge_gap.is_finite() || self.merge_gap < 0.0 {
+            bail!("merge_gap must be a non-negative number of seconds");
+        }
+        if !self.smoothing_ms.is_finite() || !(0.0..=2000.0).contains(&self.smoothing_ms) {
+            bail!("smoothing_ms must be between 0 and 2000");
+        }
+        if !(self.silence_prob_threshold > 0.0 && self.silence_prob_threshold < 1.0) {
+            bail!("silence_prob_threshold must be between 0 and 1 (exclusive)");
+        }
+        if self.max_cluster_chunks < 3 {
+            bail!("max_cluster_chunks must be at least 3");
+        }
+        Ok(())
+    }
+
+    /// `smoothing_ms` on the segmentation frame grid, rounded to an odd count
+    /// so the median window stays centred.
+    fn smoothing_frames(&self) -> usize {
+        let frame_ms = SEGMENTATION_FRAME_STEP as f64 / 16.0;
+        (self.smoothing_ms / frame_ms).round() as usize | 1
+    }
+}
+
+/// segmentation-3.0 frame grid: first frame centre and hop, in samples at 16 kHz.
+const SEGMENTATION_FRAME_START: usize = 721;
+const SEGMENTATION_FRAME_STEP: usize = 270;
+
+/// Majority vote over a centred window of `window_len` frames, removing
+/// speech/silence glitches shorter than half the window.
+fn median_smooth(labels: &[u8], window_len: usize) -> Vec<u8> {
+    let half = window_len / 2;
+    (0..labels.len())
+        .map(|i| {
+            let window = &labels[i.saturating_sub(half)..(i + half + 1).min(labels.len())];
+            let speech = window.iter().filter(|&&l| l == 1).count();
+            u8::from(speech > window.len() / 2)
+        })
+        .collect()
+}
+
+/// Turns per-window frame labels into speech ranges (sample indices), keeping
+/// state across window boundaries so speech spanning two windows stays whole.
+struct SpeechTracker {
+    total_samples: usize,
+    is_speech: bool,
+    speech_start: usize,
+    ranges: Vec<(usize, usize)>,
+}
+
+impl SpeechTracker {
+    fn new(total_samples: usize) -> Self {
+        Self {
+            total_samples,
+            is_speech: false,
+            speech_start: 0,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Feed the smoothed labels (1 = speech) of the window starting at `win_start`.
+    fn push_window(&mut self, win_start: usize, labels: &[u8]) {
+        for (i, &label) in labels.iter().enumerate() {
+            let is_speech = label == 1;
+            if is_speech == self.is_speech {
+                continue;
+            }
+            let sample_idx = win_start + SEGMENTATION_FRAME_START + i * SEGMENTATION_FRAME_STEP;
+            if is_speech {
+                // Snap to 0 if it's the very beginning of the file (first 100ms)
+                self.speech_start = if sample_idx < 1600 { 0 } else { sample_idx };
+            } else {
+                self.close(sample_idx);
+            }
+            self.is_speech = is_speech;
+        }
+    }
+
+    fn close(&mut self, end_idx: usize) {
+        let s_idx = self.speech_start.min(self.total_samples);
+        let e_idx = end_idx.min(self.total_samples);
+        if e_idx > s_idx {
+            self.ranges.push((s_idx, e_idx));
+        }
+    }
+
+    /// Close any trailing speech and return the ranges in order.
+    fn finish(mut self) -> Vec<(usize, usize)> {
+        if self.is_speech {
+            self.close(self.total_samples);
+        }
+        self.ranges.sort_by_key(|&(s, _)| s);
+        self.ranges
+    }
+}
+
+/// Join ranges separated by at most `merge_gap` samples (breaths, stutters).
+fn merge_speech_ranges(ranges: &[(usize, usize)], merge_gap: usize) -> Vec<(usize, usize)> {
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for &(start_idx, end_idx) in ranges {
+        match merged.last_mut() {
+            Some(last) if start_idx <= last.1 + merge_gap => last.1 = last.1.max(end_idx),
+            _ => merged.push((start_idx, end_idx)),
+        }
+    }
+    merged
+}
+
 /// Improved VAD segmentation via pyannote segmentation-3.0
-/// Cuts ONLY on silence, merging local speaker labels into continuous speech segments.
-fn pyannote_get_segments_fixed(
+/// Cuts ONLY on silence, merging local speaker labels into continuous speech
+/// ranges (sample indices), joined across gaps up to `config.merge_gap`.
+fn speech_ranges(
     samples: &[i16],
     sample_rate: u32,
-    segmentation_model_path: &std::path::Path,
-    merge_gap_seconds: f64,
-) -> Result<Vec<VadSegment>> {
+    session: &mut Session,
+    config: &DiarizationConfig,
+) -> Result<Vec<(usize, usize)>> {
+    config.validate()?;
     if sample_rate != 16_000 {
         bail!(
             "pyannote segmentation expects 16kHz mono. Got {} Hz.",
@@ -94,12 +459,6 @@ fn pyannote_get_segments_fixed(
 
     eprintln!("[diarization] starting advanced Powerset segmentation");
 
-    let mut session = Session::builder()
-        .context("ort: Session::builder failed")?
-        .commit_from_file(segmentation_model_path)?;
-
-    let frame_step: usize = 270;
-    let frame_start: usize = 721;
     let window_size: usize = (sample_rate as usize) * 10; // 10 seconds
 
     // Pad audio to a multiple of window_size, plus one extra window to catch trailing speech
@@ -107,17 +466,14 @@ fn pyannote_get_segments_fixed(
     padded.extend_from_slice(samples);
     let rem = padded.len() % window_size;
     if rem != 0 {
-        padded.extend(std::iter::repeat(0i16).take(window_size - rem));
+        padded.extend(std::iter::repeat_n(0i16, window_size - rem));
     }
-    padded.extend(std::iter::repeat(0i16).take(window_size));
+    padded.extend(std::iter::repeat_n(0i16, window_size));
 
-    let mut raw_segments: Vec<(usize, usize)> = Vec::new();
+    let smoothing_frames = config.smoothing_frames();
+    let mut tracker = SpeechTracker::new(samples.len());
     let mut win_start = 0usize;
 
-    // State maintained across overlapping/adjacent windows
-    let mut current_is_speech = false;
-    let mut current_speech_start_idx = 0usize;
-
     while win_start < padded.len() {
         let win_end = win_start + window_size;
         let window_i16 = &padded[win_start..win_end];
@@ -142,7 +498,6 @@ fn pyannote_get_segments_fixed(
         } else {
             view
         };
-        let _classes = frames.shape()[1];
         let mut local_labels = Vec::with_capacity(frames.shape()[0]);
 
         // 1. Decode Powerset - we only care if it's silence or speech
@@ -159,84 +514,51 @@ fn pyannote_get_segments_fixed(
             }
             p_sil /= sum_exp;
 
-            let label = if p_sil > 0.5 { 0u8 } else { 1u8 };
+            let label = if p_sil > config.silence_prob_threshold { 0u8 } else { 1u8 };
             local_labels.push(label);
         }
 
-        // 2. Median filter (~180ms) to remove micro-glitches
-        let window_len = 11;
-        let half = window_len / 2;
-        let mut smoothed = vec![0u8; local_labels.len()];
-        for i in 0..local_labels.len() {
-            let start = i.saturating_sub(half);
-            let end = (i + half + 1).min(local_labels.len());
-            let mut count_speech = 0;
-            let mut count_total = 0;
-            for &l in &local_labels[start..end] {
-                if l == 1 {
-                    count_speech += 1;
-                }
-                count_total += 1;
-            }
-            smoothed[i] = if count_speech > count_total / 2 {
-                1
-            } else {
-                0
-            };
-        }
+        // 2. Median filter to remove micro-glitches
+        let smoothed = median_smooth(&local_labels, smoothing_frames);
 
         // 3. Build contiguous speech boundaries (ignoring local speaker changes)
-        for (i, &label) in smoothed.iter().enumerate() {
-            let is_speech = label == 1;
-            if is_speech != current_is_speech {
-                let sample_idx = win_start + frame_start + i * frame_step;
-                if is_speech {
-                    // Start of speech
-                    // Snap to 0 if it's the very beginning of the file (first 100ms)
-                    current_speech_start_idx = if sample_idx < 1600 { 0 } else { sample_idx };
-                } else {
-                    // End of speech
-                    let end_idx = sample_idx;
-                    let s_idx = current_speech_start_idx.min(samples.len());
-                    let e_idx = end_idx.min(samples.len());
-                    if e_idx > s_idx {
-                        raw_segments.push((s_idx, e_idx));
-                    }
-                }
-                current_is_speech = is_speech;
-            }
-        }
+        tracker.push_window(win_start, &smoothed);
 
         win_start += window_size;
     }
 
-    // Close any trailing speech
-    if current_is_speech {
-        let e_idx = samples.len();
-        let s_idx = current_speech_start_idx.min(samples.len());
-        if e_idx > s_idx {
-            raw_segments.push((s_idx, e_idx));
-        }
-    }
-
     // 4. Merge close segments (e.g. breaths / stutters)
-    raw_segments.sort_by_key(|&(s, _)| s);
+    let merge_gap_samples = (sample_rate as f64 * config.merge_gap) as usize;
+    Ok(merge_speech_ranges(&tracker.finish(), merge_gap_samples))
+}
 
-    let mut merged_indices: Vec<(usize, usize)> = Vec::new();
-    let merge_gap_samples = (sample_rate as f64 * merge_gap_seconds) as usize;
-    let min_dur_samples = (sample_rate as f64 * 1.5) as usize; // Minimum valid duration: 1.5s
+/// Speech intervals (start, end) in seconds, for silence trimming, chapters or
+/// skip-silence playback. Same detection diarization runs on, without the
+/// minimum-duration filter diarization applies for its embeddings.
+pub fn detect_speech_segments(
+    samples: &[i16],
+    sample_rate: u32,
+    segmentation_model_path: &Path,
+    config: &DiarizationConfig,
+) -> Result<Vec<(f64, f64)>> {
+    let mut session = load_segmentation_model(segmentation_model_path)?;
+    let ranges = speech_ranges(samples, sample_rate, &mut session, config)?;
+    Ok(ranges
+        .into_iter()
+        .map(|(s, e)| (s as f64 / sample_rate as f64, e as f64 / sample_rate as f64))
+        .collect())
+}
 
-    for (start_idx, end_idx) in raw_segments {
-        if let Some(last) = merged_indices.last_mut() {
-            if start_idx <= last.1 + merge_gap_samples {
-                last.1 = last.1.max(end_idx);
-            } else {
-                merged_indices.push((start_idx, end_idx));
-            }
-        } else {
-            merged_indices.push((start_idx, end_idx));
-        }
-    }
+/// Speech segments for diarization: `speech_ranges` without the ones too short
+/// to give a reliable embedding.
+fn pyannote_get_segments_fixed(
+    samples: &[i16],
+    sample_rate: u32,
+    session: &mut Session,
+    config: &DiarizationConfig,
+) -> Result<Vec<VadSegment>> {
+    let merged_indices = speech_ranges(samples, sample_rate, session, config)?;
+    let min_dur_samples = (sample_rate as f64 * 1.5) as usize; // Minimum valid duration: 1.5s
 
     let mut out: Vec<VadSegment> = Vec::new();
     for (start_idx, end_idx) in &merged_indices {
@@ -271,25 +593,61 @@ fn pyannote_get_segments_fixed(
     Ok(out)
 }
 
-/// Run speaker diarization on 16 kHz mono i16 samples.
-/// Uses Agglomerative Hierarchical Clustering (AHC) instead of greedy online matching.
-pub fn run_diarization(
+/// Fail with the model's role and path instead of a bare ort error.
+fn check_model_file(path: &Path, what: &str) -> Result<()> {
+    if !path.exists() {
+        bail!("{} model not found at {}", what, path.display());
+    }
+    if !path.is_file() {
+        bail!("{} model path {} is not a file", what, path.display());
+    }
+    Ok(())
+}
+
+fn load_segmentation_model(path: &Path) -> Result<Session> {
+    check_model_file(path, "Segmentation")?;
+    Session::builder()
+        .context("ort: Session::builder failed")?
+        .commit_from_file(path)
+        .with_context(|| format!("Segmentation model at {} could not be loaded", path.display()))
+}
+
+/// Diarization with both ONNX models loaded once, so they are checked up front
+/// and reused across runs.
+pub struct DiarizationEngine {
+    segmentation: Session,
+    embedding: EmbeddingExtractor,
+}
+
+impl DiarizationEngine {
+    pub fn new(segmentation_path: &Path, embedding_path: &Path) -> Result<Self> {
+        let segmentation = load_segmentation_model(segmentation_path)?;
+        check_model_file(embedding_path, "Embedding")?;
+        let embedding = EmbeddingExtractor::new(embedding_path).with_context(|| {
+            format!("Embedding model at {} could not be loaded", embedding_path.display())
+        })?;
+        Ok(Self {
+            segmentation,
+            embedding,
+        })
+    }
+
+    /// Speaker segments for 16 kHz mono i16 samples.
+    pub fn run(&mut self, samples: &[i16], config: &DiarizationConfig) -> Result<Vec<SpeakerSegment>> {
+        config.validate()?;
+        diarize(samples, 16_000, &mut self.segmentation, &mut self.embedding, config)
+    }
+}
+
+fn diarize(
     samples_i16: &[i16],
     sample_rate: u32,
-    segmentation_model_path: &PathBuf,
-    embedding_model_path: &PathBuf,
-    max_speakers: usize,
-    threshold: f64,
-    merge_gap: f64,
+    segmentation: &mut Session,
+    extractor: &mut EmbeddingExtractor,
+    config: &DiarizationConfig,
 ) -> Result<Vec<SpeakerSegment>> {
-    if sample_rate != 16_000 {
-        bail!("Requires 16kHz mono.");
-    }
-
-    // Guard against misconfiguration: the Phase-3 force-merge loop does a double
-    // `clusters.remove()` per iteration and panics (index out of bounds) if it is
-    // ever asked to merge down to fewer than 1 cluster. Clamp to a sane minimum.
-    let max_speakers = max_speakers.max(1);
+    // nme_sc needs at least one cluster to search for
+    let max_speakers = config.max_speakers.max(1);
 
     let duration_secs = samples_i16.len() as f64 / sample_rate as f64;
     eprintln!(
@@ -299,15 +657,11 @@ pub fn run_diarization(
         duration_secs
     );
 
-    let segments =
-        pyannote_get_segments_fixed(samples_i16, sample_rate, segmentation_model_path, merge_gap)?;
+    let segments = pyannote_get_segments_fixed(samples_i16, sample_rate, segmentation, config)?;
     if segments.is_empty() {
         return Ok(Vec::new());
     }
 
-    let mut extractor = EmbeddingExtractor::new(embedding_model_path)
-        .map_err(|e| anyhow::anyhow!("Failed to load embedding model: {:?}", e))?;
-
     // Chunk long monologues into ~4 second parts.
     // This allows CAM++ to output sharp vectors, and independent clustering guarantees
     // dynamic resolution. If it's the same speaker, AHC merges them back seamlessly.
@@ -356,7 +710,6 @@ pub fn run_diarization(
     // (Park et al. 2019, arXiv:2003.02405). Replaces the old AHC — there is no distance
     // threshold, and max_speakers is only an upper bound on the eigengap search.
     let n = valid_embeddings.len();
-    let _ = threshold; // obsolete with spectral auto-count; kept for signature compatibility
     eprintln!(
         "[diarization] NME-SC over {} speech chunks (max_speakers <= {})",
         n, max_speakers
@@ -364,11 +717,7 @@ pub fn run_diarization(
 
     // Chronological speaker ID assignment (first to speak = "Speaker 1") happens below;
     // here we just get a raw label per chunk.
-    let segment_labels: Vec<usize> = if n <= 2 {
-        vec![0; n]
-    } else {
-        nme_sc(&valid_embeddings, max_speakers)
-    };
+    let segment_labels = cluster_chunks(&valid_embeddings, max_speakers, config.max_cluster_chunks);
 
     let mut appearance_order = Vec::new();
     for &lbl in &segment_labels {
@@ -377,16 +726,26 @@ pub fn run_diarization(
         }
     }
 
+    let confidences = segment_confidences(&valid_embeddings, &segment_labels);
+    let known_names = config.known_speakers.match_clusters(
+        &cluster_centroids(&valid_embeddings, &segment_labels),
+        KNOWN_SPEAKER_MAX_DISTANCE,
+    );
     let mut result: Vec<SpeakerSegment> = Vec::new();
     for (idx, segment) in valid_segments.into_iter().enumerate() {
-        let speaker_idx = appearance_order
-            .iter()
-            .position(|&x| x == segment_labels[idx])
-            .unwrap();
+        let label = segment_labels[idx];
+        let speaker = match &known_names[label] {
+            Some(name) => name.clone(),
+            None => {
+                let speaker_idx = appearance_order.iter().position(|&x| x == label).unwrap();
+                format!("Speaker {}", speaker_idx + 1)
+            }
+        };
         result.push(SpeakerSegment {
             start: segment.start,
             end: segment.end,
-            speaker: format!("Speaker {}", speaker_idx + 1),
+            speaker,
+            confidence: confidences[idx],
         });
     }
 
@@ -397,7 +756,7 @@ pub fn run_diarization(
     });
 
     // Final merge passes output identical contiguous blocks
-    let merged = merge_consecutive_segments(&result, merge_gap);
+    let merged = merge_consecutive_segments(&result, config.merge_gap);
 
     eprintln!(
         "[diarization] complete: {} clusters, {} merged text segments",
@@ -408,6 +767,45 @@ pub fn run_diarization(
     Ok(merged)
 }
 
+/// Run speaker diarization on 16 kHz mono i16 samples, loading both models
+/// for this one run. `threshold` is obsolete with spectral auto-count and kept
+/// for signature compatibility.
+#[allow(clippy::too_many_arguments)]
+pub fn run_diarization(
+    samples_i16: &[i16],
+    sample_rate: u32,
+    segmentation_model_path: &PathBuf,
+    embedding_model_path: &PathBuf,
+    max_speakers: usize,
+    threshold: f64,
+    merge_gap: f64,
+    known_speakers: KnownSpeakers,
+) -> Result<Vec<SpeakerSegment>> {
+    if sample_rate != 16_000 {
+        bail!("Requires 16kHz mono.");
+    }
+    let _ = threshold;
+    let config = DiarizationConfig {
+        max_speakers,
+        merge_gap,
+        known_speakers,
+        ..DiarizationConfig::default()
+    };
+    DiarizationEngine::new(segmentation_model_path, embedding_model_path)?.run(samples_i16, &config)
+}
+
+/// CAM++ embedding of a stretch of 16 kHz mono speech, for enrolling a speaker.
+pub fn speaker_embedding(embedding_model_path: &Path, samples_i16: &[i16]) -> Result<Vec<f32>> {
+    if samples_i16.len() < 16_000 * 3 / 2 {
+        bail!("Segment is too short to enroll a speaker (at least 1.5 s of speech is needed)");
+    }
+    check_model_file(embedding_model_path, "Embedding")?;
+    let mut extractor = EmbeddingExtractor::new(embedding_model_path).with_context(|| {
+        format!("Embedding model at {} could not be loaded", embedding_model_path.display())
+    })?;
+    extractor.compute(samples_i16)
+}
+
 /// Cosine similarity in [0, 1].
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     (1.0 - cosine_distance(a, b)).clamp(0.0, 1.0)
@@ -610,6 +1008,104 @@ fn nme_sc(embeddings: &[Vec<f32>], max_speakers: usize) -> Vec<usize> {
     kmeans(&spectral, k)
 }
 
+/// Averages of consecutive windows of `embeddings`, at most `max_points` of
+/// them, or None when there are few enough to cluster directly.
+fn window_representatives(embeddings: &[Vec<f32>], max_points: usize) -> Option<Vec<Vec<f32>>> {
+    if embeddings.len() <= max_points {
+        return None;
+    }
+    let window = embeddings.len().div_ceil(max_points.max(1));
+    let dim = embeddings.first().map_or(0, |e| e.len());
+    let reps = embeddings
+        .chunks(window)
+        .map(|chunk| {
+            let mut mean = vec![0.0f32; dim];
+            for emb in chunk {
+                // Unit length first so loud or long chunks don't dominate
+                let norm = emb.iter().map(|x| x * x).sum::<f32>().sqrt().max(1e-9);
+                for (m, x) in mean.iter_mut().zip(emb) {
+                    *m += x / norm;
+                }
+            }
+            mean
+        })
+        .collect();
+    Some(reps)
+}
+
+/// Raw speaker label per chunk. Above `max_chunks`, NME-SC runs on window
+/// averages and each chunk joins the nearest resulting cluster, which keeps
+/// memory and time bounded on multi-hour recordings.
+fn cluster_chunks(embeddings: &[Vec<f32>], max_speakers: usize, max_chunks: usize) -> Vec<usize> {
+    if embeddings.len() <= 2 {
+        return vec![0; embeddings.len()];
+    }
+    let Some(reps) = window_representatives(embeddings, max_chunks) else {
+        return nme_sc(embeddings, max_speakers);
+    };
+    eprintln!(
+        "[diarization] {} chunks exceed the limit of {}; clustering {} window averages",
+        embeddings.len(),
+        max_chunks,
+        reps.len()
+    );
+    let centroids = cluster_centroids(&reps, &nme_sc(&reps, max_speakers));
+    embeddings
+        .iter()
+        .map(|emb| {
+            centroids
+                .iter()
+                .map(|c| cosine_distance(emb, c))
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(label, _)| label)
+        })
+        .collect()
+}
+
+/// Confidence of assigning a point to cluster `assigned`, given its distance to
+/// every cluster: 1 - d(assigned) / d(next nearest), clamped to [0, 1].
+fn assignment_confidence(distances: &[f32], assigned: usize) -> Option<f32> {
+    let own = *distances.get(assigned)?;
+    let next = distances
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != assigned)
+        .map(|(_, &d)| d)
+        .fold(None, |min: Option<f32>, d| Some(min.map_or(d, |m| m.min(d))))?;
+    if next <= 0.0 {
+        return Some(0.0);
+    }
+    Some((1.0 - own / next).clamp(0.0, 1.0))
+}
+
+/// Per-cluster embedding sums, indexed by label. Only the direction matters
+/// for cosine distance, so there's no need to divide by the count.
+fn cluster_centroids(embeddings: &[Vec<f32>], labels: &[usize]) -> Vec<Vec<f32>> {
+    let clusters = labels.iter().max().map_or(0, |&m| m + 1);
+    let dim = embeddings.first().map_or(0, |e| e.len());
+    let mut centroids = vec![vec![0.0f32; dim]; clusters];
+    for (emb, &label) in embeddings.iter().zip(labels) {
+        for (c, x) in centroids[label].iter_mut().zip(emb) {
+            *c += x;
+        }
+    }
+    centroids
+}
+
+/// Per-chunk confidence from cosine distances to each cluster's mean embedding.
+fn segment_confidences(embeddings: &[Vec<f32>], labels: &[usize]) -> Vec<Option<f32>> {
+    let centroids = cluster_centroids(embeddings, labels);
+    embeddings
+        .iter()
+        .zip(labels)
+        .map(|(emb, &label)| {
+            let distances: Vec<f32> = centroids.iter().map(|c| cosine_distance(emb, c)).collect();
+            assignment_confidence(&distances, label)
+        })
+        .collect()
+}
+
 fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
     let mut dot = 0.0;
     let mut norm_a = 0.0;
@@ -637,6 +1133,11 @@ fn merge_consecutive_segments(segments: &[SpeakerSegment], merge_gap: f64) -> Ve
             let gap = (seg.start - last.end).max(0.0);
             if last.speaker == seg.speaker && gap <= merge_gap {
                 last.end = seg.end.max(last.end);
+                // A merged block is only as certain as its least certain part
+                last.confidence = match (last.confidence, seg.confidence) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
                 continue;
             }
         }
@@ -659,18 +1160,31 @@ pub fn format_diarized_text(
     speaker_segments: &[SpeakerSegment],
 ) -> String {
     if speaker_segments.is_empty() || text_segments.is_empty() {
-        return text_segments
-            .iter()
-            .map(|(_, _, t)| t.as_str())
-            .collect::<Vec<_>>()
-            .join(" ");
+        return crate::managers::transcription::plain_transcript_text(text_segments);
+    }
+
+    // Multi-word segments that span a speaker change are aligned word by word
+    let mut aligned: Vec<(f64, f64, String)> = Vec::with_capacity(text_segments.len());
+    for (start, end, text) in text_segments {
+        let words = split_into_timed_words(*start, *end, text);
+        let first_speaker = words
+            .first()
+            .map(|(s, e, _)| find_speaker_at_time((s + e) / 2.0, speaker_segments));
+        let spans_change = words.iter().skip(1).any(|(s, e, _)| {
+            Some(find_speaker_at_time((s + e) / 2.0, speaker_segments)) != first_speaker
+        });
+        if spans_change {
+            aligned.extend(words);
+        } else {
+            aligned.push((*start, *end, text.clone()));
+        }
     }
 
     let mut lines: Vec<String> = Vec::new();
     let mut current_speaker: Option<String> = None;
     let mut current_words: Vec<String> = Vec::new();
 
-    for (start, end, text) in text_segments {
+    for (start, end, text) in &aligned {
         let trimmed = text.trim();
         if trimmed.is_empty() {
             continue;
@@ -699,6 +1213,26 @@ pub fn format_diarized_text(
     lines.join("\n").trim().to_string()
 }
 
+/// Split a text segment into its words, timed in proportion to their length
+/// as a stand-in for how long each takes to say.
+fn split_into_timed_words(start: f64, end: f64, text: &str) -> Vec<(f64, f64, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let total: usize = words.iter().map(|w| w.chars().count()).sum();
+    if words.len() < 2 || total == 0 || end <= start {
+        return vec![(start, end, text.to_string())];
+    }
+    let per_char = (end - start) / total as f64;
+    let mut t = start;
+    words
+        .into_iter()
+        .map(|w| {
+            let word_start = t;
+            t += w.chars().count() as f64 * per_char;
+            (word_start, t, w.to_string())
+        })
+        .collect()
+}
+
 /// Find which speaker is active at a given time point.
 fn find_speaker_at_time(time: f64, segments: &[SpeakerSegment]) -> String {
     for seg in segments {
@@ -728,6 +1262,146 @@ mod tests {
     use super::*;
     use std::collections::HashSet;
 
+    // --- speech detection (frame labels -> intervals) ---
+
+    /// Frame labels for one 10s window: `runs` of (label, frames), zero-filled to
+    /// the window's frame count.
+    fn window_labels(runs: &[(u8, usize)]) -> Vec<u8> {
+        let mut labels: Vec<u8> = runs
+            .iter()
+            .flat_map(|&(label, n)| std::iter::repeat_n(label, n))
+            .collect();
+        labels.resize(589, 0);
+        labels
+    }
+
+    fn speech_intervals(windows: &[Vec<u8>], total_samples: usize, merge_gap: f64) -> Vec<(f64, f64)> {
+        let mut tracker = SpeechTracker::new(total_samples);
+        for (w, labels) in windows.iter().enumerate() {
+            let smoothed = median_smooth(labels, DiarizationConfig::default().smoothing_frames());
+            tracker.push_window(w * 160_000, &smoothed);
+        }
+        merge_speech_ranges(&tracker.finish(), (16_000.0 * merge_gap) as usize)
+            .into_iter()
+            .map(|(s, e)| (s as f64 / 16_000.0, e as f64 / 16_000.0))
+            .collect()
+    }
+
+    fn frame_time(window: usize, frame: usize) -> f64 {
+        (window * 160_000 + SEGMENTATION_FRAME_START + frame * SEGMENTATION_FRAME_STEP) as f64
+            / 16_000.0
+    }
+
+    #[test]
+    fn speech_pattern_yields_expected_intervals() {
+        // speech 100..300, a 3-frame glitch at 400, speech 450..500
+        let labels = window_labels(&[(0, 100), (1, 200), (0, 100), (1, 3), (0, 47), (1, 50)]);
+        let intervals = speech_intervals(&[labels], 160_000, 0.0);
+        assert_eq!(
+            intervals,
+            vec![(frame_time(0, 100), frame_time(0, 300)), (frame_time(0, 450), frame_time(0, 500))]
+        );
+
+        // A 20-frame pause (~0.34s) is bridged by a 0.5s merge gap
+        let labels = window_labels(&[(0, 100), (1, 200), (0, 20), (1, 80)]);
+        let intervals = speech_intervals(&[labels], 160_000, 0.5);
+        assert_eq!(intervals, vec![(frame_time(0, 100), frame_time(0, 400))]);
+    }
+
+    #[test]
+    fn speech_spanning_windows_stays_one_interval_and_is_clipped() {
+        // Speech from frame 500 of window 0 into window 1; the file ends at 12s
+        let first = window_labels(&[(0, 500), (1, 89)]);
+        let second = window_labels(&[(1, 200)]);
+        let intervals = speech_intervals(&[first, second], 192_000, 0.0);
+        assert_eq!(intervals, vec![(frame_time(0, 500), 12.0)]);
+    }
+
+    fn speech_runs(labels: &[u8]) -> usize {
+        labels.windows(2).filter(|w| w == &[0, 1]).count() + usize::from(labels.first() == Some(&1))
+    }
+
+    #[test]
+    fn larger_smoothing_window_removes_more_glitches() {
+        // Silence with speech glitches of 2, 4 and 6 frames, then steady speech
+        let labels = window_labels(&[
+            (0, 50),
+            (1, 2),
+            (0, 50),
+            (1, 4),
+            (0, 50),
+            (1, 6),
+            (0, 50),
+            (1, 200),
+        ]);
+        let default = DiarizationConfig::default();
+        assert_eq!(default.smoothing_frames(), 11);
+        let wide = DiarizationConfig {
+            smoothing_ms: 320.0,
+            ..DiarizationConfig::default()
+        };
+        assert_eq!(wide.smoothing_frames(), 19);
+        let off = DiarizationConfig {
+            smoothing_ms: 0.0,
+            ..DiarizationConfig::default()
+        };
+
+        assert_eq!(speech_runs(&median_smooth(&labels, off.smoothing_frames())), 4);
+        assert_eq!(speech_runs(&median_smooth(&labels, default.smoothing_frames())), 2);
+        assert_eq!(speech_runs(&median_smooth(&labels, wide.smoothing_frames())), 1);
+    }
+
+    #[test]
+    fn diarization_config_validation() {
+        assert!(DiarizationConfig::default().validate().is_ok());
+        for config in [
+            DiarizationConfig {
+                smoothing_ms: -1.0,
+                ..DiarizationConfig::default()
+            },
+            DiarizationConfig {
+                smoothing_ms: f64::NAN,
+                ..DiarizationConfig::default()
+            },
+            DiarizationConfig {
+                silence_prob_threshold: 0.0,
+                ..DiarizationConfig::default()
+            },
+            DiarizationConfig {
+                silence_prob_threshold: 1.0,
+                ..DiarizationConfig::default()
+            },
+            DiarizationConfig {
+                max_cluster_chunks: 2,
+                ..DiarizationConfig::default()
+            },
+        ] {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn missing_model_file_is_reported_with_its_path() {
+        let dir = std::env::temp_dir().join("crispy_test_diarization_models");
+        std::fs::create_dir_all(&dir).unwrap();
+        let segmentation = dir.join("segmentation-3.0.onnx");
+        let embedding = dir.join("wespeaker-cam.onnx");
+
+        let Err(err) = DiarizationEngine::new(&segmentation, &embedding) else {
+            panic!("engine created without model files");
+        };
+        let err = err.to_string();
+        assert!(err.starts_with("Segmentation model not found"), "{}", err);
+        assert!(err.contains("segmentation-3.0.onnx"), "{}", err);
+
+        let err = check_model_file(&embedding, "Embedding").unwrap_err().to_string();
+        assert!(err.starts_with("Embedding model not found"), "{}", err);
+        let err = check_model_file(&dir, "Embedding").unwrap_err().to_string();
+        assert!(err.contains("is not a file"), "{}", err);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     // --- nme_sc (automatic speaker-count spectral clustering) ---
 
     /// Synthetic embeddings: each cluster points along a distinct axis (so cross-cluster
@@ -781,8 +1455,101 @@ mod tests {
         assert!(distinct(&labels) <= 2, "labels={:?}", labels);
     }
 
+    #[test]
+    fn long_inputs_cluster_window_averages() {
+        // 600 chunks alternating between two speakers in turns of 30
+        let embeddings: Vec<Vec<f32>> = (0..600)
+            .map(|i| {
+                let mut v = vec![0.0f32; 6];
+                v[(i / 30) % 2] = 1.0;
+                v[5] = 0.001 * (i % 7) as f32;
+                v
+            })
+            .collect();
+
+        assert!(window_representatives(&embeddings, 1000).is_none());
+        let reps = window_representatives(&embeddings, 40).unwrap();
+        assert!(reps.len() <= 40, "{} representatives", reps.len());
+
+        // Every chunk gets a label, and no label is shared across speakers
+        let labels = cluster_chunks(&embeddings, 8, 40);
+        assert_eq!(labels.len(), embeddings.len());
+        let speaker_labels = |speaker: usize| -> HashSet<usize> {
+            (0..labels.len())
+                .filter(|i| (i / 30) % 2 == speaker)
+                .map(|i| labels[i])
+                .collect()
+        };
+        assert!(
+            speaker_labels(0).is_disjoint(&speaker_labels(1)),
+            "labels={:?}",
+            labels
+        );
+    }
+
+    #[test]
+    fn plain_and_diarized_transcripts_share_words() {
+        use crate::managers::transcription::plain_transcript_text;
+        let words = vec![
+            (0.0, 0.4, " Hello".to_string()),
+            (0.5, 0.9, "there.".to_string()),
+            (1.0, 1.2, " ".to_string()),
+            (2.0, 2.4, "General".to_string()),
+            (2.5, 2.9, " Kenobi.".to_string()),
+            (3.0, 3.4, "Indeed.".to_string()),
+        ];
+        let speakers = vec![
+            speaker(0.0, 1.5, "Speaker 1"),
+            speaker(1.5, 3.0, "Speaker 2"),
+            speaker(3.0, 4.0, "Speaker 1"),
+        ];
+        let plain = plain_transcript_text(&words);
+        let diarized = format_diarized_text(&words, &speakers);
+        assert_eq!(plain, "Hello there. General Kenobi. Indeed.");
+        assert_eq!(diarized.matches("[Speaker").count(), 3);
+
+        // Dropping the marker lines leaves exactly the plain words
+        let unmarked: Vec<&str> = diarized
+            .lines()
+            .filter(|line| !line.starts_with('['))
+            .flat_map(str::split_whitespace)
+            .collect();
+        assert_eq!(unmarked.join(" "), plain);
+    }
+
     // --- f32_to_i16 ---
 
+    #[test]
+    fn cluster_near_enrolled_speaker_adopts_name() {
+        let mut known = KnownSpeakers::default();
+        known.enroll("Alice", &[1.0, 0.1, 0.0]).unwrap();
+        known.enroll("Alice", &[1.0, -0.1, 0.0]).unwrap();
+        known.enroll("Bob", &[0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(known.speakers["Alice"].samples, 2);
+        assert!(known.enroll("  ", &[1.0, 0.0, 0.0]).is_err());
+
+        // Cluster 1 sits near Alice, cluster 0 is nobody we know
+        let embeddings = vec![
+            vec![0.0, 1.0, 0.0],
+            vec![0.1, 1.0, 0.0],
+            vec![2.0, 0.05, 0.0],
+            vec![1.9, -0.05, 0.1],
+        ];
+        let labels = vec![0, 0, 1, 1];
+        let names = known.match_clusters(
+            &cluster_centroids(&embeddings, &labels),
+            KNOWN_SPEAKER_MAX_DISTANCE,
+        );
+        assert_eq!(names, vec![None, Some("Alice".to_string())]);
+
+        // A name goes to the closest cluster only
+        let names = known.match_clusters(
+            &[vec![1.0, 0.2, 0.0], vec![1.0, 0.0, 0.0]],
+            KNOWN_SPEAKER_MAX_DISTANCE,
+        );
+        assert_eq!(names, vec![None, Some("Alice".to_string())]);
+    }
+
     #[test]
     fn f32_to_i16_silence() {
         assert_eq!(f32_to_i16(&[0.0, 0.0, 0.0]), vec![0, 0, 0]);
@@ -816,6 +1583,55 @@ mod tests {
 
     // --- cosine_distance ---
 
+    #[test]
+    fn assignment_confidence_from_distance_matrix() {
+        // Rows: one point's distances to clusters 0, 1, 2
+        let distances = [
+            [0.0f32, 0.8, 0.9], // on its centroid
+            [0.2, 0.4, 0.9],    // twice as close to its own cluster
+            [0.5, 0.5, 0.9],    // coin flip between 0 and 1
+            [0.6, 0.3, 0.9],    // nearer to another cluster than its own
+        ];
+        let confidence: Vec<f32> = distances
+            .iter()
+            .map(|row| assignment_confidence(row, 0).unwrap())
+            .collect();
+        assert_eq!(confidence[0], 1.0);
+        assert!((confidence[1] - 0.5).abs() < 1e-6);
+        assert_eq!(confidence[2], 0.0);
+        assert_eq!(confidence[3], 0.0);
+
+        assert_eq!(assignment_confidence(&[0.3], 0), None, "single cluster");
+        assert_eq!(assignment_confidence(&[0.3, 0.4], 5), None);
+    }
+
+    #[test]
+    fn segment_confidences_flag_ambiguous_points() {
+        let mut embeddings = cluster_emb(&[0, 1], 20, 6);
+        let mut labels: Vec<usize> = (0..40).map(|i| i / 20).collect();
+        // Halfway between both clusters, assigned to cluster 0
+        embeddings.push(vec![1.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        labels.push(0);
+        let confidence = segment_confidences(&embeddings, &labels);
+        assert!(confidence[..40].iter().all(|c| c.unwrap() > 0.8), "{:?}", confidence);
+        assert!(confidence[40].unwrap() < 0.2, "{:?}", confidence);
+
+        assert!(segment_confidences(&cluster_emb(&[0], 3, 4), &[0, 0, 0])
+            .iter()
+            .all(Option::is_none));
+    }
+
+    #[test]
+    fn merged_segment_keeps_lowest_confidence() {
+        let segments = vec![
+            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string(), confidence: Some(0.9) },
+            SpeakerSegment { start: 1.1, end: 2.0, speaker: "Speaker 1".to_string(), confidence: Some(0.3) },
+        ];
+        let merged = merge_consecutive_segments(&segments, 0.5);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].confidence, Some(0.3));
+    }
+
     #[test]
     fn cosine_distance_identical_vectors() {
         let v = vec![1.0, 2.0, 3.0];
@@ -851,8 +1667,8 @@ mod tests {
     #[test]
     fn merge_consecutive_same_speaker() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 1.1, end: 2.0, speaker: "Speaker 1".to_string() },
+            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 1.1, end: 2.0, speaker: "Speaker 1".to_string(), confidence: None },
         ];
         let merged = merge_consecutive_segments(&segments, 0.5);
         assert_eq!(merged.len(), 1);
@@ -863,8 +1679,8 @@ mod tests {
     #[test]
     fn merge_consecutive_different_speakers() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 1.1, end: 2.0, speaker: "Speaker 2".to_string() },
+            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 1.1, end: 2.0, speaker: "Speaker 2".to_string(), confidence: None },
         ];
         let merged = merge_consecutive_segments(&segments, 0.5);
         assert_eq!(merged.len(), 2);
@@ -873,8 +1689,8 @@ mod tests {
     #[test]
     fn merge_consecutive_gap_too_large() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 5.0, end: 6.0, speaker: "Speaker 1".to_string() },
+            SpeakerSegment { start: 0.0, end: 1.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 5.0, end: 6.0, speaker: "Speaker 1".to_string(), confidence: None },
         ];
         let merged = merge_consecutive_segments(&segments, 0.5);
         assert_eq!(merged.len(), 2, "Gap of 4s exceeds merge_gap of 0.5s");
@@ -891,8 +1707,8 @@ mod tests {
     #[test]
     fn find_speaker_exact_match() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 5.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 5.5, end: 10.0, speaker: "Speaker 2".to_string() },
+            SpeakerSegment { start: 0.0, end: 5.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 5.5, end: 10.0, speaker: "Speaker 2".to_string(), confidence: None },
         ];
         assert_eq!(find_speaker_at_time(2.5, &segments), "Speaker 1");
         assert_eq!(find_speaker_at_time(7.0, &segments), "Speaker 2");
@@ -901,7 +1717,7 @@ mod tests {
     #[test]
     fn find_speaker_boundary() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 5.0, speaker: "Speaker 1".to_string() },
+            SpeakerSegment { start: 0.0, end: 5.0, speaker: "Speaker 1".to_string(), confidence: None },
         ];
         // At exact boundary
         assert_eq!(find_speaker_at_time(0.0, &segments), "Speaker 1");
@@ -911,8 +1727,8 @@ mod tests {
     #[test]
     fn find_speaker_in_gap_picks_closest() {
         let segments = vec![
-            SpeakerSegment { start: 0.0, end: 3.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 7.0, end: 10.0, speaker: "Speaker 2".to_string() },
+            SpeakerSegment { start: 0.0, end: 3.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 7.0, end: 10.0, speaker: "Speaker 2".to_string(), confidence: None },
         ];
         // Time 4.0 is closer to Speaker 1 (ends at 3.0, dist=1.0) than Speaker 2 (starts at 7.0, dist=3.0)
         assert_eq!(find_speaker_at_time(4.0, &segments), "Speaker 1");
@@ -922,6 +1738,116 @@ mod tests {
 
     // --- format_diarized_text ---
 
+    fn speaker(start: f64, end: f64, name: &str) -> SpeakerSegment {
+        SpeakerSegment { start, end, speaker: name.to_string(), confidence: Some(0.4) }
+    }
+
+    #[test]
+    fn speaker_override_splits_straddling_segments() {
+        let segments = vec![speaker(0.0, 4.0, "Speaker 1"), speaker(4.0, 8.0, "Speaker 2")];
+        let overrides = vec![SpeakerOverride { start: 3.0, end: 5.0, speaker: "Alice".to_string() }];
+        let resolved = apply_speaker_overrides(&segments, &overrides);
+        let spans: Vec<(f64, f64, &str)> = resolved
+            .iter()
+            .map(|s| (s.start, s.end, s.speaker.as_str()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(0.0, 3.0, "Speaker 1"), (3.0, 5.0, "Alice"), (5.0, 8.0, "Speaker 2")]
+        );
+        assert_eq!(resolved[1].confidence, Some(1.0));
+        assert_eq!(resolved[0].confidence, Some(0.4));
+
+        // A later override over the same range wins
+        let mut overrides = overrides;
+        overrides.push(SpeakerOverride { start: 2.0, end: 6.0, speaker: "Bob".to_string() });
+        let resolved = apply_speaker_overrides(&segments, &overrides);
+        assert_eq!(resolved.len(), 3);
+        assert_eq!((resolved[1].start, resolved[1].end), (2.0, 6.0));
+        assert_eq!(resolved[1].speaker, "Bob");
+    }
+
+    #[test]
+    fn rerender_reflects_speaker_override() {
+        let text = vec![
+            (0.0, 1.0, "Hello".to_string()),
+            (1.0, 2.0, "there".to_string()),
+            (2.5, 3.5, "Hi".to_string()),
+        ];
+        let stored = StoredSpeakers {
+            segments: vec![speaker(0.0, 4.0, "Speaker 1")],
+            overrides: vec![SpeakerOverride { start: 2.2, end: 4.0, speaker: "Speaker 2".to_string() }],
+            ..Default::default()
+        };
+        assert_eq!(format_diarized_text(&text, &stored.segments), "[Speaker 1|0.0]\nHello there Hi");
+        assert_eq!(
+            format_diarized_text(&text, &stored.resolved()),
+            "[Speaker 1|0.0]\nHello there\n\n[Speaker 2|2.5]\nHi"
+        );
+
+        // Overrides survive a round trip through the sidecar format
+        let json = serde_json::to_string(&stored).unwrap();
+        let loaded: StoredSpeakers = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.overrides, stored.overrides);
+    }
+
+    #[test]
+    fn renamed_speakers_survive_rediarization() {
+        let text = vec![(0.5, 1.0, "Hello".to_string()), (2.5, 3.0, "Hi".to_string())];
+        let mut stored = StoredSpeakers {
+            segments: vec![speaker(0.0, 2.0, "Speaker 1"), speaker(2.0, 4.0, "Speaker 2")],
+            ..Default::default()
+        };
+        stored.rename("Speaker 1", "Alice").unwrap();
+        assert_eq!(
+            format_diarized_text(&text, &stored.resolved()),
+            "[Alice|0.5]\nHello\n\n[Speaker 2|2.5]\nHi"
+        );
+
+        // Empty, colliding and unknown names are rejected
+        assert!(stored.rename("Speaker 2", "  ").is_err());
+        assert!(stored.rename("Speaker 2", "Alice").is_err());
+        assert!(stored.rename("Speaker 9", "Bob").is_err());
+
+        // Renaming a renamed speaker updates the same label
+        stored.rename("Alice", "Alicia").unwrap();
+        assert_eq!(stored.names.get("Speaker 1").map(String::as_str), Some("Alicia"));
+
+        // A re-run replaces the segments; the name follows the label
+        stored.segments = vec![speaker(0.0, 1.5, "Speaker 1"), speaker(1.5, 4.0, "Speaker 2")];
+        let speakers: Vec<String> = stored.resolved().into_iter().map(|s| s.speaker).collect();
+        assert_eq!(speakers, vec!["Alicia", "Speaker 2"]);
+    }
+
+    #[test]
+    fn stored_speakers_save_and_load() {
+        let dir = std::env::temp_dir().join("crispy_test_diarization_store");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rec.diarization.json");
+
+        // Nothing saved yet loads as an empty timeline
+        std::fs::remove_file(&path).ok();
+        assert!(load_stored_speakers(&path).unwrap().segments.is_empty());
+
+        let stored = StoredSpeakers {
+            segments: vec![speaker(0.0, 2.5, "Speaker 1"), speaker(2.5, 6.0, "Speaker 2")],
+            ..Default::default()
+        };
+        save_stored_speakers(&path, &stored).unwrap();
+        let loaded = load_stored_speakers(&path).unwrap();
+        let spans: Vec<(f64, f64, &str, Option<f32>)> = loaded
+            .segments
+            .iter()
+            .map(|s| (s.start, s.end, s.speaker.as_str(), s.confidence))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(0.0, 2.5, "Speaker 1", Some(0.4)), (2.5, 6.0, "Speaker 2", Some(0.4))]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn format_diarized_text_no_speakers() {
         let text = vec![
@@ -946,8 +1872,8 @@ mod tests {
             (5.0, 5.5, "Hi".to_string()),
         ];
         let speakers = vec![
-            SpeakerSegment { start: 0.0, end: 2.0, speaker: "Speaker 1".to_string() },
-            SpeakerSegment { start: 4.0, end: 7.0, speaker: "Speaker 2".to_string() },
+            SpeakerSegment { start: 0.0, end: 2.0, speaker: "Speaker 1".to_string(), confidence: None },
+            SpeakerSegment { start: 4.0, end: 7.0, speaker: "Speaker 2".to_string(), confidence: None },
         ];
         let result = format_diarized_text(&text, &speakers);
         assert!(result.contains("[Speaker 1|"), "Should have Speaker 1 header");
@@ -956,6 +1882,43 @@ mod tests {
         assert!(result.contains("Hi"), "Should contain Hi");
     }
 
+    #[test]
+    fn format_diarized_text_groups_interleaved_words_by_speaker() {
+        let speakers = vec![
+            speaker(0.0, 2.0, "Speaker 1"),
+            speaker(2.0, 4.0, "Speaker 2"),
+            speaker(5.0, 7.0, "Speaker 1"),
+        ];
+        let words = vec![
+            (0.1, 0.5, "Hi".to_string()),
+            (0.6, 1.0, "Bob".to_string()),
+            (2.2, 2.6, "Hey".to_string()),
+            (2.7, 3.1, "Alice".to_string()),
+            // In the gap, nearer the second speaker's end than the next turn
+            (4.1, 4.3, "so".to_string()),
+            (5.2, 5.6, "Right".to_string()),
+        ];
+        assert_eq!(
+            format_diarized_text(&words, &speakers),
+            "[Speaker 1|0.1]\nHi Bob\n\n[Speaker 2|2.2]\nHey Alice so\n\n[Speaker 1|5.2]\nRight"
+        );
+    }
+
+    #[test]
+    fn format_diarized_text_splits_segment_at_speaker_change() {
+        let speakers = vec![speaker(0.0, 2.0, "Speaker 1"), speaker(2.0, 4.0, "Speaker 2")];
+        // One Whisper-style segment across the change; by midpoint it would all go to Speaker 2
+        let text = vec![(0.0, 4.0, "one two three four".to_string())];
+        assert_eq!(
+            format_diarized_text(&text, &speakers),
+            "[Speaker 1|0.0]\none two\n\n[Speaker 2|1.6]\nthree four"
+        );
+
+        // Segments within one speaker stay whole
+        let text = vec![(0.2, 1.8, "all mine".to_string())];
+        assert_eq!(format_diarized_text(&text, &speakers), "[Speaker 1|0.2]\nall mine");
+    }
+
     #[test]
     fn format_diarized_text_skips_empty_words() {
         let text = vec![
@@ -964,7 +1927,7 @@ mod tests {
             (1.0, 1.5, "world".to_string()),
         ];
         let speakers = vec![
-            SpeakerSegment { start: 0.0, end: 2.0, speaker: "Speaker 1".to_string() },
+            SpeakerSegment { start: 0.0, end: 2.0, speaker: "Speaker 1".to_string(), confidence: None },
         ];
         let result = format_diarized_text(&text, &speakers);
         assert!(result.contains("Hello"));
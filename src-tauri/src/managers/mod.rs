@@ -1,4 +1,6 @@
 #[cfg(feature = "diarization")]
 pub mod diarization;
 pub mod model;
+pub mod subtitles;
+pub mod transcript_diff;
 pub mod transcription;
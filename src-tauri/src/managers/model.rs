@@ -4,12 +4,12 @@ use anyhow::Result;
 use flate2::read::GzDecoder;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tar::Archive;
 use tauri::{AppHandle, Emitter, Manager};
@@ -40,6 +40,32 @@ pub struct ModelInfo {
     pub engine_type: EngineType,
     pub accuracy_score: f32,
     pub speed_score: f32,
+    /// Decode settings this model works best with; None uses the engine's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_inference_params: Option<InferenceParams>,
+}
+
+/// Per-model decode presets from the catalog, used while the user hasn't set
+/// language or translate through `set_transcription_options`. transcribe-rs
+/// 0.3 exposes only language and task through `SpeechModel`, so there is no
+/// beam size or temperature to preset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InferenceParams {
+    /// ISO 639-1 code, e.g. "en" for a model that only knows English.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate: Option<bool>,
+}
+
+impl InferenceParams {
+    /// Preset for a model that only transcribes `language`.
+    fn only_language(language: &str) -> Option<Self> {
+        Some(Self {
+            language: Some(language.to_string()),
+            translate: None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,11 +76,140 @@ pub struct DownloadProgress {
     pub percentage: f64,
 }
 
+/// Sent while a model archive unpacks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionProgress {
+    pub model_id: String,
+    /// Sum of the sizes of the entries unpacked so far.
+    pub extracted: u64,
+    /// Share of the compressed archive read so far.
+    pub percentage: f64,
+}
+
+/// Unpacked bytes between `model-extraction-progress` events.
+const EXTRACTION_PROGRESS_STEP: u64 = 8 * 1024 * 1024;
+
+/// Running total of unpacked entry sizes, deciding when the next progress
+/// event is due.
+#[derive(Default)]
+struct ExtractionCounter {
+    extracted: u64,
+    reported: u64,
+}
+
+impl ExtractionCounter {
+    /// Count an unpacked entry; true when at least a step has accumulated
+    /// since the last report.
+    fn add(&mut self, size: u64) -> bool {
+        self.extracted += size;
+        if self.extracted - self.reported >= EXTRACTION_PROGRESS_STEP {
+            self.reported = self.extracted;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Counts the bytes read through it, so extraction knows how far into the
+/// compressed archive it is.
+struct CountingReader<R> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Flags a failed read of the decompressed stream: tar hands such failures on
+/// from `unpack_in` as plain io errors, next to those of writing the files.
+struct StreamErrorFlag<'a, R> {
+    inner: R,
+    failed: &'a AtomicBool,
+}
+
+impl<R: Read> Read for StreamErrorFlag<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf).inspect_err(|_| self.failed.store(true, Ordering::Relaxed))
+    }
+}
+
+/// Why a model archive couldn't be unpacked.
+#[derive(Debug)]
+enum UnpackError {
+    /// The gzip or tar data is damaged; the archive has to be downloaded again.
+    InvalidArchive(std::io::Error),
+    /// Reading the archive file or writing the files failed (e.g. disk full).
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for UnpackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidArchive(e) => write!(f, "invalid archive: {}", e),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UnpackError {}
+
+/// Unpack a `.tar.gz` into `dest`. Every few MB unpacked, `on_progress` gets
+/// the bytes unpacked so far and the share of the archive read.
+fn unpack_tar_gz(
+    archive_path: &Path,
+    dest: &Path,
+    mut on_progress: impl FnMut(u64, f64),
+) -> std::result::Result<(), UnpackError> {
+    let tar_gz = File::open(archive_path).map_err(UnpackError::Io)?;
+    let archive_size = tar_gz.metadata().map_err(UnpackError::Io)?.len();
+    let archive_read = Arc::new(AtomicU64::new(0));
+    let stream_failed = AtomicBool::new(false);
+    let tar = StreamErrorFlag {
+        inner: GzDecoder::new(CountingReader {
+            inner: tar_gz,
+            read: archive_read.clone(),
+        }),
+        failed: &stream_failed,
+    };
+    let mut archive = Archive::new(tar);
+    let mut counter = ExtractionCounter::default();
+    for entry in archive.entries().map_err(UnpackError::InvalidArchive)? {
+        let mut entry = entry.map_err(UnpackError::InvalidArchive)?;
+        let size = entry.header().size().map_err(UnpackError::InvalidArchive)?;
+        if let Err(e) = entry.unpack_in(dest) {
+            return Err(if stream_failed.load(Ordering::Relaxed) {
+                UnpackError::InvalidArchive(e)
+            } else {
+                UnpackError::Io(e)
+            });
+        }
+        if counter.add(size) {
+            let read = archive_read.load(Ordering::Relaxed);
+            let percentage = if archive_size > 0 {
+                (read as f64 / archive_size as f64 * 100.0).min(100.0)
+            } else {
+                0.0
+            };
+            on_progress(counter.extracted, percentage);
+        }
+    }
+    Ok(())
+}
+
 pub struct ModelManager {
     app_handle: AppHandle,
     models_dir: PathBuf,
     available_models: Mutex<HashMap<String, ModelInfo>>,
     download_cancels: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    /// Models whose files exist but failed to load; shown as not downloaded
+    /// until they are deleted or downloaded again.
+    corrupt_models: Mutex<HashSet<String>>,
 }
 
 impl ModelManager {
@@ -87,6 +242,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.60,
                 speed_score: 0.85,
+                default_inference_params: None,
             },
         );
 
@@ -106,6 +262,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.75,
                 speed_score: 0.60,
+                default_inference_params: None,
             },
         );
 
@@ -125,6 +282,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.80,
                 speed_score: 0.40,
+                default_inference_params: None,
             },
         );
 
@@ -144,6 +302,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.85,
                 speed_score: 0.30,
+                default_inference_params: None,
             },
         );
 
@@ -163,6 +322,7 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.85,
                 speed_score: 0.85,
+                default_inference_params: InferenceParams::only_language("en"),
             },
         );
 
@@ -182,6 +342,7 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.80,
                 speed_score: 0.85,
+                default_inference_params: None,
             },
         );
 
@@ -201,6 +362,7 @@ impl ModelManager {
                 engine_type: EngineType::Moonshine,
                 accuracy_score: 0.70,
                 speed_score: 0.90,
+                default_inference_params: InferenceParams::only_language("en"),
             },
         );
 
@@ -225,6 +387,7 @@ impl ModelManager {
                 engine_type: EngineType::GigaAM,
                 accuracy_score: 0.85,
                 speed_score: 0.75,
+                default_inference_params: InferenceParams::only_language("ru"),
             },
         );
 
@@ -244,6 +407,7 @@ impl ModelManager {
                 engine_type: EngineType::SenseVoice,
                 accuracy_score: 0.65,
                 speed_score: 0.95,
+                default_inference_params: None,
             },
         );
 
@@ -264,6 +428,7 @@ impl ModelManager {
                 engine_type: EngineType::Canary,
                 accuracy_score: 0.75,
                 speed_score: 0.85,
+                default_inference_params: None,
             },
         );
 
@@ -284,6 +449,7 @@ impl ModelManager {
                 engine_type: EngineType::Canary,
                 accuracy_score: 0.85,
                 speed_score: 0.70,
+                default_inference_params: None,
             },
         );
 
@@ -303,6 +469,7 @@ impl ModelManager {
                 engine_type: EngineType::Cohere,
                 accuracy_score: 0.90,
                 speed_score: 0.60,
+                default_inference_params: None,
             },
         );
 
@@ -323,6 +490,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper, // placeholder, not used for inference
                 accuracy_score: 0.0,
                 speed_score: 0.0,
+                default_inference_params: None,
             },
         );
 
@@ -342,6 +510,7 @@ impl ModelManager {
                 engine_type: EngineType::Whisper, // placeholder, not used for inference
                 accuracy_score: 0.0,
                 speed_score: 0.0,
+                default_inference_params: None,
             },
         );
 
@@ -350,6 +519,7 @@ impl ModelManager {
             models_dir,
             available_models: Mutex::new(available_models),
             download_cancels: Mutex::new(HashMap::new()),
+            corrupt_models: Mutex::new(HashSet::new()),
         };
 
         manager.migrate_bundled_models()?;
@@ -387,7 +557,34 @@ impl ModelManager {
         Ok(())
     }
 
-    fn update_download_status(&self) -> Result<()> {
+    /// Flag a model whose files failed to load so the UI offers to download it
+    /// again; the next download replaces the files instead of keeping them.
+    pub fn mark_needs_redownload(&self, model_id: &str) {
+        self.corrupt_models
+            .lock()
+            .unwrap()
+            .insert(model_id.to_string());
+        if let Some(model) = self.available_models.lock().unwrap().get_mut(model_id) {
+            model.is_downloaded = false;
+        }
+    }
+
+    fn needs_redownload(&self, model_id: &str) -> bool {
+        self.corrupt_models.lock().unwrap().contains(model_id)
+    }
+
+    /// Re-read download state from disk. Downloads still in flight keep their
+    /// `is_downloading` flag and their `.extracting` directory.
+    pub fn update_download_status(&self) -> Result<()> {
+        let corrupt = self.corrupt_models.lock().unwrap().clone();
+        let active: HashSet<String> = self
+            .download_cancels
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, cancelled)| !cancelled.load(Ordering::SeqCst))
+            .map(|(id, _)| id.clone())
+            .collect();
         let mut models = self.available_models.lock().unwrap();
         for model in models.values_mut() {
             if model.is_directory {
@@ -398,11 +595,12 @@ impl ModelManager {
                 let extracting_path = self
                     .models_dir
                     .join(format!("{}.extracting", &model.filename));
-                if extracting_path.exists() {
+                if extracting_path.exists() && !active.contains(&model.id) {
                     let _ = fs::remove_dir_all(&extracting_path);
                 }
-                model.is_downloaded = model_path.exists() && model_path.is_dir();
-                model.is_downloading = false;
+                model.is_downloaded =
+                    model_path.exists() && model_path.is_dir() && !corrupt.contains(&model.id);
+                model.is_downloading = active.contains(&model.id);
                 model.partial_size = partial_path
                     .exists()
                     .then(|| partial_path.metadata().map(|m| m.len()).unwrap_or(0))
@@ -412,8 +610,8 @@ impl ModelManager {
                 let partial_path = self
                     .models_dir
                     .join(format!("{}.partial", &model.filename));
-                model.is_downloaded = model_path.exists();
-                model.is_downloading = false;
+                model.is_downloaded = model_path.exists() && !corrupt.contains(&model.id);
+                model.is_downloading = active.contains(&model.id);
                 model.partial_size = partial_path
                     .exists()
                     .then(|| partial_path.metadata().map(|m| m.len()).unwrap_or(0))
@@ -451,7 +649,7 @@ impl ModelManager {
             .models_dir
             .join(format!("{}.partial", &model_info.filename));
 
-        if model_path.exists() {
+        if model_path.exists() && !self.needs_redownload(model_id) {
             if partial_path.exists() {
                 let _ = fs::remove_file(&partial_path);
             }
@@ -459,6 +657,19 @@ impl ModelManager {
             return Ok(());
         }
 
+        // A directory model's archive may be fully downloaded already (the app
+        // quit during extraction); a damaged one can't be resumed.
+        let mut archive_complete = false;
+        if model_info.is_directory && partial_path.exists() {
+            match check_gzip_in_background(&partial_path).await {
+                GzipCheck::Complete => archive_complete = true,
+                GzipCheck::Truncated => {}
+                GzipCheck::Corrupt => {
+                    let _ = fs::remove_file(&partial_path);
+                }
+            }
+        }
+
         let mut resume_from = if partial_path.exists() {
             partial_path.metadata()?.len()
         } else {
@@ -488,6 +699,12 @@ impl ModelManager {
             }
         }
 
+        if archive_complete {
+            self.extract_model_archive(model_id, &model_info.filename, &partial_path)?;
+            self.finish_download(model_id);
+            return Ok(());
+        }
+
         let client = reqwest::Client::new();
         let mut request = client.get(&url);
         if resume_from > 0 {
@@ -617,66 +834,97 @@ impl ModelManager {
                 self.update_download_status()?;
                 return Ok(());
             }
-            let _ = self.app_handle.emit("model-extraction-started", model_id);
-            let temp_extract_dir = self
-                .models_dir
-                .join(format!("{}.extracting", &model_info.filename));
-            let final_model_dir = self.models_dir.join(&model_info.filename);
-
-            // Run extraction in a fallible closure so that ANY failure (open, create,
-            // unpack, read_dir, rename) emits `model-extraction-failed` and resets the
-            // downloading flag. Previously a failure left the frontend stuck in the
-            // "extracting" state forever because no terminal event was ever emitted.
-            let extract = || -> Result<()> {
-                if temp_extract_dir.exists() {
-                    let _ = fs::remove_dir_all(&temp_extract_dir);
-                }
-                fs::create_dir_all(&temp_extract_dir)?;
-                let tar_gz = File::open(&partial_path)?;
-                let tar = GzDecoder::new(tar_gz);
-                let mut archive = Archive::new(tar);
-                archive.unpack(&temp_extract_dir)?;
-                let extracted_dirs: Vec<_> = fs::read_dir(&temp_extract_dir)?
-                    .filter_map(|e| e.ok())
-                    .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
-                    .collect();
-                if extracted_dirs.len() == 1 {
-                    let source_dir = extracted_dirs[0].path();
-                    if final_model_dir.exists() {
-                        fs::remove_dir_all(&final_model_dir)?;
-                    }
-                    fs::rename(&source_dir, &final_model_dir)?;
-                    let _ = fs::remove_dir_all(&temp_extract_dir);
-                } else {
-                    if final_model_dir.exists() {
-                        fs::remove_dir_all(&final_model_dir)?;
-                    }
-                    fs::rename(&temp_extract_dir, &final_model_dir)?;
+            if check_gzip_in_background(&partial_path).await != GzipCheck::Complete {
+                let _ = fs::remove_file(&partial_path);
+                let mut models = self.available_models.lock().unwrap();
+                if let Some(model) = models.get_mut(model_id) {
+                    model.is_downloading = false;
                 }
-                Ok(())
-            };
+                return Err(anyhow::anyhow!("Downloaded archive is corrupt"));
+            }
+            self.extract_model_archive(model_id, &model_info.filename, &partial_path)?;
+        } else {
+            fs::rename(&partial_path, &model_path)?;
+        }
+
+        self.finish_download(model_id);
+        Ok(())
+    }
+
+    /// Unpack a downloaded `.tar.gz` into the model's directory and remove the
+    /// archive. Emits the extraction events, with `model-extraction-progress`
+    /// every few MB unpacked.
+    fn extract_model_archive(&self, model_id: &str, filename: &str, partial_path: &Path) -> Result<()> {
+        let _ = self.app_handle.emit("model-extraction-started", model_id);
+        let temp_extract_dir = self.models_dir.join(format!("{}.extracting", filename));
+        let final_model_dir = self.models_dir.join(filename);
 
-            if let Err(e) = extract() {
+        // Run extraction in a fallible closure so that ANY failure (open, create,
+        // unpack, read_dir, rename) emits `model-extraction-failed` and resets the
+        // downloading flag. Previously a failure left the frontend stuck in the
+        // "extracting" state forever because no terminal event was ever emitted.
+        let extract = || -> Result<()> {
+            if temp_extract_dir.exists() {
                 let _ = fs::remove_dir_all(&temp_extract_dir);
-                {
-                    let mut models = self.available_models.lock().unwrap();
-                    if let Some(model) = models.get_mut(model_id) {
-                        model.is_downloading = false;
-                    }
-                }
+            }
+            fs::create_dir_all(&temp_extract_dir)?;
+            unpack_tar_gz(partial_path, &temp_extract_dir, |extracted, percentage| {
                 let _ = self.app_handle.emit(
-                    "model-extraction-failed",
-                    serde_json::json!({ "model_id": model_id, "error": e.to_string() }),
+                    "model-extraction-progress",
+                    &ExtractionProgress {
+                        model_id: model_id.to_string(),
+                        extracted,
+                        percentage,
+                    },
                 );
-                return Err(anyhow::anyhow!("Failed to extract archive: {}", e));
+            })?;
+            let extracted_dirs: Vec<_> = fs::read_dir(&temp_extract_dir)?
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+                .collect();
+            if extracted_dirs.len() == 1 {
+                let source_dir = extracted_dirs[0].path();
+                if final_model_dir.exists() {
+                    fs::remove_dir_all(&final_model_dir)?;
+                }
+                fs::rename(&source_dir, &final_model_dir)?;
+                let _ = fs::remove_dir_all(&temp_extract_dir);
+            } else {
+                if final_model_dir.exists() {
+                    fs::remove_dir_all(&final_model_dir)?;
+                }
+                fs::rename(&temp_extract_dir, &final_model_dir)?;
             }
+            Ok(())
+        };
 
-            let _ = self.app_handle.emit("model-extraction-completed", model_id);
-            let _ = fs::remove_file(&partial_path);
-        } else {
-            fs::rename(&partial_path, &model_path)?;
+        if let Err(e) = extract() {
+            let _ = fs::remove_dir_all(&temp_extract_dir);
+            // Only a damaged archive is fetched anew. After a failed write
+            // (disk full) it is kept, and the next download just extracts it.
+            if matches!(e.downcast_ref(), Some(UnpackError::InvalidArchive(_))) {
+                let _ = fs::remove_file(partial_path);
+            }
+            {
+                let mut models = self.available_models.lock().unwrap();
+                if let Some(model) = models.get_mut(model_id) {
+                    model.is_downloading = false;
+                }
+            }
+            let _ = self.app_handle.emit(
+                "model-extraction-failed",
+                serde_json::json!({ "model_id": model_id, "error": e.to_string() }),
+            );
+            return Err(anyhow::anyhow!("Failed to extract archive: {}", e));
         }
 
+        let _ = self.app_handle.emit("model-extraction-completed", model_id);
+        let _ = fs::remove_file(partial_path);
+        Ok(())
+    }
+
+    fn finish_download(&self, model_id: &str) {
+        self.corrupt_models.lock().unwrap().remove(model_id);
         {
             let mut models = self.available_models.lock().unwrap();
             if let Some(model) = models.get_mut(model_id) {
@@ -686,7 +934,6 @@ impl ModelManager {
             }
         }
         let _ = self.app_handle.emit("model-download-complete", model_id);
-        Ok(())
     }
 
     pub fn delete_model(&self, model_id: &str) -> Result<()> {
@@ -714,6 +961,7 @@ impl ModelManager {
         if !deleted {
             return Err(anyhow::anyhow!("No model files found to delete"));
         }
+        self.corrupt_models.lock().unwrap().remove(model_id);
         self.update_download_status()?;
         Ok(())
     }
@@ -769,3 +1017,301 @@ impl ModelManager {
         Ok(())
     }
 }
+
+/// State of a downloaded `.tar.gz`, found by decompressing it to the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GzipCheck {
+    /// Decompresses fully and the trailer checksum matches.
+    Complete,
+    /// Valid so far but cut short: the download can be resumed.
+    Truncated,
+    /// Not gzip, or damaged: has to be downloaded again.
+    Corrupt,
+}
+
+fn check_gzip(path: &Path) -> GzipCheck {
+    let Ok(file) = File::open(path) else {
+        return GzipCheck::Corrupt;
+    };
+    let mut decoder = GzDecoder::new(std::io::BufReader::new(file));
+    match std::io::copy(&mut decoder, &mut std::io::sink()) {
+        Ok(_) => GzipCheck::Complete,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => GzipCheck::Truncated,
+        Err(_) => GzipCheck::Corrupt,
+    }
+}
+
+/// `check_gzip` on the blocking pool: it decompresses the whole archive,
+/// which would otherwise stall the async runtime. A check that couldn't run
+/// counts as truncated, so a resumable archive isn't thrown away.
+async fn check_gzip_in_background(path: &Path) -> GzipCheck {
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || check_gzip(&path))
+        .await
+        .unwrap_or(GzipCheck::Truncated)
+}
+
+/// Share of physical memory a model may take; the rest is left to the OS,
+/// the audio pipeline and whatever else is running.
+const USABLE_MEMORY_FRACTION: f64 = 0.5;
+/// Loaded size relative to the download (weights plus inference buffers).
+const MODEL_MEMORY_FACTOR: f64 = 2.0;
+/// At or below this many cores (on a non-Apple-silicon CPU), speed counts for
+/// more than accuracy.
+const SLOW_CPU_CORES: usize = 4;
+
+/// What the model recommendation knows about this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DeviceProfile {
+    /// Physical memory; None when it couldn't be read.
+    pub total_memory_mb: Option<u64>,
+    pub cpu_cores: usize,
+    /// Whisper runs on the Metal GPU and the ONNX models on fast cores.
+    pub apple_silicon: bool,
+}
+
+impl DeviceProfile {
+    pub fn detect() -> Self {
+        Self {
+            total_memory_mb: total_memory_mb(),
+            cpu_cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            apple_silicon: cfg!(all(target_os = "macos", target_arch = "aarch64")),
+        }
+    }
+
+    fn prefers_speed(&self) -> bool {
+        !self.apple_silicon && self.cpu_cores <= SLOW_CPU_CORES
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn total_memory_mb() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+#[cfg(target_os = "macos")]
+fn total_memory_mb() -> Option<u64> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", "hw.memsize"])
+        .output()
+        .ok()?;
+    let bytes: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Some(bytes / (1024 * 1024))
+}
+
+#[cfg(target_os = "windows")]
+fn total_memory_mb() -> Option<u64> {
+    use windows62::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+    Some(status.ullTotalPhys / (1024 * 1024))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn total_memory_mb() -> Option<u64> {
+    None
+}
+
+/// One entry of the ranked recommendation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelRecommendation {
+    pub model_id: String,
+    pub score: f32,
+    /// False when the model likely doesn't fit in this machine's memory.
+    pub fits: bool,
+    pub reason: String,
+}
+
+/// Rank transcription models for a device: models that fit in memory first,
+/// by accuracy and speed weighted for the CPU, then the ones that don't fit.
+/// Diarization models (no scores) are left out.
+pub fn recommend_models(models: &[ModelInfo], device: &DeviceProfile) -> Vec<ModelRecommendation> {
+    let (accuracy_weight, speed_weight) = if device.prefers_speed() {
+        (0.4, 0.6)
+    } else {
+        (0.6, 0.4)
+    };
+    let usable_mb = device
+        .total_memory_mb
+        .map(|total| total as f64 * USABLE_MEMORY_FRACTION);
+    let mut ranked: Vec<ModelRecommendation> = models
+        .iter()
+        .filter(|m| m.accuracy_score > 0.0 || m.speed_score > 0.0)
+        .map(|m| {
+            let needed_mb = m.size_mb as f64 * MODEL_MEMORY_FACTOR;
+            let fits = usable_mb.is_none_or(|usable| needed_mb <= usable);
+            let reason = if !fits {
+                format!(
+                    "Needs about {:.1} GB of memory; this machine has {:.1} GB",
+                    needed_mb / 1024.0,
+                    device.total_memory_mb.unwrap_or(0) as f64 / 1024.0
+                )
+            } else if device.prefers_speed() {
+                format!("Fits in memory; ranked for speed on {} CPU cores", device.cpu_cores)
+            } else {
+                "Fits in memory; ranked for accuracy".to_string()
+            };
+            ModelRecommendation {
+                model_id: m.id.clone(),
+                score: m.accuracy_score * accuracy_weight + m.speed_score * speed_weight,
+                fits,
+                reason,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.fits
+            .cmp(&a.fits)
+            .then(b.score.total_cmp(&a.score))
+            .then_with(|| a.model_id.cmp(&b.model_id))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, size_mb: u64, accuracy_score: f32, speed_score: f32) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            filename: id.to_string(),
+            url: None,
+            size_mb,
+            is_downloaded: false,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score,
+            speed_score,
+            default_inference_params: None,
+        }
+    }
+
+    #[test]
+    fn extraction_progress_sums_entries_and_reports_each_step() {
+        const MB: u64 = 1024 * 1024;
+        let mut counter = ExtractionCounter::default();
+        let reports: Vec<bool> = [3 * MB, 3 * MB, 3 * MB, 1024, 20 * MB, 0, 5 * MB]
+            .iter()
+            .map(|&size| counter.add(size))
+            .collect();
+        assert_eq!(reports, [false, false, true, false, true, false, false]);
+        assert_eq!(counter.extracted, 34 * MB + 1024);
+        assert_eq!(counter.reported, 29 * MB + 1024);
+    }
+
+    #[test]
+    fn gzip_check_accepts_whole_archive_and_flags_truncated_one() {
+        use flate2::{write::GzEncoder, Compression};
+        let dir = std::env::temp_dir().join("crispy_test_gzip_check");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let payload: Vec<u8> = (0..200_000u32).map(|i| (i * 7919 % 251) as u8).collect();
+        encoder.write_all(&payload).unwrap();
+        let archive = encoder.finish().unwrap();
+
+        let whole = dir.join("whole.tar.gz.partial");
+        std::fs::write(&whole, &archive).unwrap();
+        assert_eq!(check_gzip(&whole), GzipCheck::Complete);
+
+        let truncated = dir.join("truncated.tar.gz.partial");
+        std::fs::write(&truncated, &archive[..archive.len() / 2]).unwrap();
+        assert_eq!(check_gzip(&truncated), GzipCheck::Truncated);
+
+        let garbage = dir.join("garbage.tar.gz.partial");
+        std::fs::write(&garbage, b"<html>not found</html>").unwrap();
+        assert_eq!(check_gzip(&garbage), GzipCheck::Corrupt);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn only_a_damaged_archive_counts_as_invalid() {
+        use flate2::{write::GzEncoder, Compression};
+        let dir = std::env::temp_dir().join("crispy_test_unpack_archive");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let gzip = |bytes: &[u8]| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        };
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let payload = vec![7u8; 300_000];
+        let mut header = tar::Header::new_gnu();
+        header.set_size(payload.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "model/weights.bin", payload.as_slice())
+            .unwrap();
+        let archive = gzip(&builder.into_inner().unwrap());
+        let whole = dir.join("whole.tar.gz.partial");
+        std::fs::write(&whole, &archive).unwrap();
+
+        let out = dir.join("out");
+        std::fs::create_dir_all(&out).unwrap();
+        unpack_tar_gz(&whole, &out, |_, _| {}).unwrap();
+        assert_eq!(std::fs::read(out.join("model/weights.bin")).unwrap(), payload);
+
+        // Not a tar inside, or cut off mid-stream: download again
+        let not_tar = dir.join("not_tar.tar.gz.partial");
+        std::fs::write(&not_tar, gzip(&[0x55; 4096])).unwrap();
+        let truncated = dir.join("truncated.tar.gz.partial");
+        std::fs::write(&truncated, &archive[..archive.len() / 2]).unwrap();
+        let bad = dir.join("bad");
+        std::fs::create_dir_all(&bad).unwrap();
+        for path in [&not_tar, &truncated] {
+            let result = unpack_tar_gz(path, &bad, |_, _| {});
+            assert!(matches!(result, Err(UnpackError::InvalidArchive(_))), "{:?}", result);
+        }
+
+        // The archive is fine but its files can't be written
+        let blocked = dir.join("blocked");
+        std::fs::write(&blocked, b"a file, not a directory").unwrap();
+        let result = unpack_tar_gz(&whole, &blocked, |_, _| {});
+        assert!(matches!(result, Err(UnpackError::Io(_))), "{:?}", result);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn low_memory_device_gets_a_smaller_model() {
+        let models = [
+            model("small", 487, 0.60, 0.85),
+            model("turbo", 1600, 0.90, 0.80),
+            model("diarize-segmentation", 6, 0.0, 0.0),
+        ];
+        let roomy = DeviceProfile {
+            total_memory_mb: Some(16 * 1024),
+            cpu_cores: 8,
+            apple_silicon: false,
+        };
+        let ranked = recommend_models(&models, &roomy);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].model_id, "turbo");
+
+        let low_memory = DeviceProfile {
+            total_memory_mb: Some(4 * 1024),
+            ..roomy
+        };
+        let ranked = recommend_models(&models, &low_memory);
+        assert_eq!(ranked[0].model_id, "small");
+        assert!(ranked[0].fits);
+        assert_eq!(ranked[1].model_id, "turbo");
+        assert!(!ranked[1].fits);
+        assert!(ranked[1].reason.contains("memory"), "{}", ranked[1].reason);
+    }
+}
@@ -0,0 +1,220 @@
+// Subtitle rendering (WebVTT / SRT) from stored transcription segments.
+//
+// Works purely on the persisted `TranscriptionSegment`s, so re-rendering never
+// touches the audio. Word-level segments (Parakeet) are grouped into readable
+// cues; phrase-level segments (Whisper) usually map one-to-one.
+
+use crate::managers::transcription::TranscriptionSegment;
+use std::path::{Path, PathBuf};
+
+/// Longest cue we build when grouping word-level segments.
+const MAX_CUE_SECONDS: f64 = 5.0;
+const MAX_CUE_CHARS: usize = 80;
+/// A pause longer than this always starts a new cue.
+const CUE_BREAK_GAP_SECONDS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "srt" => Ok(Self::Srt),
+            "vtt" => Ok(Self::Vtt),
+            other => Err(format!("Unsupported subtitle format: {}", other)),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleCue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Group consecutive segments into cues, breaking on long pauses or when a
+/// cue would get too long to read.
+pub fn cues_from_segments(segments: &[TranscriptionSegment]) -> Vec<SubtitleCue> {
+    let mut cues: Vec<SubtitleCue> = Vec::new();
+    let mut current: Option<SubtitleCue> = None;
+
+    for seg in segments {
+        let text = seg.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(cue) = current.as_mut() {
+            let fits = seg.start - cue.end <= CUE_BREAK_GAP_SECONDS
+                && seg.end - cue.start <= MAX_CUE_SECONDS
+                && cue.text.len() + 1 + text.len() <= MAX_CUE_CHARS;
+            if fits {
+                cue.end = seg.end.max(cue.end);
+                cue.text.push(' ');
+                cue.text.push_str(text);
+                continue;
+            }
+            cues.push(current.take().unwrap());
+        }
+        current = Some(SubtitleCue {
+            start: seg.start,
+            end: seg.end,
+            text: text.to_string(),
+        });
+    }
+    cues.extend(current);
+    cues
+}
+
+/// `HH:MM:SS<sep>mmm`; SRT uses ',' and WebVTT '.' before the milliseconds.
+pub fn format_timestamp(seconds: f64, millis_separator: char) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    let (h, rem) = (total_ms / 3_600_000, total_ms % 3_600_000);
+    let (m, rem) = (rem / 60_000, rem % 60_000);
+    let (s, ms) = (rem / 1000, rem % 1000);
+    format!("{:02}:{:02}:{:02}{}{:03}", h, m, s, millis_separator, ms)
+}
+
+pub fn render_srt(cues: &[SubtitleCue]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            cue.text
+        ));
+    }
+    out
+}
+
+pub fn render_vtt(cues: &[SubtitleCue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            cue.text
+        ));
+    }
+    out
+}
+
+pub fn render(segments: &[TranscriptionSegment], format: SubtitleFormat) -> String {
+    let cues = cues_from_segments(segments);
+    match format {
+        SubtitleFormat::Srt => render_srt(&cues),
+        SubtitleFormat::Vtt => render_vtt(&cues),
+    }
+}
+
+/// Exported subtitles go into `dir`, named after the recording
+/// (`meeting.wav` -> `meeting.srt`).
+pub fn export_path(dir: &Path, recording_path: &Path, format: SubtitleFormat) -> PathBuf {
+    let stem = recording_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "transcript".to_string());
+    dir.join(format!("{}.{}", stem, format.extension()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::transcription::StoredTranscription;
+
+    fn seg(start: f64, end: f64, text: &str) -> TranscriptionSegment {
+        TranscriptionSegment {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn vtt_timestamps_use_dot_separator() {
+        assert_eq!(format_timestamp(0.0, '.'), "00:00:00.000");
+        assert_eq!(format_timestamp(3725.042, '.'), "01:02:05.042");
+    }
+
+    #[test]
+    fn srt_timestamps_use_comma_separator() {
+        assert_eq!(format_timestamp(1.5, ','), "00:00:01,500");
+        assert_eq!(format_timestamp(-0.2, ','), "00:00:00,000");
+
+        let srt = render(&[seg(1.5, 3.25, "Caption.")], SubtitleFormat::Srt);
+        assert_eq!(srt, "1\n00:00:01,500 --> 00:00:03,250\nCaption.\n\n");
+    }
+
+    #[test]
+    fn export_path_is_named_after_the_recording() {
+        let dir = Path::new("/recordings");
+        let recording = Path::new("/recordings/Team sync.wav");
+        assert_eq!(
+            export_path(dir, recording, SubtitleFormat::Srt),
+            PathBuf::from("/recordings/Team sync.srt")
+        );
+        assert_eq!(
+            export_path(dir, recording, SubtitleFormat::Vtt),
+            PathBuf::from("/recordings/Team sync.vtt")
+        );
+    }
+
+    #[test]
+    fn words_are_grouped_into_cues_and_split_on_pauses() {
+        let segments = vec![
+            seg(0.0, 0.4, "Hello"),
+            seg(0.4, 0.9, " world"),
+            seg(3.0, 3.5, "Next"),
+            seg(3.5, 4.0, " line"),
+        ];
+        let cues = cues_from_segments(&segments);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "Hello world");
+        assert_eq!(cues[0].end, 0.9);
+        assert_eq!(cues[1].text, "Next line");
+        assert_eq!(cues[1].start, 3.0);
+    }
+
+    #[test]
+    fn vtt_from_stored_segments_matches_inline() {
+        let segments = vec![
+            seg(0.0, 1.2, "First phrase."),
+            seg(1.3, 2.5, "Second phrase."),
+            seg(8.0, 9.5, "After a pause."),
+        ];
+        let inline = render(&segments, SubtitleFormat::Vtt);
+
+        let stored = StoredTranscription::new(
+            "First phrase. Second phrase. After a pause.".to_string(),
+            segments,
+            "test-model",
+        );
+        let json = serde_json::to_string(&stored).unwrap();
+        let reloaded: StoredTranscription = serde_json::from_str(&json).unwrap();
+        let from_stored = render(&reloaded.segments, SubtitleFormat::Vtt);
+
+        assert_eq!(from_stored, inline);
+        assert!(inline.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:02.500\n"));
+        assert!(inline.contains("00:00:08.000 --> 00:00:09.500\nAfter a pause."));
+    }
+
+    #[test]
+    fn parse_subtitle_format() {
+        assert_eq!(SubtitleFormat::parse("SRT").unwrap(), SubtitleFormat::Srt);
+        assert_eq!(SubtitleFormat::parse("vtt").unwrap(), SubtitleFormat::Vtt);
+        assert!(SubtitleFormat::parse("txt").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+// Word-level diff between two transcripts of the same recording.
+
+use serde::Serialize;
+
+/// Above this many word edits the diff is reported as "all removed, all
+/// added": the backtracking trace grows with the square of the edit count.
+const MAX_DIFF_EDITS: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    /// In both transcripts.
+    Equal,
+    /// Only in the second transcript.
+    Insert,
+    /// Only in the first transcript.
+    Delete,
+}
+
+/// A run of words with the same `op`, joined by single spaces.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DiffSpan {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Diff `a` against `b` word by word (whitespace-separated, compared
+/// exactly, so punctuation and case changes show up). Spans come in reading
+/// order; a changed word is a delete followed by an insert.
+pub fn diff_words(a: &str, b: &str) -> Vec<DiffSpan> {
+    let a: Vec<&str> = a.split_whitespace().collect();
+    let b: Vec<&str> = b.split_whitespace().collect();
+    let ops = myers(&a, &b).unwrap_or_else(|| {
+        a.iter()
+            .map(|w| (DiffOp::Delete, *w))
+            .chain(b.iter().map(|w| (DiffOp::Insert, *w)))
+            .collect()
+    });
+
+    let mut spans: Vec<DiffSpan> = Vec::new();
+    for (op, word) in ops {
+        match spans.last_mut() {
+            Some(span) if span.op == op => {
+                span.text.push(' ');
+                span.text.push_str(word);
+            }
+            _ => spans.push(DiffSpan {
+                op,
+                text: word.to_string(),
+            }),
+        }
+    }
+    spans
+}
+
+/// Myers' shortest edit script, one op per word in reading order. None when
+/// more than `MAX_DIFF_EDITS` edits are needed.
+fn myers<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(DiffOp, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m).min(MAX_DIFF_EDITS as isize);
+    let offset = max_d + 1;
+    // Furthest x reached on each diagonal k = x - y, indexed by k + offset
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // trace[d]: v for diagonals -d..=d before step d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max_d {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| v[(k + offset) as usize];
+            let mut x = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                at(k + 1)
+            } else {
+                at(k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(a, b, &trace));
+            }
+        }
+    }
+    None
+}
+
+/// Walk the trace back from the end of both sequences.
+fn backtrack<'a>(a: &[&'a str], b: &[&'a str], trace: &[Vec<isize>]) -> Vec<(DiffOp, &'a str)> {
+    let (mut x, mut y) = (a.len() as isize, b.len() as isize);
+    let mut ops = Vec::new();
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let at = |k: isize| v[(k + d) as usize];
+            let k = x - y;
+            let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            (at(prev_k), at(prev_k) - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push((DiffOp::Equal, a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push((DiffOp::Insert, b[(y - 1) as usize]));
+            } else {
+                ops.push((DiffOp::Delete, a[(x - 1) as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(op: DiffOp, text: &str) -> DiffSpan {
+        DiffSpan {
+            op,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_marks_inserted_deleted_and_equal_words() {
+        let spans = diff_words(
+            "the quick brown fox jumps over the dog",
+            "the quick red fox jumps over the lazy dog",
+        );
+        assert_eq!(
+            spans,
+            [
+                span(DiffOp::Equal, "the quick"),
+                span(DiffOp::Delete, "brown"),
+                span(DiffOp::Insert, "red"),
+                span(DiffOp::Equal, "fox jumps over the"),
+                span(DiffOp::Insert, "lazy"),
+                span(DiffOp::Equal, "dog"),
+            ]
+        );
+
+        assert_eq!(diff_words("same  words", "same words"), [span(DiffOp::Equal, "same words")]);
+        assert_eq!(diff_words("", "new text"), [span(DiffOp::Insert, "new text")]);
+        assert_eq!(diff_words("old text", ""), [span(DiffOp::Delete, "old text")]);
+        assert!(diff_words("", "").is_empty());
+    }
+}
@@ -1,13 +1,15 @@
 // Transcription: load model, run inference on file. Adapted from Handy (open license).
 
-use crate::managers::model::{EngineType, ModelManager};
+use crate::managers::model::{EngineType, InferenceParams, ModelInfo, ModelManager};
 use anyhow::Result;
 use log::{debug, info};
-use serde::Serialize;
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use rubato::{FftFixedIn, Resampler};
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tauri::AppHandle;
 use transcribe_rs::{
     onnx::{
@@ -19,18 +21,151 @@ use transcribe_rs::{
     SpeechModel, TranscribeOptions,
 };
 
+/// Sample rate every engine consumes.
+pub const TARGET_SAMPLE_RATE: usize = 16000;
+const RESAMPLER_CHUNK: usize = 1024;
+
 /// All engines expose the unified `SpeechModel` trait in transcribe-rs 0.3, so we
 /// keep a single boxed trait object instead of a per-engine enum.
 type LoadedEngine = Box<dyn SpeechModel>;
 
+/// Half a second of silence is enough to make engines build their sessions.
+const WARMUP_SAMPLES: usize = TARGET_SAMPLE_RATE / 2;
+
+/// The loaded engine together with the model id it came from. Both live behind
+/// one lock so they can never disagree.
+struct EngineSlot<E> {
+    engine: Option<E>,
+    model_id: Option<String>,
+}
+
+impl<E> Default for EngineSlot<E> {
+    fn default() -> Self {
+        Self {
+            engine: None,
+            model_id: None,
+        }
+    }
+}
+
+impl<E> EngineSlot<E> {
+    fn is_loaded(&self) -> bool {
+        self.engine.is_some()
+    }
+
+    fn install(&mut self, model_id: &str, engine: E) {
+        self.engine = Some(engine);
+        self.model_id = Some(model_id.to_string());
+    }
+
+    /// Load `model_id` unless it is already the loaded one, then push a short
+    /// silent buffer through `run` so lazy initialization happens now instead
+    /// of during the user's first transcription.
+    fn warm_up(
+        &mut self,
+        model_id: &str,
+        load: impl FnOnce() -> Result<E>,
+        run: impl FnOnce(&mut E, &[f32]) -> Result<()>,
+    ) -> Result<()> {
+        if self.model_id.as_deref() != Some(model_id) || !self.is_loaded() {
+            let engine = load()?;
+            self.install(model_id, engine);
+        }
+        let engine = self
+            .engine
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Model not loaded"))?;
+        run(engine, &vec![0.0; WARMUP_SAMPLES])
+    }
+}
+
 pub struct TranscriptionManager {
-    engine: Mutex<Option<LoadedEngine>>,
-    current_model_id: Mutex<Option<String>>,
+    engine: Mutex<EngineSlot<LoadedEngine>>,
     state: Mutex<HashMap<String, TranscriptionState>>,
     cancel_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    unsaved: UnsavedResults,
+    results: Mutex<ResultCache>,
     model_manager: Arc<ModelManager>,
 }
 
+/// Results kept in memory so flipping between recordings doesn't re-read disk.
+const RESULT_CACHE_CAPACITY: usize = 16;
+
+/// Small LRU of loaded transcriptions keyed by recording path, most recently
+/// used last.
+struct ResultCache {
+    entries: VecDeque<(String, StoredTranscription)>,
+    capacity: usize,
+}
+
+impl ResultCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, recording_path: &str) -> Option<StoredTranscription> {
+        let pos = self.entries.iter().position(|(p, _)| p == recording_path)?;
+        let entry = self.entries.remove(pos)?;
+        let stored = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(stored)
+    }
+
+    fn put(&mut self, recording_path: &str, stored: StoredTranscription) {
+        self.invalidate(recording_path);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((recording_path.to_string(), stored));
+    }
+
+    /// Cache what was read from disk unless a newer result was put while
+    /// the read was in flight.
+    fn fill(&mut self, recording_path: &str, stored: StoredTranscription) {
+        if !self.entries.iter().any(|(p, _)| p == recording_path) {
+            self.put(recording_path, stored);
+        }
+    }
+
+    fn invalidate(&mut self, recording_path: &str) {
+        self.entries.retain(|(p, _)| p != recording_path);
+    }
+}
+
+/// Finished transcriptions whose files could not be written (read-only or
+/// full volume), kept in memory so the UI can still show them this session.
+#[derive(Default)]
+struct UnsavedResults(Mutex<HashMap<String, StoredTranscription>>);
+
+impl UnsavedResults {
+    /// Run `save`; on failure keep `stored` and hand the error back.
+    fn save_or_keep(
+        &self,
+        recording_path: &str,
+        stored: &StoredTranscription,
+        save: impl FnOnce(&StoredTranscription) -> Result<()>,
+    ) -> Result<()> {
+        let result = save(stored);
+        let mut unsaved = self.0.lock().unwrap();
+        match result {
+            Ok(()) => {
+                unsaved.remove(recording_path);
+            }
+            Err(_) => {
+                unsaved.insert(recording_path.to_string(), stored.clone());
+            }
+        }
+        result
+    }
+
+    fn get(&self, recording_path: &str) -> Option<StoredTranscription> {
+        self.0.lock().unwrap().get(recording_path).cloned()
+    }
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct TranscriptionState {
     pub status: String,
@@ -44,6 +179,8 @@ pub struct TranscriptionStatusEvent {
     pub recording_path: String,
     pub status: String,
     pub error: Option<String>,
+    /// Timing of the finished run; only set on "completed".
+    pub stats: Option<TranscriptionStats>,
 }
 
 #[derive(Clone, Serialize)]
@@ -64,19 +201,478 @@ pub struct TranscriptionOpenEvent {
     pub recording_path: String,
 }
 
+/// Why a model could not be loaded: "not downloaded" needs a download,
+/// "corrupt" means the files are there but unusable, "failed" is anything
+/// else (out of memory, runtime or permission errors) that a fresh download
+/// would not fix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelLoadError {
+    NotFound(String),
+    NotDownloaded(String),
+    /// A download is in flight.
+    Downloading(String),
+    Corrupt { model_id: String, reason: String },
+    Failed { model_id: String, reason: String },
+}
+
+impl std::fmt::Display for ModelLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Model not found: {}", id),
+            Self::NotDownloaded(id) => write!(f, "Model not downloaded: {}", id),
+            Self::Downloading(id) => write!(
+                f,
+                "Model {} is still downloading; wait for the download to finish before transcribing.",
+                id
+            ),
+            Self::Corrupt { model_id, reason } => write!(
+                f,
+                "Model {} could not be loaded ({}). Its files look corrupt or incomplete; delete the model and download it again.",
+                model_id, reason
+            ),
+            Self::Failed { model_id, reason } => {
+                write!(f, "Model {} could not be loaded ({}).", model_id, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelLoadError {}
+
+/// Engine error messages that point at the model files themselves: missing,
+/// truncated or unparsable. Matched lowercase.
+const CORRUPT_MODEL_MARKERS: [&str; 12] = [
+    "no such file",
+    "does not exist",
+    "doesn't exist",
+    "unexpected end of file",
+    "unexpected eof",
+    "truncated",
+    "checksum",
+    "size mismatch",
+    "invalid magic",
+    "bad magic",
+    "protobuf",
+    "parse",
+];
+
+/// Files of a single-file model smaller than this share of the catalog size
+/// are taken as an interrupted write.
+const MIN_MODEL_SIZE_RATIO: f64 = 0.5;
+
+/// Classify an engine load failure. Only problems a re-download fixes (files
+/// missing or cut short, or contents that don't parse) count as corrupt.
+fn load_failure(info: &ModelInfo, model_path: &Path, engine: &str, error: &str) -> ModelLoadError {
+    let reason = format!("{} load failed: {}", engine, error);
+    let message = error.to_lowercase();
+    let corrupt = !model_path.exists()
+        || model_files_truncated(info, model_path)
+        || CORRUPT_MODEL_MARKERS.iter().any(|m| message.contains(m));
+    if corrupt {
+        ModelLoadError::Corrupt {
+            model_id: info.id.clone(),
+            reason,
+        }
+    } else {
+        ModelLoadError::Failed {
+            model_id: info.id.clone(),
+            reason,
+        }
+    }
+}
+
+fn model_files_truncated(info: &ModelInfo, model_path: &Path) -> bool {
+    if info.is_directory {
+        return std::fs::read_dir(model_path).map_or(true, |mut entries| entries.next().is_none());
+    }
+    let expected = info.size_mb as f64 * 1024.0 * 1024.0;
+    model_path
+        .metadata()
+        .map_or(true, |m| (m.len() as f64) < expected * MIN_MODEL_SIZE_RATIO)
+}
+
+/// Reject models whose files aren't complete on disk. Checked before
+/// `is_downloaded` so a model being fetched reports the download, not absence.
+/// `is_downloading` comes from the active downloads (`update_download_status`);
+/// a `.partial` left by an interrupted download doesn't count, it only lets
+/// the next download resume.
+fn ensure_model_ready(info: &ModelInfo) -> Result<(), ModelLoadError> {
+    if info.is_downloading {
+        return Err(ModelLoadError::Downloading(info.id.clone()));
+    }
+    if !info.is_downloaded {
+        return Err(ModelLoadError::NotDownloaded(info.id.clone()));
+    }
+    Ok(())
+}
+
+/// A transcription finished but its files could not be written. Carries the
+/// text so the UI can still show it.
+#[derive(Clone, Serialize)]
+pub struct TranscriptionSaveFailedEvent {
+    pub recording_path: String,
+    pub error: String,
+    pub text: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ModelWarmedEvent {
+    pub model_id: String,
+    pub elapsed_ms: u64,
+}
+
+/// Timing detail of Parakeet segments. transcribe-rs 0.3 hands back word
+/// timings through `SpeechModel`; sentence segments are built from them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampGranularity {
+    /// One segment per word, as the engine reports them.
+    #[default]
+    Word,
+    /// Words grouped into sentences, split at end punctuation or a pause.
+    Segment,
+}
+
+/// Pause between words that ends a sentence segment without punctuation.
+const SEGMENT_BREAK_SECONDS: f64 = 1.0;
+
+/// Group word-level (start, end, text) segments into sentences.
+pub fn group_words_into_segments(words: Vec<(f64, f64, String)>) -> Vec<(f64, f64, String)> {
+    let mut grouped: Vec<(f64, f64, String)> = Vec::new();
+    let mut open = false;
+    for (start, end, text) in words {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match grouped.last_mut() {
+            Some(last) if open && start - last.1 <= SEGMENT_BREAK_SECONDS => {
+                last.1 = end;
+                last.2.push(' ');
+                last.2.push_str(text);
+            }
+            _ => grouped.push((start, end, text.to_string())),
+        }
+        open = !text.ends_with(['.', '?', '!']);
+    }
+    grouped
+}
+
+/// Spoken language and task, which only Whisper takes (the other engines are
+/// single-language or detect the language themselves and ignore both), and
+/// the segment granularity, which only Parakeet takes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionOptions {
+    /// ISO 639-1 code such as "de"; `None` auto-detects.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Translate the speech to English instead of transcribing it.
+    #[serde(default)]
+    pub translate: bool,
+    /// Absent in metadata written before it existed (word timings).
+    #[serde(default)]
+    pub granularity: TimestampGranularity,
+}
+
+impl TranscriptionOptions {
+    /// Normalize a language picked in the UI: blank or "auto" means detect.
+    pub fn new(language: Option<&str>, translate: bool) -> Result<Self, String> {
+        let language = match language.map(|l| l.trim().to_ascii_lowercase()) {
+            None => None,
+            Some(l) if l.is_empty() || l == "auto" => None,
+            Some(l) if (2..=3).contains(&l.len()) && l.chars().all(|c| c.is_ascii_lowercase()) => {
+                Some(l)
+            }
+            Some(l) => return Err(format!("Unsupported language code: {}", l)),
+        };
+        Ok(Self {
+            language,
+            translate,
+            ..Self::default()
+        })
+    }
+
+    /// Whether language or translate differ from auto-detect/transcribe.
+    pub fn has_language_options(&self) -> bool {
+        self.language.is_some() || self.translate
+    }
+
+    /// Fill in the model's catalog presets when the user left language and
+    /// translate at auto-detect/transcribe; any user choice wins over them.
+    pub fn with_model_defaults(self, defaults: Option<&InferenceParams>) -> Self {
+        match defaults {
+            Some(defaults) if !self.has_language_options() => Self {
+                language: defaults.language.clone(),
+                translate: defaults.translate.unwrap_or(false),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// The options a run actually uses on `engine`: catalog presets filled in,
+    /// and language/translate reduced to the model's own preset for engines
+    /// that aren't handed them, since that preset still describes the output.
+    pub fn effective(self, engine: Option<&EngineType>, defaults: Option<&InferenceParams>) -> Self {
+        let options = self.with_model_defaults(defaults);
+        match engine {
+            Some(EngineType::Whisper) => options,
+            _ => Self {
+                language: defaults.and_then(|d| d.language.clone()),
+                translate: false,
+                ..options
+            },
+        }
+    }
+
+    /// Granularity for the engine's segments; None for engines other than
+    /// Parakeet, whose segments are used as they come.
+    fn granularity_for_engine(&self, engine: Option<&EngineType>) -> Option<TimestampGranularity> {
+        matches!(engine, Some(EngineType::Parakeet)).then_some(self.granularity)
+    }
+
+    fn for_engine(&self, engine: Option<&EngineType>) -> TranscribeOptions {
+        match engine {
+            Some(EngineType::Whisper) => TranscribeOptions {
+                language: self.language.clone(),
+                translate: self.translate,
+                ..TranscribeOptions::default()
+            },
+            _ => TranscribeOptions::default(),
+        }
+    }
+}
+
+/// A timed span of transcribed text, in seconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// One word with its timing, in seconds from the start of the recording.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionWord {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Words from segments the engine marked word-level (see
+/// `TranscriptionResult::word_level`), trimmed, skipping empty ones.
+pub fn words_from_segments(segments: &[(f64, f64, String)]) -> Vec<TranscriptionWord> {
+    segments
+        .iter()
+        .filter(|(_, _, text)| !text.trim().is_empty())
+        .map(|(start, end, text)| TranscriptionWord {
+            text: text.trim().to_string(),
+            start: *start,
+            end: *end,
+        })
+        .collect()
+}
+
+/// Wall-clock cost of a transcription, for comparing model speeds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionStats {
+    pub audio_seconds: f64,
+    pub processing_seconds: f64,
+    /// Audio seconds per processing second; above 1 is faster than real
+    /// time. None when no measurable time was spent.
+    pub real_time_factor: Option<f64>,
+}
+
+impl TranscriptionStats {
+    pub fn new(audio_seconds: f64, processing_seconds: f64) -> Self {
+        Self {
+            audio_seconds,
+            processing_seconds,
+            real_time_factor: (processing_seconds > 0.0).then(|| audio_seconds / processing_seconds),
+        }
+    }
+}
+
+/// Reading copy of (start, end, text) segments: trimmed texts joined by single
+/// spaces. The diarized transcript puts the same words under speaker markers.
+pub fn plain_transcript_text(segments: &[(f64, f64, String)]) -> String {
+    segments
+        .iter()
+        .map(|(_, _, t)| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Structured output of one `transcribe` call. Segment granularity depends on
+/// the engine (words for Parakeet, phrases for Whisper, none for some).
+/// transcribe-rs does not report language or confidence yet, so those stay
+/// `None` until an engine provides them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    pub segments: Vec<TranscriptionSegment>,
+    pub language: Option<String>,
+    pub confidence: Option<f32>,
+    /// Set by `TranscriptionManager::transcribe`.
+    #[serde(default)]
+    pub stats: Option<TranscriptionStats>,
+    /// One segment per word with its own timing: Parakeet at word
+    /// granularity. Other engines' segments are phrases, even a one-word one.
+    #[serde(default)]
+    pub word_level: bool,
+}
+
+impl TranscriptionResult {
+    /// Build from raw engine output: trimmed text plus (start, end, text)
+    /// segments, dropping whitespace-only ones.
+    pub fn from_engine_output(text: &str, segments: Option<Vec<(f64, f64, String)>>) -> Self {
+        Self {
+            text: text.trim().to_string(),
+            segments: segments
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(_, _, t)| !t.trim().is_empty())
+                .map(|(start, end, text)| TranscriptionSegment { start, end, text })
+                .collect(),
+            language: None,
+            confidence: None,
+            stats: None,
+            word_level: false,
+        }
+    }
+
+    /// Shift all segment times, e.g. by the chunk's position in the recording.
+    pub fn with_offset(mut self, offset_seconds: f64) -> Self {
+        for seg in &mut self.segments {
+            seg.start += offset_seconds;
+            seg.end += offset_seconds;
+        }
+        self
+    }
+}
+
 impl TranscriptionManager {
     pub fn new(model_manager: Arc<ModelManager>) -> Self {
         Self {
-            engine: Mutex::new(None),
-            current_model_id: Mutex::new(None),
+            engine: Mutex::new(EngineSlot::default()),
             state: Mutex::new(HashMap::new()),
             cancel_flags: Mutex::new(HashMap::new()),
+            unsaved: UnsavedResults::default(),
+            results: Mutex::new(ResultCache::new(RESULT_CACHE_CAPACITY)),
             model_manager,
         }
     }
 
     pub fn get_current_model(&self) -> Option<String> {
-        self.current_model_id.lock().unwrap().clone()
+        self.engine.lock().unwrap().model_id.clone()
+    }
+
+    pub fn is_model_loaded(&self) -> bool {
+        self.engine.lock().unwrap().is_loaded()
+    }
+
+    /// What `options` amount to on the loaded model (see
+    /// `TranscriptionOptions::effective`); stored with the result.
+    pub fn effective_options(&self, options: &TranscriptionOptions) -> TranscriptionOptions {
+        let model_info = self.loaded_model_info();
+        options.clone().effective(
+            model_info.as_ref().map(|info| &info.engine_type),
+            model_info
+                .as_ref()
+                .and_then(|info| info.default_inference_params.as_ref()),
+        )
+    }
+
+    /// Whether the loaded engine honours `TranscriptionOptions` (Whisper only).
+    pub fn engine_takes_options(&self) -> bool {
+        matches!(self.loaded_engine_type(), Some(EngineType::Whisper))
+    }
+
+    fn loaded_engine_type(&self) -> Option<EngineType> {
+        self.loaded_model_info().map(|info| info.engine_type)
+    }
+
+    fn loaded_model_info(&self) -> Option<ModelInfo> {
+        let model_id = self.get_current_model()?;
+        self.model_manager.get_model_info(&model_id)
+    }
+
+    /// Write a finished transcription and its metadata. A failed write is
+    /// returned but the result stays available through `unsaved_result`, so
+    /// the caller can report it without losing the text.
+    pub fn save_result(
+        &self,
+        app: &AppHandle,
+        recording_path: &str,
+        stored: &StoredTranscription,
+    ) -> Result<()> {
+        let saved = self.unsaved.save_or_keep(recording_path, stored, |stored| {
+            save_transcription_full(app, recording_path, stored)?;
+            if let Some(model_id) = &stored.model_id {
+                save_transcription_metadata(
+                    app,
+                    recording_path,
+                    model_id,
+                    &stored.options,
+                    stored.stats,
+                )?;
+                // The archive only feeds comparisons; losing a run there
+                // must not fail the save of the transcript itself
+                if let Err(e) = save_transcription_run(app, recording_path, model_id, stored) {
+                    eprintln!("[transcription] failed to archive run for {}: {}", recording_path, e);
+                }
+            }
+            Ok(())
+        });
+        // Only once the files hold `stored`: a load racing the write would
+        // otherwise cache the old files again
+        match &saved {
+            Ok(()) => self.cache_result(recording_path, stored),
+            Err(_) => self.invalidate_result(recording_path),
+        }
+        saved
+    }
+
+    /// A result that finished but could not be written to disk.
+    pub fn unsaved_result(&self, recording_path: &str) -> Option<StoredTranscription> {
+        self.unsaved.get(recording_path)
+    }
+
+    /// The stored transcription for a recording: unsaved results first, then
+    /// the in-memory cache, then disk.
+    pub fn load_result(
+        &self,
+        app: &AppHandle,
+        recording_path: &str,
+    ) -> Result<Option<StoredTranscription>> {
+        if let Some(unsaved) = self.unsaved_result(recording_path) {
+            return Ok(Some(unsaved));
+        }
+        if let Some(cached) = self.results.lock().unwrap().get(recording_path) {
+            return Ok(Some(cached));
+        }
+        let loaded = load_transcription_full(app, recording_path)?;
+        if let Some(stored) = &loaded {
+            self.results
+                .lock()
+                .unwrap()
+                .fill(recording_path, stored.clone());
+        }
+        Ok(loaded)
+    }
+
+    /// Cache a result whose files were just written.
+    pub fn cache_result(&self, recording_path: &str, stored: &StoredTranscription) {
+        self.results
+            .lock()
+            .unwrap()
+            .put(recording_path, stored.clone());
+    }
+
+    /// Drop the cached result after its files were rewritten, moved or deleted.
+    pub fn invalidate_result(&self, recording_path: &str) {
+        self.results.lock().unwrap().invalidate(recording_path);
     }
 
     pub fn set_state(&self, recording_path: &str, state: TranscriptionState) {
@@ -99,6 +695,19 @@ impl TranscriptionManager {
         flag
     }
 
+    /// Like `create_cancel_flag`, but None while the recording is already
+    /// being transcribed instead of replacing that run's flag.
+    pub fn claim_cancel_flag(&self, recording_path: &str) -> Option<Arc<AtomicBool>> {
+        match self.cancel_flags.lock().unwrap().entry(recording_path.to_string()) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => Some(entry.insert(Arc::new(AtomicBool::new(false))).clone()),
+        }
+    }
+
+    pub fn is_transcribing(&self, recording_path: &str) -> bool {
+        self.cancel_flags.lock().unwrap().contains_key(recording_path)
+    }
+
     pub fn cancel(&self, recording_path: &str) -> bool {
         if let Some(flag) = self.cancel_flags.lock().unwrap().get(recording_path) {
             flag.store(true, Ordering::Relaxed);
@@ -116,15 +725,54 @@ impl TranscriptionManager {
         self.state.lock().unwrap().clone()
     }
 
-    pub fn load_model(&self, model_id: &str) -> Result<()> {
+    /// Load `model_id` as the active engine. On failure the previously loaded
+    /// engine (if any) stays active; files that are missing, truncated or
+    /// unparsable mark the model for re-download.
+    pub fn load_model(&self, model_id: &str) -> Result<(), ModelLoadError> {
+        let loaded = self.load_engine(model_id)?;
+        self.engine.lock().unwrap().install(model_id, loaded);
+        debug!("Transcription model loaded: {}", model_id);
+        Ok(())
+    }
+
+    /// Make sure `model_id` is loaded and run a silent buffer through it.
+    pub fn warmup(&self, model_id: &str) -> Result<()> {
+        let start = Instant::now();
+        self.engine.lock().unwrap().warm_up(
+            model_id,
+            || self.load_engine(model_id).map_err(Into::into),
+            |engine, audio| {
+                engine
+                    .transcribe(audio, &TranscribeOptions::default())
+                    .map(|_| ())
+                    .map_err(|e| anyhow::anyhow!("Warm-up transcription failed: {}", e))
+            },
+        )?;
+        info!("Transcription model {} warmed up in {:?}", model_id, start.elapsed());
+        Ok(())
+    }
+
+    fn load_engine(&self, model_id: &str) -> Result<LoadedEngine, ModelLoadError> {
+        self.open_engine(model_id).inspect_err(|e| {
+            if matches!(e, ModelLoadError::Corrupt { .. }) {
+                self.model_manager.mark_needs_redownload(model_id);
+            }
+        })
+    }
+
+    fn open_engine(&self, model_id: &str) -> Result<LoadedEngine, ModelLoadError> {
         let model_info = self
             .model_manager
             .get_model_info(model_id)
-            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
-        if !model_info.is_downloaded {
-            return Err(anyhow::anyhow!("Model not downloaded"));
-        }
-        let model_path = self.model_manager.get_model_path(model_id)?;
+            .ok_or_else(|| ModelLoadError::NotFound(model_id.to_string()))?;
+        ensure_model_ready(&model_info)?;
+        let model_path = self
+            .model_manager
+            .get_model_path(model_id)
+            .map_err(|_| ModelLoadError::NotDownloaded(model_id.to_string()))?;
+        let failed = |engine: &str, e: &dyn std::fmt::Display| {
+            load_failure(&model_info, &model_path, engine, &e.to_string())
+        };
 
         // ONNX dir models are quantized variants (model.int8.onnx) except the few
         // shipped as FP32; pick the quantization from the filename convention.
@@ -136,162 +784,1038 @@ impl TranscriptionManager {
 
         let loaded: LoadedEngine = match model_info.engine_type {
             EngineType::Whisper => Box::new(
-                WhisperEngine::load(&model_path)
-                    .map_err(|e| anyhow::anyhow!("Whisper load failed: {}", e))?,
+                WhisperEngine::load(&model_path).map_err(|e| failed("Whisper", &e))?,
             ),
             EngineType::Parakeet => Box::new(
-                ParakeetModel::load(&model_path, &quant)
-                    .map_err(|e| anyhow::anyhow!("Parakeet load failed: {}", e))?,
+                ParakeetModel::load(&model_path, &quant).map_err(|e| failed("Parakeet", &e))?,
             ),
             EngineType::Moonshine => Box::new(
                 MoonshineModel::load(&model_path, MoonshineVariant::Base, &quant)
-                    .map_err(|e| anyhow::anyhow!("Moonshine load failed: {}", e))?,
+                    .map_err(|e| failed("Moonshine", &e))?,
             ),
             EngineType::GigaAM => Box::new(
-                GigaAMModel::load(&model_path, &quant)
-                    .map_err(|e| anyhow::anyhow!("GigaAM load failed: {}", e))?,
+                GigaAMModel::load(&model_path, &quant).map_err(|e| failed("GigaAM", &e))?,
             ),
             EngineType::SenseVoice => Box::new(
-                SenseVoiceModel::load(&model_path, &quant)
-                    .map_err(|e| anyhow::anyhow!("SenseVoice load failed: {}", e))?,
+                SenseVoiceModel::load(&model_path, &quant).map_err(|e| failed("SenseVoice", &e))?,
             ),
             EngineType::Canary => Box::new(
-                CanaryModel::load(&model_path, &quant)
-                    .map_err(|e| anyhow::anyhow!("Canary load failed: {}", e))?,
+                CanaryModel::load(&model_path, &quant).map_err(|e| failed("Canary", &e))?,
             ),
             EngineType::Cohere => Box::new(
-                CohereModel::load(&model_path, &quant)
-                    .map_err(|e| anyhow::anyhow!("Cohere load failed: {}", e))?,
+                CohereModel::load(&model_path, &quant).map_err(|e| failed("Cohere", &e))?,
             ),
         };
-
-        *self.engine.lock().unwrap() = Some(loaded);
-        *self.current_model_id.lock().unwrap() = Some(model_id.to_string());
-        debug!("Transcription model loaded: {}", model_id);
-        Ok(())
+        Ok(loaded)
     }
 
-    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+    /// Run the loaded engine on 16kHz mono audio. Segment times are relative
+    /// to the start of `audio`.
+    pub fn transcribe(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<TranscriptionResult> {
         if audio.is_empty() {
-            return Ok(String::new());
+            return Ok(TranscriptionResult::default());
         }
+        let model_info = self.loaded_model_info();
+        let engine_type = model_info.as_ref().map(|info| &info.engine_type);
+        let defaults = model_info
+            .as_ref()
+            .and_then(|info| info.default_inference_params.as_ref());
+        let options = options.clone().with_model_defaults(defaults);
+        let engine_options = options.for_engine(engine_type);
+        let granularity = options.granularity_for_engine(engine_type);
         let mut engine_guard = self.engine.lock().unwrap();
-        let engine = engine_guard.as_mut().ok_or_else(|| {
+        let engine = engine_guard.engine.as_mut().ok_or_else(|| {
             anyhow::anyhow!("Model not loaded. Select and load a model first.")
         })?;
 
+        let started = Instant::now();
         let result = engine
-            .transcribe(&audio, &TranscribeOptions::default())
+            .transcribe(&audio, &engine_options)
             .map_err(|x| anyhow::anyhow!("Transcription failed: {}", x))?;
+        let stats = TranscriptionStats::new(
+            audio.len() as f64 / TARGET_SAMPLE_RATE as f64,
+            started.elapsed().as_secs_f64(),
+        );
 
-        let text = result.text.trim().to_string();
-        if text.is_empty() {
+        let segments = result.segments.map(|segments| {
+            segments
+                .into_iter()
+                .map(|s| (s.start as f64, s.end as f64, s.text))
+                .collect()
+        });
+        let segments = match granularity {
+            Some(TimestampGranularity::Segment) => segments.map(group_words_into_segments),
+            _ => segments,
+        };
+        let mut result = TranscriptionResult::from_engine_output(&result.text, segments);
+        result.stats = Some(stats);
+        result.word_level = matches!(granularity, Some(TimestampGranularity::Word));
+        if result.text.is_empty() {
             info!("Transcription result is empty");
         } else {
-            info!("Transcription length: {} chars", text.len());
+            info!(
+                "Transcription length: {} chars, {} segments",
+                result.text.len(),
+                result.segments.len()
+            );
         }
-        Ok(text)
+        Ok(result)
     }
+}
 
-    /// Transcribe audio and return word-level segments with timestamps.
-    /// Returns Vec<(start_seconds, end_seconds, word_text)>.
-    /// For Parakeet: uses Word-level timestamps for precise per-word timing.
-    /// For Whisper/Moonshine: returns single segment per chunk (fallback).
-    pub fn transcribe_with_timestamps(
-        &self,
-        audio: Vec<f32>,
-        chunk_offset_seconds: f64,
-    ) -> Result<Vec<(f64, f64, String)>> {
-        if audio.is_empty() {
-            return Ok(Vec::new());
-        }
-        let mut engine_guard = self.engine.lock().unwrap();
-        let engine = engine_guard.as_mut().ok_or_else(|| {
-            anyhow::anyhow!("Model not loaded. Select and load a model first.")
-        })?;
-
-        let result = engine
-            .transcribe(&audio, &TranscribeOptions::default())
-            .map_err(|x| anyhow::anyhow!("Transcription failed: {}", x))?;
+/// Which channels of a stereo file to transcribe. Crispy's own recordings
+/// carry the same mic + app mix on both channels, so this only matters for
+/// imported files that keep a speaker or source per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TranscriptionSource {
+    /// Average of all channels.
+    #[default]
+    Mix,
+    Left,
+    Right,
+}
 
-        let text = result.text.trim().to_string();
-        if text.is_empty() {
-            return Ok(Vec::new());
+impl TranscriptionSource {
+    /// Collapse one interleaved frame to mono. A mono file has nothing to
+    /// select, so every source reads its only channel.
+    fn downmix(self, frame: &[f32]) -> f32 {
+        match self {
+            Self::Mix => frame.iter().sum::<f32>() / frame.len() as f32,
+            Self::Left => frame[0],
+            Self::Right => frame.get(1).copied().unwrap_or(frame[0]),
         }
+    }
+}
 
-        // If we have segments (word timestamps), use them
-        if let Some(segments) = result.segments {
-            if !segments.is_empty() {
-                let word_segments: Vec<(f64, f64, String)> = segments
-                    .into_iter()
-                    .filter(|s| !s.text.trim().is_empty())
-                    .map(|s| {
-                        (
-                            chunk_offset_seconds + s.start as f64,
-                            chunk_offset_seconds + s.end as f64,
-                            s.text,
-                        )
-                    })
-                    .collect();
-                info!("Transcription with {} word segments", word_segments.len());
-                return Ok(word_segments);
-            }
-        }
+/// Decode a WAV file into the 16kHz mono f32 the engines consume. Files that
+/// are already 16kHz mono (the transcription-optimized recording format) pass
+/// through unchanged; anything else is downmixed per `source` and goes through
+/// the FFT resampler.
+pub fn wav_to_16k_mono_f32(path: &Path, source: TranscriptionSource) -> Result<Vec<f32>> {
+    wav_to_mono_f32(path, source, TARGET_SAMPLE_RATE)
+}
 
-        // Fallback: return whole text as single segment
-        let chunk_duration = audio.len() as f64 / 16000.0;
-        info!("Transcription fallback: single segment, {} chars", text.len());
-        Ok(vec![(
-            chunk_offset_seconds,
-            chunk_offset_seconds + chunk_duration,
-            text,
-        )])
+/// `wav_to_16k_mono_f32` at any output rate.
+pub fn wav_to_mono_f32(path: &Path, source: TranscriptionSource, target_rate: usize) -> Result<Vec<f32>> {
+    let decoder = WavMonoDecoder::open(path, source, target_rate)?;
+    let mut out = Vec::with_capacity(decoder.output_len());
+    for block in decoder {
+        out.extend_from_slice(&block?);
     }
+    Ok(out)
 }
 
-/// Base directory for transcriptions: ~/Documents/Crispy/Transcriptions (next to Recordings and settings).
-fn transcriptions_dir(app: &AppHandle) -> Result<PathBuf> {
-    let dir = crate::paths::transcriptions_dir(app)
-        .map_err(|e| anyhow::anyhow!(e))?;
-    std::fs::create_dir_all(&dir)?;
-    Ok(dir)
+/// Input frames decoded per block by `WavMonoDecoder`.
+const DECODE_BLOCK_FRAMES: usize = 16 * RESAMPLER_CHUNK;
+
+/// Streaming `wav_to_mono_f32`: yields the output a block at a time, so a long
+/// recording never has to sit in memory as a whole.
+pub struct WavMonoDecoder {
+    reader: hound::WavReader<std::io::BufReader<std::fs::File>>,
+    spec: hound::WavSpec,
+    source: TranscriptionSource,
+    resampler: Option<MonoResampler>,
+    output_len: usize,
 }
 
-/// Store transcription result by recording path. Uses a hash of path as filename.
-pub fn transcription_result_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
-    let dir = transcriptions_dir(_app)?;
-    let name = transcription_file_stem(recording_path);
-    Ok(dir.join(format!("{}.txt", name)))
+impl WavMonoDecoder {
+    pub fn open(path: &Path, source: TranscriptionSource, target_rate: usize) -> Result<Self> {
+        let reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let rate_in = spec.sample_rate as usize;
+        let frames_in = reader.len() as usize / spec.channels.max(1) as usize;
+        Ok(Self {
+            reader,
+            spec,
+            source,
+            resampler: Some(MonoResampler::new(rate_in, target_rate, frames_in)?),
+            output_len: frames_in * target_rate / rate_in.max(1),
+        })
+    }
+
+    /// Total number of output samples the file decodes to.
+    pub fn output_len(&self) -> usize {
+        self.output_len
+    }
+
+    fn next_block(&mut self) -> Result<Option<Vec<f32>>> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(None);
+        };
+        let channels = self.spec.channels.max(1) as usize;
+        let mut frame: Vec<f32> = Vec::with_capacity(channels);
+        let mut frames = 0;
+        let mut push = |s: f32| -> Result<()> {
+            frame.push(s);
+            if frame.len() == channels {
+                resampler.push(self.source.downmix(&frame))?;
+                frame.clear();
+                frames += 1;
+            }
+            Ok(())
+        };
+        let wanted = DECODE_BLOCK_FRAMES * channels;
+        match self.spec.sample_format {
+            hound::SampleFormat::Int => {
+                let int_scale = (1i64 << self.spec.bits_per_sample.saturating_sub(1)) as f32;
+                for s in self.reader.samples::<i32>().take(wanted) {
+                    push(s? as f32 / int_scale)?;
+                }
+            }
+            hound::SampleFormat::Float => {
+                for s in self.reader.samples::<f32>().take(wanted) {
+                    push(s?)?;
+                }
+            }
+        }
+        if frames == DECODE_BLOCK_FRAMES {
+            return Ok(Some(resampler.drain()));
+        }
+        // End of file: flush the resampler tail
+        self.resampler.take().map(MonoResampler::finish).transpose()
+    }
 }
 
-fn transcription_file_stem(recording_path: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut h = DefaultHasher::new();
-    recording_path.hash(&mut h);
-    format!("{:016x}", h.finish())
+impl Iterator for WavMonoDecoder {
+    type Item = Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block().transpose()
+    }
 }
 
-/// Path to metadata file (model_id) for a transcription. Same stem as .txt but .meta.
-pub fn transcription_metadata_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
-    let dir = transcriptions_dir(_app)?;
-    let name = transcription_file_stem(recording_path);
-    Ok(dir.join(format!("{}.meta", name)))
+/// Resample a mono buffer that is already in memory, e.g. a denoised copy
+/// that is transcribed without being written and decoded again.
+pub fn resample_mono(samples: &[f32], rate_in: usize, target_rate: usize) -> Result<Vec<f32>> {
+    if rate_in == target_rate {
+        return Ok(samples.to_vec());
+    }
+    let mut resampler = MonoResampler::new(rate_in, target_rate, samples.len())?;
+    let mut out = Vec::with_capacity(samples.len() * target_rate / rate_in + RESAMPLER_CHUNK);
+    for &sample in samples {
+        resampler.push(sample)?;
+    }
+    out.extend_from_slice(&resampler.drain());
+    out.extend_from_slice(&resampler.finish()?);
+    Ok(out)
 }
 
-/// Path to chat history file for a transcription. Same stem as .txt but .chat.json.
-pub fn transcription_chat_history_path(
-    _app: &AppHandle,
-    recording_path: &str,
-) -> Result<PathBuf> {
-    let dir = transcriptions_dir(_app)?;
-    let name = transcription_file_stem(recording_path);
-    Ok(dir.join(format!("{}.chat.json", name)))
+/// Mono samples fed one at a time through the FFT resampler in
+/// `RESAMPLER_CHUNK` blocks; a pass-through when the rates already match.
+/// Output is taken in pieces with `drain`, the resampler's leading delay
+/// removed, and `finish` cuts the total to the length implied by the rate
+/// ratio.
+struct MonoResampler {
+    resampler: Option<FftFixedIn<f32>>,
+    expected: usize,
+    input: Vec<f32>,
+    out: Vec<f32>,
+    /// Leading delay-line samples not yet dropped from `out`.
+    delay_left: usize,
+    emitted: usize,
+}
+
+impl MonoResampler {
+    fn new(rate_in: usize, target_rate: usize, frames_in: usize) -> Result<Self> {
+        let resampler = if rate_in == target_rate {
+            None
+        } else {
+            Some(FftFixedIn::<f32>::new(
+                rate_in,
+                target_rate,
+                RESAMPLER_CHUNK,
+                1,
+                1,
+            )?)
+        };
+        Ok(Self {
+            delay_left: resampler.as_ref().map_or(0, |r| r.output_delay()),
+            resampler,
+            expected: frames_in * target_rate / rate_in.max(1),
+            input: Vec::with_capacity(RESAMPLER_CHUNK),
+            out: Vec::new(),
+            emitted: 0,
+        })
+    }
+
+    fn push(&mut self, sample: f32) -> Result<()> {
+        self.input.push(sample);
+        if self.input.len() == RESAMPLER_CHUNK {
+            match self.resampler.as_mut() {
+                Some(r) => self.out.extend_from_slice(&r.process(&[&self.input], None)?[0]),
+                None => self.out.extend_from_slice(&self.input),
+            }
+            self.input.clear();
+        }
+        Ok(())
+    }
+
+    /// Output produced so far, minus what was already taken.
+    fn drain(&mut self) -> Vec<f32> {
+        let skip = self.delay_left.min(self.out.len());
+        self.delay_left -= skip;
+        let mut out: Vec<f32> = self.out.drain(..).skip(skip).collect();
+        if self.resampler.is_some() {
+            out.truncate(self.expected.saturating_sub(self.emitted));
+        }
+        self.emitted += out.len();
+        out
+    }
+
+    /// The rest of the output, tail included.
+    fn finish(mut self) -> Result<Vec<f32>> {
+        match self.resampler.as_mut() {
+            Some(r) => {
+                // Feed the tail as a partial chunk, then flush until the
+                // resampler's delay line has drained.
+                if !self.input.is_empty() {
+                    self.out
+                        .extend_from_slice(&r.process_partial(Some(&[&self.input]), None)?[0]);
+                }
+                while self.emitted + self.out.len() < self.delay_left + self.expected {
+                    let flushed = r.process_partial::<&[f32]>(None, None)?;
+                    if flushed[0].is_empty() {
+                        break;
+                    }
+                    self.out.extend_from_slice(&flushed[0]);
+                }
+            }
+            None => {
+                let input = std::mem::take(&mut self.input);
+                self.out.extend_from_slice(&input);
+            }
+        }
+        Ok(self.drain())
+    }
+}
+
+/// Hands out the 16kHz audio of consecutive `ChunkWindow`s from a stream of
+/// decoded blocks, keeping only the current window (and its overlap with the
+/// next one) in memory.
+pub struct WindowReader<I> {
+    blocks: I,
+    buffer: Vec<f32>,
+    /// Absolute sample index of `buffer[0]`.
+    buffer_start: usize,
+}
+
+impl<I: Iterator<Item = Result<Vec<f32>>>> WindowReader<I> {
+    pub fn new(blocks: I) -> Self {
+        Self {
+            blocks,
+            buffer: Vec::new(),
+            buffer_start: 0,
+        }
+    }
+
+    /// The samples of `window`. Windows must be requested in order; audio
+    /// before `window.start` is released. Shorter than the window only if the
+    /// stream ends early.
+    pub fn window(&mut self, window: &ChunkWindow) -> Result<&[f32]> {
+        let release = window
+            .start
+            .saturating_sub(self.buffer_start)
+            .min(self.buffer.len());
+        self.buffer.drain(..release);
+        self.buffer_start += release;
+        while self.buffer_start + self.buffer.len() < window.end {
+            match self.blocks.next() {
+                Some(block) => self.buffer.extend_from_slice(&block?),
+                None => break,
+            }
+        }
+        let start = (window.start - self.buffer_start).min(self.buffer.len());
+        let end = (window.end - self.buffer_start).min(self.buffer.len());
+        Ok(&self.buffer[start..end])
+    }
+}
+
+/// One transcription window over the 16kHz buffer, in samples. Neighbouring
+/// windows overlap; each owns the half of the overlap nearest to it, so a word
+/// transcribed twice at a seam is kept by exactly one window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkWindow {
+    pub start: usize,
+    pub end: usize,
+    pub own_start: usize,
+    pub own_end: usize,
+}
+
+impl ChunkWindow {
+    /// Whether a segment (absolute seconds) belongs to this window, judged by its midpoint.
+    pub fn owns(&self, start_seconds: f64, end_seconds: f64, sample_rate: usize) -> bool {
+        let mid = (start_seconds + end_seconds) / 2.0 * sample_rate as f64;
+        mid >= self.own_start as f64 && mid < self.own_end as f64
+    }
+}
+
+/// Split `total` samples into windows of `chunk` samples overlapping by `overlap`.
+pub fn chunk_windows(total: usize, chunk: usize, overlap: usize) -> Vec<ChunkWindow> {
+    if total == 0 || chunk == 0 {
+        return Vec::new();
+    }
+    let overlap = overlap.min(chunk - 1);
+    let step = chunk - overlap;
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + chunk).min(total);
+        let last = end == total;
+        windows.push(ChunkWindow {
+            start,
+            end,
+            own_start: if start == 0 { 0 } else { start + overlap / 2 },
+            own_end: if last { total } else { start + step + overlap / 2 },
+        });
+        if last {
+            return windows;
+        }
+        start += step;
+    }
+}
+
+/// Windows up to the first one reached after `cancel_flag` is set, so a
+/// cancelled transcription stops at the next chunk boundary.
+pub fn uncancelled_windows(
+    windows: Vec<ChunkWindow>,
+    cancel_flag: &AtomicBool,
+) -> impl Iterator<Item = ChunkWindow> + '_ {
+    windows
+        .into_iter()
+        .take_while(move |_| !cancel_flag.load(Ordering::Relaxed))
+}
+
+/// Upper bound on how many words a seam can repeat (a few seconds of speech).
+pub const SEAM_MAX_WORDS: usize = 24;
+
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How many leading segments of `next` repeat the trailing segments of `prev`
+/// at a chunk seam: the longest run (at least `min_len`, at most `max_len`)
+/// whose normalized text matches and, when `time_tolerance` is given, whose
+/// start times agree within that many seconds.
+pub fn seam_overlap(
+    prev: &[(f64, f64, String)],
+    next: &[(f64, f64, String)],
+    min_len: usize,
+    max_len: usize,
+    time_tolerance: Option<f64>,
+) -> usize {
+    let max_k = max_len.min(prev.len()).min(next.len());
+    for k in (min_len.max(1)..=max_k).rev() {
+        let tail = &prev[prev.len() - k..];
+        let head = &next[..k];
+        let matches = tail.iter().zip(head).all(|(a, b)| {
+            let word = normalize_word(&a.2);
+            !word.is_empty()
+                && word == normalize_word(&b.2)
+                && time_tolerance.map_or(true, |tol| (a.0 - b.0).abs() <= tol)
+        });
+        if matches {
+            return k;
+        }
+    }
+    0
+}
+
+/// Text-only variant for engines without timestamps: returns `next` minus the
+/// leading words that repeat the end of `prev`. Requires two matching words so
+/// a single common word ("the") at the seam isn't dropped.
+pub fn stitch_chunk_text(prev: &str, next: &str) -> String {
+    let to_words = |text: &str| -> Vec<(f64, f64, String)> {
+        text.split_whitespace()
+            .map(|w| (0.0, 0.0, w.to_string()))
+            .collect()
+    };
+    let next_words = to_words(next);
+    let skip = seam_overlap(&to_words(prev), &next_words, 2, SEAM_MAX_WORDS, None);
+    next_words
+        .into_iter()
+        .skip(skip)
+        .map(|(_, _, w)| w)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The engine behind `transcribe_windows`: the loaded model, or a fake in tests.
+pub trait ChunkTranscriber {
+    /// Run the engine on 16kHz mono audio. Segment times are relative to the
+    /// start of `audio`.
+    fn transcribe_chunk(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<TranscriptionResult>;
+
+    /// Transcribe audio with segment times in recording time, offset by
+    /// `chunk_offset_seconds`.
+    /// For Parakeet: word-level segments for precise per-word timing.
+    /// For Whisper/Moonshine: the engine's phrases, or the whole text as a
+    /// single segment spanning the chunk (fallback).
+    fn transcribe_with_timestamps(
+        &self,
+        audio: Vec<f32>,
+        chunk_offset_seconds: f64,
+        options: &TranscriptionOptions,
+    ) -> Result<TranscriptionResult> {
+        let chunk_duration = audio.len() as f64 / TARGET_SAMPLE_RATE as f64;
+        let mut result = self
+            .transcribe_chunk(audio, options)?
+            .with_offset(chunk_offset_seconds);
+        if result.text.is_empty() {
+            result.segments.clear();
+            return Ok(result);
+        }
+
+        // If we have segments (word timestamps), use them
+        if !result.segments.is_empty() {
+            info!("Transcription with {} segments", result.segments.len());
+            return Ok(result);
+        }
+
+        // Fallback: return whole text as single segment
+        info!("Transcription fallback: single segment, {} chars", result.text.len());
+        result.segments.push(TranscriptionSegment {
+            start: chunk_offset_seconds,
+            end: chunk_offset_seconds + chunk_duration,
+            text: result.text.clone(),
+        });
+        result.word_level = false;
+        Ok(result)
+    }
+}
+
+impl ChunkTranscriber for TranscriptionManager {
+    fn transcribe_chunk(
+        &self,
+        audio: Vec<f32>,
+        options: &TranscriptionOptions,
+    ) -> Result<TranscriptionResult> {
+        self.transcribe(audio, options)
+    }
+}
+
+/// How `transcribe_windows` cuts the audio, in 16kHz samples.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkSettings {
+    pub chunk_samples: usize,
+    pub overlap_samples: usize,
+    /// One part per word (for speaker alignment) instead of one per window.
+    pub word_timestamps: bool,
+}
+
+/// What `transcribe_windows` collected from the windows it got through.
+#[derive(Debug, Default)]
+pub struct WindowedTranscript {
+    /// (start_seconds, end_seconds, text) in recording time.
+    pub parts: Vec<(f64, f64, String)>,
+    /// Word timings, when the engine reports word-level segments.
+    pub words: Vec<TranscriptionWord>,
+    /// The audio counted once; inference time summed over chunks, so
+    /// re-transcribed overlap shows up as slower processing.
+    pub stats: TranscriptionStats,
+}
+
+/// Re-transcribed words at a seam land within this many seconds of each other.
+const SEAM_TIME_TOLERANCE: f64 = 0.5;
+
+/// Transcribe `total_samples` of 16kHz audio streamed from `blocks` window by
+/// window, dropping what neighbouring windows transcribed twice at their seam.
+/// Stops at the next window boundary once `cancel_flag` is set. After each
+/// window `on_progress` gets the fraction done and an ETA in seconds.
+pub fn transcribe_windows<I>(
+    engine: &impl ChunkTranscriber,
+    blocks: I,
+    total_samples: usize,
+    settings: ChunkSettings,
+    options: &TranscriptionOptions,
+    cancel_flag: &AtomicBool,
+    mut on_progress: impl FnMut(f32, Option<u64>),
+) -> Result<WindowedTranscript>
+where
+    I: Iterator<Item = Result<Vec<f32>>>,
+{
+    let ChunkSettings {
+        chunk_samples,
+        overlap_samples,
+        word_timestamps,
+    } = settings;
+    let total_seconds = total_samples as f32 / TARGET_SAMPLE_RATE as f32;
+    let start = Instant::now();
+    let mut out = WindowedTranscript::default();
+    let mut processed_samples = 0usize;
+    let mut processing_seconds = 0.0;
+    let mut add_processing = |stats: Option<TranscriptionStats>| {
+        processing_seconds += stats.map_or(0.0, |s| s.processing_seconds);
+    };
+    // Owned segments of the previous window, for seam de-duplication on the plain path
+    let mut prev_segments: Vec<(f64, f64, String)> = Vec::new();
+
+    let windows = chunk_windows(total_samples, chunk_samples, overlap_samples);
+    let mut audio = WindowReader::new(blocks);
+    for window in uncancelled_windows(windows, cancel_flag) {
+        let chunk = audio.window(&window)?;
+        let chunk_start_seconds = window.start as f64 / TARGET_SAMPLE_RATE as f64;
+        if word_timestamps {
+            // Word-level timestamps for precise speaker alignment
+            let result =
+                engine.transcribe_with_timestamps(chunk.to_vec(), chunk_start_seconds, options)?;
+            add_processing(result.stats);
+            let word_segments: Vec<(f64, f64, String)> = result
+                .segments
+                .into_iter()
+                .filter(|s| {
+                    !s.text.trim().is_empty() && window.owns(s.start, s.end, TARGET_SAMPLE_RATE)
+                })
+                .map(|s| (s.start, s.end, s.text))
+                .collect();
+            // Midpoint ownership can still double a word whose time drifted across the seam
+            let skip = if overlap_samples > 0 {
+                seam_overlap(
+                    &out.parts,
+                    &word_segments,
+                    1,
+                    SEAM_MAX_WORDS,
+                    Some(SEAM_TIME_TOLERANCE),
+                )
+            } else {
+                0
+            };
+            let kept: Vec<(f64, f64, String)> = word_segments.into_iter().skip(skip).collect();
+            if result.word_level {
+                out.words.extend(words_from_segments(&kept));
+            }
+            out.parts.extend(kept);
+        } else {
+            let result = engine
+                .transcribe_chunk(chunk.to_vec(), options)?
+                .with_offset(chunk_start_seconds);
+            add_processing(result.stats);
+            let word_level = result.word_level;
+            let chunk_text = if overlap_samples == 0 {
+                let segments: Vec<(f64, f64, String)> = result
+                    .segments
+                    .into_iter()
+                    .map(|s| (s.start, s.end, s.text))
+                    .collect();
+                if word_level {
+                    out.words.extend(words_from_segments(&segments));
+                }
+                result.text
+            } else if !result.segments.is_empty() {
+                // Drop words owned by the neighbouring window, then any seam repeats
+                let owned: Vec<(f64, f64, String)> = result
+                    .segments
+                    .into_iter()
+                    .filter(|s| window.owns(s.start, s.end, TARGET_SAMPLE_RATE))
+                    .map(|s| (s.start, s.end, s.text))
+                    .collect();
+                let skip = seam_overlap(
+                    &prev_segments,
+                    &owned,
+                    1,
+                    SEAM_MAX_WORDS,
+                    Some(SEAM_TIME_TOLERANCE),
+                );
+                let text = owned[skip..]
+                    .iter()
+                    .map(|(_, _, t)| t.trim())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if word_level {
+                    out.words.extend(words_from_segments(&owned[skip..]));
+                }
+                prev_segments = owned;
+                text
+            } else {
+                let prev_text = out.parts.last().map(|(_, _, t)| t.as_str()).unwrap_or("");
+                stitch_chunk_text(prev_text, &result.text)
+            };
+            if !chunk_text.trim().is_empty() {
+                let own_start_seconds = window.own_start as f64 / TARGET_SAMPLE_RATE as f64;
+                let own_end_seconds = window.own_end as f64 / TARGET_SAMPLE_RATE as f64;
+                out.parts.push((own_start_seconds, own_end_seconds, chunk_text));
+            }
+        }
+        processed_samples = window.end;
+        let progress = (window.end as f32 / total_samples as f32).min(1.0);
+        let processed_seconds = window.end as f32 / TARGET_SAMPLE_RATE as f32;
+        let eta_seconds = if processed_seconds > 0.5 {
+            let elapsed = start.elapsed().as_secs_f32();
+            let rate = elapsed / processed_seconds;
+            let remaining_seconds = (total_seconds - processed_seconds).max(0.0) * rate;
+            Some(remaining_seconds.round() as u64)
+        } else {
+            None
+        };
+        on_progress(progress, eta_seconds);
+    }
+    out.stats = TranscriptionStats::new(
+        processed_samples as f64 / TARGET_SAMPLE_RATE as f64,
+        processing_seconds,
+    );
+    Ok(out)
+}
+
+/// Frame statistics behind the speech-presence decision.
+#[derive(Debug, Clone, Copy)]
+pub struct SpeechPresence {
+    /// Fraction of 20ms frames loud enough and with a speech-like zero-crossing rate.
+    pub active_ratio: f32,
+    /// Standard deviation of frame energy in dB. Speech is syllabically modulated
+    /// (pauses, stressed/unstressed syllables); tones and drones are flat.
+    pub energy_modulation_db: f32,
+}
+
+const SPEECH_FRAME_SECONDS: f32 = 0.02;
+/// -45 dBFS: anything quieter is treated as silence.
+const SPEECH_MIN_RMS: f32 = 0.0056;
+const SPEECH_ZCR_RANGE: (f32, f32) = (0.01, 0.35);
+const SPEECH_MIN_ACTIVE_RATIO: f32 = 0.05;
+const SPEECH_MIN_MODULATION_DB: f32 = 3.0;
+
+/// Cheap energy + zero-crossing analysis used before inference.
+pub fn analyze_speech_presence(samples: &[f32], sample_rate: usize) -> SpeechPresence {
+    let mut detector = SpeechDetector::new(sample_rate);
+    detector.push(samples);
+    detector.presence()
+}
+
+/// True if the audio plausibly contains speech. Deliberately permissive: it is
+/// only meant to catch music-only or silent recordings that engines would
+/// otherwise fill with hallucinated text.
+pub fn has_speech(samples: &[f32], sample_rate: usize) -> bool {
+    analyze_speech_presence(samples, sample_rate).is_speech()
+}
+
+/// `has_speech` over a WAV file, decoded as a stream.
+pub fn wav_has_speech(path: &Path, source: TranscriptionSource) -> Result<bool> {
+    let mut detector = SpeechDetector::new(TARGET_SAMPLE_RATE);
+    for block in WavMonoDecoder::open(path, source, TARGET_SAMPLE_RATE)? {
+        detector.push(&block?);
+    }
+    Ok(detector.presence().is_speech())
+}
+
+impl SpeechPresence {
+    pub fn is_speech(&self) -> bool {
+        self.active_ratio >= SPEECH_MIN_ACTIVE_RATIO
+            && self.energy_modulation_db >= SPEECH_MIN_MODULATION_DB
+    }
+}
+
+/// `analyze_speech_presence` fed block by block, for audio that is decoded
+/// as a stream. Keeps running frame statistics instead of the samples.
+pub struct SpeechDetector {
+    frame_len: usize,
+    frame: Vec<f32>,
+    frames: usize,
+    active: usize,
+    db_sum: f64,
+    db_sq_sum: f64,
+}
+
+impl SpeechDetector {
+    pub fn new(sample_rate: usize) -> Self {
+        let frame_len = ((sample_rate as f32 * SPEECH_FRAME_SECONDS) as usize).max(1);
+        Self {
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            frames: 0,
+            active: 0,
+            db_sum: 0.0,
+            db_sq_sum: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                self.close_frame();
+            }
+        }
+    }
+
+    fn close_frame(&mut self) {
+        let frame = &self.frame;
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        let crossings = frame
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count();
+        let zcr = crossings as f32 / frame.len() as f32;
+        if rms >= SPEECH_MIN_RMS && zcr >= SPEECH_ZCR_RANGE.0 && zcr <= SPEECH_ZCR_RANGE.1 {
+            self.active += 1;
+        }
+        let db = 20.0 * rms.max(1e-5).log10() as f64;
+        self.frames += 1;
+        self.db_sum += db;
+        self.db_sq_sum += db * db;
+        self.frame.clear();
+    }
+
+    /// Statistics over everything pushed so far, a trailing partial frame included.
+    pub fn presence(mut self) -> SpeechPresence {
+        if !self.frame.is_empty() {
+            self.close_frame();
+        }
+        if self.frames == 0 {
+            return SpeechPresence {
+                active_ratio: 0.0,
+                energy_modulation_db: 0.0,
+            };
+        }
+        let n = self.frames as f64;
+        let mean = self.db_sum / n;
+        let variance = (self.db_sq_sum / n - mean * mean).max(0.0);
+        SpeechPresence {
+            active_ratio: self.active as f32 / self.frames as f32,
+            energy_modulation_db: variance.sqrt() as f32,
+        }
+    }
+}
+
+/// Base directory for transcriptions: ~/Documents/Crispy/Transcriptions (next to Recordings and settings).
+fn transcriptions_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = crate::paths::transcriptions_dir(app)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Store transcription result by recording. Files are named after the
+/// recording's stable id (see `transcription_file_stem`).
+pub fn transcription_result_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.txt", name)))
+}
+
+/// Extensions of the per-recording files in the transcriptions dir, all
+/// sharing one stem.
+const TRANSCRIPTION_FILE_EXTENSIONS: [&str; 7] = [
+    "txt",
+    "json",
+    "meta",
+    "chat.json",
+    "diarization.json",
+    "words.json",
+    "runs.json",
+];
+
+/// Transcriptions are keyed by the recording's stable id, so they follow it
+/// through renames and a move of the recordings directory. A recording from
+/// before ids keeps the hash of its path until `migrate_recording_id` gives
+/// it one. Read-only, so it is safe to call from any command.
+fn transcription_file_stem(recording_path: &str) -> String {
+    recording_id(Path::new(recording_path))
+        .unwrap_or_else(|| legacy_transcription_file_stem(recording_path))
+}
+
+/// Held while a recording gets its id, so two callers (the startup pass and
+/// a rename, say) can't assign different ones.
+static RECORDING_ID_LOCK: Mutex<()> = Mutex::new(());
+
+/// Give a recording from before ids a stable id and re-link its transcription
+/// files from the path-hash names. The id is re-read under the lock; files
+/// still under the hash (an interrupted migration) are moved as well.
+fn migrate_recording_id(dir: &Path, recording_path: &str) -> Result<String> {
+    let _guard = RECORDING_ID_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = Path::new(recording_path);
+    let id = match recording_id(path) {
+        Some(id) => id,
+        None => {
+            if !path.is_file() {
+                anyhow::bail!("Recording not found: {}", recording_path);
+            }
+            let id = new_recording_id();
+            assign_recording_id(path, &id)?;
+            id
+        }
+    };
+    let legacy = legacy_transcription_file_stem(recording_path);
+    for ext in TRANSCRIPTION_FILE_EXTENSIONS {
+        let old = dir.join(format!("{}.{}", legacy, ext));
+        let new = dir.join(format!("{}.{}", id, ext));
+        if old.exists() && !new.exists() {
+            std::fs::rename(&old, &new)?;
+        }
+    }
+    Ok(id)
+}
+
+fn legacy_transcription_file_stem(recording_path: &str) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut h = DefaultHasher::new();
+    recording_path.hash(&mut h);
+    format!("{:016x}", h.finish())
+}
+
+/// Stems this recording's transcription files may be stored under: its id,
+/// and the path hash used before ids.
+pub fn transcription_file_stems(recording_path: &Path) -> Vec<String> {
+    let mut stems = vec![legacy_transcription_file_stem(&recording_path.to_string_lossy())];
+    stems.extend(recording_id(recording_path));
+    stems
+}
+
+/// Stem of a per-recording file in the transcriptions dir (`<stem>.txt`,
+/// `<stem>.chat.json`, ...); None for anything else.
+pub fn transcription_file_stem_of(file_name: &str) -> Option<&str> {
+    let (stem, ext) = file_name.split_once('.')?;
+    TRANSCRIPTION_FILE_EXTENSIONS.contains(&ext).then_some(stem)
+}
+
+/// Make sure the recording has a stable id (migrating its transcription files
+/// if needed) and return it; call before moving a recording.
+pub fn ensure_recording_id(app: &AppHandle, recording_path: &str) -> Result<String> {
+    let dir = transcriptions_dir(app)?;
+    migrate_recording_id(&dir, recording_path)
+}
+
+/// Fresh id for a new recording: creation time plus a per-process counter.
+pub fn new_recording_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}-{:x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Id kept under "id" in the recording's `.recinfo.json` sidecar. Anything
+/// that isn't a plain token is ignored, since it ends up in file names.
+pub fn recording_id(recording_path: &Path) -> Option<String> {
+    let json = std::fs::read_to_string(crate::paths::recording_info_path(recording_path)).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&json).ok()?;
+    let id = value.get("id")?.as_str()?;
+    let valid = !id.is_empty()
+        && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| id.to_string())
+}
+
+/// Store `id` in the recording's sidecar, keeping whatever else it holds
+/// (imported recordings have no sidecar until now).
+fn assign_recording_id(recording_path: &Path, id: &str) -> Result<()> {
+    let path = crate::paths::recording_info_path(recording_path);
+    let mut value = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+    value["id"] = serde_json::Value::String(id.to_string());
+    std::fs::write(&path, serde_json::to_string_pretty(&value)?)?;
+    Ok(())
+}
+
+/// Path to metadata file (model_id) for a transcription. Same stem as .txt but .meta.
+pub fn transcription_metadata_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.meta", name)))
+}
+
+/// Path to chat history file for a transcription. Same stem as .txt but .chat.json.
+pub fn transcription_chat_history_path(
+    _app: &AppHandle,
+    recording_path: &str,
+) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.chat.json", name)))
+}
+
+/// Path to the structured (JSON) transcription. Same stem as .txt but .json;
+/// the .txt stays alongside as a human-readable export.
+pub fn transcription_json_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Path to the speaker timeline (diarization output and manual overrides).
+/// Same stem as .txt but .diarization.json.
+pub fn transcription_diarization_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.diarization.json", name)))
+}
+
+/// Path to the word timings. Same stem as .txt but .words.json.
+pub fn transcription_words_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.words.json", name)))
+}
+
+/// Path to the latest transcription from each model, for comparing runs.
+/// Same stem as .txt but .runs.json.
+pub fn transcription_runs_path(_app: &AppHandle, recording_path: &str) -> Result<PathBuf> {
+    let dir = transcriptions_dir(_app)?;
+    let name = transcription_file_stem(recording_path);
+    Ok(dir.join(format!("{}.runs.json", name)))
+}
+
+/// Current on-disk version of `StoredTranscription`.
+pub const TRANSCRIPTION_FORMAT_VERSION: u32 = 1;
+
+/// Structured transcription as persisted in `<stem>.json`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredTranscription {
+    pub version: u32,
+    pub text: String,
+    #[serde(default)]
+    pub segments: Vec<TranscriptionSegment>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// Set when the speech check found no speech and inference was skipped.
+    #[serde(default)]
+    pub no_speech: bool,
+    /// Language/translate options the transcription ran with.
+    #[serde(default)]
+    pub options: TranscriptionOptions,
+    #[serde(default)]
+    pub stats: Option<TranscriptionStats>,
+}
+
+impl StoredTranscription {
+    pub fn new(text: String, segments: Vec<TranscriptionSegment>, model_id: &str) -> Self {
+        Self {
+            version: TRANSCRIPTION_FORMAT_VERSION,
+            text,
+            segments,
+            language: None,
+            model_id: Some(model_id.to_string()),
+            no_speech: false,
+            options: TranscriptionOptions::default(),
+            stats: None,
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TranscriptionMetadata {
     model_id: String,
+    /// Absent in metadata written before options existed (auto-detect, no translate).
+    #[serde(default)]
+    options: TranscriptionOptions,
+    /// Absent for transcriptions saved before timing was recorded.
+    #[serde(default)]
+    stats: Option<TranscriptionStats>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -306,16 +1830,161 @@ pub fn save_transcription_result(app: &AppHandle, recording_path: &str, text: &s
     Ok(())
 }
 
-pub fn save_transcription_metadata(app: &AppHandle, recording_path: &str, model_id: &str) -> Result<()> {
+/// Write the structured JSON and the plain .txt export.
+pub fn save_transcription_full(
+    app: &AppHandle,
+    recording_path: &str,
+    transcription: &StoredTranscription,
+) -> Result<()> {
+    let json_path = transcription_json_path(app, recording_path)?;
+    std::fs::write(&json_path, serde_json::to_string_pretty(transcription)?)?;
+    save_transcription_result(app, recording_path, &transcription.text)
+}
+
+/// Load the structured transcription. Results saved before the JSON format
+/// existed (.txt only) are wrapped and the JSON is written next to them.
+pub fn load_transcription_full(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<Option<StoredTranscription>> {
+    let json_path = transcription_json_path(app, recording_path)?;
+    let txt_path = transcription_result_path(app, recording_path)?;
+    let model_id = if json_path.exists() {
+        None
+    } else {
+        load_transcription_metadata(app, recording_path).unwrap_or(None)
+    };
+    load_transcription_full_from(&json_path, &txt_path, model_id)
+}
+
+fn load_transcription_full_from(
+    json_path: &Path,
+    txt_path: &Path,
+    legacy_model_id: Option<String>,
+) -> Result<Option<StoredTranscription>> {
+    if json_path.exists() {
+        let json = std::fs::read_to_string(json_path)?;
+        let stored: StoredTranscription = serde_json::from_str(&json)
+            .map_err(|e| anyhow::anyhow!("transcription json: {}", e))?;
+        return Ok(Some(stored));
+    }
+    if !txt_path.exists() {
+        return Ok(None);
+    }
+    let stored = StoredTranscription {
+        version: TRANSCRIPTION_FORMAT_VERSION,
+        text: std::fs::read_to_string(txt_path)?,
+        segments: Vec::new(),
+        language: None,
+        model_id: legacy_model_id,
+        no_speech: false,
+        options: TranscriptionOptions::default(),
+        stats: None,
+    };
+    // Best effort: a failed migration write still returns the wrapped result
+    if let Ok(json) = serde_json::to_string_pretty(&stored) {
+        let _ = std::fs::write(json_path, json);
+    }
+    Ok(Some(stored))
+}
+
+/// Latest transcription per model id. Empty for recordings transcribed
+/// before runs were kept; the current result is not included.
+pub fn load_transcription_runs(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<BTreeMap<String, StoredTranscription>> {
+    read_runs(&transcription_runs_path(app, recording_path)?)
+}
+
+/// Keep `stored` as `model_id`'s run, replacing that model's previous one.
+pub fn save_transcription_run(
+    app: &AppHandle,
+    recording_path: &str,
+    model_id: &str,
+    stored: &StoredTranscription,
+) -> Result<()> {
+    let path = transcription_runs_path(app, recording_path)?;
+    let mut runs = read_runs(&path)?;
+    runs.insert(model_id.to_string(), stored.clone());
+    write_runs(&path, &runs)
+}
+
+/// An unreadable archive (e.g. cut short by a crash) starts over empty rather
+/// than blocking every later run from being kept.
+fn read_runs(path: &Path) -> Result<BTreeMap<String, StoredTranscription>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json).unwrap_or_else(|e| {
+        eprintln!("Warning: discarding unreadable {}: {}", path.display(), e);
+        BTreeMap::new()
+    }))
+}
+
+/// Write through a sibling temp file and rename it over the archive, so a
+/// crash mid-write leaves the previous runs intact.
+fn write_runs(path: &Path, runs: &BTreeMap<String, StoredTranscription>) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(runs)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+pub fn save_transcription_words(
+    app: &AppHandle,
+    recording_path: &str,
+    words: &[TranscriptionWord],
+) -> Result<()> {
+    let path = transcription_words_path(app, recording_path)?;
+    std::fs::write(&path, serde_json::to_string(words)?)?;
+    Ok(())
+}
+
+/// Word timings, empty if the engine produced none.
+pub fn load_transcription_words(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<Vec<TranscriptionWord>> {
+    let path = transcription_words_path(app, recording_path)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("words: {}", e))
+}
+
+pub fn save_transcription_metadata(
+    app: &AppHandle,
+    recording_path: &str,
+    model_id: &str,
+    options: &TranscriptionOptions,
+    stats: Option<TranscriptionStats>,
+) -> Result<()> {
     let path = transcription_metadata_path(app, recording_path)?;
     let meta = TranscriptionMetadata {
         model_id: model_id.to_string(),
+        options: options.clone(),
+        stats,
     };
-    let json = serde_json::to_string(&meta)?;
-    std::fs::write(&path, json)?;
+    write_metadata(&path, &meta)
+}
+
+fn write_metadata(path: &Path, meta: &TranscriptionMetadata) -> Result<()> {
+    std::fs::write(path, serde_json::to_string(meta)?)?;
     Ok(())
 }
 
+fn read_metadata(path: &Path) -> Result<Option<TranscriptionMetadata>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = std::fs::read_to_string(path)?;
+    let meta = serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("metadata: {}", e))?;
+    Ok(Some(meta))
+}
+
 pub fn load_transcription_result(app: &AppHandle, recording_path: &str) -> Result<Option<String>> {
     let path = transcription_result_path(app, recording_path)?;
     if !path.exists() {
@@ -327,12 +1996,25 @@ pub fn load_transcription_result(app: &AppHandle, recording_path: &str) -> Resul
 
 pub fn load_transcription_metadata(app: &AppHandle, recording_path: &str) -> Result<Option<String>> {
     let path = transcription_metadata_path(app, recording_path)?;
-    if !path.exists() {
-        return Ok(None);
-    }
-    let json = std::fs::read_to_string(&path)?;
-    let meta: TranscriptionMetadata = serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("metadata: {}", e))?;
-    Ok(Some(meta.model_id))
+    Ok(read_metadata(&path)?.map(|meta| meta.model_id))
+}
+
+/// Options a recording was last transcribed with, to re-run it the same way.
+pub fn load_transcription_options(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<Option<TranscriptionOptions>> {
+    let path = transcription_metadata_path(app, recording_path)?;
+    Ok(read_metadata(&path)?.map(|meta| meta.options))
+}
+
+/// Timing of the recording's last transcription, if it was recorded.
+pub fn load_transcription_stats(
+    app: &AppHandle,
+    recording_path: &str,
+) -> Result<Option<TranscriptionStats>> {
+    let path = transcription_metadata_path(app, recording_path)?;
+    Ok(read_metadata(&path)?.and_then(|meta| meta.stats))
 }
 
 pub fn save_transcription_chat_history(
@@ -359,3 +2041,998 @@ pub fn load_transcription_chat_history(
         serde_json::from_str(&json).map_err(|e| anyhow::anyhow!("chat history: {}", e))?;
     Ok(messages)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{WavConfig, WavWriter};
+
+    fn temp_wav(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crispy_test_{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("audio.wav")
+    }
+
+    #[test]
+    fn warm_up_loads_model_and_runs_silence() {
+        let mut slot: EngineSlot<Vec<usize>> = EngineSlot::default();
+        assert!(!slot.is_loaded());
+        slot.warm_up(
+            "tiny",
+            || Ok(Vec::new()),
+            |runs, audio| {
+                assert!(audio.iter().all(|s| *s == 0.0));
+                runs.push(audio.len());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert!(slot.is_loaded());
+        assert_eq!(slot.model_id.as_deref(), Some("tiny"));
+        assert_eq!(slot.engine.as_deref(), Some(&[WARMUP_SAMPLES][..]));
+
+        // Already loaded: warm-up reuses the engine instead of reloading
+        slot.warm_up(
+            "tiny",
+            || panic!("should not reload"),
+            |runs, audio| {
+                runs.push(audio.len());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(slot.engine.as_ref().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn failed_load_keeps_previous_engine_and_id() {
+        let corrupt = || ModelLoadError::Corrupt {
+            model_id: "broken".to_string(),
+            reason: "truncated file".to_string(),
+        };
+
+        let mut empty: EngineSlot<Vec<usize>> = EngineSlot::default();
+        let err = empty
+            .warm_up("broken", || Err(corrupt().into()), |_, _| Ok(()))
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<ModelLoadError>(), Some(&corrupt()));
+        assert!(!empty.is_loaded());
+        assert_eq!(empty.model_id, None);
+
+        let mut slot: EngineSlot<Vec<usize>> = EngineSlot::default();
+        slot.install("good", vec![1]);
+        assert!(slot
+            .warm_up("broken", || Err(corrupt().into()), |_, _| Ok(()))
+            .is_err());
+        assert_eq!(slot.model_id.as_deref(), Some("good"));
+        assert_eq!(slot.engine, Some(vec![1]));
+    }
+
+    #[test]
+    fn model_load_errors_distinguish_missing_from_corrupt() {
+        let missing = ModelLoadError::NotDownloaded("small".to_string()).to_string();
+        assert_eq!(missing, "Model not downloaded: small");
+        let corrupt = ModelLoadError::Corrupt {
+            model_id: "small".to_string(),
+            reason: "Whisper load failed: bad magic".to_string(),
+        }
+        .to_string();
+        assert!(corrupt.contains("bad magic"));
+        assert!(corrupt.contains("download it again"));
+    }
+
+    #[test]
+    fn only_file_problems_count_as_corrupt() {
+        let dir = std::env::temp_dir().join("crispy_test_load_failure");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ggml-tiny.bin");
+        std::fs::write(&path, vec![0u8; 2 * 1024 * 1024]).unwrap();
+        let info = ModelInfo {
+            id: "tiny".to_string(),
+            name: "Tiny".to_string(),
+            description: String::new(),
+            filename: "ggml-tiny.bin".to_string(),
+            url: None,
+            size_mb: 2,
+            is_downloaded: true,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score: 0.5,
+            speed_score: 0.5,
+            default_inference_params: None,
+        };
+        let is_corrupt = |info: &ModelInfo, path: &Path, error: &str| {
+            matches!(load_failure(info, path, "Whisper", error), ModelLoadError::Corrupt { .. })
+        };
+
+        assert!(is_corrupt(&info, &path, "invalid magic number"));
+        assert!(is_corrupt(&info, &path, "Protobuf parsing failed"));
+        assert!(!is_corrupt(&info, &path, "failed to allocate memory"));
+        assert!(!is_corrupt(&info, &path, "Permission denied (os error 13)"));
+        assert!(is_corrupt(&info, &dir.join("missing.bin"), "failed to allocate memory"));
+        let bigger = ModelInfo { size_mb: 10, ..info.clone() };
+        assert!(is_corrupt(&bigger, &path, "failed to allocate memory"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn result_cache_hits_misses_and_evicts_least_recent() {
+        let stored = |text: &str| StoredTranscription::new(text.to_string(), Vec::new(), "small");
+        let mut cache = ResultCache::new(2);
+        assert!(cache.get("a.wav").is_none());
+
+        cache.put("a.wav", stored("a"));
+        cache.put("b.wav", stored("b"));
+        assert_eq!(cache.get("a.wav").unwrap().text, "a");
+        // "b" is now least recently used and makes room for "c"
+        cache.put("c.wav", stored("c"));
+        assert!(cache.get("b.wav").is_none());
+        assert_eq!(cache.get("a.wav").unwrap().text, "a");
+        assert_eq!(cache.get("c.wav").unwrap().text, "c");
+    }
+
+    #[test]
+    fn result_cache_invalidation_forces_reload() {
+        let mut cache = ResultCache::new(4);
+        cache.put(
+            "a.wav",
+            StoredTranscription::new("old".to_string(), Vec::new(), "small"),
+        );
+        // What save_result does when the write fails
+        cache.invalidate("a.wav");
+        assert!(cache.get("a.wav").is_none());
+
+        cache.put(
+            "a.wav",
+            StoredTranscription::new("new".to_string(), Vec::new(), "small"),
+        );
+        assert_eq!(cache.get("a.wav").unwrap().text, "new");
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn stale_load_does_not_replace_a_saved_result() {
+        let mut cache = ResultCache::new(4);
+        // save_result caches what it wrote while a load still holds the old files
+        cache.put(
+            "a.wav",
+            StoredTranscription::new("new".to_string(), Vec::new(), "small"),
+        );
+        cache.fill(
+            "a.wav",
+            StoredTranscription::new("old".to_string(), Vec::new(), "small"),
+        );
+        assert_eq!(cache.get("a.wav").unwrap().text, "new");
+
+        cache.fill(
+            "b.wav",
+            StoredTranscription::new("b".to_string(), Vec::new(), "small"),
+        );
+        assert_eq!(cache.get("b.wav").unwrap().text, "b");
+    }
+
+    #[test]
+    fn failed_save_keeps_transcription_text() {
+        let unsaved = UnsavedResults::default();
+        let stored = StoredTranscription::new("hello world".to_string(), Vec::new(), "small");
+
+        let err = unsaved
+            .save_or_keep("/rec/a.wav", &stored, |_| {
+                Err(anyhow::anyhow!("Read-only file system (os error 30)"))
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("Read-only"));
+        assert_eq!(unsaved.get("/rec/a.wav").unwrap().text, "hello world");
+        assert!(unsaved.get("/rec/b.wav").is_none());
+
+        // A later successful save (e.g. after freeing space) drops the copy
+        let mut written = None;
+        unsaved
+            .save_or_keep("/rec/a.wav", &stored, |s| {
+                written = Some(s.text.clone());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(written.as_deref(), Some("hello world"));
+        assert!(unsaved.get("/rec/a.wav").is_none());
+    }
+
+    #[test]
+    fn downloading_model_is_rejected_before_load() {
+        let info = |is_downloaded, is_downloading, partial_size| ModelInfo {
+            id: "small".to_string(),
+            name: "Small".to_string(),
+            description: String::new(),
+            filename: "ggml-small.bin".to_string(),
+            url: None,
+            size_mb: 487,
+            is_downloaded,
+            is_downloading,
+            partial_size,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score: 0.0,
+            speed_score: 0.0,
+            default_inference_params: None,
+        };
+        let downloading = Err(ModelLoadError::Downloading("small".to_string()));
+
+        assert_eq!(ensure_model_ready(&info(false, true, 1024)), downloading);
+        // A .partial with no active download is left over, not in flight
+        assert_eq!(ensure_model_ready(&info(true, false, 1024)), Ok(()));
+        assert_eq!(
+            ensure_model_ready(&info(false, false, 1024)),
+            Err(ModelLoadError::NotDownloaded("small".to_string()))
+        );
+        assert_eq!(
+            ensure_model_ready(&info(false, false, 0)),
+            Err(ModelLoadError::NotDownloaded("small".to_string()))
+        );
+        assert_eq!(ensure_model_ready(&info(true, false, 0)), Ok(()));
+        assert!(downloading.unwrap_err().to_string().contains("still downloading"));
+    }
+
+    #[test]
+    fn chunk_windows_without_overlap_match_plain_slicing() {
+        let windows = chunk_windows(250, 100, 0);
+        let bounds: Vec<(usize, usize)> = windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(bounds, vec![(0, 100), (100, 200), (200, 250)]);
+        assert_eq!(windows[1].own_start, 100);
+        assert_eq!(windows[1].own_end, 200);
+    }
+
+    #[test]
+    fn chunk_windows_with_overlap_share_seams_once() {
+        // 20s chunks, 4s overlap, 50s total at 1 sample/s for readability
+        let windows = chunk_windows(50, 20, 4);
+        let bounds: Vec<(usize, usize)> = windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(bounds, vec![(0, 20), (16, 36), (32, 50)]);
+        // Ownership is contiguous and covers everything exactly once
+        assert_eq!(windows[0].own_start, 0);
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].own_end, pair[1].own_start);
+        }
+        assert_eq!(windows.last().unwrap().own_end, 50);
+        assert_eq!(windows[0].own_end, 18);
+    }
+
+    #[test]
+    fn cancel_flag_stops_chunk_loop_at_next_window() {
+        let cancel_flag = AtomicBool::new(false);
+        let mut transcribed = Vec::new();
+        for window in uncancelled_windows(chunk_windows(500, 100, 0), &cancel_flag) {
+            transcribed.push(window.start);
+            if transcribed.len() == 2 {
+                cancel_flag.store(true, Ordering::Relaxed);
+            }
+        }
+        assert_eq!(transcribed, vec![0, 100]);
+
+        // Cancelled before the first chunk: nothing runs
+        assert_eq!(uncancelled_windows(chunk_windows(500, 100, 0), &cancel_flag).count(), 0);
+    }
+
+    #[test]
+    fn words_in_overlap_are_kept_once() {
+        let sr = TARGET_SAMPLE_RATE;
+        let windows = chunk_windows(50 * sr, 20 * sr, 4 * sr);
+        // A word at 17.0-17.6s is inside both window 0 (0-20s) and window 1 (16-36s)
+        let owners = windows
+            .iter()
+            .filter(|w| w.owns(17.0, 17.6, TARGET_SAMPLE_RATE))
+            .count();
+        assert_eq!(owners, 1);
+        assert!(windows[0].owns(17.0, 17.6, TARGET_SAMPLE_RATE));
+        assert!(windows[1].owns(18.2, 18.8, TARGET_SAMPLE_RATE));
+    }
+
+    fn words(list: &[(f64, &str)]) -> Vec<(f64, f64, String)> {
+        list.iter()
+            .map(|(t, w)| (*t, *t + 0.3, w.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn stitch_text_drops_repeated_phrase_at_seam() {
+        let first = "the quick brown fox jumps";
+        let second = "Fox jumps, over the lazy dog";
+        let stitched = format!("{} {}", first, stitch_chunk_text(first, second));
+        assert_eq!(stitched, "the quick brown fox jumps over the lazy dog");
+    }
+
+    #[test]
+    fn stitch_text_keeps_single_common_word() {
+        assert_eq!(stitch_chunk_text("I saw the", "the end"), "the end");
+        assert_eq!(stitch_chunk_text("", "hello world"), "hello world");
+    }
+
+    #[test]
+    fn seam_overlap_uses_timestamps() {
+        let prev = words(&[(17.0, "over"), (17.4, "the"), (17.8, "lazy")]);
+        // Same words, re-transcribed by the next window with slightly drifted times
+        let next = words(&[(17.05, "the"), (17.85, "lazy"), (18.3, "dog")]);
+        let skip = seam_overlap(&prev, &next, 1, SEAM_MAX_WORDS, Some(0.5));
+        assert_eq!(skip, 2);
+        let merged: Vec<String> = prev
+            .iter()
+            .chain(next.iter().skip(skip))
+            .map(|(_, _, w)| w.clone())
+            .collect();
+        assert_eq!(merged.join(" "), "over the lazy dog");
+
+        // Same words far apart in time are genuine repeats, not a seam duplicate
+        let later = words(&[(25.0, "the"), (25.4, "lazy")]);
+        assert_eq!(seam_overlap(&prev, &later, 1, SEAM_MAX_WORDS, Some(0.5)), 0);
+    }
+
+    /// Harmonic-rich "voice" at 120Hz gated by a ~3Hz syllable envelope with pauses.
+    fn speech_like_signal(seconds: f32) -> Vec<f32> {
+        let n = (seconds * TARGET_SAMPLE_RATE as f32) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / TARGET_SAMPLE_RATE as f32;
+                let envelope = (2.0 * std::f32::consts::PI * 3.0 * t).sin().max(0.0).powi(2);
+                let voice: f32 = (1..=8)
+                    .map(|k| (2.0 * std::f32::consts::PI * 120.0 * k as f32 * t).sin() / k as f32)
+                    .sum();
+                0.2 * envelope * voice
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steady_tone_is_not_speech() {
+        let tone: Vec<f32> = (0..TARGET_SAMPLE_RATE * 3)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / TARGET_SAMPLE_RATE as f32).sin())
+            .collect();
+        let p = analyze_speech_presence(&tone, TARGET_SAMPLE_RATE);
+        assert!(p.active_ratio > 0.9, "tone frames are loud: {:?}", p);
+        assert!(p.energy_modulation_db < SPEECH_MIN_MODULATION_DB, "tone is flat: {:?}", p);
+        assert!(!has_speech(&tone, TARGET_SAMPLE_RATE));
+    }
+
+    #[test]
+    fn modulated_voice_is_speech() {
+        let speech = speech_like_signal(3.0);
+        assert!(has_speech(&speech, TARGET_SAMPLE_RATE), "{:?}", analyze_speech_presence(&speech, TARGET_SAMPLE_RATE));
+    }
+
+    #[test]
+    fn silence_and_empty_are_not_speech() {
+        assert!(!has_speech(&vec![0.0; TARGET_SAMPLE_RATE], TARGET_SAMPLE_RATE));
+        assert!(!has_speech(&[], TARGET_SAMPLE_RATE));
+    }
+
+    #[test]
+    fn streamed_speech_detection_matches_whole_buffer() {
+        let mut signal = speech_like_signal(2.0);
+        signal.extend(vec![0.0; TARGET_SAMPLE_RATE]);
+        let whole = analyze_speech_presence(&signal, TARGET_SAMPLE_RATE);
+        let mut detector = SpeechDetector::new(TARGET_SAMPLE_RATE);
+        // Block size unrelated to the 20ms frame length
+        for block in signal.chunks(777) {
+            detector.push(block);
+        }
+        let streamed = detector.presence();
+        assert!((whole.active_ratio - streamed.active_ratio).abs() < 1e-6);
+        assert!((whole.energy_modulation_db - streamed.energy_modulation_db).abs() < 1e-3);
+    }
+
+    #[test]
+    fn window_reader_streams_overlapping_windows() {
+        let audio: Vec<f32> = (0..5000).map(|i| i as f32).collect();
+        let blocks = audio.chunks(333).map(|b| Ok(b.to_vec()));
+        let mut reader = WindowReader::new(blocks);
+        for window in chunk_windows(audio.len(), 2000, 300) {
+            let chunk = reader.window(&window).unwrap().to_vec();
+            assert_eq!(chunk, &audio[window.start..window.end]);
+            // Only the current window and one partly used block are held
+            assert!(reader.buffer.len() <= 2000 + 333);
+        }
+    }
+
+    /// Says "w<n>" for a half-second word at every whole second n of the
+    /// recording. Each sample holds its own index, so a chunk knows where it is.
+    struct SecondsEngine {
+        calls: std::cell::Cell<usize>,
+        word_level: bool,
+    }
+
+    impl SecondsEngine {
+        fn new(word_level: bool) -> Self {
+            Self {
+                calls: std::cell::Cell::new(0),
+                word_level,
+            }
+        }
+    }
+
+    impl ChunkTranscriber for SecondsEngine {
+        fn transcribe_chunk(
+            &self,
+            audio: Vec<f32>,
+            _options: &TranscriptionOptions,
+        ) -> Result<TranscriptionResult> {
+            self.calls.set(self.calls.get() + 1);
+            let first = audio[0] as usize;
+            let segments: Vec<(f64, f64, String)> = (first.div_ceil(TARGET_SAMPLE_RATE)..)
+                .map(|n| (n, n * TARGET_SAMPLE_RATE - first))
+                .take_while(|(_, i)| *i < audio.len())
+                .map(|(n, i)| {
+                    let start = i as f64 / TARGET_SAMPLE_RATE as f64;
+                    (start, start + 0.5, format!("w{}", n))
+                })
+                .collect();
+            let text = plain_transcript_text(&segments);
+            let mut result = TranscriptionResult::from_engine_output(&text, Some(segments));
+            result.stats = Some(TranscriptionStats::new(
+                audio.len() as f64 / TARGET_SAMPLE_RATE as f64,
+                0.01,
+            ));
+            result.word_level = self.word_level;
+            Ok(result)
+        }
+    }
+
+    #[test]
+    fn chunk_loop_transcribes_every_window_once_with_rising_progress() {
+        let total = 60 * TARGET_SAMPLE_RATE;
+        let audio: Vec<f32> = (0..total).map(|i| i as f32).collect();
+        let expected: Vec<String> = (0..60).map(|n| format!("w{}", n)).collect();
+        let settings = ChunkSettings {
+            chunk_samples: 20 * TARGET_SAMPLE_RATE,
+            overlap_samples: 2 * TARGET_SAMPLE_RATE,
+            word_timestamps: false,
+        };
+
+        for word_timestamps in [false, true] {
+            let engine = SecondsEngine::new(true);
+            let mut progress = Vec::new();
+            let transcript = transcribe_windows(
+                &engine,
+                audio.chunks(4096).map(|b| Ok(b.to_vec())),
+                total,
+                ChunkSettings {
+                    word_timestamps,
+                    ..settings
+                },
+                &TranscriptionOptions::default(),
+                &AtomicBool::new(false),
+                |fraction, _eta| progress.push(fraction),
+            )
+            .unwrap();
+
+            assert!(engine.calls.get() > 1);
+            assert_eq!(progress.len(), engine.calls.get());
+            assert!(progress.windows(2).all(|p| p[0] < p[1]), "{:?}", progress);
+            assert_eq!(progress.last(), Some(&1.0));
+            // Overlapping windows cover 66s of audio; the recording is 60s
+            assert_eq!(transcript.stats.audio_seconds, 60.0);
+            let processing = 0.01 * engine.calls.get() as f64;
+            assert!((transcript.stats.processing_seconds - processing).abs() < 1e-9);
+            // Words at the seams are kept by one window only
+            let text = plain_transcript_text(&transcript.parts);
+            assert_eq!(text.split(' ').collect::<Vec<_>>(), expected);
+            let words: Vec<&str> = transcript.words.iter().map(|w| w.text.as_str()).collect();
+            assert_eq!(words, expected);
+        }
+    }
+
+    #[test]
+    fn chunk_loop_takes_words_only_from_word_level_engines() {
+        let total = 60 * TARGET_SAMPLE_RATE;
+        let audio: Vec<f32> = (0..total).map(|i| i as f32).collect();
+        let overlap = 2 * TARGET_SAMPLE_RATE;
+        for (overlap_samples, word_timestamps) in [(0, false), (overlap, false), (0, true)] {
+            // One-word segments, but not marked word-level by the engine
+            let engine = SecondsEngine::new(false);
+            let transcript = transcribe_windows(
+                &engine,
+                audio.chunks(4096).map(|b| Ok(b.to_vec())),
+                total,
+                ChunkSettings {
+                    chunk_samples: 20 * TARGET_SAMPLE_RATE,
+                    overlap_samples,
+                    word_timestamps,
+                },
+                &TranscriptionOptions::default(),
+                &AtomicBool::new(false),
+                |_, _| {},
+            )
+            .unwrap();
+            assert!(!transcript.parts.is_empty());
+            assert!(transcript.words.is_empty());
+        }
+    }
+
+    #[test]
+    fn chunk_loop_stops_when_cancelled() {
+        let total = 60 * TARGET_SAMPLE_RATE;
+        let audio: Vec<f32> = (0..total).map(|i| i as f32).collect();
+        let engine = SecondsEngine::new(true);
+        let cancel_flag = AtomicBool::new(false);
+        let transcript = transcribe_windows(
+            &engine,
+            audio.chunks(4096).map(|b| Ok(b.to_vec())),
+            total,
+            ChunkSettings {
+                chunk_samples: 20 * TARGET_SAMPLE_RATE,
+                overlap_samples: 0,
+                word_timestamps: false,
+            },
+            &TranscriptionOptions::default(),
+            &cancel_flag,
+            |_, _| cancel_flag.store(true, Ordering::Relaxed),
+        )
+        .unwrap();
+        assert_eq!(engine.calls.get(), 1);
+        assert_eq!(transcript.parts.len(), 1);
+    }
+
+    #[test]
+    fn long_recording_decodes_in_blocks() {
+        let path = temp_wav("streamed_decode");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..48000 * 3 {
+            let s = ((i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 8000.0) as i16;
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let decoder = WavMonoDecoder::open(&path, TranscriptionSource::Mix, TARGET_SAMPLE_RATE).unwrap();
+        let output_len = decoder.output_len();
+        let blocks: Vec<Vec<f32>> = decoder.map(|b| b.unwrap()).collect();
+        assert!(blocks.len() > 1);
+        assert_eq!(output_len, 48000);
+        assert_eq!(blocks.iter().map(Vec::len).sum::<usize>(), output_len);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn load_full_wraps_legacy_txt_and_migrates() {
+        let dir = std::env::temp_dir().join("crispy_test_transcription_legacy");
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join("abc.json");
+        let txt_path = dir.join("abc.txt");
+        std::fs::remove_file(&json_path).ok();
+        std::fs::write(&txt_path, "old plain transcript").unwrap();
+
+        let stored = load_transcription_full_from(&json_path, &txt_path, Some("small".to_string()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version, TRANSCRIPTION_FORMAT_VERSION);
+        assert_eq!(stored.text, "old plain transcript");
+        assert!(stored.segments.is_empty());
+        assert_eq!(stored.model_id.as_deref(), Some("small"));
+        // Migrated: the JSON now exists and round-trips
+        assert!(json_path.exists());
+        let again = load_transcription_full_from(&json_path, &txt_path, None).unwrap().unwrap();
+        assert_eq!(again, stored);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn load_full_reads_structured_json() {
+        let dir = std::env::temp_dir().join("crispy_test_transcription_json");
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join("abc.json");
+        let txt_path = dir.join("abc.txt");
+        let stored = StoredTranscription::new(
+            "hello world".to_string(),
+            vec![TranscriptionSegment { start: 0.0, end: 1.5, text: "hello world".to_string() }],
+            "parakeet-tdt-0.6b-v3",
+        );
+        std::fs::write(&json_path, serde_json::to_string(&stored).unwrap()).unwrap();
+
+        let loaded = load_transcription_full_from(&json_path, &txt_path, None).unwrap().unwrap();
+        assert_eq!(loaded, stored);
+        assert_eq!(loaded.segments[0].end, 1.5);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn load_full_returns_none_without_any_result() {
+        let dir = std::env::temp_dir().join("crispy_test_transcription_none");
+        std::fs::create_dir_all(&dir).unwrap();
+        let loaded =
+            load_transcription_full_from(&dir.join("x.json"), &dir.join("x.txt"), None).unwrap();
+        assert!(loaded.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn transcription_result_from_engine_output() {
+        let result = TranscriptionResult::from_engine_output(
+            "  hello world \n",
+            Some(vec![
+                (0.0, 0.4, "hello".to_string()),
+                (0.4, 0.5, " ".to_string()),
+                (0.5, 0.9, " world".to_string()),
+            ]),
+        );
+        assert_eq!(result.text, "hello world");
+        assert_eq!(result.segments.len(), 2);
+        assert_eq!(result.segments[1].text, " world");
+        assert_eq!(result.language, None);
+        assert_eq!(result.confidence, None);
+    }
+
+    #[test]
+    fn transcription_result_without_segments_and_offset() {
+        let result = TranscriptionResult::from_engine_output("text only", None);
+        assert_eq!(result.text, "text only");
+        assert!(result.segments.is_empty());
+
+        let shifted = TranscriptionResult::from_engine_output(
+            "a",
+            Some(vec![(1.0, 2.0, "a".to_string())]),
+        )
+        .with_offset(30.0);
+        assert_eq!(shifted.segments[0].start, 31.0);
+        assert_eq!(shifted.segments[0].end, 32.0);
+    }
+
+    #[test]
+    fn transcription_format_recording_skips_resampling() {
+        let path = temp_wav("16k_passthrough");
+        let mut writer = WavWriter::with_config(path.clone(), WavConfig::TRANSCRIPTION).unwrap();
+        // 0.5s at 48kHz of a slow ramp; decimated to 8000 mono samples on disk
+        let mix: Vec<f32> = (0..24000).map(|i| (i as f32 / 24000.0) - 0.5).collect();
+        writer.write_samples(&mix, &mix).unwrap();
+        writer.finalize().unwrap();
+
+        let on_disk: Vec<i16> = hound::WavReader::open(&path)
+            .unwrap()
+            .into_samples::<i16>()
+            .map(|s| s.unwrap())
+            .collect();
+        let decoded = wav_to_16k_mono_f32(&path, TranscriptionSource::Mix).unwrap();
+
+        // Pass-through: exactly the stored samples, no resampler padding
+        assert_eq!(decoded.len(), 8000);
+        for (d, s) in decoded.iter().zip(on_disk.iter()) {
+            assert_eq!(*d, *s as f32 / 32768.0);
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn standard_recording_is_resampled_to_16k() {
+        let path = temp_wav("48k_resample");
+        let mut writer = WavWriter::new(path.clone()).unwrap();
+        let tone = vec![0.25f32; 48000];
+        writer.write_samples(&tone, &tone).unwrap();
+        writer.finalize().unwrap();
+
+        let decoded = wav_to_16k_mono_f32(&path, TranscriptionSource::Mix).unwrap();
+        // One second of input, give or take the resampler's block granularity
+        assert!(decoded.len().abs_diff(16000) <= RESAMPLER_CHUNK);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn resampled_tail_keeps_the_rate_ratio() {
+        let path = temp_wav("48k_tail");
+        let mut writer = WavWriter::new(path.clone()).unwrap();
+        // 1.5s, deliberately not a multiple of the resampler chunk
+        let tone: Vec<f32> = (0..72_000)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 0.5)
+            .collect();
+        writer.write_samples(&tone, &tone).unwrap();
+        writer.finalize().unwrap();
+
+        let decoded = wav_to_16k_mono_f32(&path, TranscriptionSource::Mix).unwrap();
+        assert!(decoded.len().abs_diff(24_000) <= RESAMPLER_CHUNK);
+        // The last samples carry the tone, not zero padding
+        let tail_peak = decoded[decoded.len() - 100..]
+            .iter()
+            .fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(tail_peak > 0.1, "tail peak {}", tail_peak);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn transcription_source_selects_channels_of_split_file() {
+        let path = temp_wav("split_channels");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE as u32,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..1600 {
+            writer.write_sample(16384i16).unwrap(); // left: 0.5
+            writer.write_sample(-8192i16).unwrap(); // right: -0.25
+        }
+        writer.finalize().unwrap();
+
+        for (source, expected) in [
+            (TranscriptionSource::Mix, 0.125),
+            (TranscriptionSource::Left, 0.5),
+            (TranscriptionSource::Right, -0.25),
+        ] {
+            let decoded = wav_to_16k_mono_f32(&path, source).unwrap();
+            assert_eq!(decoded.len(), 1600, "{:?}", source);
+            assert!(decoded.iter().all(|&s| s == expected), "{:?}", source);
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn transcription_options_round_trip() {
+        let options = TranscriptionOptions::new(Some(" DE "), true).unwrap();
+        assert_eq!(options.language.as_deref(), Some("de"));
+        let json = serde_json::to_string(&options).unwrap();
+        let back: TranscriptionOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, options);
+
+        assert_eq!(
+            TranscriptionOptions::new(Some("auto"), false).unwrap(),
+            TranscriptionOptions::default()
+        );
+        assert_eq!(TranscriptionOptions::new(Some(""), false).unwrap().language, None);
+        assert!(TranscriptionOptions::new(Some("german"), false).is_err());
+        assert!(TranscriptionOptions::new(Some("d3"), false).is_err());
+
+        // Only Whisper is handed the language and task
+        let whisper = options.for_engine(Some(&EngineType::Whisper));
+        assert_eq!(whisper.language.as_deref(), Some("de"));
+        assert!(whisper.translate);
+        let parakeet = options.for_engine(Some(&EngineType::Parakeet));
+        assert_eq!(parakeet.language, None);
+        assert!(!parakeet.translate);
+    }
+
+    #[test]
+    fn catalog_inference_params_apply_unless_the_user_chose() {
+        let defaults = InferenceParams {
+            language: Some("en".to_string()),
+            translate: None,
+        };
+        let applied = TranscriptionOptions::default().with_model_defaults(Some(&defaults));
+        let whisper = applied.for_engine(Some(&EngineType::Whisper));
+        assert_eq!(whisper.language.as_deref(), Some("en"));
+        assert!(!whisper.translate);
+
+        // A language picked through the options command wins
+        let chosen = TranscriptionOptions::new(Some("de"), false).unwrap();
+        assert_eq!(chosen.clone().with_model_defaults(Some(&defaults)), chosen);
+        assert_eq!(
+            TranscriptionOptions::default().with_model_defaults(None),
+            TranscriptionOptions::default()
+        );
+
+        // Stored options say what the engine actually did
+        let german = TranscriptionOptions::new(Some("de"), true).unwrap();
+        let on_whisper = german.clone().effective(Some(&EngineType::Whisper), Some(&defaults));
+        assert_eq!(on_whisper, german);
+        let on_parakeet = german.effective(Some(&EngineType::Parakeet), Some(&defaults));
+        assert_eq!(on_parakeet.language.as_deref(), Some("en"));
+        assert!(!on_parakeet.translate);
+
+        // Catalog entries without presets still deserialize
+        let json = r#"{"language":"en"}"#;
+        assert_eq!(serde_json::from_str::<InferenceParams>(json).unwrap(), defaults);
+    }
+
+    #[test]
+    fn chosen_granularity_reaches_parakeet_params() {
+        let options = TranscriptionOptions {
+            granularity: TimestampGranularity::Segment,
+            ..TranscriptionOptions::default()
+        };
+        assert_eq!(
+            options.granularity_for_engine(Some(&EngineType::Parakeet)),
+            Some(TimestampGranularity::Segment)
+        );
+        assert_eq!(options.granularity_for_engine(Some(&EngineType::Whisper)), None);
+        assert!(!options.has_language_options());
+        // Persisted with the rest of the options; older metadata reads as words
+        let json = serde_json::to_string(&options).unwrap();
+        assert!(json.contains(r#""granularity":"segment""#), "{}", json);
+        let legacy: TranscriptionOptions = serde_json::from_str(r#"{"language":null}"#).unwrap();
+        assert_eq!(legacy.granularity, TimestampGranularity::Word);
+
+        let words = vec![
+            (0.0, 0.3, " Hi".to_string()),
+            (0.3, 0.6, " there.".to_string()),
+            (0.7, 1.0, " Next".to_string()),
+            (2.5, 2.8, " later".to_string()),
+        ];
+        assert_eq!(
+            group_words_into_segments(words),
+            vec![
+                (0.0, 0.6, "Hi there.".to_string()),
+                (0.7, 1.0, "Next".to_string()),
+                (2.5, 2.8, "later".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_archive_survives_a_corrupt_file() {
+        let dir = std::env::temp_dir().join("crispy_test_runs_archive");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("abc.runs.json");
+        std::fs::write(&path, "{\"small\": {\"text\": \"cut sh").unwrap();
+        assert!(read_runs(&path).unwrap().is_empty());
+
+        let mut runs = BTreeMap::new();
+        runs.insert(
+            "small".to_string(),
+            StoredTranscription::new("hello".to_string(), Vec::new(), "small"),
+        );
+        write_runs(&path, &runs).unwrap();
+        assert_eq!(read_runs(&path).unwrap()["small"].text, "hello");
+        assert!(!path.with_extension("json.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn words_round_trip() {
+        let segments = vec![
+            (0.0, 0.4, " Hello".to_string()),
+            (0.4, 0.9, "world.".to_string()),
+        ];
+        let words = words_from_segments(&segments);
+        assert_eq!(
+            words,
+            vec![
+                TranscriptionWord { text: "Hello".to_string(), start: 0.0, end: 0.4 },
+                TranscriptionWord { text: "world.".to_string(), start: 0.4, end: 0.9 },
+            ]
+        );
+        let json = serde_json::to_string(&words).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<TranscriptionWord>>(&json).unwrap(), words);
+    }
+
+    #[test]
+    fn real_time_factor_is_audio_over_processing_time() {
+        let stats = TranscriptionStats::new(60.0, 15.0);
+        assert_eq!(stats.real_time_factor, Some(4.0));
+        assert_eq!(TranscriptionStats::new(60.0, 0.0).real_time_factor, None);
+    }
+
+    #[test]
+    fn metadata_round_trips_language() {
+        let dir = std::env::temp_dir().join("crispy_test_transcription_metadata");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("meta.meta");
+
+        let meta = TranscriptionMetadata {
+            model_id: "whisper-small".to_string(),
+            options: TranscriptionOptions::new(Some("fr"), false).unwrap(),
+            stats: None,
+        };
+        write_metadata(&path, &meta).unwrap();
+        let loaded = read_metadata(&path).unwrap().unwrap();
+        assert_eq!(loaded.model_id, "whisper-small");
+        assert_eq!(loaded.options.language.as_deref(), Some("fr"));
+
+        // Metadata from before options existed still loads
+        std::fs::write(&path, r#"{"model_id":"parakeet"}"#).unwrap();
+        let legacy = read_metadata(&path).unwrap().unwrap();
+        assert_eq!(legacy.options, TranscriptionOptions::default());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn moved_recording_keeps_its_transcript() {
+        let root = std::env::temp_dir().join("crispy_test_recording_id");
+        std::fs::remove_dir_all(&root).ok();
+        let (old_dir, new_dir, transcripts) =
+            (root.join("old"), root.join("new"), root.join("transcripts"));
+        for dir in [&old_dir, &new_dir, &transcripts] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let old_path = old_dir.join("call.wav");
+        std::fs::write(&old_path, b"RIFF").unwrap();
+        let old_str = old_path.to_string_lossy().to_string();
+
+        // A transcript saved before ids, under the hash of the old path
+        let legacy = legacy_transcription_file_stem(&old_str);
+        std::fs::write(transcripts.join(format!("{}.txt", legacy)), "hello").unwrap();
+
+        // Getters only read: until migrated, the hash is still the stem
+        assert_eq!(transcription_file_stem(&old_str), legacy);
+        assert_eq!(recording_id(&old_path), None);
+
+        let id = migrate_recording_id(&transcripts, &old_str).unwrap();
+        assert_ne!(id, legacy);
+        assert_eq!(transcription_file_stem(&old_str), id);
+        assert_eq!(recording_id(&old_path).as_deref(), Some(id.as_str()));
+        assert!(!transcripts.join(format!("{}.txt", legacy)).exists());
+
+        // Move the recording with its sidecar: same stem, same transcript
+        let new_path = new_dir.join("renamed.wav");
+        std::fs::rename(&old_path, &new_path).unwrap();
+        std::fs::rename(
+            crate::paths::recording_info_path(&old_path),
+            crate::paths::recording_info_path(&new_path),
+        )
+        .unwrap();
+        let stem = transcription_file_stem(&new_path.to_string_lossy());
+        assert_eq!(stem, id);
+        let text = std::fs::read_to_string(transcripts.join(format!("{}.txt", stem))).unwrap();
+        assert_eq!(text, "hello");
+
+        // Ids that would escape the transcriptions dir are not trusted
+        std::fs::write(crate::paths::recording_info_path(&new_path), r#"{"id":"../x"}"#).unwrap();
+        assert_eq!(recording_id(&new_path), None);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn concurrent_migrations_agree_on_one_id() {
+        let root = std::env::temp_dir().join("crispy_test_recording_id_race");
+        std::fs::remove_dir_all(&root).ok();
+        let transcripts = root.join("transcripts");
+        std::fs::create_dir_all(&transcripts).unwrap();
+        let path = root.join("call.wav");
+        std::fs::write(&path, b"RIFF").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+        let legacy = legacy_transcription_file_stem(&path_str);
+        std::fs::write(transcripts.join(format!("{}.txt", legacy)), "hello").unwrap();
+
+        let ids: Vec<String> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| migrate_recording_id(&transcripts, &path_str).unwrap()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(ids.iter().all(|id| *id == ids[0]));
+        assert_eq!(recording_id(&path).as_deref(), Some(ids[0].as_str()));
+        let text = std::fs::read_to_string(transcripts.join(format!("{}.txt", ids[0]))).unwrap();
+        assert_eq!(text, "hello");
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn default_downmix_averages_each_stereo_frame() {
+        let path = temp_wav("stereo_average");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: TARGET_SAMPLE_RATE as u32,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        let frames: Vec<(i16, i16)> = (0..1600i16).map(|i| (i * 8, -i * 4)).collect();
+        for &(left, right) in &frames {
+            writer.write_sample(left).unwrap();
+            writer.write_sample(right).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let decoded = wav_to_16k_mono_f32(&path, TranscriptionSource::default()).unwrap();
+        assert_eq!(decoded.len(), frames.len());
+        for (mono, &(left, right)) in decoded.iter().zip(&frames) {
+            let expected = (left as f32 + right as f32) / 2.0 / 32768.0;
+            assert!((mono - expected).abs() < 1e-6, "{} vs {}", mono, expected);
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 
 fn fallback_documents_dir() -> Option<PathBuf> {
@@ -40,6 +40,11 @@ pub fn transcriptions_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(crispy_documents_root(app)?.join("Transcriptions"))
 }
 
+/// `<name>.recinfo.json` next to a recording: capture settings and its stable id.
+pub fn recording_info_path(recording_path: &Path) -> PathBuf {
+    recording_path.with_extension("recinfo.json")
+}
+
 pub fn ensure_dir(path: &PathBuf) -> Result<(), String> {
     std::fs::create_dir_all(path)
         .map_err(|e| format!("Failed to create dir {}: {}", path.display(), e))
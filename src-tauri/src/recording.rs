@@ -1,12 +1,17 @@
-use std::collections::VecDeque;
+use rubato::{FftFixedIn, Resampler};
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 use screencapturekit::stream::sc_stream::SCStream;
 
 pub const SAMPLE_RATE: usize = 48000;
 pub const CHANNELS: usize = 2; // Stereo
+/// The mic buffer keeps this much audio while nothing drains it, which is
+/// also the longest possible pre-roll.
+pub const ROLLING_BUFFER_SECONDS: usize = 10;
 
 /// Resample audio from one sample rate to another using linear interpolation
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
@@ -43,6 +48,9 @@ pub struct RecordableApp {
     pub id: String,
     pub name: String,
     pub bundle_id: String,
+    /// Base64 PNG of the app icon. Only filled by `attach_app_icons`, and only
+    /// on macOS/Windows.
+    pub icon_base64: Option<String>,
 }
 
 pub struct RecordingState {
@@ -62,8 +70,12 @@ impl RecordingState {
     pub fn new() -> Self {
         Self {
             writer: Arc::new(Mutex::new(None)),
-            mic_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(SAMPLE_RATE * 10))),
-            app_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(SAMPLE_RATE * 10))),
+            mic_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(
+                SAMPLE_RATE * ROLLING_BUFFER_SECONDS,
+            ))),
+            app_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(
+                SAMPLE_RATE * ROLLING_BUFFER_SECONDS,
+            ))),
             worker: None,
             #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
             app_audio_stream: Arc::new(Mutex::new(None)),
@@ -75,16 +87,212 @@ impl RecordingState {
     }
 }
 
+/// Reset the buffers for a new recording, keeping the newest `pre_roll`
+/// samples of mic audio. Pre-roll is mic-only: the mic buffer is a capped
+/// rolling buffer the monitoring stream feeds while it runs, but app audio is
+/// only captured while recording, so the app side gets as much silence to
+/// stay aligned with the mic.
+pub fn start_buffers_with_pre_roll(mic: &mut VecDeque<f32>, app: &mut VecDeque<f32>, pre_roll: usize) {
+    let excess = mic.len().saturating_sub(pre_roll);
+    mic.drain(..excess);
+    app.clear();
+    app.resize(mic.len(), 0.0);
+}
+
+/// Output format for a recording. The mic/app mix is always produced at
+/// `SAMPLE_RATE`; lower rates go through `OutputResampler` in `WavWriter`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WavConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl WavConfig {
+    /// 48kHz stereo, the native capture format.
+    pub const STANDARD: WavConfig = WavConfig {
+        sample_rate: SAMPLE_RATE as u32,
+        channels: CHANNELS as u16,
+    };
+
+    /// 16kHz mono: what the transcription engines consume, so the file can be
+    /// fed to them without resampling.
+    pub const TRANSCRIPTION: WavConfig = WavConfig {
+        sample_rate: 16000,
+        channels: 1,
+    };
+
+    /// Map the `recording_format` setting to a config ("transcription" or anything else).
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "transcription" => Self::TRANSCRIPTION,
+            _ => Self::STANDARD,
+        }
+    }
+}
+
+impl Default for WavConfig {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Slow-release peak limiter for the summed mic+app signal. Attack is
+/// instantaneous so no sample exceeds the threshold; gain then recovers
+/// towards unity over roughly `release` so attenuation doesn't pump.
+pub struct MixLimiter {
+    threshold: f32,
+    gain: f32,
+    release_coeff: f32,
+}
+
+impl MixLimiter {
+    /// Just under full scale, so the 16-bit writer never clips.
+    pub const THRESHOLD: f32 = 0.98;
+
+    pub fn new(sample_rate: usize, release: Duration) -> Self {
+        let release_samples = (release.as_secs_f32() * sample_rate as f32).max(1.0);
+        Self {
+            threshold: Self::THRESHOLD,
+            gain: 1.0,
+            release_coeff: 1.0 - (-1.0 / release_samples).exp(),
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            self.gain += (1.0 - self.gain) * self.release_coeff;
+            let level = sample.abs() * self.gain;
+            if level > self.threshold {
+                self.gain = self.threshold / sample.abs();
+            }
+            *sample *= self.gain;
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+/// Input frames per block through `OutputResampler`.
+const OUTPUT_RESAMPLER_CHUNK: usize = 1024;
+
+/// Band-limited conversion from `SAMPLE_RATE` to a lower output rate, fed as
+/// audio arrives. The FFT resampler low-passes below the new Nyquist, so
+/// content above it doesn't fold back into the speech band. The filter delay
+/// is dropped at the start and flushed at the end, so the output length
+/// follows the rate ratio.
+struct OutputResampler {
+    resampler: FftFixedIn<f32>,
+    /// Input not yet processed, per channel.
+    pending: Vec<Vec<f32>>,
+    rate_out: usize,
+    frames_in: u64,
+    frames_out: u64,
+    /// Leading output frames still to drop (the filter delay).
+    delay: usize,
+}
+
+impl OutputResampler {
+    fn new(rate_out: usize, channels: usize) -> Result<Self, String> {
+        let resampler = FftFixedIn::<f32>::new(SAMPLE_RATE, rate_out, OUTPUT_RESAMPLER_CHUNK, 1, channels)
+            .map_err(|e| format!("Failed to create resampler: {}", e))?;
+        let delay = resampler.output_delay();
+        Ok(Self {
+            resampler,
+            pending: vec![Vec::with_capacity(OUTPUT_RESAMPLER_CHUNK); channels],
+            rate_out,
+            frames_in: 0,
+            frames_out: 0,
+            delay,
+        })
+    }
+
+    /// Queue one input frame; returns output per channel once a block is full.
+    fn push(&mut self, frame: &[f32]) -> Result<Option<Vec<Vec<f32>>>, String> {
+        for (pending, &sample) in self.pending.iter_mut().zip(frame) {
+            pending.push(sample);
+        }
+        self.frames_in += 1;
+        if self.pending[0].len() < OUTPUT_RESAMPLER_CHUNK {
+            return Ok(None);
+        }
+        let out = self
+            .resampler
+            .process(&self.pending, None)
+            .map_err(|e| format!("Failed to resample: {}", e))?;
+        self.pending.iter_mut().for_each(Vec::clear);
+        Ok(Some(self.take(out)))
+    }
+
+    /// Process the queued tail and drain the filter delay.
+    fn finish(&mut self) -> Result<Vec<Vec<f32>>, String> {
+        let expected = self.frames_in * self.rate_out as u64 / SAMPLE_RATE as u64;
+        let mut out: Vec<Vec<f32>> = vec![Vec::new(); self.pending.len()];
+        let mut input = Some(std::mem::take(&mut self.pending));
+        while self.frames_out < expected {
+            let block = self
+                .resampler
+                .process_partial(input.take().as_deref(), None)
+                .map_err(|e| format!("Failed to resample: {}", e))?;
+            if block[0].is_empty() {
+                break;
+            }
+            for (all, part) in out.iter_mut().zip(self.take(block)) {
+                all.extend(part);
+            }
+        }
+        let excess = (self.frames_out.saturating_sub(expected)) as usize;
+        for channel in out.iter_mut() {
+            channel.truncate(channel.len().saturating_sub(excess));
+        }
+        Ok(out)
+    }
+
+    /// `block` without whatever is left of the leading delay.
+    fn take(&mut self, mut block: Vec<Vec<f32>>) -> Vec<Vec<f32>> {
+        let skip = self.delay.min(block[0].len());
+        self.delay -= skip;
+        for channel in block.iter_mut() {
+            channel.drain(..skip);
+        }
+        self.frames_out += block[0].len() as u64;
+        block
+    }
+}
+
 pub struct WavWriter {
     writer: hound::WavWriter<std::io::BufWriter<std::fs::File>>,
     output_path: PathBuf,
+    config: WavConfig,
+    /// None at `SAMPLE_RATE`, where frames are written as they come.
+    resampler: Option<OutputResampler>,
+    /// Digital ceiling as linear amplitude (1.0 = 0 dBFS).
+    ceiling: f32,
+    /// TPDF dither before the i16 conversion (opt-in).
+    dither: bool,
+    rng_state: u32,
 }
 
 impl WavWriter {
     pub fn new(output_path: PathBuf) -> Result<Self, String> {
+        Self::with_config(output_path, WavConfig::default())
+    }
+
+    pub fn with_config(output_path: PathBuf, config: WavConfig) -> Result<Self, String> {
+        if config.channels == 0 || config.channels > 2 {
+            return Err(format!("Unsupported channel count: {}", config.channels));
+        }
+        if config.sample_rate == 0 || !(SAMPLE_RATE as u32).is_multiple_of(config.sample_rate) {
+            return Err(format!(
+                "Sample rate {} must evenly divide {}",
+                config.sample_rate, SAMPLE_RATE
+            ));
+        }
+
         let spec = hound::WavSpec {
-            channels: CHANNELS as u16,
-            sample_rate: SAMPLE_RATE as u32,
+            channels: config.channels,
+            sample_rate: config.sample_rate,
             bits_per_sample: 16,
             sample_format: hound::SampleFormat::Int,
         };
@@ -95,32 +303,104 @@ impl WavWriter {
         Ok(Self {
             writer,
             output_path,
+            config,
+            resampler: (config.sample_rate as usize != SAMPLE_RATE)
+                .then(|| OutputResampler::new(config.sample_rate as usize, config.channels as usize))
+                .transpose()?,
+            ceiling: 1.0,
+            dither: false,
+            rng_state: 0x9E37_79B9,
         })
     }
 
+    /// Limit output peaks to `db` dBFS (clamped to -24..=0).
+    pub fn set_ceiling_db(&mut self, db: f32) {
+        self.ceiling = 10f32.powf(db.clamp(-24.0, 0.0) / 20.0);
+    }
+
+    /// Add triangular (TPDF) dither of ±1 LSB and round instead of truncating,
+    /// which trades truncation distortion on quiet passages for a low noise floor.
+    pub fn set_dither(&mut self, enabled: bool) {
+        self.dither = enabled;
+    }
+
+    /// Uniform in [0, 1) from a xorshift32 generator.
+    fn next_uniform(&mut self) -> f32 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Write 48kHz frames. In mono mode the channels are averaged; lower
+    /// rates are low-passed and resampled, with the tail written by `finalize`.
     pub fn write_samples(&mut self, left: &[f32], right: &[f32]) -> Result<(), String> {
         if left.len() != right.len() {
             return Err("Left and right channel length mismatch".to_string());
         }
 
-        // Interleave and write samples
-        for i in 0..left.len() {
-            // Convert f32 (-1.0 to 1.0) to i16
-            let left_sample = (left[i].clamp(-1.0, 1.0) * 32767.0) as i16;
-            let right_sample = (right[i].clamp(-1.0, 1.0) * 32767.0) as i16;
-            
-            self.writer
-                .write_sample(left_sample)
-                .map_err(|e| format!("Failed to write left sample: {}", e))?;
-            self.writer
-                .write_sample(right_sample)
-                .map_err(|e| format!("Failed to write right sample: {}", e))?;
+        for (&l, &r) in left.iter().zip(right) {
+            let mono = [(l + r) * 0.5];
+            let stereo = [l, r];
+            let frame: &[f32] = if self.config.channels == 1 { &mono } else { &stereo };
+            match self.resampler.as_mut() {
+                Some(resampler) => {
+                    if let Some(block) = resampler.push(frame)? {
+                        self.write_block(&block)?;
+                    }
+                }
+                None => self.write_frame(frame)?,
+            }
         }
 
         Ok(())
     }
 
-    pub fn finalize(self) -> Result<PathBuf, String> {
+    /// Per-channel output from the resampler, written frame by frame.
+    fn write_block(&mut self, block: &[Vec<f32>]) -> Result<(), String> {
+        let mut frame = Vec::with_capacity(block.len());
+        for i in 0..block[0].len() {
+            frame.clear();
+            frame.extend(block.iter().map(|channel| channel[i]));
+            self.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self, frame: &[f32]) -> Result<(), String> {
+        if let [mono] = frame {
+            return self.write_one(*mono, "mono");
+        }
+        self.write_one(frame[0], "left")?;
+        self.write_one(frame[1], "right")
+    }
+
+    fn write_one(&mut self, sample: f32, channel: &str) -> Result<(), String> {
+        // Convert f32 (-ceiling to ceiling) to i16
+        let limit = self.ceiling * 32767.0;
+        let scaled = sample.clamp(-self.ceiling, self.ceiling) * 32767.0;
+        let value = if self.dither {
+            let noise = self.next_uniform() - self.next_uniform();
+            (scaled + noise).round().clamp(-limit, limit) as i16
+        } else {
+            scaled as i16
+        };
+        self.writer
+            .write_sample(value)
+            .map_err(|e| format!("Failed to write {} sample: {}", channel, e))
+    }
+
+    pub fn config(&self) -> WavConfig {
+        self.config
+    }
+
+    pub fn finalize(mut self) -> Result<PathBuf, String> {
+        if let Some(mut resampler) = self.resampler.take() {
+            let tail = resampler.finish()?;
+            self.write_block(&tail)?;
+        }
         self.writer
             .finalize()
             .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
@@ -158,6 +438,7 @@ pub fn get_recordable_apps() -> Result<Vec<RecordableApp>, String> {
                 id: format!("{}_{}", bundle_id, pid),
                 name: app_name,
                 bundle_id,
+                icon_base64: None,
             })
         })
         .collect();
@@ -170,6 +451,7 @@ pub fn get_recordable_apps() -> Result<Vec<RecordableApp>, String> {
         id: "none".to_string(),
         name: "None (Mic only)".to_string(),
         bundle_id: "none".to_string(),
+        icon_base64: None,
     });
     
     Ok(apps)
@@ -187,14 +469,226 @@ pub fn get_recordable_apps() -> Result<Vec<RecordableApp>, String> {
             id: "none".to_string(),
             name: "None (Mic only)".to_string(),
             bundle_id: "none".to_string(),
+            icon_base64: None,
         },
     ])
 }
 
+/// How long an enumerated app list is reused before querying the OS again.
+const RECORDABLE_APPS_TTL: Duration = Duration::from_secs(2);
+
+/// Short-lived cache for `get_recordable_apps`: the picker polls it and
+/// enumeration (SCShareableContent / process snapshot) is slow. Errors are
+/// never cached.
+struct RecordableAppsCache {
+    fetched_at: Option<Instant>,
+    apps: Vec<RecordableApp>,
+}
+
+impl RecordableAppsCache {
+    const fn new() -> Self {
+        Self {
+            fetched_at: None,
+            apps: Vec::new(),
+        }
+    }
+
+    fn get_or_refresh<F>(
+        &mut self,
+        now: Instant,
+        ttl: Duration,
+        force: bool,
+        fetch: F,
+    ) -> Result<Vec<RecordableApp>, String>
+    where
+        F: FnOnce() -> Result<Vec<RecordableApp>, String>,
+    {
+        if !force {
+            if let Some(at) = self.fetched_at {
+                if now.saturating_duration_since(at) < ttl {
+                    return Ok(self.apps.clone());
+                }
+            }
+        }
+        match fetch() {
+            Ok(apps) => {
+                self.apps = apps.clone();
+                self.fetched_at = Some(now);
+                Ok(apps)
+            }
+            Err(e) => {
+                self.invalidate();
+                Err(e)
+            }
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.fetched_at = None;
+        self.apps.clear();
+    }
+}
+
+static RECORDABLE_APPS_CACHE: Mutex<RecordableAppsCache> = Mutex::new(RecordableAppsCache::new());
+
+/// Cached `get_recordable_apps`; `force` bypasses the TTL and refreshes the cache.
+pub fn get_recordable_apps_cached(force: bool) -> Result<Vec<RecordableApp>, String> {
+    RECORDABLE_APPS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_refresh(Instant::now(), RECORDABLE_APPS_TTL, force, get_recordable_apps)
+}
+
+/// Drop the cached list, e.g. after an app we tried to capture turned out to be gone.
+pub fn invalidate_recordable_apps_cache() {
+    RECORDABLE_APPS_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .invalidate();
+}
+
+/// Icons keyed by bundle_id (macOS) / app id (Windows). Resolving one shells
+/// out, so each app is looked up at most once per session; misses are cached too.
+static APP_ICON_CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+fn encode_icon_png(png: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(png)
+}
+
+/// Resolve the app bundle via Spotlight, then convert its .icns to a 64px PNG with `sips`.
+#[cfg(target_os = "macos")]
+fn resolve_app_icon(app: &RecordableApp) -> Option<String> {
+    use std::process::Command;
+
+    let bundle_id = &app.bundle_id;
+    if bundle_id.is_empty() || bundle_id.contains('"') {
+        return None;
+    }
+    let found = Command::new("mdfind")
+        .arg(format!("kMDItemCFBundleIdentifier == \"{}\"", bundle_id))
+        .output()
+        .ok()?;
+    let app_path = PathBuf::from(
+        String::from_utf8_lossy(&found.stdout)
+            .lines()
+            .find(|l| l.ends_with(".app"))?,
+    );
+
+    let plist = Command::new("/usr/libexec/PlistBuddy")
+        .args(["-c", "Print :CFBundleIconFile"])
+        .arg(app_path.join("Contents/Info.plist"))
+        .output()
+        .ok()?;
+    let mut icon_file = String::from_utf8_lossy(&plist.stdout).trim().to_string();
+    if !plist.status.success() || icon_file.is_empty() {
+        return None;
+    }
+    if !icon_file.ends_with(".icns") {
+        icon_file.push_str(".icns");
+    }
+
+    let png_path = std::env::temp_dir().join(format!("crispy_icon_{}.png", bundle_id));
+    let converted = Command::new("sips")
+        .args(["-s", "format", "png", "-Z", "64"])
+        .arg(app_path.join("Contents/Resources").join(icon_file))
+        .arg("--out")
+        .arg(&png_path)
+        .output()
+        .ok()?;
+    let png = std::fs::read(&png_path).ok();
+    let _ = std::fs::remove_file(&png_path);
+    if !converted.status.success() {
+        return None;
+    }
+    png.map(|bytes| encode_icon_png(&bytes))
+}
+
+#[cfg(target_os = "windows")]
+fn resolve_app_icon(app: &RecordableApp) -> Option<String> {
+    let pid: u32 = app.id.rsplit('_').next()?.parse().ok()?;
+    crate::windows_audio::app_icon_base64_windows(pid)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn resolve_app_icon(_app: &RecordableApp) -> Option<String> {
+    None
+}
+
+/// Fill `icon_base64` for each app. Kept separate from enumeration so the
+/// plain list stays fast; the picker asks for icons explicitly.
+pub fn attach_app_icons(apps: &mut [RecordableApp]) {
+    let cache = APP_ICON_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    attach_icons_with(apps, cache, resolve_app_icon);
+}
+
+fn attach_icons_with(
+    apps: &mut [RecordableApp],
+    cache: &Mutex<HashMap<String, Option<String>>>,
+    resolve: impl Fn(&RecordableApp) -> Option<String>,
+) {
+    for app in apps.iter_mut().filter(|a| a.id != "none") {
+        // Windows groups by exe name, so the bundle_id is the stable key there too
+        let key = app.bundle_id.clone();
+        if let Some(icon) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            app.icon_base64 = icon.clone();
+            continue;
+        }
+        let icon = resolve(app);
+        cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, icon.clone());
+        app.icon_base64 = icon;
+    }
+}
+
+/// How app audio is folded to mono.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AppDownmix {
+    /// Mean of all channels; never louder than the loudest channel.
+    #[default]
+    Average,
+    /// Straight sum, which keeps the level of correlated (centered) content.
+    /// Peaks can exceed full scale and are left to the mix limiter.
+    Sum,
+    /// One 0-based channel, for hard-panned sources. Falls back to averaging
+    /// when the stream has fewer channels.
+    Channel(usize),
+}
+
+impl AppDownmix {
+    fn fold(self, mut frame: impl Iterator<Item = f32>, channels: usize) -> f32 {
+        match self {
+            Self::Sum => frame.sum(),
+            Self::Channel(c) if c < channels => frame.nth(c).unwrap_or(0.0),
+            _ => frame.sum::<f32>() / channels.max(1) as f32,
+        }
+    }
+}
+
+/// Mono from interleaved app audio.
+pub fn downmix_interleaved(samples: &[f32], channels: usize, method: AppDownmix) -> Vec<f32> {
+    let channels = channels.max(1);
+    samples
+        .chunks_exact(channels)
+        .map(|frame| method.fold(frame.iter().copied(), channels))
+        .collect()
+}
+
+/// `downmix_interleaved` for planar buffers (one slice per channel).
+pub fn downmix_planar(planes: &[&[f32]], method: AppDownmix) -> Vec<f32> {
+    let len = planes.iter().map(|p| p.len()).min().unwrap_or(0);
+    (0..len)
+        .map(|i| method.fold(planes.iter().map(move |p| p[i]), planes.len()))
+        .collect()
+}
+
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 pub fn start_app_audio_capture(
     app_id: &str,
     app_buffer: Arc<Mutex<VecDeque<f32>>>,
+    downmix: AppDownmix,
 ) -> Result<SCStream, String> {
     use screencapturekit::prelude::*;
     
@@ -242,6 +736,7 @@ pub fn start_app_audio_capture(
     struct AudioHandler {
         buffer: Arc<Mutex<VecDeque<f32>>>,
         detected_sample_rate: Arc<Mutex<Option<usize>>>,
+        downmix: AppDownmix,
     }
     
     impl SCStreamOutputTrait for AudioHandler {
@@ -278,12 +773,7 @@ pub fn start_app_audio_capture(
                         )
                     };
 
-                    let len = left_samples.len().min(right_samples.len());
-                    Some(
-                        (0..len)
-                            .map(|i| (left_samples[i] + right_samples[i]) / 2.0)
-                            .collect(),
-                    )
+                    Some(downmix_planar(&[left_samples, right_samples], self.downmix))
                 } else {
                     let Some(audio_buffer) = audio_buffer_list.buffer(0) else {
                         return;
@@ -301,18 +791,7 @@ pub fn start_app_audio_capture(
                     };
 
                     if num_channels >= 2 {
-                        Some(
-                            samples
-                                .chunks(num_channels)
-                                .map(|chunk| {
-                                    let mut sum = 0.0;
-                                    for &s in chunk {
-                                        sum += s;
-                                    }
-                                    sum / num_channels as f32
-                                })
-                                .collect(),
-                        )
+                        Some(downmix_interleaved(samples, num_channels, self.downmix))
                     } else {
                         Some(samples.to_vec())
                     }
@@ -374,6 +853,7 @@ pub fn start_app_audio_capture(
     let handler = AudioHandler {
         buffer: app_buffer,
         detected_sample_rate: Arc::new(Mutex::new(None)),
+        downmix,
     };
     
     let mut stream = SCStream::new(&filter, &config);
@@ -389,14 +869,16 @@ pub fn start_app_audio_capture(
     app_id: &str,
     app_buffer: Arc<Mutex<VecDeque<f32>>>,
     stop_flag: Arc<std::sync::atomic::AtomicBool>,
+    downmix: AppDownmix,
 ) -> Result<std::thread::JoinHandle<()>, String> {
-    crate::windows_audio::start_app_audio_capture_windows(app_id, app_buffer, stop_flag)
+    crate::windows_audio::start_app_audio_capture_windows(app_id, app_buffer, stop_flag, downmix)
 }
 
 #[cfg(not(any(all(target_os = "macos", target_arch = "aarch64"), target_os = "windows")))]
 pub fn start_app_audio_capture(
     _app_id: &str,
     _app_buffer: Arc<Mutex<VecDeque<f32>>>,
+    _downmix: AppDownmix,
 ) -> Result<(), String> {
     Err("App audio capture is not supported on this platform".to_string())
 }
@@ -503,6 +985,116 @@ mod tests {
         std::fs::remove_dir_all(&dir).ok();
     }
 
+    fn write_and_read(name: &str, samples: &[f32], setup: impl FnOnce(&mut WavWriter)) -> Vec<i16> {
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.wav");
+        let mut writer = WavWriter::new(path.clone()).unwrap();
+        setup(&mut writer);
+        writer.write_samples(samples, samples).unwrap();
+        writer.finalize().unwrap();
+        let reader = hound::WavReader::open(&path).unwrap();
+        let out = reader.into_samples::<i16>().map(|s| s.unwrap()).collect();
+        std::fs::remove_dir_all(&dir).ok();
+        out
+    }
+
+    #[test]
+    fn wav_writer_dither_randomizes_lsbs_on_quiet_signal() {
+        // A constant 0.3 LSB: truncation writes pure zeros (the signal is lost)
+        let quiet = vec![0.3f32 / 32767.0; 20_000];
+        let plain = write_and_read("crispy_test_wav_no_dither", &quiet, |_| {});
+        assert!(plain.iter().all(|s| *s == 0));
+
+        let dithered = write_and_read("crispy_test_wav_dither", &quiet, |w| w.set_dither(true));
+        // TPDF noise of ±1 LSB only touches the lowest bits...
+        assert!(dithered.iter().all(|s| (-1..=1).contains(s)));
+        assert!(dithered.iter().any(|s| *s != 0));
+        // ...and preserves the signal level on average
+        let mean = dithered.iter().map(|s| *s as f64).sum::<f64>() / dithered.len() as f64;
+        assert!((mean - 0.3).abs() < 0.05, "mean {}", mean);
+    }
+
+    #[test]
+    fn wav_writer_respects_ceiling() {
+        let loud = vec![1.5f32, 1.0, 0.9, -1.0, -2.0];
+        let limit = (10f32.powf(-6.0 / 20.0) * 32767.0) as i16;
+        for dither in [false, true] {
+            let out = write_and_read("crispy_test_wav_ceiling", &loud, |w| {
+                w.set_ceiling_db(-6.0);
+                w.set_dither(dither);
+            });
+            assert!(out.iter().all(|s| s.abs() <= limit), "{:?}", out);
+            assert!(out.iter().any(|s| s.abs() >= limit - 1));
+        }
+    }
+
+    #[test]
+    fn mix_limiter_keeps_summed_signal_below_full_scale() {
+        let mut limiter = MixLimiter::new(SAMPLE_RATE, Duration::from_millis(500));
+        // Mic and app each at 0.8 peak: fine alone, 1.6 when summed
+        let mut mixed: Vec<f32> = (0..SAMPLE_RATE / 10)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let tone = (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+                0.8 * tone + 0.8 * tone
+            })
+            .collect();
+        limiter.process(&mut mixed);
+        assert!(mixed.iter().all(|s| s.abs() < 1.0));
+        assert!(limiter.gain() < 0.7);
+    }
+
+    #[test]
+    fn mix_limiter_passes_quiet_audio_unchanged() {
+        let mut limiter = MixLimiter::new(SAMPLE_RATE, Duration::from_millis(500));
+        let original: Vec<f32> = (0..4800).map(|i| 0.3 * ((i as f32) * 0.05).sin()).collect();
+        let mut processed = original.clone();
+        limiter.process(&mut processed);
+        assert_eq!(processed, original);
+    }
+
+    #[test]
+    fn mix_limiter_recovers_after_release() {
+        let mut limiter = MixLimiter::new(SAMPLE_RATE, Duration::from_millis(100));
+        limiter.process(&mut [2.0]);
+        assert!(limiter.gain() < 0.5);
+        let mut quiet_tail = vec![0.1; SAMPLE_RATE];
+        limiter.process(&mut quiet_tail);
+        assert!(limiter.gain() > 0.99);
+    }
+
+    #[test]
+    fn downmix_keeps_selected_app_channel() {
+        use AppDownmix::{Average, Channel};
+        // Hard-panned source: everything on the right channel
+        let interleaved = [0.0f32, 0.8, 0.0, -0.4, 0.0, 0.2];
+        assert_eq!(downmix_interleaved(&interleaved, 2, Average), vec![0.4, -0.2, 0.1]);
+        assert_eq!(downmix_interleaved(&interleaved, 2, Channel(1)), vec![0.8, -0.4, 0.2]);
+        assert_eq!(downmix_interleaved(&interleaved, 2, Channel(0)), vec![0.0, 0.0, 0.0]);
+        // A channel the stream doesn't have falls back to averaging
+        assert_eq!(downmix_interleaved(&interleaved, 2, Channel(5)), vec![0.4, -0.2, 0.1]);
+
+        let left = [0.0f32, 0.0, 0.0];
+        let right = [0.8f32, -0.4, 0.2, 0.9];
+        assert_eq!(downmix_planar(&[&left, &right], Average), vec![0.4, -0.2, 0.1]);
+        assert_eq!(downmix_planar(&[&left, &right], Channel(1)), vec![0.8, -0.4, 0.2]);
+    }
+
+    #[test]
+    fn downmix_methods_on_a_stereo_frame() {
+        let frame = [0.25f32, 0.5];
+        assert_eq!(downmix_interleaved(&frame, 2, AppDownmix::Average), vec![0.375]);
+        assert_eq!(downmix_interleaved(&frame, 2, AppDownmix::Sum), vec![0.75]);
+        assert_eq!(downmix_interleaved(&frame, 2, AppDownmix::Channel(0)), vec![0.25]);
+        assert_eq!(downmix_interleaved(&frame, 2, AppDownmix::Channel(1)), vec![0.5]);
+
+        // Correlated content keeps its level only when summed
+        let (left, right) = ([0.3f32], [0.3f32]);
+        assert_eq!(downmix_planar(&[&left, &right], AppDownmix::Sum), vec![0.6]);
+        assert_eq!(downmix_planar(&[&left, &right], AppDownmix::Average), vec![0.3]);
+    }
+
     #[test]
     fn wav_writer_rejects_mismatched_channels() {
         let dir = std::env::temp_dir().join("crispy_test_wavwriter_mismatch");
@@ -541,12 +1133,218 @@ mod tests {
         std::fs::remove_dir_all(&dir).ok();
     }
 
+    #[test]
+    fn wav_writer_transcription_config_writes_16k_mono() {
+        let dir = std::env::temp_dir().join("crispy_test_wavwriter_16k");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test_16k.wav");
+
+        let mut writer = WavWriter::with_config(path.clone(), WavConfig::TRANSCRIPTION).unwrap();
+        // One second at 48kHz, identical channels as produced by the mixer,
+        // written in uneven blocks like the recording worker does
+        let mix = vec![0.5f32; 48000];
+        for block in mix.chunks(700) {
+            writer.write_samples(block, block).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!(spec.channels, 1);
+        assert_eq!(spec.sample_rate, 16000);
+        let samples: Vec<i16> = reader.into_samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples.len(), 16000);
+        // DC passes; the filter only rings at the edges of the buffer
+        let expected = 0.5 * 32767.0;
+        assert!(samples[500..15500]
+            .iter()
+            .all(|&s| (s as f32 - expected).abs() < expected * 0.01));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn wav_writer_16k_filters_content_above_8khz() {
+        let dir = std::env::temp_dir().join("crispy_test_wavwriter_alias");
+        std::fs::create_dir_all(&dir).unwrap();
+        let rms_at_16k = |freq: f32, name: &str| {
+            let path = dir.join(name);
+            let mut writer = WavWriter::with_config(path.clone(), WavConfig::TRANSCRIPTION).unwrap();
+            let tone: Vec<f32> = (0..48000)
+                .map(|i| 0.5 * (2.0 * std::f32::consts::PI * freq * i as f32 / 48000.0).sin())
+                .collect();
+            writer.write_samples(&tone, &tone).unwrap();
+            writer.finalize().unwrap();
+            let samples: Vec<f32> = hound::WavReader::open(&path)
+                .unwrap()
+                .into_samples::<i16>()
+                .map(|s| s.unwrap() as f32 / 32768.0)
+                .collect();
+            let body = &samples[1000..15000];
+            (body.iter().map(|s| s * s).sum::<f32>() / body.len() as f32).sqrt()
+        };
+        // 1 kHz is in the passband; 12 kHz would alias to 4 kHz unfiltered
+        assert!(rms_at_16k(1000.0, "pass.wav") > 0.3);
+        assert!(rms_at_16k(12000.0, "alias.wav") < 0.01);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn wav_writer_rejects_non_divisor_rate() {
+        let path = std::env::temp_dir().join("crispy_test_wavwriter_bad_rate.wav");
+        let config = WavConfig { sample_rate: 44100, channels: 1 };
+        assert!(WavWriter::with_config(path, config).is_err());
+    }
+
+    #[test]
+    fn wav_config_from_setting() {
+        assert_eq!(WavConfig::from_setting("transcription"), WavConfig::TRANSCRIPTION);
+        assert_eq!(WavConfig::from_setting("standard"), WavConfig::STANDARD);
+        assert_eq!(WavConfig::from_setting(""), WavConfig::STANDARD);
+    }
+
+    fn test_app(name: &str) -> RecordableApp {
+        RecordableApp {
+            id: format!("{}_1", name),
+            name: name.to_string(),
+            bundle_id: name.to_string(),
+            icon_base64: None,
+        }
+    }
+
+    #[test]
+    fn recordable_app_serializes_missing_icon_as_null() {
+        let json = serde_json::to_value(test_app("a")).unwrap();
+        assert!(json["icon_base64"].is_null());
+    }
+
+    #[test]
+    fn attach_icons_resolves_each_app_once_and_skips_none() {
+        let png_magic = [0x89u8, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        let resolved = std::cell::RefCell::new(Vec::new());
+        let resolve = |app: &RecordableApp| {
+            resolved.borrow_mut().push(app.bundle_id.clone());
+            (app.bundle_id == "a").then(|| encode_icon_png(&png_magic))
+        };
+        let none = RecordableApp {
+            id: "none".to_string(),
+            name: "None (Mic only)".to_string(),
+            bundle_id: "none".to_string(),
+            icon_base64: None,
+        };
+        let cache = Mutex::new(HashMap::new());
+
+        for _ in 0..2 {
+            let mut apps = vec![none.clone(), test_app("a"), test_app("b")];
+            attach_icons_with(&mut apps, &cache, resolve);
+            assert_eq!(apps[0].icon_base64, None);
+            assert_eq!(apps[1].icon_base64.as_deref(), Some("iVBORw0KGgo="));
+            assert_eq!(apps[2].icon_base64, None);
+        }
+        // Hits and misses both come from the cache the second time
+        assert_eq!(*resolved.borrow(), ["a", "b"]);
+    }
+
+    #[test]
+    fn encode_icon_png_produces_base64_png() {
+        let png_magic = [0x89u8, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        assert_eq!(encode_icon_png(&png_magic), "iVBORw0KGgo=");
+    }
+
+    #[test]
+    fn recordable_apps_cache_reuses_within_ttl_and_expires() {
+        let mut cache = RecordableAppsCache::new();
+        let ttl = Duration::from_secs(2);
+        let t0 = Instant::now();
+        let mut calls = 0;
+
+        let apps = cache
+            .get_or_refresh(t0, ttl, false, || {
+                calls += 1;
+                Ok(vec![test_app("a")])
+            })
+            .unwrap();
+        assert_eq!(apps[0].name, "a");
+
+        // Within TTL: served from cache, fetch not called
+        let apps = cache
+            .get_or_refresh(t0 + Duration::from_millis(1500), ttl, false, || {
+                calls += 1;
+                Ok(vec![test_app("b")])
+            })
+            .unwrap();
+        assert_eq!(apps[0].name, "a");
+        assert_eq!(calls, 1);
+
+        // Past TTL: refreshed
+        let apps = cache
+            .get_or_refresh(t0 + Duration::from_secs(3), ttl, false, || {
+                calls += 1;
+                Ok(vec![test_app("c")])
+            })
+            .unwrap();
+        assert_eq!(apps[0].name, "c");
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn recordable_apps_cache_force_bypasses_ttl() {
+        let mut cache = RecordableAppsCache::new();
+        let ttl = Duration::from_secs(2);
+        let t0 = Instant::now();
+        cache.get_or_refresh(t0, ttl, false, || Ok(vec![test_app("a")])).unwrap();
+        let apps = cache.get_or_refresh(t0, ttl, true, || Ok(vec![test_app("b")])).unwrap();
+        assert_eq!(apps[0].name, "b");
+        // The forced result is what later cached reads see
+        let apps = cache.get_or_refresh(t0, ttl, false, || Ok(Vec::new())).unwrap();
+        assert_eq!(apps[0].name, "b");
+    }
+
+    #[test]
+    fn recordable_apps_cache_does_not_keep_errors_or_invalidated_data() {
+        let mut cache = RecordableAppsCache::new();
+        let ttl = Duration::from_secs(2);
+        let t0 = Instant::now();
+        cache.get_or_refresh(t0, ttl, false, || Ok(vec![test_app("a")])).unwrap();
+        assert!(cache.get_or_refresh(t0, ttl, true, || Err("boom".to_string())).is_err());
+        let apps = cache.get_or_refresh(t0, ttl, false, || Ok(vec![test_app("b")])).unwrap();
+        assert_eq!(apps[0].name, "b");
+
+        cache.invalidate();
+        let apps = cache.get_or_refresh(t0, ttl, false, || Ok(vec![test_app("c")])).unwrap();
+        assert_eq!(apps[0].name, "c");
+    }
+
     #[test]
     fn recording_constants() {
         assert_eq!(SAMPLE_RATE, 48000);
         assert_eq!(CHANNELS, 2);
     }
 
+    #[test]
+    fn recording_starts_with_the_pre_roll() {
+        let mut mic: VecDeque<f32> = (0..3 * SAMPLE_RATE).map(|i| i as f32).collect();
+        let mut app: VecDeque<f32> = VecDeque::from(vec![0.5; 100]);
+        start_buffers_with_pre_roll(&mut mic, &mut app, SAMPLE_RATE);
+
+        // The newest second of mic audio, matched by as much app silence
+        assert_eq!(mic.len(), SAMPLE_RATE);
+        assert_eq!(mic.front().copied(), Some((2 * SAMPLE_RATE) as f32));
+        assert_eq!(mic.back().copied(), Some((3 * SAMPLE_RATE - 1) as f32));
+        assert_eq!(app.len(), SAMPLE_RATE);
+        assert!(app.iter().all(|&s| s == 0.0));
+
+        // Less buffered than asked for: all of it, still aligned
+        let mut mic: VecDeque<f32> = VecDeque::from(vec![0.1; 500]);
+        start_buffers_with_pre_roll(&mut mic, &mut app, SAMPLE_RATE);
+        assert_eq!((mic.len(), app.len()), (500, 500));
+
+        // Off: both start empty
+        start_buffers_with_pre_roll(&mut mic, &mut app, 0);
+        assert!(mic.is_empty() && app.is_empty());
+    }
+
     #[test]
     fn recording_state_initializes_with_empty_buffers() {
         let state = RecordingState::new();
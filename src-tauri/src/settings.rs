@@ -1,9 +1,14 @@
 // LLM settings storage and retrieval
 
+use crate::audio::ResamplerKind;
+use crate::managers::transcription::{TimestampGranularity, TranscriptionOptions, TranscriptionSource};
+use crate::recording::AppDownmix;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Manager};
 
 /// Serializes the read-modify-write cycle for the settings file so concurrent
@@ -45,12 +50,85 @@ pub struct AppSettings {
     pub diarization_threshold: String,
     #[serde(default = "default_diarization_merge_gap")]
     pub diarization_merge_gap: String,
+    /// "standard" (48 kHz stereo) or "transcription" (16 kHz mono, no resampling later).
+    #[serde(default = "default_recording_format")]
+    pub recording_format: String,
+    /// Skip inference (and save an empty "no speech" result) for music-only/silent audio.
+    /// Opt-in: the detector can misjudge quiet or far-field speech.
+    #[serde(default = "default_false_string")]
+    pub skip_non_speech: String,
+    #[serde(default = "default_transcription_chunk_seconds")]
+    pub transcription_chunk_seconds: String,
+    /// Overlap between consecutive chunks; must be shorter than the chunk.
+    #[serde(default = "default_transcription_overlap_seconds")]
+    pub transcription_overlap_seconds: String,
+    /// TPDF dither before the 16-bit conversion of recordings.
+    #[serde(default = "default_false_string")]
+    pub recording_dither: String,
+    /// Digital output ceiling for recordings, in dBFS (-24..=0).
+    #[serde(default = "default_recording_ceiling_db")]
+    pub recording_ceiling_db: String,
+    /// Limit the summed mic+app signal so the recorded mix never clips.
+    #[serde(default = "default_false_string")]
+    pub recording_auto_attenuation: String,
+    /// Seconds of mic audio from before the start a recording begins with (0 = off).
+    /// Mic only, and only while monitoring runs: that stream is what keeps the
+    /// rolling mic buffer filled. App audio is captured from the start on.
+    #[serde(default = "default_recording_pre_roll_seconds")]
+    pub recording_pre_roll_seconds: String,
+    /// App audio channel to keep instead of averaging: "auto" or a 0-based index.
+    #[serde(default = "default_app_capture_channel")]
+    pub app_capture_channel: String,
+    /// How app audio is folded to mono when no channel is picked: "average" or "sum".
+    #[serde(default = "default_app_capture_downmix")]
+    pub app_capture_downmix: String,
+    /// What to transcribe from a stereo file: "mix", "left" or "right". Crispy's
+    /// own recordings hold the same mix on both channels.
+    #[serde(default = "default_transcription_source")]
+    pub transcription_source: String,
+    /// Parakeet segment timing: "word" or "segment" (sentences).
+    #[serde(default = "default_timestamp_granularity")]
+    pub timestamp_granularity: String,
+    /// Language for Whisper models: an ISO 639-1 code, or "auto" to detect.
+    #[serde(default = "default_transcription_language")]
+    pub transcription_language: String,
+    /// Whisper translates the speech to English instead of transcribing it.
+    #[serde(default = "default_false_string")]
+    pub transcription_translate: String,
+    /// Mic resampling to the recording rate: "linear" (cheaper) or "sinc".
+    #[serde(default = "default_resampler_quality")]
+    pub resampler_quality: String,
+    /// RNNoise monitoring plays its first frame instead of dropping it as
+    /// warm-up: output starts ~10 ms sooner, possibly with a brief artifact.
+    #[serde(default = "default_false_string")]
+    pub rnnoise_low_latency: String,
+    /// Whether monitoring was on when last started/stopped; resumed at launch.
+    #[serde(default = "default_false_string")]
+    pub monitoring_enabled: String,
+    /// cpal host monitoring was last started on; "" is the platform default.
+    #[serde(default)]
+    pub monitoring_host: String,
+    /// Tray popup size in logical pixels, "<width>x<height>".
+    #[serde(default = "default_tray_popup_size")]
+    pub tray_popup_size: String,
 }
 
 fn default_false_string() -> String {
     "false".to_string()
 }
 
+fn default_transcription_chunk_seconds() -> String {
+    "30".to_string()
+}
+
+fn default_transcription_overlap_seconds() -> String {
+    "0".to_string()
+}
+
+fn default_true_string() -> String {
+    "true".to_string()
+}
+
 fn default_diarization_max_speakers() -> String {
     // Upper bound for NME-SC's automatic speaker-count estimation (not a hard target).
     "6".to_string()
@@ -64,6 +142,46 @@ fn default_diarization_merge_gap() -> String {
     "2.5".to_string()
 }
 
+fn default_recording_format() -> String {
+    "standard".to_string()
+}
+
+fn default_app_capture_channel() -> String {
+    "auto".to_string()
+}
+
+fn default_app_capture_downmix() -> String {
+    "average".to_string()
+}
+
+fn default_transcription_source() -> String {
+    "mix".to_string()
+}
+
+fn default_timestamp_granularity() -> String {
+    "word".to_string()
+}
+
+fn default_transcription_language() -> String {
+    "auto".to_string()
+}
+
+fn default_resampler_quality() -> String {
+    "linear".to_string()
+}
+
+fn default_recording_ceiling_db() -> String {
+    "0".to_string()
+}
+
+fn default_recording_pre_roll_seconds() -> String {
+    "0".to_string()
+}
+
+fn default_tray_popup_size() -> String {
+    "260x280".to_string()
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
@@ -78,10 +196,217 @@ impl Default for AppSettings {
             diarization_max_speakers: "6".to_string(),
             diarization_threshold: "0.50".to_string(),
             diarization_merge_gap: "2.5".to_string(),
+            recording_format: "standard".to_string(),
+            skip_non_speech: "false".to_string(),
+            transcription_chunk_seconds: "30".to_string(),
+            transcription_overlap_seconds: "0".to_string(),
+            recording_dither: "false".to_string(),
+            recording_ceiling_db: "0".to_string(),
+            recording_auto_attenuation: "false".to_string(),
+            recording_pre_roll_seconds: "0".to_string(),
+            app_capture_channel: "auto".to_string(),
+            app_capture_downmix: "average".to_string(),
+            transcription_source: "mix".to_string(),
+            timestamp_granularity: "word".to_string(),
+            transcription_language: "auto".to_string(),
+            transcription_translate: "false".to_string(),
+            resampler_quality: "linear".to_string(),
+            rnnoise_low_latency: "false".to_string(),
+            monitoring_enabled: "false".to_string(),
+            monitoring_host: String::new(),
+            tray_popup_size: "260x280".to_string(),
         }
     }
 }
 
+/// Parse and validate a chunk length / overlap pair (seconds).
+pub fn parse_transcription_chunking(chunk: &str, overlap: &str) -> Result<(f64, f64)> {
+    let chunk: f64 = chunk
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid chunk length: {}", chunk))?;
+    let overlap: f64 = overlap
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid chunk overlap: {}", overlap))?;
+    if !chunk.is_finite() || chunk < 1.0 {
+        return Err(anyhow::anyhow!("Chunk length must be at least 1 second"));
+    }
+    if !overlap.is_finite() || overlap < 0.0 {
+        return Err(anyhow::anyhow!("Chunk overlap cannot be negative"));
+    }
+    if overlap >= chunk {
+        return Err(anyhow::anyhow!("Chunk overlap must be shorter than the chunk length"));
+    }
+    Ok((chunk, overlap))
+}
+
+/// Parse an output ceiling in dBFS; must be between -24 and 0.
+pub fn parse_ceiling_db(value: &str) -> Result<f32> {
+    let db: f32 = value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid output ceiling: {}", value))?;
+    if !db.is_finite() || !(-24.0..=0.0).contains(&db) {
+        return Err(anyhow::anyhow!("Output ceiling must be between -24 and 0 dBFS"));
+    }
+    Ok(db)
+}
+
+/// Parse a recording pre-roll length, bounded by the rolling mic buffer.
+pub fn parse_pre_roll_seconds(value: &str) -> Result<f64> {
+    let seconds: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid pre-roll: {}", value))?;
+    let max = crate::recording::ROLLING_BUFFER_SECONDS as f64;
+    if !seconds.is_finite() || !(0.0..=max).contains(&seconds) {
+        return Err(anyhow::anyhow!("Pre-roll must be between 0 and {} seconds", max));
+    }
+    Ok(seconds)
+}
+
+/// Parse the tray popup size "<width>x<height>", clamped to the sizes the
+/// popup allows.
+pub fn parse_tray_popup_size(value: &str) -> Result<(f64, f64)> {
+    let invalid = || anyhow::anyhow!("Invalid tray popup size: {}", value);
+    let (width, height) = value.trim().split_once('x').ok_or_else(invalid)?;
+    let width: f64 = width.trim().parse().map_err(|_| invalid())?;
+    let height: f64 = height.trim().parse().map_err(|_| invalid())?;
+    if !width.is_finite() || !height.is_finite() {
+        return Err(invalid());
+    }
+    Ok(crate::window::clamp_tray_popup_size(width, height))
+}
+
+/// Parse the app capture channel: "auto" (average all channels) or an index.
+pub fn parse_app_capture_channel(value: &str) -> Result<Option<usize>> {
+    match value.trim() {
+        "" | "auto" => Ok(None),
+        other => other
+            .parse()
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("Invalid app capture channel: {}", value)),
+    }
+}
+
+/// Parse the app capture downmix: "average" or "sum".
+pub fn parse_app_capture_downmix(value: &str) -> Result<AppDownmix> {
+    match value.trim() {
+        "average" => Ok(AppDownmix::Average),
+        "sum" => Ok(AppDownmix::Sum),
+        _ => Err(anyhow::anyhow!("Invalid app capture downmix: {}", value)),
+    }
+}
+
+/// Parse the transcription source: "mix", "left" or "right".
+pub fn parse_transcription_source(value: &str) -> Result<TranscriptionSource> {
+    match value.trim() {
+        "mix" => Ok(TranscriptionSource::Mix),
+        "left" => Ok(TranscriptionSource::Left),
+        "right" => Ok(TranscriptionSource::Right),
+        _ => Err(anyhow::anyhow!("Invalid transcription source: {}", value)),
+    }
+}
+
+/// Parse the transcription language: an ISO 639-1 code, or "auto" (None).
+pub fn parse_transcription_language(value: &str) -> Result<Option<String>> {
+    TranscriptionOptions::new(Some(value), false)
+        .map(|options| options.language)
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Parse the timestamp granularity: "word" or "segment".
+pub fn parse_timestamp_granularity(value: &str) -> Result<TimestampGranularity> {
+    match value.trim() {
+        "word" => Ok(TimestampGranularity::Word),
+        "segment" => Ok(TimestampGranularity::Segment),
+        _ => Err(anyhow::anyhow!("Invalid timestamp granularity: {}", value)),
+    }
+}
+
+/// Parse the resampler quality: "linear" or "sinc".
+pub fn parse_resampler_quality(value: &str) -> Result<ResamplerKind> {
+    match value.trim() {
+        "linear" => Ok(ResamplerKind::Linear),
+        "sinc" => Ok(ResamplerKind::Sinc),
+        _ => Err(anyhow::anyhow!("Invalid resampler quality: {}", value)),
+    }
+}
+
+impl AppSettings {
+    /// Validated (chunk_seconds, overlap_seconds); falls back to the defaults if
+    /// the stored pair is invalid (e.g. hand-edited settings file).
+    pub fn transcription_chunking(&self) -> (f64, f64) {
+        parse_transcription_chunking(
+            &self.transcription_chunk_seconds,
+            &self.transcription_overlap_seconds,
+        )
+        .unwrap_or((30.0, 0.0))
+    }
+
+    /// The saved monitoring host; None for the platform default.
+    pub fn monitoring_host(&self) -> Option<String> {
+        let host = self.monitoring_host.trim();
+        (!host.is_empty()).then(|| host.to_string())
+    }
+
+    /// Validated recording ceiling in dBFS; 0 (no extra ceiling) if invalid.
+    pub fn recording_ceiling_db(&self) -> f32 {
+        parse_ceiling_db(&self.recording_ceiling_db).unwrap_or(0.0)
+    }
+
+    /// Tray popup (width, height); the default size if invalid.
+    pub fn tray_popup_size(&self) -> (f64, f64) {
+        parse_tray_popup_size(&self.tray_popup_size)
+            .unwrap_or(crate::window::TRAY_POPUP_DEFAULT_SIZE)
+    }
+
+    /// Validated pre-roll in seconds; 0 (off) if invalid.
+    pub fn recording_pre_roll_seconds(&self) -> f64 {
+        parse_pre_roll_seconds(&self.recording_pre_roll_seconds).unwrap_or(0.0)
+    }
+
+    /// App audio channel to keep; `None` averages all channels.
+    pub fn app_capture_channel(&self) -> Option<usize> {
+        parse_app_capture_channel(&self.app_capture_channel).unwrap_or(None)
+    }
+
+    /// App audio to mono: a picked channel wins, then the downmix method;
+    /// averaging if the stored value is invalid.
+    pub fn app_downmix(&self) -> AppDownmix {
+        match self.app_capture_channel() {
+            Some(channel) => AppDownmix::Channel(channel),
+            None => parse_app_capture_downmix(&self.app_capture_downmix).unwrap_or_default(),
+        }
+    }
+
+    /// Channels to transcribe; the full mix if the stored value is invalid.
+    pub fn transcription_source(&self) -> TranscriptionSource {
+        parse_transcription_source(&self.transcription_source).unwrap_or_default()
+    }
+
+    /// Parakeet segment timing; words if the stored value is invalid.
+    pub fn timestamp_granularity(&self) -> TimestampGranularity {
+        parse_timestamp_granularity(&self.timestamp_granularity).unwrap_or_default()
+    }
+
+    /// Language, translate and granularity for new transcriptions; the
+    /// auto-detect defaults for invalid stored values.
+    pub fn transcription_options(&self) -> TranscriptionOptions {
+        TranscriptionOptions {
+            language: parse_transcription_language(&self.transcription_language).unwrap_or(None),
+            translate: self.transcription_translate == "true",
+            granularity: self.timestamp_granularity(),
+        }
+    }
+
+    /// Mic resampler; linear if the stored value is invalid.
+    pub fn resampler_quality(&self) -> ResamplerKind {
+        parse_resampler_quality(&self.resampler_quality).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmSettingsPublic {
     pub endpoint: String,
@@ -206,7 +531,12 @@ pub fn update_app_setting(app: &AppHandle, key: &str, value: String) -> Result<(
     // non-reentrant double-lock.
     let _guard = SETTINGS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let mut file = load_settings_file(app)?;
-    let settings = &mut file.app;
+    apply_app_setting(&mut file.app, key, value)?;
+    save_settings_file(app, &file)
+}
+
+/// Set one app setting by key, validating values that have constraints.
+fn apply_app_setting(settings: &mut AppSettings, key: &str, value: String) -> Result<()> {
     match key {
         "selected_microphone" => settings.selected_microphone = value,
         "selected_output_device" => settings.selected_output_device = value,
@@ -219,9 +549,127 @@ pub fn update_app_setting(app: &AppHandle, key: &str, value: String) -> Result<(
         "diarization_max_speakers" => settings.diarization_max_speakers = value,
         "diarization_threshold" => settings.diarization_threshold = value,
         "diarization_merge_gap" => settings.diarization_merge_gap = value,
+        "recording_format" => settings.recording_format = value,
+        "skip_non_speech" => settings.skip_non_speech = value,
+        "transcription_chunk_seconds" => {
+            parse_transcription_chunking(&value, &settings.transcription_overlap_seconds)?;
+            settings.transcription_chunk_seconds = value
+        }
+        "transcription_overlap_seconds" => {
+            parse_transcription_chunking(&settings.transcription_chunk_seconds, &value)?;
+            settings.transcription_overlap_seconds = value
+        }
+        "recording_dither" => settings.recording_dither = value,
+        "recording_ceiling_db" => {
+            parse_ceiling_db(&value)?;
+            settings.recording_ceiling_db = value
+        }
+        "recording_auto_attenuation" => settings.recording_auto_attenuation = value,
+        "recording_pre_roll_seconds" => {
+            parse_pre_roll_seconds(&value)?;
+            settings.recording_pre_roll_seconds = value
+        }
+        "app_capture_channel" => {
+            parse_app_capture_channel(&value)?;
+            settings.app_capture_channel = value
+        }
+        "app_capture_downmix" => {
+            parse_app_capture_downmix(&value)?;
+            settings.app_capture_downmix = value
+        }
+        "transcription_source" => {
+            parse_transcription_source(&value)?;
+            settings.transcription_source = value
+        }
+        "timestamp_granularity" => {
+            parse_timestamp_granularity(&value)?;
+            settings.timestamp_granularity = value
+        }
+        "transcription_language" => {
+            parse_transcription_language(&value)?;
+            settings.transcription_language = value
+        }
+        "transcription_translate" => settings.transcription_translate = value,
+        "resampler_quality" => {
+            parse_resampler_quality(&value)?;
+            settings.resampler_quality = value
+        }
+        "rnnoise_low_latency" => settings.rnnoise_low_latency = value,
+        "monitoring_enabled" => settings.monitoring_enabled = value,
+        "monitoring_host" => settings.monitoring_host = value,
+        "tray_popup_size" => {
+            parse_tray_popup_size(&value)?;
+            settings.tray_popup_size = value
+        }
         _ => return Err(anyhow::anyhow!("Unknown setting key: {}", key)),
     }
-    save_settings_file(app, &file)
+    Ok(())
+}
+
+/// Quiet period before debounced setting changes are written.
+const SETTINGS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Latest value per key plus when the last change arrived.
+#[derive(Default)]
+struct PendingWrites {
+    values: BTreeMap<String, String>,
+    last_change: Option<Instant>,
+    flusher_running: bool,
+}
+
+impl PendingWrites {
+    fn set(&mut self, key: &str, value: String, now: Instant) {
+        self.values.insert(key.to_string(), value);
+        self.last_change = Some(now);
+    }
+
+    /// Everything pending once no change arrived for `delay`; `None` while
+    /// changes are still coming in.
+    fn take_due(&mut self, now: Instant, delay: Duration) -> Option<Vec<(String, String)>> {
+        let last = self.last_change?;
+        if now.duration_since(last) < delay {
+            return None;
+        }
+        self.last_change = None;
+        Some(std::mem::take(&mut self.values).into_iter().collect())
+    }
+}
+
+static PENDING_WRITES: Mutex<PendingWrites> = Mutex::new(PendingWrites {
+    values: BTreeMap::new(),
+    last_change: None,
+    flusher_running: false,
+});
+
+/// `update_app_setting` for values that change in rapid bursts (slider drags):
+/// only the last value per key is written, once changes settle.
+pub fn update_app_setting_debounced(app: &AppHandle, key: &str, value: String) {
+    let mut pending = PENDING_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    pending.set(key, value, Instant::now());
+    if pending.flusher_running {
+        return;
+    }
+    pending.flusher_running = true;
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SETTINGS_DEBOUNCE);
+        let due = {
+            let mut pending = PENDING_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+            match pending.take_due(Instant::now(), SETTINGS_DEBOUNCE) {
+                Some(due) => {
+                    pending.flusher_running = false;
+                    due
+                }
+                None => continue,
+            }
+        };
+        for (key, value) in due {
+            if let Err(e) = update_app_setting(&app, &key, value) {
+                eprintln!("Failed to persist setting {}: {}", key, e);
+            }
+        }
+        break;
+    });
 }
 
 #[cfg(test)]
@@ -250,6 +698,157 @@ mod tests {
         assert_eq!(settings.diarization_max_speakers, "6");
         assert_eq!(settings.diarization_threshold, "0.50");
         assert_eq!(settings.diarization_merge_gap, "2.5");
+        assert_eq!(settings.recording_format, "standard");
+        assert_eq!(settings.skip_non_speech, "false");
+        assert_eq!(settings.transcription_chunk_seconds, "30");
+        assert_eq!(settings.transcription_overlap_seconds, "0");
+        assert_eq!(settings.recording_dither, "false");
+        assert_eq!(settings.recording_ceiling_db, "0");
+        assert_eq!(settings.recording_auto_attenuation, "false");
+        assert_eq!(settings.recording_pre_roll_seconds, "0");
+        assert_eq!(settings.app_capture_channel, "auto");
+        assert_eq!(settings.app_capture_downmix, "average");
+        assert_eq!(settings.transcription_source, "mix");
+        assert_eq!(settings.timestamp_granularity, "word");
+        assert_eq!(settings.transcription_language, "auto");
+        assert_eq!(settings.transcription_translate, "false");
+        assert_eq!(settings.resampler_quality, "linear");
+        assert_eq!(settings.rnnoise_low_latency, "false");
+        assert_eq!(settings.monitoring_enabled, "false");
+        assert!(settings.monitoring_host.is_empty());
+        assert_eq!(settings.tray_popup_size, "260x280");
+    }
+
+    #[test]
+    fn debounced_writes_coalesce_until_quiet() {
+        let start = Instant::now();
+        let delay = Duration::from_millis(500);
+        let mut pending = PendingWrites::default();
+        pending.set("microphone_volume", "40".to_string(), start);
+        pending.set("microphone_volume", "55".to_string(), start + Duration::from_millis(100));
+        pending.set("selected_model", "rnnnoise".to_string(), start + Duration::from_millis(200));
+        assert!(pending.take_due(start + Duration::from_millis(600), delay).is_none());
+
+        let due = pending
+            .take_due(start + Duration::from_millis(700), delay)
+            .unwrap();
+        assert_eq!(
+            due,
+            vec![
+                ("microphone_volume".to_string(), "55".to_string()),
+                ("selected_model".to_string(), "rnnnoise".to_string()),
+            ]
+        );
+        assert!(pending.take_due(start + Duration::from_secs(5), delay).is_none());
+    }
+
+    #[test]
+    fn persisted_volume_is_reloaded() {
+        let mut file = SettingsFile::default();
+        apply_app_setting(&mut file.app, "microphone_volume", "35".to_string()).unwrap();
+        apply_app_setting(&mut file.app, "selected_model", "rnnnoise".to_string()).unwrap();
+        let json = serde_json::to_string(&file).unwrap();
+        let reloaded: SettingsFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.app.microphone_volume, "35");
+        assert_eq!(reloaded.app.selected_model, "rnnnoise");
+        assert!(apply_app_setting(&mut file.app, "no_such_key", String::new()).is_err());
+    }
+
+    #[test]
+    fn transcription_source_parsing() {
+        assert_eq!(parse_transcription_source("left").unwrap(), TranscriptionSource::Left);
+        assert_eq!(parse_transcription_source(" right ").unwrap(), TranscriptionSource::Right);
+        // The recorder doesn't split mic and app across channels
+        assert!(parse_transcription_source("mic").is_err());
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.transcription_source(), TranscriptionSource::Mix);
+        assert!(apply_app_setting(&mut settings, "transcription_source", "both".to_string()).is_err());
+        apply_app_setting(&mut settings, "transcription_source", "right".to_string()).unwrap();
+        assert_eq!(settings.transcription_source(), TranscriptionSource::Right);
+    }
+
+    #[test]
+    fn timestamp_granularity_parsing() {
+        assert_eq!(parse_timestamp_granularity("segment").unwrap(), TimestampGranularity::Segment);
+        assert!(parse_timestamp_granularity("token").is_err());
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.timestamp_granularity(), TimestampGranularity::Word);
+        apply_app_setting(&mut settings, "timestamp_granularity", "segment".to_string()).unwrap();
+        assert_eq!(settings.timestamp_granularity(), TimestampGranularity::Segment);
+    }
+
+    #[test]
+    fn transcription_options_persist_in_settings() {
+        assert_eq!(parse_transcription_language(" DE ").unwrap().as_deref(), Some("de"));
+        assert_eq!(parse_transcription_language("auto").unwrap(), None);
+        assert!(parse_transcription_language("german").is_err());
+
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.transcription_options(), TranscriptionOptions::default());
+        assert!(apply_app_setting(&mut settings, "transcription_language", "d3".to_string()).is_err());
+        apply_app_setting(&mut settings, "transcription_language", "fr".to_string()).unwrap();
+        apply_app_setting(&mut settings, "transcription_translate", "true".to_string()).unwrap();
+        let options = settings.transcription_options();
+        assert_eq!(options.language.as_deref(), Some("fr"));
+        assert!(options.translate);
+    }
+
+    #[test]
+    fn resampler_quality_parsing() {
+        assert_eq!(parse_resampler_quality("sinc").unwrap(), ResamplerKind::Sinc);
+        assert!(parse_resampler_quality("cubic").is_err());
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.resampler_quality(), ResamplerKind::Linear);
+        settings.resampler_quality = "bogus".to_string();
+        assert_eq!(settings.resampler_quality(), ResamplerKind::Linear);
+    }
+
+    #[test]
+    fn app_capture_channel_parsing() {
+        assert_eq!(parse_app_capture_channel("auto").unwrap(), None);
+        assert_eq!(parse_app_capture_channel("1").unwrap(), Some(1));
+        assert!(parse_app_capture_channel("left").is_err());
+        assert_eq!(AppSettings::default().app_capture_channel(), None);
+    }
+
+    #[test]
+    fn app_downmix_prefers_picked_channel() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.app_downmix(), AppDownmix::Average);
+        settings.app_capture_downmix = "sum".to_string();
+        assert_eq!(settings.app_downmix(), AppDownmix::Sum);
+        settings.app_capture_channel = "1".to_string();
+        assert_eq!(settings.app_downmix(), AppDownmix::Channel(1));
+        assert!(parse_app_capture_downmix("median").is_err());
+    }
+
+    #[test]
+    fn recording_ceiling_validation() {
+        assert_eq!(parse_ceiling_db("-1.5").unwrap(), -1.5);
+        assert!(parse_ceiling_db("3").is_err());
+        assert!(parse_ceiling_db("-30").is_err());
+        assert!(parse_ceiling_db("loud").is_err());
+        let settings = AppSettings {
+            recording_ceiling_db: "6".to_string(),
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.recording_ceiling_db(), 0.0);
+    }
+
+    #[test]
+    fn transcription_chunking_validation() {
+        assert_eq!(parse_transcription_chunking("20", "2.5").unwrap(), (20.0, 2.5));
+        assert!(parse_transcription_chunking("20", "20").is_err());
+        assert!(parse_transcription_chunking("10", "-1").is_err());
+        assert!(parse_transcription_chunking("0.5", "0").is_err());
+        assert!(parse_transcription_chunking("abc", "0").is_err());
+
+        let settings = AppSettings {
+            transcription_chunk_seconds: "5".to_string(),
+            transcription_overlap_seconds: "8".to_string(),
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.transcription_chunking(), (30.0, 0.0));
     }
 
     #[test]
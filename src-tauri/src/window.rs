@@ -25,6 +25,26 @@ fn configure_popup_for_fullscreen(window: &tauri::WebviewWindow) {
     }
 }
 
+/// Default tray popup size, in logical pixels.
+pub const TRAY_POPUP_DEFAULT_SIZE: (f64, f64) = (260.0, 280.0);
+/// Smallest popup that still fits the meter and controls.
+pub const TRAY_POPUP_MIN_SIZE: (f64, f64) = (220.0, 240.0);
+/// Largest popup; beyond this it stops looking like a tray popup.
+pub const TRAY_POPUP_MAX_SIZE: (f64, f64) = (640.0, 720.0);
+
+/// Clamp each side of `(width, height)` to `[min, max]`, rounded to whole
+/// logical pixels.
+pub fn clamp_size(size: (f64, f64), min: (f64, f64), max: (f64, f64)) -> (f64, f64) {
+    (
+        size.0.clamp(min.0, max.0).round(),
+        size.1.clamp(min.1, max.1).round(),
+    )
+}
+
+pub fn clamp_tray_popup_size(width: f64, height: f64) -> (f64, f64) {
+    clamp_size((width, height), TRAY_POPUP_MIN_SIZE, TRAY_POPUP_MAX_SIZE)
+}
+
 pub fn show_main_window(app: &tauri::AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let _ = window.show();
@@ -61,9 +81,9 @@ fn show_tray_popup_window(window: &tauri::WebviewWindow) {
     // Windows/Linux: force focus so click-outside triggers blur and hides the tray.
     #[cfg(not(target_os = "macos"))]
     let _ = window.set_focus();
+    position_tray_popup(window);
     #[cfg(target_os = "macos")]
     {
-        let _ = window.move_window(Position::TrayBottomCenter);
         // After Finder activation workaround, restore focus to the tray window
         let window_clone = window.clone();
         std::thread::spawn(move || {
@@ -71,6 +91,13 @@ fn show_tray_popup_window(window: &tauri::WebviewWindow) {
             let _ = window_clone.set_focus();
         });
     }
+}
+
+/// Place the popup at the tray icon. Uses the window's current size, so call
+/// it again after resizing.
+fn position_tray_popup(window: &tauri::WebviewWindow) {
+    #[cfg(target_os = "macos")]
+    let _ = window.move_window(Position::TrayBottomCenter);
     #[cfg(not(target_os = "macos"))]
     let _ = window.move_window(Position::TrayCenter);
 }
@@ -85,10 +112,13 @@ pub fn show_or_toggle_tray_popup(app: &tauri::AppHandle) {
         return;
     }
 
+    let (width, height) = crate::settings::load_app_settings(app)
+        .unwrap_or_default()
+        .tray_popup_size();
     let url = WebviewUrl::App("index.html".into());
     let _ = WebviewWindowBuilder::new(app, "tray-popup", url)
         .title("Crispy")
-        .inner_size(260.0, 280.0)
+        .inner_size(width, height)
         .decorations(false)
         .resizable(false)
         .build();
@@ -97,3 +127,44 @@ pub fn show_or_toggle_tray_popup(app: &tauri::AppHandle) {
         show_tray_popup_window(&window);
     }
 }
+
+/// Store the tray popup size (clamped to the allowed range) and apply it to
+/// the popup if it exists; a visible popup is moved back to the tray icon.
+/// Returns the size actually used.
+#[tauri::command]
+pub fn set_tray_popup_size(app: tauri::AppHandle, width: f64, height: f64) -> Result<(f64, f64), String> {
+    if !width.is_finite() || !height.is_finite() {
+        return Err("Invalid tray popup size".to_string());
+    }
+    let (width, height) = clamp_tray_popup_size(width, height);
+    crate::settings::update_app_setting(&app, "tray_popup_size", format!("{}x{}", width, height))
+        .map_err(|e| e.to_string())?;
+    if let Some(window) = app.get_webview_window("tray-popup") {
+        window
+            .set_size(tauri::LogicalSize::new(width, height))
+            .map_err(|e| format!("Failed to resize tray popup: {}", e))?;
+        if window.is_visible().unwrap_or(false) {
+            position_tray_popup(&window);
+        }
+    }
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_is_clamped_to_bounds_per_side() {
+        let min = (200.0, 100.0);
+        let max = (400.0, 300.0);
+        assert_eq!(clamp_size((250.0, 150.0), min, max), (250.0, 150.0));
+        assert_eq!(clamp_size((50.0, 1000.0), min, max), (200.0, 300.0));
+        assert_eq!(clamp_size((900.0, 0.0), min, max), (400.0, 100.0));
+        assert_eq!(clamp_size((260.4, 280.6), min, max), (260.0, 281.0));
+        assert_eq!(
+            clamp_tray_popup_size(TRAY_POPUP_DEFAULT_SIZE.0, TRAY_POPUP_DEFAULT_SIZE.1),
+            TRAY_POPUP_DEFAULT_SIZE
+        );
+    }
+}
@@ -35,11 +35,12 @@ use windows62::{
             Com::{CoInitializeEx, CoTaskMemAlloc, COINIT_APARTMENTTHREADED},
             Com::StructuredStorage::{PropVariantClear, PROPVARIANT},
             Diagnostics::ToolHelp::{
-                CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32,
+                CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
                 TH32CS_SNAPPROCESS,
             },
             Threading::{
                 CreateEventW, SetEvent, WaitForSingleObject, OpenProcess,
+                QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
                 PROCESS_QUERY_LIMITED_INFORMATION, INFINITE,
             },
             Variant::VT_BLOB,
@@ -58,6 +59,13 @@ fn audio_debug_enabled() -> bool {
     std::env::var("CRISPY_AUDIO_DEBUG").is_ok()
 }
 
+/// Decode a NUL-terminated UTF-16 buffer (e.g. `PROCESSENTRY32W::szExeFile`).
+#[cfg(target_os = "windows")]
+fn wide_to_string(buf: &[u16]) -> String {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..len])
+}
+
 #[cfg(target_os = "windows")]
 pub fn get_recordable_apps_windows() -> Result<Vec<RecordableApp>, String> {
     unsafe {
@@ -76,23 +84,15 @@ pub fn get_recordable_apps_windows() -> Result<Vec<RecordableApp>, String> {
         }
 
         let mut entries = Vec::new();
-        let mut entry = PROCESSENTRY32 {
-            dwSize: std::mem::size_of::<PROCESSENTRY32>() as u32,
+        // Wide variant: the ANSI entry mangles names outside the system code page.
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
             ..Default::default()
         };
 
-        if Process32First(snapshot, &mut entry).is_ok() {
+        if Process32FirstW(snapshot, &mut entry).is_ok() {
             loop {
-                // Convert process name from fixed-size array to String
-                let process_name = String::from_utf8_lossy(
-                    &entry
-                        .szExeFile
-                        .iter()
-                        .take_while(|&&c| c != 0)
-                        .map(|&c| c as u8)
-                        .collect::<Vec<u8>>(),
-                )
-                .to_string();
+                let process_name = wide_to_string(&entry.szExeFile);
 
                 // Filter out system processes and keep only user applications
                 if !process_name.is_empty()
@@ -107,7 +107,7 @@ pub fn get_recordable_apps_windows() -> Result<Vec<RecordableApp>, String> {
                     });
                 }
 
-                if Process32Next(snapshot, &mut entry).is_err() {
+                if Process32NextW(snapshot, &mut entry).is_err() {
                     break;
                 }
             }
@@ -133,6 +133,7 @@ pub fn get_recordable_apps_windows() -> Result<Vec<RecordableApp>, String> {
                 id: format!("{}_{}", root.name, root.pid),
                 name: root.name.clone(),
                 bundle_id: root.name.clone(),
+                icon_base64: None,
             });
         }
 
@@ -145,6 +146,7 @@ pub fn get_recordable_apps_windows() -> Result<Vec<RecordableApp>, String> {
                 id: "none".to_string(),
                 name: "None (Mic only)".to_string(),
                 bundle_id: "none".to_string(),
+                icon_base64: None,
             },
         );
 
@@ -275,6 +277,7 @@ pub fn start_app_audio_capture_windows(
     app_id: &str,
     app_buffer: Arc<Mutex<VecDeque<f32>>>,
     stop: Arc<AtomicBool>,
+    downmix: crate::recording::AppDownmix,
 ) -> Result<std::thread::JoinHandle<()>, String> {
     let pid = parse_pid(app_id)?;
 
@@ -294,7 +297,7 @@ pub fn start_app_audio_capture_windows(
         let app_buffer = app_buffer.clone();
         let stop = stop.clone();
         move || {
-            if let Err(e) = capture_process_loopback(pid, app_buffer, stop) {
+            if let Err(e) = capture_process_loopback(pid, app_buffer, stop, downmix) {
                 eprintln!("Process loopback capture error: {e}");
                 eprintln!("Note: On Windows ARM64, some applications may not support audio capture.");
                 eprintln!("Try selecting a different process or running this app as Administrator.");
@@ -305,6 +308,53 @@ pub fn start_app_audio_capture_windows(
     Ok(handle)
 }
 
+/// Full path of a running process's executable.
+#[cfg(target_os = "windows")]
+fn process_image_path(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buf = [0u16; 1024];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            windows62::core::PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        )
+        .is_ok();
+        let _ = CloseHandle(handle);
+        ok.then(|| String::from_utf16_lossy(&buf[..len as usize]))
+    }
+}
+
+/// Extract the executable's associated icon as a base64 PNG. Goes through
+/// System.Drawing in PowerShell, which is slow, so callers cache the result.
+#[cfg(target_os = "windows")]
+pub fn app_icon_base64_windows(pid: u32) -> Option<String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let exe = process_image_path(pid)?;
+    let script = format!(
+        "Add-Type -AssemblyName System.Drawing; \
+         $i = [System.Drawing.Icon]::ExtractAssociatedIcon('{}'); \
+         $m = New-Object System.IO.MemoryStream; \
+         $i.ToBitmap().Save($m, [System.Drawing.Imaging.ImageFormat]::Png); \
+         [Convert]::ToBase64String($m.ToArray())",
+        exe.replace('\'', "''")
+    );
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let encoded = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!encoded.is_empty()).then_some(encoded)
+}
+
 #[cfg(target_os = "windows")]
 fn get_windows_build_number() -> u32 {
     use winreg::RegKey;
@@ -319,11 +369,53 @@ fn get_windows_build_number() -> u32 {
     0
 }
 
+/// Silence passed through before the gate closes: short pauses inside playback
+/// keep their timing, but an idle target doesn't flood the app buffer.
+#[cfg(target_os = "windows")]
+const LOOPBACK_SILENCE_HOLD_FRAMES: usize = 48_000 / 10;
+
+/// Samples below this are treated as digital silence.
+#[cfg(target_os = "windows")]
+const LOOPBACK_SILENCE_EPSILON: f32 = 1e-6;
+
+/// Drops sustained silence from process loopback. The target emits silent
+/// packets continuously while it isn't playing; pushing them all would let the
+/// app buffer run ahead and make the recording worker trim the mic to match.
+/// The worker already fills a missing app signal with silence.
+#[cfg(target_os = "windows")]
+struct LoopbackSilenceGate {
+    silent_frames: usize,
+    hold_frames: usize,
+}
+
+#[cfg(target_os = "windows")]
+impl LoopbackSilenceGate {
+    fn new(hold_frames: usize) -> Self {
+        Self {
+            silent_frames: 0,
+            hold_frames,
+        }
+    }
+
+    /// The part of a mono packet to push: all of it when it has sound, and
+    /// silence only until `hold_frames` of it have run back to back.
+    fn pass<'a>(&mut self, packet: &'a [f32]) -> &'a [f32] {
+        if packet.iter().any(|s| s.abs() > LOOPBACK_SILENCE_EPSILON) {
+            self.silent_frames = 0;
+            return packet;
+        }
+        let allowed = self.hold_frames.saturating_sub(self.silent_frames).min(packet.len());
+        self.silent_frames = self.silent_frames.saturating_add(packet.len());
+        &packet[..allowed]
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn capture_process_loopback(
     pid: u32,
     app_buffer: Arc<Mutex<VecDeque<f32>>>,
     stop: Arc<AtomicBool>,
+    downmix: crate::recording::AppDownmix,
 ) -> Result<(), String> {
     // Verify process exists before attempting loopback
     let process_handle = unsafe {
@@ -482,6 +574,7 @@ fn capture_process_loopback(
     let mut total_frames_captured = 0u64;
     let mut packet_count = 0u64;
     let mut silent_packet_count = 0u64; // New metric
+    let mut silence_gate = LoopbackSilenceGate::new(LOOPBACK_SILENCE_HOLD_FRAMES);
     let mut last_log_time = std::time::Instant::now();
 
     while !stop.load(Ordering::SeqCst) {
@@ -535,17 +628,12 @@ fn capture_process_loopback(
                     )
                 };
 
-                // Downmix stereo to mono
-                for frame in samples.chunks(in_channels) {
-                    let mono_sample = if in_channels == 2 {
-                        (frame[0] + frame[1]) / 2.0
-                    } else if in_channels == 1 {
-                        frame[0]
-                    } else {
-                        frame.iter().sum::<f32>() / in_channels as f32
-                    };
-                    temp_mono.push(mono_sample);
-                }
+                // Downmix stereo to mono per the configured method
+                temp_mono.extend(crate::recording::downmix_interleaved(
+                    samples,
+                    in_channels,
+                    downmix,
+                ));
             }
 
             unsafe {
@@ -555,7 +643,7 @@ fn capture_process_loopback(
             }
 
             // ... (Push to buffer) ...
-            let out = &temp_mono[..];
+            let out = silence_gate.pass(&temp_mono);
             {
                 let mut buf = app_buffer.lock().unwrap();
                 let max_len = 48_000 * 10;
@@ -592,3 +680,41 @@ fn capture_process_loopback(
 
     Ok(())
 }
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_to_string_decodes_unicode_name() {
+        let mut buf = [0u16; 260];
+        let name: Vec<u16> = "Телеграм 微信.exe".encode_utf16().collect();
+        buf[..name.len()].copy_from_slice(&name);
+        assert_eq!(wide_to_string(&buf), "Телеграм 微信.exe");
+    }
+
+    #[test]
+    fn silence_gate_drops_sustained_silence_only() {
+        let mut gate = LoopbackSilenceGate::new(1000);
+        let silent = vec![0.0f32; 480];
+        let sound = vec![0.1f32; 480];
+
+        // A short pause is kept in full, then the rest of the run is dropped
+        assert_eq!(gate.pass(&silent).len(), 480);
+        assert_eq!(gate.pass(&silent).len(), 480);
+        assert_eq!(gate.pass(&silent).len(), 40);
+        assert_eq!(gate.pass(&silent).len(), 0);
+
+        // Sound always passes and reopens the gate for the next pause
+        assert_eq!(gate.pass(&sound).len(), 480);
+        assert_eq!(gate.pass(&silent).len(), 480);
+    }
+
+    #[test]
+    fn wide_to_string_handles_unterminated_and_invalid() {
+        let name: Vec<u16> = "app.exe".encode_utf16().collect();
+        assert_eq!(wide_to_string(&name), "app.exe");
+        // Lone surrogate is replaced instead of failing
+        assert_eq!(wide_to_string(&[0x61, 0xD800, 0x62, 0]), "a\u{FFFD}b");
+    }
+}
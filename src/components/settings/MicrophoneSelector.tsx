@@ -37,7 +37,7 @@ export const MicrophoneSelector: React.FC<MicrophoneSelectorProps> = React.memo(
     const microphoneOptions = [
       { value: "Default", label: "System Default" },
       ...audioDevices.map((device) => ({
-        value: device.name,
+        value: device.id,
         label: device.name,
       })),
     ];
@@ -38,7 +38,7 @@ export const OutputDeviceSelector: React.FC<OutputDeviceSelectorProps> =
       const outputDeviceOptions = [
         { value: "Default", label: "System Default" },
         ...outputDevices.map((device) => ({
-          value: device.name,
+          value: device.id,
           label: device.name,
         })),
       ];
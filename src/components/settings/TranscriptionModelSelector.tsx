@@ -34,10 +34,13 @@ const getStatusMeta = (
 const getDownloadLabel = (
   progress: { percentage: number } | undefined,
   isExtracting: boolean,
-  isPending: boolean
+  isPending: boolean,
+  extractionPercentage?: number
 ) => {
   if (progress) return `${Math.round(progress.percentage)}%`;
   if (isPending) return "Starting…";
+  if (isExtracting && extractionPercentage !== undefined)
+    return `Extracting… ${Math.round(extractionPercentage)}%`;
   if (isExtracting) return "Extracting…";
   return "Download";
 };
@@ -58,6 +61,7 @@ export const TranscriptionModelSelector: React.FC<{ grouped?: boolean }> = ({
     modelStatus,
     modelError,
     extractingModels,
+    extractionProgress,
     downloadStats,
     pendingDownloads,
     cancelDownload,
@@ -234,7 +238,12 @@ export const TranscriptionModelSelector: React.FC<{ grouped?: boolean }> = ({
                     disabled={isDownloading || isPending}
                     className="shrink-0 text-xs font-medium text-logo-primary hover:underline disabled:opacity-60 disabled:no-underline"
                   >
-                    {getDownloadLabel(progress, isExtracting, isPending)}
+                    {getDownloadLabel(
+                      progress,
+                      isExtracting,
+                      isPending,
+                      extractionProgress[model.id]
+                    )}
                   </button>
                   {(isDownloading || isPending) && (
                     <button
@@ -150,6 +150,7 @@ export const useSettings = () => {
       const defaults = await invoke<{
         default_input: string | null;
         blackhole_output: string | null;
+        blackhole_outputs: string[];
       }>("get_default_devices");
 
       // Set default input if not already set
@@ -6,6 +6,12 @@ import { sortModels, type TranscriptionModelInfo } from "@/lib/utils/models";
 
 export type { TranscriptionModelInfo } from "@/lib/utils/models";
 
+export interface ExtractionProgressPayload {
+  model_id: string;
+  extracted: number;
+  percentage: number;
+}
+
 export interface DownloadProgressPayload {
   model_id: string;
   downloaded: number;
@@ -56,6 +62,9 @@ export function useTranscriptionModels() {
   const [extractingModels, setExtractingModels] = useState<
     Record<string, true>
   >({});
+  const [extractionProgress, setExtractionProgress] = useState<
+    Record<string, number>
+  >({});
   const [downloadProgress, setDownloadProgress] = useState<
     Record<string, DownloadProgressPayload>
   >({});
@@ -217,6 +226,25 @@ export function useTranscriptionModels() {
     setModelStatus("extracting");
   }, []);
 
+  const handleExtractProgress = useCallback(
+    (event: { payload: ExtractionProgressPayload }) => {
+      setExtractionProgress((prev) => ({
+        ...prev,
+        [event.payload.model_id]: event.payload.percentage,
+      }));
+    },
+    []
+  );
+
+  const clearExtractionProgress = useCallback((modelId: string) => {
+    setExtractionProgress((prev) => {
+      if (!(modelId in prev)) return prev;
+      const next = { ...prev };
+      delete next[modelId];
+      return next;
+    });
+  }, []);
+
   const handleExtractComplete = useCallback(
     (event: { payload: string }) => {
       setExtractingModels((prev) => {
@@ -224,10 +252,11 @@ export function useTranscriptionModels() {
         delete next[event.payload];
         return next;
       });
+      clearExtractionProgress(event.payload);
       setModelStatus(currentModelId === "none" ? "unloaded" : "ready");
       refresh();
     },
-    [refresh, currentModelId]
+    [refresh, currentModelId, clearExtractionProgress]
   );
 
   const handleExtractFailed = useCallback(
@@ -237,10 +266,11 @@ export function useTranscriptionModels() {
         delete next[event.payload.model_id];
         return next;
       });
+      clearExtractionProgress(event.payload.model_id);
       setModelStatus("error");
       setModelError(`Failed to extract model: ${event.payload.error}`);
     },
-    []
+    [clearExtractionProgress]
   );
 
   // Setup Tauri listeners with proper lifecycle management
@@ -248,6 +278,7 @@ export function useTranscriptionModels() {
   useTauriListen<DownloadProgressPayload>("model-download-progress", handleDownloadProgress);
   useTauriListen<string>("model-download-complete", handleDownloadComplete);
   useTauriListen<string>("model-extraction-started", handleExtractStart);
+  useTauriListen<ExtractionProgressPayload>("model-extraction-progress", handleExtractProgress);
   useTauriListen<string>("model-extraction-completed", handleExtractComplete);
   useTauriListen<{ model_id: string; error: string }>("model-extraction-failed", handleExtractFailed);
 
@@ -370,6 +401,7 @@ export function useTranscriptionModels() {
     modelStatus,
     modelError,
     extractingModels,
+    extractionProgress,
     refresh,
     selected,
     current,